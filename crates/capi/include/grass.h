@@ -0,0 +1,225 @@
+#ifndef GRASS_H
+#define GRASS_H
+
+/* Declarations for src/lib.rs. Regenerate with `cbindgen --config cbindgen.toml --output include/grass.h`. */
+
+#include <stdbool.h>
+#include <stdint.h>
+
+// `GrassOutputStyle` values
+#define GRASS_OUTPUT_STYLE_EXPANDED 0
+
+#define GRASS_OUTPUT_STYLE_COMPRESSED 1
+
+// `GrassInputSyntax` values. By default, the syntax is inferred from the
+// file extension, falling back to SCSS
+#define GRASS_INPUT_SYNTAX_AUTO 0
+
+#define GRASS_INPUT_SYNTAX_SCSS 1
+
+#define GRASS_INPUT_SYNTAX_SASS 2
+
+#define GRASS_INPUT_SYNTAX_CSS 3
+
+// `GrassLogLevel` values
+#define GRASS_LOG_LEVEL_WARNING 0
+
+#define GRASS_LOG_LEVEL_DEPRECATION 1
+
+#define GRASS_LOG_LEVEL_DEBUG 2
+
+// The result of a [`GrassImporterFn`]
+typedef struct GrassImportResult GrassImportResult;
+
+// Options for compilation, created with [`grass_options_new`]
+typedef struct GrassOptions GrassOptions;
+
+// Loads a stylesheet that isn't found on the file system or in a load path
+//
+// `containing_url` is the canonical URL of the stylesheet containing the
+// rule, or a `file:` URL, and may be `NULL`. The callback reports its result
+// with [`grass_import_result_set_contents`] or
+// [`grass_import_result_set_error`]. If it calls neither, the URL is passed
+// on to the next importer.
+//
+// Options may be shared between threads, so the callback may be called from
+// any thread compiling with them, including from several threads at once.
+// Both the callback and `user_data` must be safe to use this way.
+typedef void (*GrassImporterFn)(void *user_data, const char *url, const char *containing_url, bool from_import, GrassImportResult *result);
+
+// Receives `@warn` and `@debug` messages and deprecation warnings
+//
+// `deprecation` is the ID of the deprecation for `GRASS_LOG_LEVEL_DEPRECATION`
+// messages, and `NULL` otherwise. `file` is `NULL` for messages without a
+// location, in which case `line` and `column` are zero. All strings are only
+// valid for the duration of the call.
+//
+// Options may be shared between threads, so the callback may be called from
+// any thread compiling with them, including from several threads at once.
+// Both the callback and `user_data` must be safe to use this way.
+typedef void (*GrassLoggerFn)(void *user_data, int level, const char *message, const char *deprecation, const char *file, uint32_t line, uint32_t column);
+
+// An error returned by [`grass_compile_string`] or [`grass_compile_file`]
+typedef struct {
+  // The error message, without a location
+  char *message;
+  // The error as it would be printed by the grass binary, including the
+  // source line and Sass stack trace
+  char *formatted;
+  // The name of the file the error occurred in, or `NULL`
+  char *file;
+  // The 1-based line of the error, or zero
+  uint32_t line;
+  // The 1-based column of the error, or zero
+  uint32_t column;
+} GrassError;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates options with the same defaults as [`Options::default`]
+GrassOptions *grass_options_new(void);
+
+// Frees options created with [`grass_options_new`]. `options` may be `NULL`
+//
+// # Safety
+//
+// `options` must have been returned by [`grass_options_new`] and not yet freed
+void grass_options_free(GrassOptions *options);
+
+// See [`Options::style`]. `style` is a `GrassOutputStyle`
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`]
+void grass_options_set_style(GrassOptions *options, int style);
+
+// See [`Options::input_syntax`]. `syntax` is a `GrassInputSyntax`
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`]
+void grass_options_set_input_syntax(GrassOptions *options, int syntax);
+
+// See [`Options::load_path`]
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`], and
+// `path` a valid string
+void grass_options_add_load_path(GrassOptions *options, const char *path);
+
+// See [`Options::quiet`]
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`]
+void grass_options_set_quiet(GrassOptions *options, bool quiet);
+
+// See [`Options::allows_charset`]
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`]
+void grass_options_set_allows_charset(GrassOptions *options, bool allows_charset);
+
+// See [`Options::unicode_error_messages`]
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`]
+void grass_options_set_unicode_error_messages(GrassOptions *options, bool unicode_error_messages);
+
+// See [`Options::precision`]
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`]
+void grass_options_set_precision(GrassOptions *options, uint8_t precision);
+
+// Adds an importer, consulted after load paths in the order importers were
+// added. See [`Options::importer`]
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`].
+// `importer` is called with `user_data` from the thread compiling the
+// stylesheet. If `options` is used by several threads at once, it may be
+// called from all of them concurrently.
+void grass_options_add_importer(GrassOptions *options, GrassImporterFn importer, void *user_data);
+
+// Replaces the default logger, which prints to standard error. See
+// [`Options::logger`]
+//
+// # Safety
+//
+// `options` must be a valid pointer returned by [`grass_options_new`].
+// `logger` is called with `user_data` from the thread compiling the
+// stylesheet. If `options` is used by several threads at once, it may be
+// called from all of them concurrently.
+void grass_options_set_logger(GrassOptions *options, GrassLoggerFn logger, void *user_data);
+
+// Reports the stylesheet loaded by an importer. `canonical_url` may be
+// `NULL`, in which case the URL passed to the importer is used. `syntax` is a
+// `GrassInputSyntax`, where `GRASS_INPUT_SYNTAX_AUTO` means SCSS
+//
+// # Safety
+//
+// `result` must be the pointer passed to the importer, and `contents` a valid
+// string
+void grass_import_result_set_contents(GrassImportResult *result, const char *contents, int syntax, const char *canonical_url);
+
+// Reports that an importer failed to load a stylesheet it recognized
+//
+// # Safety
+//
+// `result` must be the pointer passed to the importer, and `message` a valid
+// string
+void grass_import_result_set_error(GrassImportResult *result, const char *message);
+
+// Compiles `input` to CSS
+//
+// Returns the CSS, which must be freed with [`grass_string_free`], or `NULL`
+// on failure. On failure, if `error` isn't `NULL`, it's set to an error which
+// must be freed with [`grass_error_free`]. `options` may be `NULL` to use the
+// defaults.
+//
+// # Safety
+//
+// `input` must be a valid string, and `options` a valid pointer returned by
+// [`grass_options_new`] or `NULL`
+char *grass_compile_string(const char *input, const GrassOptions *options, GrassError **error);
+
+// Compiles the file at `path` to CSS
+//
+// The result and errors are returned as in [`grass_compile_string`].
+//
+// # Safety
+//
+// `path` must be a valid string, and `options` a valid pointer returned by
+// [`grass_options_new`] or `NULL`
+char *grass_compile_file(const char *path, const GrassOptions *options, GrassError **error);
+
+// Frees a string returned by grass. `s` may be `NULL`
+//
+// # Safety
+//
+// `s` must have been returned by grass and not yet freed
+void grass_string_free(char *s);
+
+// Frees an error returned by grass. `error` may be `NULL`
+//
+// # Safety
+//
+// `error` must have been returned by grass and not yet freed
+void grass_error_free(GrassError *error);
+
+// The version of grass, as a static string which must not be freed
+const char *grass_version(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* GRASS_H */
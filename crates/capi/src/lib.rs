@@ -0,0 +1,692 @@
+//! A C API for embedding grass in programs written in other languages
+//!
+//! This crate builds as a `cdylib` and `staticlib` named `grass_capi`,
+//! exporting the functions below, which are declared in `include/grass.h`.
+//! The header is generated from this file, and a test checks that it is up to
+//! date. It can be regenerated with
+//!
+//! ```bash
+//! cbindgen --config cbindgen.toml --output include/grass.h
+//! ```
+//!
+//! Build the library with the `release-capi` profile rather than `release`:
+//!
+//! ```bash
+//! cargo build -p grass-capi --profile release-capi
+//! ```
+//!
+//! The `release` profile aborts on panic, so an internal compiler error would
+//! abort the host process. With `release-capi`, it is instead reported as an
+//! error with the message "Internal compiler error.".
+//!
+//! All strings are NUL-terminated UTF-8. Strings returned by grass are owned by
+//! the caller and must be freed with [`grass_string_free`], and errors with
+//! [`grass_error_free`]. Strings passed to grass, including those passed to
+//! importer results, are copied and remain owned by the caller.
+//!
+//! Options are built with an opaque [`GrassOptions`] handle rather than a
+//! struct, so that new options can be added without breaking the ABI.
+//!
+//! ```c
+//! GrassOptions *options = grass_options_new();
+//! grass_options_set_style(options, GRASS_OUTPUT_STYLE_COMPRESSED);
+//! grass_options_add_load_path(options, "vendor");
+//!
+//! GrassError *error = NULL;
+//! char *css = grass_compile_string("a { b { color: red; } }", options, &error);
+//! if (css != NULL) {
+//!     puts(css);
+//!     grass_string_free(css);
+//! } else {
+//!     fprintf(stderr, "%s:%u:%u: %s\n", error->file, error->line, error->column, error->message);
+//!     grass_error_free(error);
+//! }
+//! grass_options_free(options);
+//! ```
+
+use std::{
+    collections::HashMap,
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    ptr,
+    sync::{Mutex, PoisonError},
+};
+
+use grass_compiler::{
+    from_path, from_string, Deprecation, Error, ErrorKind, ImportContext, Importer, ImporterResult,
+    InputSyntax, Logger, Options, OutputStyle, SpanLoc, StackFrame,
+};
+
+/// `GrassOutputStyle` values
+pub const GRASS_OUTPUT_STYLE_EXPANDED: c_int = 0;
+pub const GRASS_OUTPUT_STYLE_COMPRESSED: c_int = 1;
+
+/// `GrassInputSyntax` values. By default, the syntax is inferred from the
+/// file extension, falling back to SCSS
+pub const GRASS_INPUT_SYNTAX_AUTO: c_int = 0;
+pub const GRASS_INPUT_SYNTAX_SCSS: c_int = 1;
+pub const GRASS_INPUT_SYNTAX_SASS: c_int = 2;
+pub const GRASS_INPUT_SYNTAX_CSS: c_int = 3;
+
+/// `GrassLogLevel` values
+pub const GRASS_LOG_LEVEL_WARNING: c_int = 0;
+pub const GRASS_LOG_LEVEL_DEPRECATION: c_int = 1;
+pub const GRASS_LOG_LEVEL_DEBUG: c_int = 2;
+
+/// Loads a stylesheet that isn't found on the file system or in a load path
+///
+/// `containing_url` is the canonical URL of the stylesheet containing the
+/// rule, or a `file:` URL, and may be `NULL`. The callback reports its result
+/// with [`grass_import_result_set_contents`] or
+/// [`grass_import_result_set_error`]. If it calls neither, the URL is passed
+/// on to the next importer.
+///
+/// Options may be shared between threads, so the callback may be called from
+/// any thread compiling with them, including from several threads at once.
+/// Both the callback and `user_data` must be safe to use this way.
+pub type GrassImporterFn = Option<
+    unsafe extern "C" fn(
+        user_data: *mut c_void,
+        url: *const c_char,
+        containing_url: *const c_char,
+        from_import: bool,
+        result: *mut GrassImportResult,
+    ),
+>;
+
+/// Receives `@warn` and `@debug` messages and deprecation warnings
+///
+/// `deprecation` is the ID of the deprecation for `GRASS_LOG_LEVEL_DEPRECATION`
+/// messages, and `NULL` otherwise. `file` is `NULL` for messages without a
+/// location, in which case `line` and `column` are zero. All strings are only
+/// valid for the duration of the call.
+///
+/// Options may be shared between threads, so the callback may be called from
+/// any thread compiling with them, including from several threads at once.
+/// Both the callback and `user_data` must be safe to use this way.
+pub type GrassLoggerFn = Option<
+    unsafe extern "C" fn(
+        user_data: *mut c_void,
+        level: c_int,
+        message: *const c_char,
+        deprecation: *const c_char,
+        file: *const c_char,
+        line: u32,
+        column: u32,
+    ),
+>;
+
+/// Options for compilation, created with [`grass_options_new`]
+#[derive(Debug)]
+pub struct GrassOptions {
+    style: OutputStyle,
+    syntax: Option<InputSyntax>,
+    load_paths: Vec<PathBuf>,
+    quiet: bool,
+    allows_charset: bool,
+    unicode_error_messages: bool,
+    precision: Option<u8>,
+    importers: Vec<CImporter>,
+    logger: Option<CLogger>,
+}
+
+impl Default for GrassOptions {
+    fn default() -> Self {
+        Self {
+            style: OutputStyle::Expanded,
+            syntax: None,
+            load_paths: Vec::new(),
+            quiet: false,
+            allows_charset: true,
+            unicode_error_messages: true,
+            precision: None,
+            importers: Vec::new(),
+            logger: None,
+        }
+    }
+}
+
+/// An error returned by [`grass_compile_string`] or [`grass_compile_file`]
+#[repr(C)]
+#[derive(Debug)]
+pub struct GrassError {
+    /// The error message, without a location
+    pub message: *mut c_char,
+    /// The error as it would be printed by the grass binary, including the
+    /// source line and Sass stack trace
+    pub formatted: *mut c_char,
+    /// The name of the file the error occurred in, or `NULL`
+    pub file: *mut c_char,
+    /// The 1-based line of the error, or zero
+    pub line: u32,
+    /// The 1-based column of the error, or zero
+    pub column: u32,
+}
+
+/// The result of a [`GrassImporterFn`]
+#[derive(Debug, Default)]
+pub struct GrassImportResult {
+    result: Option<Result<(ImporterResult, Option<String>), String>>,
+}
+
+/// Copies a string passed to grass
+unsafe fn string_arg(s: *const c_char) -> Option<String> {
+    if s.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(s).to_string_lossy().into_owned())
+    }
+}
+
+/// Converts `s` to a C string, replacing interior NULs
+fn to_c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "\u{fffd}")).unwrap_or_default()
+}
+
+/// Returns an owned string to the caller, replacing interior NULs
+fn c_string(s: &str) -> *mut c_char {
+    to_c_string(s).into_raw()
+}
+
+fn syntax_from_c(syntax: c_int) -> Option<InputSyntax> {
+    match syntax {
+        GRASS_INPUT_SYNTAX_SCSS => Some(InputSyntax::Scss),
+        GRASS_INPUT_SYNTAX_SASS => Some(InputSyntax::Sass),
+        GRASS_INPUT_SYNTAX_CSS => Some(InputSyntax::Css),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+struct CImporter {
+    callback: GrassImporterFn,
+    user_data: *mut c_void,
+    /// Stylesheets returned by the callback, keyed by their canonical URL,
+    /// since the callback both resolves and loads a URL
+    loaded: Mutex<HashMap<String, ImporterResult>>,
+}
+
+// SAFETY: the caller of `grass_options_add_importer` promises that the callback
+// and its `user_data` may be used from any thread compiling with the options
+unsafe impl Send for CImporter {}
+unsafe impl Sync for CImporter {}
+
+impl Importer for CImporter {
+    fn canonicalize(&self, url: &str, context: &ImportContext) -> Result<Option<String>, String> {
+        let callback = match self.callback {
+            Some(callback) => callback,
+            None => return Ok(None),
+        };
+
+        let c_url = CString::new(url).map_err(|_| "URL contains a NUL byte.".to_owned())?;
+        let containing_url = context
+            .containing_url
+            .and_then(|url| CString::new(url).ok());
+
+        let mut result = GrassImportResult::default();
+        unsafe {
+            callback(
+                self.user_data,
+                c_url.as_ptr(),
+                containing_url
+                    .as_ref()
+                    .map_or(ptr::null(), |url| url.as_ptr()),
+                context.from_import,
+                &mut result,
+            );
+        }
+
+        match result.result {
+            Some(Ok((loaded, canonical_url))) => {
+                let canonical_url = canonical_url.unwrap_or_else(|| url.to_owned());
+                self.loaded
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(canonical_url.clone(), loaded);
+                Ok(Some(canonical_url))
+            }
+            Some(Err(message)) => Err(message),
+            None => Ok(None),
+        }
+    }
+
+    fn load(&self, canonical_url: &str) -> Result<ImporterResult, String> {
+        self.loaded
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(canonical_url)
+            .cloned()
+            .ok_or_else(|| "Can't find stylesheet to import.".to_owned())
+    }
+}
+
+#[derive(Debug)]
+struct CLogger {
+    callback: GrassLoggerFn,
+    user_data: *mut c_void,
+}
+
+// SAFETY: the caller of `grass_options_set_logger` promises that the callback
+// and its `user_data` may be used from any thread compiling with the options
+unsafe impl Send for CLogger {}
+unsafe impl Sync for CLogger {}
+
+impl CLogger {
+    fn log(
+        &self,
+        level: c_int,
+        message: &str,
+        deprecation: Option<Deprecation>,
+        location: Option<&SpanLoc>,
+    ) {
+        let callback = match self.callback {
+            Some(callback) => callback,
+            None => return,
+        };
+
+        let message = to_c_string(message);
+        let deprecation = deprecation.map(|deprecation| to_c_string(deprecation.id()));
+        let file = location.map(|loc| to_c_string(loc.file.name()));
+        let (line, column) = location.map_or((0, 0), |loc| {
+            (loc.begin.line as u32 + 1, loc.begin.column as u32 + 1)
+        });
+
+        unsafe {
+            callback(
+                self.user_data,
+                level,
+                message.as_ptr(),
+                deprecation.as_ref().map_or(ptr::null(), |id| id.as_ptr()),
+                file.as_ref().map_or(ptr::null(), |file| file.as_ptr()),
+                line,
+                column,
+            );
+        }
+    }
+}
+
+impl Logger for CLogger {
+    fn debug(&self, location: SpanLoc, message: &str, _trace: &[StackFrame]) {
+        self.log(GRASS_LOG_LEVEL_DEBUG, message, None, Some(&location));
+    }
+
+    fn warn(&self, location: SpanLoc, message: &str, _trace: &[StackFrame]) {
+        self.log(GRASS_LOG_LEVEL_WARNING, message, None, Some(&location));
+    }
+
+    fn deprecation(
+        &self,
+        deprecation: Deprecation,
+        location: SpanLoc,
+        message: &str,
+        _trace: &[StackFrame],
+    ) {
+        self.log(
+            GRASS_LOG_LEVEL_DEPRECATION,
+            message,
+            Some(deprecation),
+            Some(&location),
+        );
+    }
+
+    fn warn_without_span(&self, message: &str) {
+        self.log(GRASS_LOG_LEVEL_WARNING, message, None, None);
+    }
+}
+
+/// Creates options with the same defaults as [`Options::default`]
+#[no_mangle]
+pub extern "C" fn grass_options_new() -> *mut GrassOptions {
+    Box::into_raw(Box::default())
+}
+
+/// Frees options created with [`grass_options_new`]. `options` may be `NULL`
+///
+/// # Safety
+///
+/// `options` must have been returned by [`grass_options_new`] and not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_free(options: *mut GrassOptions) {
+    if !options.is_null() {
+        drop(Box::from_raw(options));
+    }
+}
+
+/// See [`Options::style`]. `style` is a `GrassOutputStyle`
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`]
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_set_style(options: *mut GrassOptions, style: c_int) {
+    (*options).style = if style == GRASS_OUTPUT_STYLE_COMPRESSED {
+        OutputStyle::Compressed
+    } else {
+        OutputStyle::Expanded
+    };
+}
+
+/// See [`Options::input_syntax`]. `syntax` is a `GrassInputSyntax`
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`]
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_set_input_syntax(options: *mut GrassOptions, syntax: c_int) {
+    (*options).syntax = syntax_from_c(syntax);
+}
+
+/// See [`Options::load_path`]
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`], and
+/// `path` a valid string
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_add_load_path(
+    options: *mut GrassOptions,
+    path: *const c_char,
+) {
+    if let Some(path) = string_arg(path) {
+        (*options).load_paths.push(PathBuf::from(path));
+    }
+}
+
+/// See [`Options::quiet`]
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`]
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_set_quiet(options: *mut GrassOptions, quiet: bool) {
+    (*options).quiet = quiet;
+}
+
+/// See [`Options::allows_charset`]
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`]
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_set_allows_charset(
+    options: *mut GrassOptions,
+    allows_charset: bool,
+) {
+    (*options).allows_charset = allows_charset;
+}
+
+/// See [`Options::unicode_error_messages`]
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`]
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_set_unicode_error_messages(
+    options: *mut GrassOptions,
+    unicode_error_messages: bool,
+) {
+    (*options).unicode_error_messages = unicode_error_messages;
+}
+
+/// See [`Options::precision`]
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`]
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_set_precision(options: *mut GrassOptions, precision: u8) {
+    (*options).precision = Some(precision);
+}
+
+/// Adds an importer, consulted after load paths in the order importers were
+/// added. See [`Options::importer`]
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`].
+/// `importer` is called with `user_data` from the thread compiling the
+/// stylesheet. If `options` is used by several threads at once, it may be
+/// called from all of them concurrently.
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_add_importer(
+    options: *mut GrassOptions,
+    importer: GrassImporterFn,
+    user_data: *mut c_void,
+) {
+    (*options).importers.push(CImporter {
+        callback: importer,
+        user_data,
+        loaded: Mutex::new(HashMap::new()),
+    });
+}
+
+/// Replaces the default logger, which prints to standard error. See
+/// [`Options::logger`]
+///
+/// # Safety
+///
+/// `options` must be a valid pointer returned by [`grass_options_new`].
+/// `logger` is called with `user_data` from the thread compiling the
+/// stylesheet. If `options` is used by several threads at once, it may be
+/// called from all of them concurrently.
+#[no_mangle]
+pub unsafe extern "C" fn grass_options_set_logger(
+    options: *mut GrassOptions,
+    logger: GrassLoggerFn,
+    user_data: *mut c_void,
+) {
+    (*options).logger = Some(CLogger {
+        callback: logger,
+        user_data,
+    });
+}
+
+/// Reports the stylesheet loaded by an importer. `canonical_url` may be
+/// `NULL`, in which case the URL passed to the importer is used. `syntax` is a
+/// `GrassInputSyntax`, where `GRASS_INPUT_SYNTAX_AUTO` means SCSS
+///
+/// # Safety
+///
+/// `result` must be the pointer passed to the importer, and `contents` a valid
+/// string
+#[no_mangle]
+pub unsafe extern "C" fn grass_import_result_set_contents(
+    result: *mut GrassImportResult,
+    contents: *const c_char,
+    syntax: c_int,
+    canonical_url: *const c_char,
+) {
+    (*result).result = Some(Ok((
+        ImporterResult::new(
+            string_arg(contents).unwrap_or_default(),
+            syntax_from_c(syntax).unwrap_or(InputSyntax::Scss),
+        ),
+        string_arg(canonical_url),
+    )));
+}
+
+/// Reports that an importer failed to load a stylesheet it recognized
+///
+/// # Safety
+///
+/// `result` must be the pointer passed to the importer, and `message` a valid
+/// string
+#[no_mangle]
+pub unsafe extern "C" fn grass_import_result_set_error(
+    result: *mut GrassImportResult,
+    message: *const c_char,
+) {
+    (*result).result = Some(Err(string_arg(message).unwrap_or_default()));
+}
+
+fn error_to_c(err: &Error) -> *mut GrassError {
+    let formatted = err.to_string();
+
+    let error = match err.clone().kind() {
+        ErrorKind::ParseError { message, loc, .. } => GrassError {
+            message: c_string(&message),
+            formatted: c_string(&formatted),
+            file: c_string(loc.file.name()),
+            line: loc.begin.line as u32 + 1,
+            column: loc.begin.column as u32 + 1,
+        },
+        _ => {
+            let message = formatted.trim_end();
+            GrassError {
+                message: c_string(message.strip_prefix("Error: ").unwrap_or(message)),
+                formatted: c_string(&formatted),
+                file: ptr::null_mut(),
+                line: 0,
+                column: 0,
+            }
+        }
+    };
+
+    Box::into_raw(Box::new(error))
+}
+
+fn message_to_c(message: &str) -> *mut GrassError {
+    Box::into_raw(Box::new(GrassError {
+        message: c_string(message),
+        formatted: c_string(&format!("Error: {}\n", message)),
+        file: ptr::null_mut(),
+        line: 0,
+        column: 0,
+    }))
+}
+
+/// Compiles with `options`, which may be `NULL`, writing any error to `error`
+unsafe fn compile(
+    options: *const GrassOptions,
+    error: *mut *mut GrassError,
+    compile: impl FnOnce(&Options) -> Result<String, Box<Error>>,
+) -> *mut c_char {
+    let default_options = GrassOptions::default();
+    let c_options = options.as_ref().unwrap_or(&default_options);
+
+    let mut options = Options::default()
+        .style(c_options.style)
+        .load_paths(&c_options.load_paths)
+        .quiet(c_options.quiet)
+        .allows_charset(c_options.allows_charset)
+        .unicode_error_messages(c_options.unicode_error_messages);
+
+    if let Some(syntax) = c_options.syntax {
+        options = options.input_syntax(syntax);
+    }
+
+    if let Some(precision) = c_options.precision {
+        options = options.precision(precision);
+    }
+
+    for importer in &c_options.importers {
+        options = options.importer(importer);
+    }
+
+    if let Some(logger) = &c_options.logger {
+        options = options.logger(logger);
+    }
+
+    match catch_unwind(AssertUnwindSafe(|| compile(&options))) {
+        Ok(Ok(css)) => c_string(&css),
+        Ok(Err(err)) => fail(error, error_to_c(&err)),
+        Err(..) => fail(error, message_to_c("Internal compiler error.")),
+    }
+}
+
+/// Reports `result` through `error`, if it isn't `NULL`
+unsafe fn fail(error: *mut *mut GrassError, result: *mut GrassError) -> *mut c_char {
+    if error.is_null() {
+        grass_error_free(result);
+    } else {
+        *error = result;
+    }
+
+    ptr::null_mut()
+}
+
+/// Compiles `input` to CSS
+///
+/// Returns the CSS, which must be freed with [`grass_string_free`], or `NULL`
+/// on failure. On failure, if `error` isn't `NULL`, it's set to an error which
+/// must be freed with [`grass_error_free`]. `options` may be `NULL` to use the
+/// defaults.
+///
+/// # Safety
+///
+/// `input` must be a valid string, and `options` a valid pointer returned by
+/// [`grass_options_new`] or `NULL`
+#[no_mangle]
+pub unsafe extern "C" fn grass_compile_string(
+    input: *const c_char,
+    options: *const GrassOptions,
+    error: *mut *mut GrassError,
+) -> *mut c_char {
+    let input = match input.as_ref().map(|input| CStr::from_ptr(input).to_str()) {
+        Some(Ok(input)) => input.to_owned(),
+        Some(Err(..)) => return fail(error, message_to_c("Input is not valid UTF-8.")),
+        None => return fail(error, message_to_c("Input is NULL.")),
+    };
+
+    compile(options, error, |options| from_string(input, options))
+}
+
+/// Compiles the file at `path` to CSS
+///
+/// The result and errors are returned as in [`grass_compile_string`].
+///
+/// # Safety
+///
+/// `path` must be a valid string, and `options` a valid pointer returned by
+/// [`grass_options_new`] or `NULL`
+#[no_mangle]
+pub unsafe extern "C" fn grass_compile_file(
+    path: *const c_char,
+    options: *const GrassOptions,
+    error: *mut *mut GrassError,
+) -> *mut c_char {
+    let path = match string_arg(path) {
+        Some(path) => path,
+        None => return fail(error, message_to_c("Path is NULL.")),
+    };
+
+    compile(options, error, |options| {
+        from_path(Path::new(&path), options)
+    })
+}
+
+/// Frees a string returned by grass. `s` may be `NULL`
+///
+/// # Safety
+///
+/// `s` must have been returned by grass and not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn grass_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Frees an error returned by grass. `error` may be `NULL`
+///
+/// # Safety
+///
+/// `error` must have been returned by grass and not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn grass_error_free(error: *mut GrassError) {
+    if error.is_null() {
+        return;
+    }
+
+    let error = Box::from_raw(error);
+    grass_string_free(error.message);
+    grass_string_free(error.formatted);
+    grass_string_free(error.file);
+}
+
+/// The version of grass, as a static string which must not be freed
+#[no_mangle]
+pub extern "C" fn grass_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
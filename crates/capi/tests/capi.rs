@@ -0,0 +1,413 @@
+use std::{
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    io::Write,
+    path::Path,
+    ptr,
+};
+
+use grass_capi::*;
+
+unsafe fn take_string(s: *mut c_char) -> String {
+    assert!(!s.is_null());
+    let string = CStr::from_ptr(s).to_str().unwrap().to_owned();
+    grass_string_free(s);
+    string
+}
+
+unsafe fn str_arg(s: *const c_char) -> Option<String> {
+    (!s.is_null()).then(|| CStr::from_ptr(s).to_str().unwrap().to_owned())
+}
+
+fn compile(input: &str, options: *const GrassOptions) -> Result<String, (String, u32, u32)> {
+    let input = CString::new(input).unwrap();
+    let mut error = ptr::null_mut();
+
+    unsafe {
+        let css = grass_compile_string(input.as_ptr(), options, &mut error);
+
+        if css.is_null() {
+            assert!(!error.is_null());
+            let result = (
+                str_arg((*error).message).unwrap(),
+                (*error).line,
+                (*error).column,
+            );
+            grass_error_free(error);
+            Err(result)
+        } else {
+            assert!(error.is_null());
+            Ok(take_string(css))
+        }
+    }
+}
+
+#[test]
+fn compile_string_with_default_options() {
+    assert_eq!(
+        Ok("a b {\n  color: red;\n}\n".to_owned()),
+        compile("a { b { color: red; } }", ptr::null())
+    );
+}
+
+#[test]
+fn compile_string_compressed() {
+    unsafe {
+        let options = grass_options_new();
+        grass_options_set_style(options, GRASS_OUTPUT_STYLE_COMPRESSED);
+
+        assert_eq!(
+            Ok("a b{color:red}".to_owned()),
+            compile("a { b { color: red; } }", options)
+        );
+
+        grass_options_free(options);
+    }
+}
+
+#[test]
+fn compile_string_indented_syntax() {
+    unsafe {
+        let options = grass_options_new();
+        grass_options_set_input_syntax(options, GRASS_INPUT_SYNTAX_SASS);
+
+        assert_eq!(
+            Ok("a {\n  color: red;\n}\n".to_owned()),
+            compile("a\n  color: red", options)
+        );
+
+        grass_options_free(options);
+    }
+}
+
+#[test]
+fn compile_string_precision() {
+    unsafe {
+        let options = grass_options_new();
+        grass_options_set_precision(options, 2);
+
+        assert_eq!(
+            Ok("a {\n  b: 0.33;\n}\n".to_owned()),
+            compile("@use 'sass:math';\na { b: math.div(1, 3); }", options)
+        );
+
+        grass_options_free(options);
+    }
+}
+
+#[test]
+fn error_has_location() {
+    assert_eq!(
+        Err(("Undefined variable.".to_owned(), 2, 6)),
+        compile("a {\n  b: $c;\n}", ptr::null())
+    );
+}
+
+#[test]
+fn error_without_out_pointer_returns_null() {
+    let input = CString::new("a {").unwrap();
+
+    unsafe {
+        assert!(grass_compile_string(input.as_ptr(), ptr::null(), ptr::null_mut()).is_null());
+    }
+}
+
+#[test]
+fn null_input_is_error() {
+    let mut error = ptr::null_mut();
+
+    unsafe {
+        assert!(grass_compile_string(ptr::null(), ptr::null(), &mut error).is_null());
+        assert_eq!(Some("Input is NULL.".to_owned()), str_arg((*error).message));
+        assert!((*error).file.is_null());
+        assert_eq!(0, (*error).line);
+        grass_error_free(error);
+    }
+}
+
+#[test]
+fn compile_file_with_load_path() {
+    let dir = tempfile::Builder::new()
+        .rand_bytes(10)
+        .tempdir_in(".")
+        .unwrap();
+
+    let mut library = std::fs::File::create(dir.path().join("_library.scss")).unwrap();
+    write!(library, "$color: red;").unwrap();
+
+    let input_path = dir.path().join("input.scss");
+    let mut input = std::fs::File::create(&input_path).unwrap();
+    write!(input, "@use 'library';\na {{ color: library.$color; }}").unwrap();
+
+    let path = CString::new(input_path.to_str().unwrap()).unwrap();
+    let load_path = CString::new(dir.path().to_str().unwrap()).unwrap();
+
+    unsafe {
+        let options = grass_options_new();
+        grass_options_add_load_path(options, load_path.as_ptr());
+
+        let mut error = ptr::null_mut();
+        let css = grass_compile_file(path.as_ptr(), options, &mut error);
+        assert_eq!("a {\n  color: red;\n}\n", take_string(css));
+
+        grass_options_free(options);
+    }
+}
+
+#[test]
+fn compile_missing_file_is_error() {
+    let path = CString::new("does-not-exist.scss").unwrap();
+    let mut error = ptr::null_mut();
+
+    unsafe {
+        assert!(grass_compile_file(path.as_ptr(), ptr::null(), &mut error).is_null());
+        assert!(!error.is_null());
+        grass_error_free(error);
+    }
+}
+
+#[derive(Debug, Default)]
+struct ImporterState {
+    calls: Vec<(String, Option<String>, bool)>,
+}
+
+unsafe extern "C" fn importer(
+    user_data: *mut c_void,
+    url: *const c_char,
+    containing_url: *const c_char,
+    from_import: bool,
+    result: *mut GrassImportResult,
+) {
+    let state = &mut *user_data.cast::<ImporterState>();
+    let url = str_arg(url).unwrap();
+    state
+        .calls
+        .push((url.clone(), str_arg(containing_url), from_import));
+
+    match url.as_str() {
+        "virtual/colors" => {
+            let contents = CString::new("$primary: red;").unwrap();
+            let canonical_url = CString::new("virtual:colors").unwrap();
+            grass_import_result_set_contents(
+                result,
+                contents.as_ptr(),
+                GRASS_INPUT_SYNTAX_SCSS,
+                canonical_url.as_ptr(),
+            );
+        }
+        "virtual/indented" => {
+            let contents = CString::new("a\n  color: blue").unwrap();
+            grass_import_result_set_contents(
+                result,
+                contents.as_ptr(),
+                GRASS_INPUT_SYNTAX_SASS,
+                ptr::null(),
+            );
+        }
+        "virtual/error" => {
+            let message = CString::new("virtual error").unwrap();
+            grass_import_result_set_error(result, message.as_ptr());
+        }
+        _ => {}
+    }
+}
+
+fn compile_with_importer(
+    input: &str,
+    state: &mut ImporterState,
+) -> Result<String, (String, u32, u32)> {
+    unsafe {
+        let options = grass_options_new();
+        grass_options_set_quiet(options, true);
+        grass_options_add_importer(
+            options,
+            Some(importer),
+            (state as *mut ImporterState).cast(),
+        );
+
+        let result = compile(input, options);
+
+        grass_options_free(options);
+        result
+    }
+}
+
+#[test]
+fn importer_loads_stylesheet() {
+    let mut state = ImporterState::default();
+
+    assert_eq!(
+        Ok("a {\n  color: red;\n}\n".to_owned()),
+        compile_with_importer(
+            "@use 'virtual/colors';\na { color: colors.$primary; }",
+            &mut state
+        )
+    );
+
+    assert_eq!(1, state.calls.len());
+    assert_eq!("virtual/colors", state.calls[0].0);
+    assert!(state.calls[0].1.as_deref().unwrap().starts_with("file:///"));
+    assert!(!state.calls[0].2);
+}
+
+#[test]
+fn importer_loads_indented_syntax_with_default_canonical_url() {
+    let mut state = ImporterState::default();
+
+    assert_eq!(
+        Ok("a {\n  color: blue;\n}\n".to_owned()),
+        compile_with_importer("@import 'virtual/indented';", &mut state)
+    );
+
+    assert!(state.calls[0].2);
+}
+
+#[test]
+fn importer_error() {
+    let mut state = ImporterState::default();
+
+    assert_eq!(
+        Err(("virtual error".to_owned(), 1, 1)),
+        compile_with_importer("@use 'virtual/error';", &mut state)
+    );
+}
+
+#[test]
+fn importer_not_found() {
+    let mut state = ImporterState::default();
+
+    let (message, ..) = compile_with_importer("@use 'virtual/missing';", &mut state).unwrap_err();
+    assert_eq!("Can't find stylesheet to import.", message);
+}
+
+type LogEntry = (c_int, String, Option<String>, Option<String>, u32, u32);
+
+unsafe extern "C" fn logger(
+    user_data: *mut c_void,
+    level: c_int,
+    message: *const c_char,
+    deprecation: *const c_char,
+    file: *const c_char,
+    line: u32,
+    column: u32,
+) {
+    let entries = &mut *user_data.cast::<Vec<LogEntry>>();
+    entries.push((
+        level,
+        str_arg(message).unwrap(),
+        str_arg(deprecation),
+        str_arg(file),
+        line,
+        column,
+    ));
+}
+
+#[test]
+fn logger_receives_messages() {
+    let mut entries: Vec<LogEntry> = Vec::new();
+
+    unsafe {
+        let options = grass_options_new();
+        grass_options_set_logger(
+            options,
+            Some(logger),
+            (&mut entries as *mut Vec<LogEntry>).cast(),
+        );
+
+        assert_eq!(
+            Ok(String::new()),
+            compile("@warn 'a';\n@debug 'b';", options)
+        );
+
+        grass_options_free(options);
+    }
+
+    assert_eq!(
+        (
+            GRASS_LOG_LEVEL_WARNING,
+            "\"a\"".to_owned(),
+            None,
+            Some("stdin".to_owned()),
+            1,
+            7
+        ),
+        entries[0]
+    );
+    assert_eq!(GRASS_LOG_LEVEL_DEBUG, entries[1].0);
+    assert_eq!("\"b\"", entries[1].1);
+    assert_eq!(2, entries[1].4);
+}
+
+#[test]
+fn logger_receives_deprecations() {
+    let mut entries: Vec<LogEntry> = Vec::new();
+
+    unsafe {
+        let options = grass_options_new();
+        grass_options_set_logger(
+            options,
+            Some(logger),
+            (&mut entries as *mut Vec<LogEntry>).cast(),
+        );
+
+        compile("a { $b: c !global; }", options).unwrap();
+
+        grass_options_free(options);
+    }
+
+    assert_eq!(1, entries.len());
+    assert_eq!(GRASS_LOG_LEVEL_DEPRECATION, entries[0].0);
+    assert_eq!(Some("new-global"), entries[0].2.as_deref());
+    assert_eq!(1, entries[0].4);
+}
+
+#[test]
+fn version() {
+    unsafe {
+        assert_eq!(
+            env!("CARGO_PKG_VERSION"),
+            CStr::from_ptr(grass_version()).to_str().unwrap()
+        );
+    }
+}
+
+#[test]
+fn header_declares_every_function() {
+    let source = include_str!("../src/lib.rs");
+    let header = include_str!("../include/grass.h");
+
+    let functions = source
+        .lines()
+        .filter_map(|line| line.split("extern \"C\" fn ").nth(1))
+        .map(|rest| &rest[..rest.find('(').unwrap()])
+        .collect::<Vec<_>>();
+
+    assert!(functions.len() > 10);
+
+    for function in functions {
+        assert!(
+            header.contains(&format!(" {}(", function))
+                || header.contains(&format!("*{}(", function)),
+            "`{}` is not declared in include/grass.h",
+            function
+        );
+    }
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(dir.join("cbindgen.toml")).unwrap();
+
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(dir)
+        .with_config(config)
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+
+    assert!(
+        String::from_utf8(generated).unwrap() == include_str!("../include/grass.h"),
+        "include/grass.h is out of date. Regenerate it with `cbindgen --config cbindgen.toml --output include/grass.h`"
+    );
+}
@@ -0,0 +1,922 @@
+//! Information about the declarations and references in a stylesheet, for
+//! editor tooling such as a language server
+//!
+//! [`analyze`] parses and evaluates a stylesheet the same way [`crate::from_string_with_file_name`]
+//! does, but rather than producing CSS it reports where variables, mixins,
+//! functions and module namespaces are declared and used.
+//!
+//! ```
+//! # use grass_compiler as grass;
+//! use grass::analysis::{analyze, SymbolKind};
+//!
+//! let analysis = analyze(
+//!     "$width: 10px * 2;\na { width: $width; }".to_owned(),
+//!     "input.scss".as_ref(),
+//!     &grass::Options::default(),
+//! );
+//!
+//! assert!(analysis.error.is_none());
+//!
+//! let reference = &analysis.references[0];
+//! assert_eq!(SymbolKind::Variable, reference.kind);
+//! assert_eq!("width", reference.name);
+//!
+//! let definition = reference.definition.as_ref().unwrap();
+//! assert_eq!(0, definition.location.begin.line);
+//! assert_eq!(Some("20px"), definition.value.as_deref());
+//! ```
+//!
+//! This API is experimental, and may change in minor releases.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use codemap::{CodeMap, Span, SpanLoc, Spanned};
+
+use crate::{
+    ast::*, builtin::modules::Module, common::Identifier, error::SassError, evaluate::Visitor,
+    parse_stylesheet, raw_to_parse_error, utils::MapView, value, value::Value, Options,
+};
+
+/// The result of analyzing a stylesheet
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Analysis {
+    /// The error that stopped parsing or evaluation, if any
+    ///
+    /// If parsing failed, no other information is available. If evaluation
+    /// failed, modules loaded after the error are missing.
+    pub error: Option<Box<SassError>>,
+
+    /// The variables, mixins, functions and module namespaces declared in the
+    /// stylesheet, in source order
+    pub symbols: Vec<Symbol>,
+
+    /// Every use of a variable, mixin, function or module namespace in the
+    /// stylesheet, in source order
+    pub references: Vec<Reference>,
+
+    /// The modules loaded by the stylesheet's `@use` rules
+    pub modules: Vec<ModuleInfo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Variable,
+    Mixin,
+    Function,
+    /// The namespace of a module loaded with `@use`
+    Namespace,
+}
+
+/// A declaration of a member or module namespace
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Symbol {
+    pub kind: SymbolKind,
+
+    /// The name of the member, without the leading `$` of variables
+    pub name: String,
+
+    /// The location of the name, or of the whole `@use` rule for namespaces
+    pub location: SpanLoc,
+
+    /// For variables, the last value assigned by this declaration, formatted
+    /// like `meta.inspect(..)`. This is `None` if the declaration was never
+    /// evaluated
+    pub value: Option<String>,
+}
+
+/// A use of a member or module namespace
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Reference {
+    pub kind: SymbolKind,
+
+    /// The namespace the member was referenced through, if any
+    pub namespace: Option<String>,
+
+    /// The name of the member, without the leading `$` of variables
+    pub name: String,
+
+    /// The location of the name
+    pub location: SpanLoc,
+
+    /// The declaration this refers to, which may be in another file
+    ///
+    /// This is `None` for built-in members, plain CSS functions, parameters
+    /// and loop variables, and references that could not be resolved
+    pub definition: Option<Symbol>,
+}
+
+/// A module loaded by `@use`, along with its public members
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ModuleInfo {
+    /// The namespace of the module, or `None` for `@use "..." as *`
+    pub namespace: Option<String>,
+
+    /// The URL of the module, as written in the `@use` rule
+    pub url: String,
+
+    /// The file the module was loaded from, or `None` for built-in modules and
+    /// modules that could not be loaded
+    pub path: Option<PathBuf>,
+
+    /// The names and values of the module's variables, as returned by
+    /// `meta.module-variables(..)`
+    pub variables: Vec<(String, String)>,
+
+    /// The names of the module's functions, as returned by
+    /// `meta.module-functions(..)`
+    pub functions: Vec<String>,
+
+    /// The names of the module's mixins
+    pub mixins: Vec<String>,
+}
+
+/// Analyze `input` as if it were the contents of the file at `path`
+///
+/// Imports are resolved relative to `path` and read through [`Options::fs`],
+/// and warnings are reported to [`Options::logger`], just as they are during
+/// compilation.
+pub fn analyze(input: String, path: &Path, options: &Options) -> Analysis {
+    value::with_precision(options.precision, || {
+        analyze_with_file_name(input, path, options)
+    })
+}
+
+fn analyze_with_file_name(input: String, path: &Path, options: &Options) -> Analysis {
+    let mut map = CodeMap::new();
+
+    let (stylesheet, empty_span) = match parse_stylesheet(&mut map, input, path, options) {
+        Ok(v) => v,
+        Err(e) => {
+            return Analysis {
+                error: Some(e),
+                symbols: Vec::new(),
+                references: Vec::new(),
+                modules: Vec::new(),
+            }
+        }
+    };
+
+    let mut visitor = Visitor::new(path, options, &mut map, empty_span);
+    visitor.recorder = Some(Recorder::default());
+
+    let result = visitor.visit_stylesheet(stylesheet.clone());
+
+    let recorder = visitor.recorder.take().unwrap_or_default();
+    let modules = Arc::clone(&visitor.env.modules);
+    let global_modules = visitor.env.global_modules.clone();
+    drop(visitor);
+
+    let error = result
+        .err()
+        .map(|e| raw_to_parse_error(&map, *e, options.unicode_error_messages));
+
+    let mut collector = Collector {
+        map: &map,
+        recorder: &recorder,
+        path,
+        symbols: Vec::new(),
+        references: Vec::new(),
+        scopes: vec![HashMap::new()],
+        deferred: Vec::new(),
+        uses: Vec::new(),
+        imports: Vec::new(),
+    };
+
+    collector.visit_body(&stylesheet.body);
+    collector.resolve_deferred();
+
+    let mut global_modules = global_modules.into_iter();
+
+    let module_infos = stylesheet
+        .body
+        .iter()
+        .filter_map(|stmt| match stmt {
+            AstStmt::Use(use_rule) => Some(use_rule),
+            _ => None,
+        })
+        .map(|use_rule| {
+            let namespace = use_rule
+                .namespace
+                .as_deref()
+                .map(|namespace| namespace.trim_start_matches("sass:"));
+
+            let module = match namespace {
+                Some(namespace) => (*modules)
+                    .borrow()
+                    .get(Identifier::from(namespace), use_rule.span)
+                    .ok(),
+                None => global_modules.next(),
+            };
+
+            collector.module_info(use_rule, namespace, module)
+        })
+        .collect();
+
+    Analysis {
+        error,
+        symbols: collector.symbols,
+        references: collector.references,
+        modules: module_infos,
+    }
+}
+
+/// Information collected by the [`Visitor`] while evaluating a stylesheet
+/// that is being analyzed
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    /// The last value assigned by each variable declaration, keyed by the span
+    /// of the declaration
+    pub variable_values: HashMap<Span, Value>,
+
+    /// Every stylesheet loaded by `@use`, `@forward` or `@import`
+    pub loaded: Vec<LoadedStyleSheet>,
+}
+
+#[derive(Debug)]
+pub(crate) struct LoadedStyleSheet {
+    /// The file containing the rule that loaded the stylesheet
+    pub from: PathBuf,
+
+    /// The URL as written in the rule
+    pub url: String,
+
+    pub style_sheet: StyleSheet,
+}
+
+/// A member declared by another module, keyed by the name it is visible as
+type Member = (SymbolKind, Identifier, Symbol);
+
+struct Use {
+    namespace: Option<Identifier>,
+    members: Vec<Member>,
+}
+
+struct Collector<'a> {
+    map: &'a CodeMap,
+    recorder: &'a Recorder,
+    path: &'a Path,
+    symbols: Vec<Symbol>,
+    references: Vec<Reference>,
+    /// Lexical scopes, mapping names to their declaration in `symbols`.
+    /// Parameters and loop variables have no declaration
+    scopes: Vec<HashMap<(SymbolKind, Identifier), Option<usize>>>,
+    /// References to names that were not declared in an enclosing scope when
+    /// they were visited, by their index in `references`
+    deferred: Vec<(usize, SymbolKind, Identifier)>,
+    uses: Vec<Use>,
+    /// The URLs of every `@import` in the stylesheet
+    imports: Vec<String>,
+}
+
+impl<'a> Collector<'a> {
+    fn visit_body(&mut self, body: &[AstStmt]) {
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn scoped(&mut self, callback: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        callback(self);
+        self.scopes.pop();
+    }
+
+    fn visit_stmt(&mut self, stmt: &AstStmt) {
+        match stmt {
+            AstStmt::If(if_rule) => {
+                for clause in &if_rule.if_clauses {
+                    self.visit_expr(&clause.condition.node);
+                    self.scoped(|this| this.visit_body(&clause.body));
+                }
+
+                if let Some(body) = &if_rule.else_clause {
+                    self.scoped(|this| this.visit_body(body));
+                }
+            }
+            AstStmt::For(for_rule) => {
+                self.visit_expr(&for_rule.from.node);
+                self.visit_expr(&for_rule.to.node);
+                self.scoped(|this| {
+                    this.declare(SymbolKind::Variable, for_rule.variable.node, None, false);
+                    this.visit_body(&for_rule.body);
+                });
+            }
+            AstStmt::Each(each_rule) => {
+                self.visit_expr(&each_rule.list);
+                self.scoped(|this| {
+                    for &variable in &each_rule.variables {
+                        this.declare(SymbolKind::Variable, variable, None, false);
+                    }
+                    this.visit_body(&each_rule.body);
+                });
+            }
+            AstStmt::While(while_rule) => {
+                self.visit_expr(&while_rule.condition.node);
+                self.scoped(|this| this.visit_body(&while_rule.body));
+            }
+            AstStmt::Return(AstReturn { val, .. })
+            | AstStmt::Warn(AstWarn { value: val, .. })
+            | AstStmt::ErrorRule(AstErrorRule { value: val, .. })
+            | AstStmt::Debug(AstDebugRule { value: val, .. }) => self.visit_expr(val),
+            AstStmt::RuleSet(rule_set) => {
+                self.visit_interpolation(&rule_set.selector);
+                self.scoped(|this| this.visit_body(&rule_set.body));
+            }
+            AstStmt::Style(style) => {
+                self.visit_interpolation(&style.name);
+                if let Some(value) = &style.value {
+                    self.visit_expr(&value.node);
+                }
+                self.scoped(|this| this.visit_body(&style.body));
+            }
+            AstStmt::Media(media) => {
+                self.visit_interpolation(&media.query);
+                self.scoped(|this| this.visit_body(&media.body));
+            }
+            AstStmt::Supports(supports) => {
+                self.visit_supports_condition(&supports.condition);
+                self.scoped(|this| this.visit_body(&supports.children));
+            }
+            AstStmt::AtRootRule(at_root) => {
+                if let Some(query) = &at_root.query {
+                    self.visit_interpolation(&query.node);
+                }
+                self.scoped(|this| this.visit_body(&at_root.children));
+            }
+            AstStmt::UnknownAtRule(rule) => {
+                self.visit_interpolation(&rule.name);
+                if let Some(value) = &rule.value {
+                    self.visit_interpolation(value);
+                }
+                if let Some(children) = &rule.children {
+                    self.scoped(|this| this.visit_body(children));
+                }
+            }
+            AstStmt::Extend(extend) => self.visit_interpolation(&extend.value),
+            AstStmt::LoudComment(comment) => self.visit_interpolation(&comment.text),
+            AstStmt::SilentComment(..) => {}
+            AstStmt::Include(include) => {
+                self.visit_argument_invocation(&include.args);
+
+                let name_span = suffix(include.name.span, include.name.node.as_str().len());
+                self.reference(
+                    SymbolKind::Mixin,
+                    include.namespace,
+                    include.name.node,
+                    name_span,
+                );
+
+                if let Some(content) = &include.content {
+                    self.scoped(|this| {
+                        this.declare_arguments(&content.args);
+                        this.visit_body(&content.body);
+                    });
+                }
+            }
+            AstStmt::ContentRule(content) => self.visit_argument_invocation(&content.args),
+            AstStmt::VariableDecl(decl) => {
+                self.visit_expr(&decl.value);
+
+                // the name, including the `$`, follows the namespace and `.`
+                let offset = decl
+                    .namespace
+                    .map_or(0, |namespace| namespace.span.high() - decl.span.low() + 1);
+                let name_span = prefix(
+                    decl.span,
+                    offset,
+                    offset + 1 + decl.name.as_str().len() as u64,
+                );
+
+                if decl.namespace.is_some() {
+                    self.reference(SymbolKind::Variable, decl.namespace, decl.name, name_span);
+                } else {
+                    let symbol = self.variable_symbol(decl, name_span);
+                    self.declare(
+                        SymbolKind::Variable,
+                        decl.name,
+                        Some(symbol),
+                        decl.is_global,
+                    );
+                }
+            }
+            AstStmt::FunctionDecl(function) => {
+                let symbol =
+                    self.symbol(SymbolKind::Function, function.name.node, function.name.span);
+                self.declare(
+                    SymbolKind::Function,
+                    function.name.node,
+                    Some(symbol),
+                    false,
+                );
+                self.scoped(|this| {
+                    this.declare_arguments(&function.arguments);
+                    this.visit_body(&function.children);
+                });
+            }
+            AstStmt::Mixin(mixin) => {
+                let symbol = self.symbol(SymbolKind::Mixin, mixin.name.node, mixin.name.span);
+                self.declare(SymbolKind::Mixin, mixin.name.node, Some(symbol), false);
+                self.scoped(|this| {
+                    this.declare_arguments(&mixin.args);
+                    this.visit_body(&mixin.body);
+                });
+            }
+            AstStmt::ImportRule(import_rule) => {
+                for import in &import_rule.imports {
+                    match import {
+                        AstImport::Sass(import) => self.imports.push(import.url.clone()),
+                        AstImport::Plain(import) => {
+                            self.visit_interpolation(&import.url);
+                            if let Some(modifiers) = &import.modifiers {
+                                self.visit_interpolation(modifiers);
+                            }
+                        }
+                    }
+                }
+            }
+            AstStmt::Use(use_rule) => {
+                for variable in &use_rule.configuration {
+                    self.visit_expr(&variable.expr.node);
+                }
+
+                let url = use_rule.url.to_string_lossy();
+                let members = self.module_members(self.path, &url, &mut HashSet::new());
+                let namespace = use_rule
+                    .namespace
+                    .as_deref()
+                    .map(|namespace| Identifier::from(namespace.trim_start_matches("sass:")));
+
+                if let Some(namespace) = namespace {
+                    let symbol = self.symbol(SymbolKind::Namespace, namespace, use_rule.span);
+                    self.declare(SymbolKind::Namespace, namespace, Some(symbol), true);
+                }
+
+                self.uses.push(Use { namespace, members });
+            }
+            AstStmt::Forward(forward_rule) => {
+                for variable in &forward_rule.configuration {
+                    self.visit_expr(&variable.expr.node);
+                }
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &AstExpr) {
+        match expr {
+            AstExpr::BinaryOp(op) => {
+                self.visit_expr(&op.lhs);
+                self.visit_expr(&op.rhs);
+            }
+            AstExpr::Calculation { args, .. } => {
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+            }
+            AstExpr::FunctionCall(call) => {
+                self.visit_argument_invocation(&call.arguments);
+
+                let offset = call
+                    .namespace
+                    .map_or(0, |namespace| namespace.span.high() - call.span.low() + 1);
+                let name_span = prefix(call.span, offset, offset + call.name.as_str().len() as u64);
+
+                self.reference(SymbolKind::Function, call.namespace, call.name, name_span);
+            }
+            AstExpr::If(ternary) => self.visit_argument_invocation(&ternary.0),
+            AstExpr::InterpolatedFunction(function) => {
+                self.visit_interpolation(&function.name);
+                self.visit_argument_invocation(&function.arguments);
+            }
+            AstExpr::List(list) => {
+                for elem in &list.elems {
+                    self.visit_expr(&elem.node);
+                }
+            }
+            AstExpr::Map(map) => {
+                for (key, value) in &map.0 {
+                    self.visit_expr(&key.node);
+                    self.visit_expr(value);
+                }
+            }
+            AstExpr::Paren(inner) | AstExpr::UnaryOp(_, inner, _) => self.visit_expr(inner),
+            AstExpr::String(StringExpr(interpolation, ..), ..) => {
+                self.visit_interpolation(interpolation);
+            }
+            AstExpr::Supports(condition) => self.visit_supports_condition(condition),
+            AstExpr::Variable { name, namespace } => {
+                self.reference(SymbolKind::Variable, *namespace, name.node, name.span);
+            }
+            AstExpr::True
+            | AstExpr::False
+            | AstExpr::Null
+            | AstExpr::Color(..)
+            | AstExpr::Number { .. }
+            | AstExpr::ParentSelector => {}
+        }
+    }
+
+    fn visit_interpolation(&mut self, interpolation: &Interpolation) {
+        for part in &interpolation.contents {
+            if let InterpolationPart::Expr(expr) = part {
+                self.visit_expr(&expr.node);
+            }
+        }
+    }
+
+    fn visit_argument_invocation(&mut self, arguments: &ArgumentInvocation) {
+        for arg in &arguments.positional {
+            self.visit_expr(arg);
+        }
+
+        for arg in arguments.named.values() {
+            self.visit_expr(arg);
+        }
+
+        for arg in arguments.rest.iter().chain(&arguments.keyword_rest) {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_supports_condition(&mut self, condition: &AstSupportsCondition) {
+        match condition {
+            AstSupportsCondition::Anything { contents } => self.visit_interpolation(contents),
+            AstSupportsCondition::Declaration { name, value } => {
+                self.visit_expr(name);
+                self.visit_expr(value);
+            }
+            AstSupportsCondition::Function { name, args } => {
+                self.visit_interpolation(name);
+                self.visit_interpolation(args);
+            }
+            AstSupportsCondition::Interpolation(expr) => self.visit_expr(expr),
+            AstSupportsCondition::Negation(inner) => self.visit_supports_condition(inner),
+            AstSupportsCondition::Operation { left, right, .. } => {
+                self.visit_supports_condition(left);
+                self.visit_supports_condition(right);
+            }
+        }
+    }
+
+    /// Declare the parameters of a mixin, function or content block in the
+    /// current scope
+    fn declare_arguments(&mut self, arguments: &ArgumentDeclaration) {
+        for arg in &arguments.args {
+            if let Some(default) = &arg.default {
+                self.visit_expr(default);
+            }
+            self.declare(SymbolKind::Variable, arg.name, None, false);
+        }
+
+        if let Some(rest) = arguments.rest {
+            self.declare(SymbolKind::Variable, rest, None, false);
+        }
+    }
+
+    fn declare(
+        &mut self,
+        kind: SymbolKind,
+        name: Identifier,
+        symbol: Option<Symbol>,
+        is_global: bool,
+    ) {
+        let index = symbol.map(|symbol| {
+            self.symbols.push(symbol);
+            self.symbols.len() - 1
+        });
+
+        // assigning to a variable declared in an enclosing local scope doesn't
+        // declare a new one
+        if kind == SymbolKind::Variable
+            && !is_global
+            && self.scopes[1..]
+                .iter()
+                .any(|scope| scope.contains_key(&(kind, name)))
+        {
+            return;
+        }
+
+        let scope = if is_global {
+            &mut self.scopes[0]
+        } else {
+            self.scopes.last_mut().unwrap()
+        };
+
+        scope.entry((kind, name)).or_insert(index);
+    }
+
+    fn reference(
+        &mut self,
+        kind: SymbolKind,
+        namespace: Option<Spanned<Identifier>>,
+        name: Identifier,
+        span: Span,
+    ) {
+        let definition = match namespace {
+            Some(namespace) => {
+                let definition = self
+                    .lookup(SymbolKind::Namespace, namespace.node)
+                    .flatten()
+                    .map(|index| self.symbols[index].clone());
+
+                self.references.push(Reference {
+                    kind: SymbolKind::Namespace,
+                    namespace: None,
+                    name: namespace.node.to_string(),
+                    location: self.map.look_up_span(namespace.span),
+                    definition,
+                });
+
+                self.uses
+                    .iter()
+                    .find(|module| module.namespace == Some(namespace.node))
+                    .and_then(|module| find_member(&module.members, kind, name))
+            }
+            None => match self.lookup(kind, name) {
+                Some(index) => index.map(|index| self.symbols[index].clone()),
+                None => {
+                    self.deferred.push((self.references.len(), kind, name));
+                    None
+                }
+            },
+        };
+
+        self.references.push(Reference {
+            kind,
+            namespace: namespace.map(|namespace| namespace.node.to_string()),
+            name: name.to_string(),
+            location: self.map.look_up_span(span),
+            definition,
+        });
+    }
+
+    fn lookup(&self, kind: SymbolKind, name: Identifier) -> Option<Option<usize>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&(kind, name)).copied())
+    }
+
+    /// Resolve references to members that are declared later in the
+    /// stylesheet, or that come from modules used with `as *` or from
+    /// imported files
+    fn resolve_deferred(&mut self) {
+        let mut imported = None;
+
+        for (index, kind, name) in std::mem::take(&mut self.deferred) {
+            let definition = if let Some(declaration) = self.scopes[0].get(&(kind, name)) {
+                declaration.map(|declaration| self.symbols[declaration].clone())
+            } else if let Some(member) = self
+                .uses
+                .iter()
+                .filter(|module| module.namespace.is_none())
+                .find_map(|module| find_member(&module.members, kind, name))
+            {
+                Some(member)
+            } else {
+                let imported = imported.get_or_insert_with(|| {
+                    let mut visited = HashSet::new();
+                    self.imports
+                        .iter()
+                        .flat_map(|url| self.imported_members(self.path, url, &mut visited))
+                        .collect::<Vec<_>>()
+                });
+
+                find_member(imported, kind, name)
+            };
+
+            self.references[index].definition = definition;
+        }
+    }
+
+    fn module_info(
+        &self,
+        use_rule: &AstUseRule,
+        namespace: Option<&str>,
+        module: Option<Arc<RefCell<Module>>>,
+    ) -> ModuleInfo {
+        let url = use_rule.url.to_string_lossy().into_owned();
+
+        let path = self
+            .loaded(self.path, &url)
+            .map(|loaded| loaded.style_sheet.url.clone());
+
+        let mut info = ModuleInfo {
+            namespace: namespace.map(ToOwned::to_owned),
+            url,
+            path,
+            variables: Vec::new(),
+            functions: Vec::new(),
+            mixins: Vec::new(),
+        };
+
+        if let Some(module) = module {
+            let scope = (*module).borrow().scope();
+
+            // not every view over forwarded members supports iteration, so
+            // look each value up by name
+            info.variables = scope
+                .variables
+                .keys()
+                .into_iter()
+                .filter(Identifier::is_public)
+                .map(|name| {
+                    let value = scope
+                        .variables
+                        .get(name)
+                        .and_then(|value| value.inspect(use_rule.span).ok())
+                        .unwrap_or_default();
+                    (name.to_string(), value)
+                })
+                .collect();
+
+            info.functions = public_names(scope.functions.keys());
+            info.mixins = public_names(scope.mixins.keys());
+        }
+
+        info
+    }
+
+    fn loaded(&self, from: &Path, url: &str) -> Option<&'a LoadedStyleSheet> {
+        self.recorder
+            .loaded
+            .iter()
+            .find(|loaded| loaded.from == from && loaded.url == url)
+    }
+
+    /// The public members of the module loaded by `url` from `from`, including
+    /// those it forwards
+    fn module_members(
+        &self,
+        from: &Path,
+        url: &str,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Vec<Member> {
+        let loaded = match self.loaded(from, url) {
+            Some(loaded) if visited.insert(loaded.style_sheet.url.clone()) => loaded,
+            _ => return Vec::new(),
+        };
+
+        let mut members: Vec<Member> = self
+            .top_level_members(&loaded.style_sheet)
+            .into_iter()
+            .filter(|(_, name, _)| name.is_public())
+            .collect();
+
+        for stmt in &loaded.style_sheet.body {
+            let forward_rule = match stmt {
+                AstStmt::Forward(forward_rule) => forward_rule,
+                _ => continue,
+            };
+
+            let forwarded = self.module_members(
+                &loaded.style_sheet.url,
+                &forward_rule.url.to_string_lossy(),
+                visited,
+            );
+
+            for (kind, name, symbol) in forwarded {
+                let name = match &forward_rule.prefix {
+                    Some(prefix) => Identifier::from(format!("{}{}", prefix, name)),
+                    None => name,
+                };
+
+                let (shown, hidden) = if kind == SymbolKind::Variable {
+                    (
+                        &forward_rule.shown_variables,
+                        &forward_rule.hidden_variables,
+                    )
+                } else {
+                    (
+                        &forward_rule.shown_mixins_and_functions,
+                        &forward_rule.hidden_mixins_and_functions,
+                    )
+                };
+
+                let is_visible = !matches!(shown, Some(shown) if !shown.contains(&name))
+                    && !matches!(hidden, Some(hidden) if hidden.contains(&name));
+
+                if is_visible {
+                    members.push((kind, name, symbol));
+                }
+            }
+        }
+
+        members
+    }
+
+    /// The members of the file imported by `url` from `from`, including those
+    /// of the files it imports
+    fn imported_members(
+        &self,
+        from: &Path,
+        url: &str,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Vec<Member> {
+        let loaded = match self.loaded(from, url) {
+            Some(loaded) if visited.insert(loaded.style_sheet.url.clone()) => loaded,
+            _ => return Vec::new(),
+        };
+
+        let mut members = self.top_level_members(&loaded.style_sheet);
+
+        for stmt in &loaded.style_sheet.body {
+            if let AstStmt::ImportRule(import_rule) = stmt {
+                for import in &import_rule.imports {
+                    if let AstImport::Sass(import) = import {
+                        members.extend(self.imported_members(
+                            &loaded.style_sheet.url,
+                            &import.url,
+                            visited,
+                        ));
+                    }
+                }
+            }
+        }
+
+        members
+    }
+
+    /// The variables, mixins and functions declared at the root of a
+    /// stylesheet other than the one being analyzed
+    fn top_level_members(&self, style_sheet: &StyleSheet) -> Vec<Member> {
+        let mut members = Vec::new();
+
+        for stmt in &style_sheet.body {
+            match stmt {
+                AstStmt::VariableDecl(decl) if decl.namespace.is_none() => {
+                    let name_span = prefix(decl.span, 0, 1 + decl.name.as_str().len() as u64);
+                    let symbol = self.variable_symbol(decl, name_span);
+                    members.push((SymbolKind::Variable, decl.name, symbol));
+                }
+                AstStmt::FunctionDecl(function) => {
+                    let symbol =
+                        self.symbol(SymbolKind::Function, function.name.node, function.name.span);
+                    members.push((SymbolKind::Function, function.name.node, symbol));
+                }
+                AstStmt::Mixin(mixin) => {
+                    let symbol = self.symbol(SymbolKind::Mixin, mixin.name.node, mixin.name.span);
+                    members.push((SymbolKind::Mixin, mixin.name.node, symbol));
+                }
+                _ => {}
+            }
+        }
+
+        members
+    }
+
+    fn symbol(&self, kind: SymbolKind, name: Identifier, span: Span) -> Symbol {
+        Symbol {
+            kind,
+            name: name.to_string(),
+            location: self.map.look_up_span(span),
+            value: None,
+        }
+    }
+
+    fn variable_symbol(&self, decl: &AstVariableDecl, name_span: Span) -> Symbol {
+        Symbol {
+            value: self
+                .recorder
+                .variable_values
+                .get(&decl.span)
+                .and_then(|value| value.inspect(decl.span).ok()),
+            ..self.symbol(SymbolKind::Variable, decl.name, name_span)
+        }
+    }
+}
+
+fn find_member(members: &[Member], kind: SymbolKind, name: Identifier) -> Option<Symbol> {
+    members
+        .iter()
+        .find(|(member_kind, member_name, _)| *member_kind == kind && *member_name == name)
+        .map(|(.., symbol)| symbol.clone())
+}
+
+fn public_names(names: Vec<Identifier>) -> Vec<String> {
+    names
+        .into_iter()
+        .filter(Identifier::is_public)
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// The part of `span` from `start` to `end`, clamped to the span so that
+/// names containing escapes can't produce an out of bounds span
+pub(crate) fn prefix(span: Span, start: u64, end: u64) -> Span {
+    let end = end.min(span.len());
+    span.subspan(start.min(end), end)
+}
+
+/// The last `len` bytes of `span`
+fn suffix(span: Span, len: usize) -> Span {
+    span.subspan(span.len().saturating_sub(len as u64), span.len())
+}
@@ -225,7 +225,11 @@ impl ArgumentResult {
             if min == 1 {
                 return Err(("At least one argument must be passed.", self.span()).into());
             }
-            todo!("min args greater than one")
+            return Err((
+                format!("At least {} arguments must be passed.", min),
+                self.span(),
+            )
+                .into());
         }
         Ok(())
     }
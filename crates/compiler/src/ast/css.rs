@@ -10,6 +10,8 @@ pub(crate) enum CssStmt {
         selector: ExtendedSelector,
         body: Vec<Self>,
         is_group_end: bool,
+        /// The span of the selector, used for source maps
+        span: Span,
     },
     Style(Style),
     Media(MediaRule, bool),
@@ -71,11 +73,13 @@ impl CssStmt {
             CssStmt::RuleSet {
                 selector,
                 is_group_end,
+                span,
                 ..
             } => CssStmt::RuleSet {
                 selector: selector.clone(),
                 body: Vec::new(),
                 is_group_end: *is_group_end,
+                span: *span,
             },
             CssStmt::Style(..) | CssStmt::Comment(..) | CssStmt::Import(..) => unreachable!(),
             CssStmt::Media(media, is_group_end) => CssStmt::Media(
@@ -40,11 +40,12 @@ pub(crate) struct AstSassImport {
 pub(crate) struct AstIf {
     pub if_clauses: Vec<AstIfClause>,
     pub else_clause: Option<Vec<AstStmt>>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct AstIfClause {
-    pub condition: AstExpr,
+    pub condition: Spanned<AstExpr>,
     pub body: Vec<AstStmt>,
 }
 
@@ -60,7 +61,6 @@ pub(crate) struct AstFor {
 #[derive(Debug, Clone)]
 pub(crate) struct AstReturn {
     pub val: AstExpr,
-    #[allow(unused)]
     pub span: Span,
 }
 
@@ -90,7 +90,9 @@ impl AstStyle {
 pub(crate) struct AstEach {
     pub variables: Vec<Identifier>,
     pub list: AstExpr,
+    pub list_span: Span,
     pub body: Vec<AstStmt>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -105,7 +107,7 @@ pub(crate) type CssMediaQuery = MediaQuery;
 
 #[derive(Debug, Clone)]
 pub(crate) struct AstWhile {
-    pub condition: AstExpr,
+    pub condition: Spanned<AstExpr>,
     pub body: Vec<AstStmt>,
 }
 
@@ -160,7 +162,7 @@ pub(crate) struct AstLoudComment {
 
 #[derive(Debug, Clone)]
 pub(crate) struct AstMixin {
-    pub name: Identifier,
+    pub name: Spanned<Identifier>,
     pub args: ArgumentDeclaration,
     pub body: Vec<AstStmt>,
     /// Whether the mixin contains a `@content` rule.
@@ -170,6 +172,7 @@ pub(crate) struct AstMixin {
 #[derive(Debug, Clone)]
 pub(crate) struct AstContentRule {
     pub args: ArgumentInvocation,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -268,6 +271,7 @@ impl Default for AtRootQuery {
 #[derive(Debug, Clone)]
 pub(crate) struct AstImportRule {
     pub imports: Vec<AstImport>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
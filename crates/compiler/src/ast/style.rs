@@ -1,4 +1,4 @@
-use codemap::Spanned;
+use codemap::{Span, Spanned};
 
 use crate::{interner::InternedString, value::Value};
 
@@ -8,4 +8,6 @@ pub(crate) struct Style {
     pub property: InternedString,
     pub value: Box<Spanned<Value>>,
     pub declared_as_custom_property: bool,
+    /// The span of the declaration, used for source maps
+    pub span: Span,
 }
@@ -35,9 +35,9 @@ fn hsl_3_args(
                 name,
                 Value::List(
                     if args.len() == 4 {
-                        vec![hue, saturation, lightness, alpha]
+                        vec![hue, saturation, lightness, alpha].into()
                     } else {
-                        vec![hue, saturation, lightness]
+                        vec![hue, saturation, lightness].into()
                     },
                     ListSeparator::Comma,
                     Brackets::None
@@ -196,7 +196,6 @@ fn darken(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value>
         .assert_color_with_name("color", args.span())?;
 
     let amount = match args.get_err(1, "amount")? {
-        Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
         Value::Dimension(SassNumber {
             num: n,
             unit: u,
@@ -233,7 +232,6 @@ fn saturate(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value
     }
 
     let amount = match args.get_err(1, "amount")? {
-        Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
         Value::Dimension(SassNumber {
             num: n,
             unit: u,
@@ -280,7 +278,6 @@ fn desaturate(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Val
         .get_err(0, "color")?
         .assert_color_with_name("color", args.span())?;
     let amount = match args.get_err(1, "amount")? {
-        Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
         Value::Dimension(SassNumber {
             num: n,
             unit: u,
@@ -336,10 +333,6 @@ pub(crate) fn complement(mut args: ArgumentResult, visitor: &mut Visitor) -> Sas
 pub(crate) fn invert(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value> {
     args.max_args(2)?;
     let weight = match args.get(1, "weight") {
-        Some(Spanned {
-            node: Value::Dimension(SassNumber { num: n, .. }),
-            ..
-        }) if n.is_nan() => todo!(),
         Some(Spanned {
             node:
                 Value::Dimension(SassNumber {
@@ -35,7 +35,6 @@ fn hwb_inner(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Valu
 
     let hue = match args.get(0, "hue") {
         Some(v) => match v.node {
-            Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
             Value::Dimension(SassNumber { num: n, .. }) => n,
             v => {
                 return Err((
@@ -60,7 +59,6 @@ fn hwb_inner(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Valu
 
     let alpha = match args.get(3, "alpha") {
         Some(v) => match v.node {
-            Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
             Value::Dimension(SassNumber {
                 num: n,
                 unit: Unit::Percent,
@@ -71,7 +71,6 @@ pub(crate) fn alpha(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResu
 pub(crate) fn opacity(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value> {
     args.max_args(1)?;
     match args.get_err(0, "color")? {
-        Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
         Value::Color(c) => Ok(Value::Dimension(SassNumber {
             num: c.alpha(),
             unit: Unit::None,
@@ -113,7 +112,6 @@ fn transparentize(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult
         .get_err(0, "color")?
         .assert_color_with_name("color", args.span())?;
     let amount = match args.get_err(1, "amount")? {
-        Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
         Value::Dimension(SassNumber {
             num: n,
             unit: u,
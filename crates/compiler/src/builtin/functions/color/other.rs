@@ -3,7 +3,6 @@ use crate::builtin::builtin_imports::*;
 macro_rules! opt_rgba {
     ($args:ident, $name:ident, $arg:literal, $low:literal, $high:literal) => {
         let $name = match $args.default_named_arg($arg, Value::Null) {
-            Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
             Value::Dimension(SassNumber {
                 num: n, unit: u, ..
             }) => Some(bound!($args, $arg, n, u, $low, $high)),
@@ -22,7 +21,6 @@ macro_rules! opt_rgba {
 macro_rules! opt_hsl {
     ($args:ident, $name:ident, $arg:literal, $low:literal, $high:literal) => {
         let $name = match $args.default_named_arg($arg, Value::Null) {
-            Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
             Value::Dimension(SassNumber {
                 num: n, unit: u, ..
             }) => Some(bound!($args, $arg, n, u, $low, $high) / Number(100.0)),
@@ -66,7 +64,6 @@ pub(crate) fn change_color(mut args: ArgumentResult, visitor: &mut Visitor) -> S
     }
 
     let hue = match args.default_named_arg("hue", Value::Null) {
-        Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
         Value::Dimension(SassNumber { num: n, .. }) => Some(n),
         Value::Null => None,
         v => {
@@ -119,7 +116,6 @@ pub(crate) fn adjust_color(mut args: ArgumentResult, visitor: &mut Visitor) -> S
     }
 
     let hue = match args.default_named_arg("hue", Value::Null) {
-        Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
         Value::Dimension(SassNumber { num: n, .. }) => Some(n),
         Value::Null => None,
         v => {
@@ -264,7 +264,7 @@ pub(crate) fn parse_channels(
         return Err((err_buffer, span).into());
     }
 
-    let mut list = channels.clone().as_list();
+    let mut list = channels.clone().as_list().into_vec();
 
     if list.len() > 3 {
         return Err((
@@ -415,7 +415,6 @@ pub(crate) fn mix(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult
             as_slash: None,
         }),
     ) {
-        Value::Dimension(SassNumber { num: n, .. }) if n.is_nan() => todo!(),
         Value::Dimension(SassNumber {
             num: n,
             unit: u,
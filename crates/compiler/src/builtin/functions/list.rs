@@ -2,8 +2,12 @@ use crate::builtin::builtin_imports::*;
 
 pub(crate) fn length(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value> {
     args.max_args(1)?;
+    let len = match args.get_err(0, "list")? {
+        Value::Map(map) => map.len(),
+        v => v.as_list().len(),
+    };
     Ok(Value::Dimension(SassNumber {
-        num: (Number::from(args.get_err(0, "list")?.as_list().len())),
+        num: (Number::from(len)),
         unit: Unit::None,
         as_slash: None,
     }))
@@ -11,7 +15,7 @@ pub(crate) fn length(mut args: ArgumentResult, visitor: &mut Visitor) -> SassRes
 
 pub(crate) fn nth(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value> {
     args.max_args(2)?;
-    let mut list = args.get_err(0, "list")?.as_list();
+    let list = args.get_err(0, "list")?.as_list();
     let (n, unit) = match args.get_err(1, "n")? {
         Value::Dimension(SassNumber {
             num: n, unit: u, ..
@@ -45,13 +49,15 @@ pub(crate) fn nth(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult
             .into());
     }
 
-    Ok(list.remove(if n.is_positive() {
+    let index = if n.is_positive() {
         let index = n.assert_int_with_name("n", args.span())? - 1;
         debug_assert!(index > -1);
         index as usize
     } else {
         list.len() - n.abs().assert_int_with_name("n", args.span())? as usize
-    }))
+    };
+
+    Ok(list[index].clone())
 }
 
 pub(crate) fn list_separator(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value> {
@@ -71,13 +77,9 @@ pub(crate) fn set_nth(mut args: ArgumentResult, visitor: &mut Visitor) -> SassRe
     args.max_args(3)?;
     let (mut list, sep, brackets) = match args.get_err(0, "list")? {
         Value::List(v, sep, b) => (v, sep, b),
-        Value::ArgList(v) => (
-            v.elems.into_iter().collect(),
-            ListSeparator::Comma,
-            Brackets::None,
-        ),
+        Value::ArgList(v) => (v.elems.into(), ListSeparator::Comma, Brackets::None),
         Value::Map(m) => (m.as_list(), ListSeparator::Comma, Brackets::None),
-        v => (vec![v], ListSeparator::Undecided, Brackets::None),
+        v => (vec![v].into(), ListSeparator::Undecided, Brackets::None),
     };
     let (n, unit) = match args.get_err(1, "n")? {
         Value::Dimension(SassNumber {
@@ -133,7 +135,7 @@ pub(crate) fn append(mut args: ArgumentResult, visitor: &mut Visitor) -> SassRes
     args.max_args(3)?;
     let (mut list, sep, brackets) = match args.get_err(0, "list")? {
         Value::List(v, sep, b) => (v, sep, b),
-        v => (vec![v], ListSeparator::Undecided, Brackets::None),
+        v => (vec![v].into(), ListSeparator::Undecided, Brackets::None),
     };
     let val = args.get_err(1, "val")?;
     let sep = match args.default_arg(
@@ -179,12 +181,12 @@ pub(crate) fn join(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResul
     let (mut list1, sep1, brackets) = match args.get_err(0, "list1")? {
         Value::List(v, sep, brackets) => (v, sep, brackets),
         Value::Map(m) => (m.as_list(), ListSeparator::Comma, Brackets::None),
-        v => (vec![v], ListSeparator::Undecided, Brackets::None),
+        v => (vec![v].into(), ListSeparator::Undecided, Brackets::None),
     };
     let (list2, sep2) = match args.get_err(1, "list2")? {
         Value::List(v, sep, ..) => (v, sep),
         Value::Map(m) => (m.as_list(), ListSeparator::Comma),
-        v => (vec![v], ListSeparator::Undecided),
+        v => (vec![v].into(), ListSeparator::Undecided),
     };
     let sep = match args.default_arg(
         2,
@@ -259,7 +261,7 @@ pub(crate) fn index(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResu
     args.max_args(2)?;
     let list = args.get_err(0, "list")?.as_list();
     let value = args.get_err(1, "value")?;
-    let index = match list.into_iter().position(|v| v == value) {
+    let index = match list.iter().position(|v| *v == value) {
         Some(v) => Number::from(v + 1),
         None => return Ok(Value::Null),
     };
@@ -275,13 +277,13 @@ pub(crate) fn zip(args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Val
         .get_variadic()?
         .into_iter()
         .map(|x| x.node.as_list())
-        .collect::<Vec<Vec<Value>>>();
+        .collect::<Vec<PersistentVec<Value>>>();
 
-    let len = lists.iter().map(Vec::len).min().unwrap_or(0);
+    let len = lists.iter().map(PersistentVec::len).min().unwrap_or(0);
 
     if len == 0 {
         return Ok(Value::List(
-            Vec::new(),
+            PersistentVec::new(),
             ListSeparator::Comma,
             Brackets::None,
         ));
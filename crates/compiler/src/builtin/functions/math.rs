@@ -116,6 +116,7 @@ pub(crate) fn comparable(mut args: ArgumentResult, visitor: &mut Visitor) -> Sas
 #[cfg(feature = "random")]
 pub(crate) fn random(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value> {
     args.max_args(1)?;
+    visitor.mark_uncacheable();
     let limit = args.default_arg(0, "limit", Value::Null);
 
     if matches!(limit, Value::Null) {
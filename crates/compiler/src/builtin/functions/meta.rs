@@ -283,9 +283,12 @@ pub(crate) fn get_function(mut args: ArgumentResult, visitor: &mut Visitor) -> S
     } else {
         match visitor.env.get_fn(name, None)? {
             Some(f) => Some(f),
-            None => GLOBAL_FUNCTIONS
-                .get(name.as_str())
-                .map(|f| SassFunction::Builtin(f.clone(), name)),
+            None => match visitor.host_function(name, None) {
+                Some(f) => Some(SassFunction::Host(f)),
+                None => GLOBAL_FUNCTIONS
+                    .get(name.as_str())
+                    .map(|f| SassFunction::Builtin(f.clone(), name)),
+            },
         }
     };
 
@@ -300,6 +303,15 @@ pub(crate) fn call(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResul
     let func = match args.get_err(0, "function")? {
         Value::FunctionRef(f) => *f,
         Value::String(name, ..) => {
+            visitor.emit_deprecation(
+                Deprecation::CallString,
+                &format!(
+                    "Passing a string to call() is deprecated and will be illegal in Dart Sass 2.0.0.\n\nRecommendation: call(get-function(\"{}\"))",
+                    name
+                ),
+                span,
+            )?;
+
             let name = Identifier::from(name);
 
             match visitor.env.get_fn(name, None)? {
@@ -59,6 +59,96 @@ pub(crate) static GLOBAL_FUNCTIONS: Lazy<GlobalFunctionMap> = Lazy::new(|| {
     m
 });
 
+/// The name of the module function equivalent to a global built-in function,
+/// e.g. `map.get` for `map-get`
+///
+/// Returns `None` for functions which are also plain CSS functions, such as
+/// `rgb()` or `min()`, and for the legacy color adjustment functions, which are
+/// covered by a separate deprecation
+pub(crate) fn module_equivalent(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "adjust-color" => "color.adjust",
+        "alpha" => "color.alpha",
+        "blue" => "color.blue",
+        "change-color" => "color.change",
+        "complement" => "color.complement",
+        "green" => "color.green",
+        "hue" => "color.hue",
+        "ie-hex-str" => "color.ie-hex-str",
+        "lightness" => "color.lightness",
+        "mix" => "color.mix",
+        "red" => "color.red",
+        "saturation" => "color.saturation",
+        "scale-color" => "color.scale",
+        "append" => "list.append",
+        "index" => "list.index",
+        "is-bracketed" => "list.is-bracketed",
+        "join" => "list.join",
+        "length" => "list.length",
+        "list-separator" => "list.separator",
+        "nth" => "list.nth",
+        "set-nth" => "list.set-nth",
+        "zip" => "list.zip",
+        "map-get" => "map.get",
+        "map-has-key" => "map.has-key",
+        "map-keys" => "map.keys",
+        "map-merge" => "map.merge",
+        "map-remove" => "map.remove",
+        "map-values" => "map.values",
+        "ceil" => "math.ceil",
+        "comparable" => "math.compatible",
+        "floor" => "math.floor",
+        "percentage" => "math.percentage",
+        "random" => "math.random",
+        "unit" => "math.unit",
+        "unitless" => "math.is-unitless",
+        "call" => "meta.call",
+        "content-exists" => "meta.content-exists",
+        "feature-exists" => "meta.feature-exists",
+        "function-exists" => "meta.function-exists",
+        "get-function" => "meta.get-function",
+        "inspect" => "meta.inspect",
+        "keywords" => "meta.keywords",
+        "mixin-exists" => "meta.mixin-exists",
+        "type-of" => "meta.type-of",
+        "variable-exists" => "meta.variable-exists",
+        "is-superselector" => "selector.is-superselector",
+        "selector-append" => "selector.append",
+        "selector-extend" => "selector.extend",
+        "selector-nest" => "selector.nest",
+        "selector-parse" => "selector.parse",
+        "selector-replace" => "selector.replace",
+        "selector-unify" => "selector.unify",
+        "simple-selectors" => "selector.simple-selectors",
+        "quote" => "string.quote",
+        "str-index" => "string.index",
+        "str-insert" => "string.insert",
+        "str-length" => "string.length",
+        "str-slice" => "string.slice",
+        "to-lower-case" => "string.to-lower-case",
+        "to-upper-case" => "string.to-upper-case",
+        "unique-id" => "string.unique-id",
+        "unquote" => "string.unquote",
+        _ => return None,
+    })
+}
+
+/// Whether `name` is one of the legacy global color adjustment functions, such
+/// as `lighten()`, which are superseded by `color.adjust()`
+pub(crate) fn is_legacy_color_function(name: &str) -> bool {
+    matches!(
+        name,
+        "lighten"
+            | "darken"
+            | "desaturate"
+            | "adjust-hue"
+            | "opacify"
+            | "fade-in"
+            | "transparentize"
+            | "fade-out"
+    )
+}
+
 pub(crate) static DISALLOWED_PLAIN_CSS_FUNCTION_NAMES: Lazy<BTreeSet<&str>> = Lazy::new(|| {
     GLOBAL_FUNCTIONS
         .keys()
@@ -32,16 +32,12 @@ pub(crate) fn simple_selectors(
         args.get_err(0, "selector")?
             .to_selector(visitor, "selector", false, args.span())?;
 
-    if selector.0.components.len() != 1 {
-        return Err(("$selector: expected selector.", args.span()).into());
-    }
-
-    let compound = if let Some(ComplexSelectorComponent::Compound(compound)) =
-        selector.0.components[0].components.first().cloned()
-    {
-        compound
-    } else {
-        todo!()
+    let compound = match selector.0.components.as_slice() {
+        [complex] => match complex.components.as_slice() {
+            [ComplexSelectorComponent::Compound(compound)] => compound.clone(),
+            _ => return Err(("$selector: expected selector.", args.span()).into()),
+        },
+        _ => return Err(("$selector: expected selector.", args.span()).into()),
     };
 
     Ok(Value::List(
@@ -211,8 +211,9 @@ pub(crate) fn str_insert(mut args: ArgumentResult, visitor: &mut Visitor) -> Sas
 
 #[cfg(feature = "random")]
 #[allow(clippy::needless_pass_by_value)]
-pub(crate) fn unique_id(args: ArgumentResult, _: &mut Visitor) -> SassResult<Value> {
+pub(crate) fn unique_id(args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Value> {
     args.max_args(0)?;
+    visitor.mark_uncacheable();
     let mut rng = thread_rng();
     let string: String = std::iter::repeat(())
         .map(|()| rng.sample(Alphanumeric))
@@ -2,8 +2,8 @@ mod functions;
 pub(crate) mod modules;
 
 pub(crate) use functions::{
-    color, list, map, math, meta, selector, string, Builtin, DISALLOWED_PLAIN_CSS_FUNCTION_NAMES,
-    GLOBAL_FUNCTIONS,
+    color, is_legacy_color_function, list, map, math, meta, module_equivalent, selector, string,
+    Builtin, DISALLOWED_PLAIN_CSS_FUNCTION_NAMES, GLOBAL_FUNCTIONS,
 };
 
 /// Imports common to all builtin fns
@@ -22,8 +22,9 @@ mod builtin_imports {
         error::SassResult,
         evaluate::Visitor,
         unit::Unit,
+        utils::PersistentVec,
         value::{CalculationArg, Number, SassFunction, SassMap, SassNumber, Value},
-        Options,
+        Deprecation, Options,
     };
 
     pub(crate) use std::{
@@ -73,9 +73,11 @@ fn load_css(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<()> {
 
     let _configuration = Arc::new(RefCell::new(configuration));
 
-    let style_sheet = visitor.load_style_sheet(url.as_ref(), false, args.span())?;
+    visitor.with_stack_frame("load-css()".to_owned(), span, |visitor| {
+        let style_sheet = visitor.load_style_sheet(url.as_ref(), false, span)?;
 
-    visitor.visit_stylesheet(style_sheet)?;
+        visitor.visit_stylesheet(style_sheet)
+    })?;
 
     // todo: support the $with argument to load-css
     // visitor.load_module(
@@ -170,7 +172,7 @@ fn calc_args(mut args: ArgumentResult, visitor: &mut Visitor) -> SassResult<Valu
                 ),
             })
         })
-        .collect::<SassResult<Vec<_>>>()?;
+        .collect::<SassResult<PersistentVec<_>>>()?;
 
     Ok(Value::List(args, ListSeparator::Comma, Brackets::None))
 }
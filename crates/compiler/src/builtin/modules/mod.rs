@@ -133,6 +133,15 @@ impl ModuleScope {
     }
 }
 
+/// The parts of a module which can change after it has been executed: the
+/// values of its variables, and its scope, which is replaced when the module
+/// is forwarded
+#[derive(Debug, Clone)]
+pub(crate) struct ModuleState {
+    scope: ModuleScope,
+    variables: BTreeMap<Identifier, Value>,
+}
+
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub(crate) enum Module {
@@ -142,7 +151,6 @@ pub(crate) enum Module {
         upstream: Vec<Module>,
         #[allow(dead_code)]
         extension_store: ExtensionStore,
-        #[allow(dead_code)]
         env: Environment,
     },
     Builtin {
@@ -279,7 +287,7 @@ impl Module {
         }
     }
 
-    fn scope(&self) -> ModuleScope {
+    pub fn scope(&self) -> ModuleScope {
         match self {
             Self::Builtin { scope } | Self::Environment { scope, .. } => scope.clone(),
             Self::Forwarded(forwarded) => (*forwarded.inner).borrow().scope(),
@@ -293,6 +301,25 @@ impl Module {
         }
     }
 
+    /// A snapshot of the state of a module executed from a stylesheet
+    pub fn state(&self) -> Option<ModuleState> {
+        match self {
+            Self::Environment { scope, env, .. } => Some(ModuleState {
+                scope: scope.clone(),
+                variables: (*env.global_vars()).borrow().clone(),
+            }),
+            Self::Builtin { .. } | Self::Forwarded(..) => None,
+        }
+    }
+
+    /// Restores a snapshot taken by [`Module::state`]
+    pub fn restore(&mut self, state: ModuleState) {
+        if let Self::Environment { scope, env, .. } = self {
+            *scope = state.scope;
+            *(*env.global_vars()).borrow_mut() = state.variables;
+        }
+    }
+
     pub fn get_var(&self, name: Spanned<Identifier>) -> SassResult<Value> {
         let scope = self.scope();
 
@@ -15,6 +15,8 @@
 //! Named colors retain their original casing,
 //! so `rEd` should be emitted as `rEd`.
 
+use std::hash::{Hash, Hasher};
+
 use crate::value::{fuzzy_round, Number};
 pub(crate) use name::NAMED_COLORS;
 
@@ -54,6 +56,18 @@ impl PartialEq for Color {
 
 impl Eq for Color {}
 
+impl Hash for Color {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // channels are equal if they are both at least their maximum value, so
+        // they are hashed as at most that value
+        for channel in [self.rgba.red, self.rgba.green, self.rgba.blue] {
+            channel.min(Number(255.0)).hash(state);
+        }
+
+        self.alpha.min(Number::one()).hash(state);
+    }
+}
+
 impl Color {
     pub const fn new_rgba(
         red: Number,
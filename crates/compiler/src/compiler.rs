@@ -0,0 +1,442 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    ptr,
+    sync::{Arc, Mutex, PoisonError, Weak},
+};
+
+use codemap::{CodeMap, File, Span};
+
+use crate::{
+    ast::{Configuration, CssStmt, StyleSheet},
+    builtin::modules::{Module, ModuleState},
+    common::Identifier,
+    compile_with_cache, value, InputSyntax, Options, Result,
+};
+
+/// Stale sources are kept until they outweigh the live ones by this many bytes
+const MIN_STALE_BYTES: usize = 1 << 20;
+
+/// Positions in a [`CodeMap`] are 32-bit, so the cache is cleared well before
+/// they could overflow
+const MAX_SOURCE_BYTES: usize = (u32::MAX / 2) as usize;
+
+/// A compiler which keeps parsed stylesheets and executed modules between
+/// compilations
+///
+/// Each call to [`grass::from_path`](crate::from_path) lexes and parses every
+/// stylesheet it loads from scratch. A `Compiler` instead caches every
+/// stylesheet it parses by its canonical path, so that entry points which share
+/// dependencies, or which are recompiled after a change, only parse the files
+/// which are new or have changed.
+///
+/// Files are still read on every compilation, and a cached stylesheet is only
+/// reused if its contents are identical to the contents it was parsed from, so
+/// the cache never needs to be invalidated by hand. Warnings emitted while
+/// parsing a stylesheet are not emitted again when it is reused.
+///
+/// Modules loaded with `@use` and `@forward` are cached as well, along with the
+/// CSS they emit. A cached module is reused when it is loaded with the same
+/// configuration, as long as its stylesheet and every stylesheet it loads are
+/// unchanged, so after a change only the modules which changed and the modules
+/// which depend on them are executed again. `@debug` and `@warn` rules in a
+/// reused module are not run again. Modules which call `math.random()` or
+/// `unique-id()` are never reused. Executed modules can't be shared between
+/// threads, so each thread compiling with a `Compiler` reuses only the modules
+/// it executed itself.
+///
+/// Compilations using the same `Compiler` run one at a time. To compile in
+/// parallel, use a `Compiler` per thread.
+///
+/// Names of variables, functions, mixins, properties, and unknown units are
+/// interned in a table shared by every thread for the life of the process,
+/// whether or not a `Compiler` is used. Dropping a `Compiler` doesn't free
+/// them, so a long-running process grows by each distinct name it compiles.
+/// This is negligible for a fixed set of stylesheets, but a server compiling
+/// arbitrary input, particularly property names built with interpolation,
+/// should run compilations in a separate process it can restart.
+///
+/// ```
+/// # use grass_compiler as grass;
+/// fn main() -> Result<(), Box<grass::Error>> {
+///     let compiler = grass::Compiler::new(grass::Options::default());
+///
+///     let css = compiler.compile_string("a { b: c; }".to_owned())?;
+///     assert_eq!(css, "a {\n  b: c;\n}\n");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Compiler<'a> {
+    options: Options<'a>,
+    state: Mutex<State>,
+}
+
+/// The stylesheets and modules cached by a [`Compiler`], along with the code
+/// map their spans refer to
+#[derive(Debug, Default)]
+struct State {
+    map: CodeMap,
+    cache: StyleSheetCache,
+    /// Identifies this state in the module cache of each thread, whose modules
+    /// refer to its code map
+    modules: Arc<()>,
+    /// The files loaded by the last compilation of each path
+    loaded_files: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+thread_local! {
+    /// The modules executed on this thread, along with the state of the
+    /// `Compiler` they were executed with
+    ///
+    /// Modules are made of `RefCell`s, so unlike parsed stylesheets they can't
+    /// be kept in a `Compiler`, which may be used from several threads.
+    static MODULES: RefCell<Vec<(Weak<()>, ModuleCache)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Removes the modules this thread executed with the state identified by
+/// `owner`, dropping those whose state no longer exists
+fn take_modules(owner: &Arc<()>) -> ModuleCache {
+    MODULES.with(|modules| {
+        let mut modules = modules.borrow_mut();
+        modules.retain(|(state, _)| state.strong_count() > 0);
+
+        match modules
+            .iter()
+            .position(|(state, _)| ptr::eq(state.as_ptr(), Arc::as_ptr(owner)))
+        {
+            Some(idx) => modules.swap_remove(idx).1,
+            None => ModuleCache::default(),
+        }
+    })
+}
+
+/// Keeps `cache` for the next compilation on this thread with the state
+/// identified by `owner`
+fn store_modules(owner: &Arc<()>, cache: ModuleCache) {
+    MODULES.with(|modules| {
+        modules.borrow_mut().push((Arc::downgrade(owner), cache));
+    });
+}
+
+impl<'a> Compiler<'a> {
+    #[must_use]
+    pub fn new(options: Options<'a>) -> Self {
+        Self {
+            options,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    #[must_use]
+    pub fn options(&self) -> &Options<'a> {
+        &self.options
+    }
+
+    /// Compile CSS from a path, like [`crate::from_path`]
+    pub fn compile<P: AsRef<Path>>(&self, path: P) -> Result<String> {
+        let input = String::from_utf8(self.options.fs.read(path.as_ref())?)?;
+        self.compile_string_with_file_name(input, path)
+    }
+
+    /// Compile CSS from a string, like [`crate::from_string`]
+    pub fn compile_string(&self, input: String) -> Result<String> {
+        self.compile_string_with_file_name(input, "stdin")
+    }
+
+    /// Compile CSS from a string, as if it were read from the file at `path`,
+    /// like [`crate::from_string_with_file_name`]
+    pub fn compile_string_with_file_name<P: AsRef<Path>>(
+        &self,
+        input: String,
+        path: P,
+    ) -> Result<String> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if state.cache.is_stale() {
+            *state = State::default();
+        }
+
+        let State {
+            map,
+            cache,
+            modules: owner,
+            loaded_files,
+        } = &mut *state;
+
+        // the modules are taken out of the thread local while compiling, so
+        // that importers and loggers may compile with other `Compiler`s
+        let mut modules = take_modules(owner);
+
+        let result = value::with_precision(self.options.precision, || {
+            compile_with_cache(
+                map,
+                Some((cache, &mut modules)),
+                None,
+                input,
+                path.as_ref(),
+                &self.options,
+                false,
+            )
+        });
+
+        modules.prune(cache);
+        loaded_files.insert(
+            path.as_ref().to_path_buf(),
+            modules.loaded_files.iter().cloned().collect(),
+        );
+        store_modules(owner, modules);
+
+        result.map(|(css, _)| css)
+    }
+
+    /// The number of stylesheets currently cached
+    #[must_use]
+    pub fn cached_style_sheets(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .cache
+            .entries
+            .len()
+    }
+
+    /// The number of executed modules currently cached for the current thread,
+    /// including modules loaded by other cached modules
+    #[must_use]
+    pub fn cached_modules(&self) -> usize {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let modules = take_modules(&state.modules);
+        let len = modules.len();
+        store_modules(&state.modules, modules);
+
+        len
+    }
+
+    /// The canonical paths of the stylesheets loaded by the last compilation of
+    /// `path`, whether or not it succeeded
+    ///
+    /// `path` itself is not included. This is empty if `path` has not been
+    /// compiled.
+    #[must_use]
+    pub fn loaded_files<P: AsRef<Path>>(&self, path: P) -> Vec<PathBuf> {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .loaded_files
+            .get(path.as_ref())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Removes every cached stylesheet and module, freeing their sources
+    pub fn clear(&self) {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = State::default();
+    }
+}
+
+/// Parsed stylesheets, keyed by their canonical path
+#[derive(Debug, Default)]
+pub(crate) struct StyleSheetCache {
+    entries: HashMap<PathBuf, CachedStyleSheet>,
+    /// The size of every file added to the code map, including those which
+    /// have since changed
+    total_bytes: usize,
+    /// The size of the files in `entries`
+    live_bytes: usize,
+}
+
+#[derive(Debug)]
+struct CachedStyleSheet {
+    file: Arc<File>,
+    syntax: InputSyntax,
+    style_sheet: StyleSheet,
+}
+
+impl StyleSheetCache {
+    /// The stylesheet at `canonical` and the file it was parsed from, if it
+    /// was parsed from exactly `contents` as `syntax`
+    pub fn get(
+        &self,
+        canonical: &Path,
+        contents: &str,
+        syntax: InputSyntax,
+    ) -> Option<(&Arc<File>, &StyleSheet)> {
+        self.entries
+            .get(canonical)
+            .filter(|entry| entry.syntax == syntax && entry.file.source() == contents)
+            .map(|entry| (&entry.file, &entry.style_sheet))
+    }
+
+    pub fn insert(
+        &mut self,
+        canonical: PathBuf,
+        file: Arc<File>,
+        syntax: InputSyntax,
+        style_sheet: StyleSheet,
+    ) {
+        self.live_bytes += file.source().len();
+
+        let entry = CachedStyleSheet {
+            file,
+            syntax,
+            style_sheet,
+        };
+
+        if let Some(old) = self.entries.insert(canonical, entry) {
+            self.live_bytes -= old.file.source().len();
+        }
+    }
+
+    /// The file the stylesheet at `canonical` was parsed from
+    pub fn file(&self, canonical: &Path) -> Option<&Arc<File>> {
+        self.entries.get(canonical).map(|entry| &entry.file)
+    }
+
+    /// Records a file added to the code map
+    pub fn record_file(&mut self, file: &File) {
+        self.total_bytes += file.source().len();
+    }
+
+    /// Whether so much of the code map is taken up by old versions of files
+    /// that it should be rebuilt
+    fn is_stale(&self) -> bool {
+        let stale_bytes = self.total_bytes.saturating_sub(self.live_bytes);
+
+        self.total_bytes > MAX_SOURCE_BYTES || stale_bytes > self.live_bytes.max(MIN_STALE_BYTES)
+    }
+}
+
+/// Modules executed by previous compilations, keyed by the canonical path of
+/// their stylesheet
+///
+/// A module is only reused if its stylesheet is unchanged, it is configured
+/// with the same values, and every stylesheet loaded while executing it, or
+/// any of its upstream modules, still resolves to the same unchanged file.
+///
+/// Each cached module is used at most once per compilation. Like modules which
+/// are executed afresh for every `@use`, a reused module's variables are then
+/// never modified by more than one of the stylesheets which load it.
+#[derive(Debug, Default)]
+pub(crate) struct ModuleCache {
+    entries: HashMap<PathBuf, Vec<CachedModule>>,
+    /// The canonical paths of the stylesheets loaded by the current or most
+    /// recent compilation
+    pub loaded_files: BTreeSet<PathBuf>,
+}
+
+impl ModuleCache {
+    /// Removes a module at `canonical` which was executed from `file` with
+    /// `configuration`
+    pub fn take(
+        &mut self,
+        canonical: &Path,
+        file: &Arc<File>,
+        configuration: &ConfigurationKey,
+    ) -> Option<CachedModule> {
+        let entries = self.entries.get_mut(canonical)?;
+
+        let idx = entries.iter().position(|entry| {
+            Arc::ptr_eq(&entry.file, file) && &entry.configuration == configuration
+        })?;
+
+        Some(entries.swap_remove(idx))
+    }
+
+    pub fn insert(&mut self, module: CachedModule) {
+        self.entries
+            .entry(module.canonical.clone())
+            .or_default()
+            .push(module);
+    }
+
+    fn len(&self) -> usize {
+        self.entries.values().flatten().map(CachedModule::len).sum()
+    }
+
+    /// Drops modules whose stylesheets have changed since they were executed
+    fn prune(&mut self, style_sheets: &StyleSheetCache) {
+        self.entries.retain(|canonical, entries| {
+            let file = style_sheets.file(canonical);
+            entries.retain(|entry| file.is_some_and(|file| Arc::ptr_eq(file, &entry.file)));
+            !entries.is_empty()
+        });
+    }
+}
+
+/// A module executed by a previous compilation, along with everything needed to
+/// reproduce the effects of executing it
+#[derive(Debug)]
+pub(crate) struct CachedModule {
+    pub canonical: PathBuf,
+    /// The file the module's stylesheet was parsed from
+    pub file: Arc<File>,
+    pub configuration: ConfigurationKey,
+    pub module: Arc<RefCell<Module>>,
+    /// The top-level CSS emitted by the module and its upstream modules. The
+    /// chunks emitted by upstream modules are shared with their own entries
+    pub css: Vec<Arc<[CssStmt]>>,
+    /// Plain CSS `@import`s emitted by the module, which are hoisted to the top
+    /// of the output
+    pub imports: Vec<CssStmt>,
+    /// The configured variables used by the module
+    pub consumed: Vec<Identifier>,
+    /// The state of the module and of every module it loaded, as of when it
+    /// finished executing
+    pub states: Vec<(Arc<RefCell<Module>>, ModuleState)>,
+    /// The stylesheets loaded while executing the module, excluding those
+    /// loaded by its cached upstream modules
+    pub loads: Vec<Load>,
+    /// The cached modules loaded by this module, which are reused along with it
+    pub upstream: Vec<CachedModule>,
+}
+
+impl CachedModule {
+    fn len(&self) -> usize {
+        1 + self.upstream.iter().map(CachedModule::len).sum::<usize>()
+    }
+}
+
+/// A stylesheet loaded while executing a cached module
+#[derive(Debug)]
+pub(crate) struct Load {
+    /// The stylesheet `url` was resolved relative to
+    pub base: PathBuf,
+    pub url: String,
+    pub for_import: bool,
+    pub canonical: PathBuf,
+    pub file: Arc<File>,
+}
+
+/// The values a module was configured with
+///
+/// Values are compared by their inspected form rather than with `==`, since
+/// equal values such as `1in` and `96px` may still produce different CSS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConfigurationKey {
+    implicit: bool,
+    values: Vec<(Identifier, String)>,
+}
+
+impl ConfigurationKey {
+    /// The key for `configuration`, or `None` if one of its values can't be
+    /// inspected
+    pub fn new(configuration: &Configuration, span: Span) -> Option<Self> {
+        let values = configuration
+            .values
+            .keys()
+            .into_iter()
+            .map(|name| {
+                let value = configuration.values.get(name)?.value;
+                Some((name, value.inspect(span).ok()?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            implicit: configuration.is_implicit(),
+            values,
+        })
+    }
+}
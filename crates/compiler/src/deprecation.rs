@@ -0,0 +1,104 @@
+use std::fmt::{self, Display};
+
+/// A deprecated feature of the Sass language
+///
+/// Each deprecation has a stable identifier, matching the one used by
+/// `dart-sass`, which can be used to silence it, make it fatal, or opt in to it
+/// early. See [`crate::Options::silence_deprecation`],
+/// [`crate::Options::fatal_deprecation`], and
+/// [`crate::Options::future_deprecation`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Deprecation {
+    /// Passing a string directly to `meta.call()`.
+    CallString,
+
+    /// Declaring new variables with `!global`.
+    NewGlobal,
+
+    /// `/` operator for division.
+    SlashDiv,
+
+    /// `@import` rules.
+    ///
+    /// This is a future deprecation and must be opted in to.
+    Import,
+
+    /// Global built-in functions that are available in `sass:` modules.
+    ///
+    /// This is a future deprecation and must be opted in to.
+    GlobalBuiltin,
+
+    /// Legacy color adjustment functions such as `lighten()` and `darken()`.
+    ///
+    /// This is a future deprecation and must be opted in to.
+    ColorFunctions,
+}
+
+impl Deprecation {
+    /// Every deprecation known to `grass`
+    pub const ALL: [Deprecation; 6] = [
+        Deprecation::CallString,
+        Deprecation::NewGlobal,
+        Deprecation::SlashDiv,
+        Deprecation::Import,
+        Deprecation::GlobalBuiltin,
+        Deprecation::ColorFunctions,
+    ];
+
+    /// The stable identifier of this deprecation, e.g. `slash-div`
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Deprecation::CallString => "call-string",
+            Deprecation::NewGlobal => "new-global",
+            Deprecation::SlashDiv => "slash-div",
+            Deprecation::Import => "import",
+            Deprecation::GlobalBuiltin => "global-builtin",
+            Deprecation::ColorFunctions => "color-functions",
+        }
+    }
+
+    /// A short description of the deprecated feature
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Deprecation::CallString => "Passing a string directly to meta.call().",
+            Deprecation::NewGlobal => "Declaring new variables with !global.",
+            Deprecation::SlashDiv => "/ operator for division.",
+            Deprecation::Import => "@import rules.",
+            Deprecation::GlobalBuiltin => {
+                "Global built-in functions that are available in sass: modules."
+            }
+            Deprecation::ColorFunctions => "Legacy color adjustment functions.",
+        }
+    }
+
+    /// Whether this deprecation is not yet active by default
+    ///
+    /// Warnings for future deprecations are only emitted if they have been
+    /// opted in to using [`crate::Options::future_deprecation`] or made fatal
+    /// using [`crate::Options::fatal_deprecation`]
+    #[must_use]
+    pub const fn is_future(self) -> bool {
+        matches!(
+            self,
+            Deprecation::Import | Deprecation::GlobalBuiltin | Deprecation::ColorFunctions
+        )
+    }
+
+    /// Look up a deprecation by its identifier
+    #[must_use]
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|deprecation| deprecation.id() == id)
+    }
+}
+
+impl Display for Deprecation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
@@ -0,0 +1,1134 @@
+//! A host mode implementing the [Embedded Sass protocol], which allows hosts
+//! such as the `sass-embedded` npm package to use grass as their compiler
+//!
+//! The host communicates with the compiler over a pair of streams, usually the
+//! standard input and output of a `grass --embedded` process. Each message is
+//! a protocol buffer, preceded by its length and the ID of the compilation it
+//! belongs to. During a compilation, the compiler calls back into the host to
+//! resolve URLs with host importers, to run host functions, and to report log
+//! events.
+//!
+//! Compilations are run one at a time, in the order they are requested. When
+//! `CompileRequest.source_map` is set, the source map is returned with the CSS,
+//! with its sources named by their canonical URLs.
+//!
+//! This API is experimental, and may change in minor releases.
+//!
+//! [Embedded Sass protocol]: https://github.com/sass/sass/blob/main/spec/embedded-protocol.md
+
+use std::{
+    cell::RefCell,
+    collections::{HashSet, VecDeque},
+    fmt,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use codemap::{Span, SpanLoc};
+
+use crate::{
+    error::{write_diagnostic, write_stack_trace},
+    importer::{file_url, file_url_to_path},
+    value::{ArgList, FunctionHost, HostFunction, HostFunctionId, SassFunction, Value},
+    Deprecation, ErrorKind, FileImporter, Fs, ImportContext, Importer, ImporterResult, InputSyntax,
+    Logger, Options, OutputStyle, StackFrame, StdFs,
+};
+
+use self::proto::{read_packet, write_packet, Decoder, Encoder, Field};
+
+mod proto;
+mod value;
+
+/// The version of the embedded protocol implemented by this module
+pub const PROTOCOL_VERSION: &str = "2.7.1";
+
+/// The `ProtocolError.type` values
+#[derive(Debug, Clone, Copy)]
+enum ProtocolErrorType {
+    Parse = 0,
+    Params = 1,
+}
+
+/// The `LogEventType` values
+#[derive(Debug, Clone, Copy)]
+enum LogEventType {
+    Warning = 0,
+    DeprecationWarning = 1,
+    Debug = 2,
+}
+
+/// The ID used in protocol errors which aren't caused by a specific request
+const NO_ID: u32 = u32::MAX;
+
+/// Serves compilations over the embedded protocol until `input` is closed or
+/// a protocol error occurs
+///
+/// Protocol errors are reported to the host as `ProtocolError` messages, after
+/// which this returns `Ok(())`. Errors reading from `input` or writing to
+/// `output` are returned.
+///
+/// ```no_run
+/// # use grass_compiler as grass;
+/// fn main() -> std::io::Result<()> {
+///     grass::embedded::serve(std::io::stdin(), std::io::stdout())
+/// }
+/// ```
+pub fn serve<R: Read + Send, W: Write + Send>(mut input: R, mut output: W) -> io::Result<()> {
+    let connection = Connection {
+        input: Mutex::new(&mut input),
+        output: Mutex::new(&mut output),
+        queued: Mutex::new(VecDeque::new()),
+        next_request_id: AtomicU32::new(0),
+        closed: AtomicBool::new(false),
+        io_error: Mutex::new(None),
+    };
+
+    while let Some((compilation_id, payload)) = connection.next_message() {
+        match inbound_message(&payload) {
+            Ok((1, request)) => {
+                let response = compile(&connection, compilation_id, request);
+
+                if !connection.is_closed() {
+                    connection.send(compilation_id, |message| {
+                        message.message(2, |message| response.encode(message));
+                    });
+                }
+            }
+            Ok((6, request)) => connection.version_response(compilation_id, request),
+            Ok((field, _)) => connection.protocol_error(
+                compilation_id,
+                ProtocolErrorType::Params,
+                NO_ID,
+                &format!("Unexpected message {} outside of a host callback.", field),
+            ),
+            Err(message) => {
+                connection.protocol_error(compilation_id, ProtocolErrorType::Parse, NO_ID, &message)
+            }
+        }
+
+        if connection.is_closed() {
+            break;
+        }
+    }
+
+    match connection
+        .io_error
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+    {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Returns the field number and contents of the message set in an
+/// `InboundMessage`
+fn inbound_message(payload: &[u8]) -> Result<(u32, &[u8]), String> {
+    let mut decoder = Decoder::new(payload);
+    let mut message = None;
+
+    while let Some((number, field)) = decoder.next_field()? {
+        message = Some((number, field.as_bytes()?));
+    }
+
+    message.ok_or_else(|| "InboundMessage.message is not set.".to_owned())
+}
+
+/// Returns the value of the `id` field of a request or response
+fn message_id(message: &[u8]) -> Result<u32, String> {
+    let mut decoder = Decoder::new(message);
+    let mut id = 0;
+
+    while let Some((number, field)) = decoder.next_field()? {
+        if number == 1 {
+            id = field.as_u32()?;
+        }
+    }
+
+    Ok(id)
+}
+
+/// The streams to the host, which are shared by the file system, logger and
+/// importers of a compilation, and so must be `Sync` like them
+struct Connection<'io> {
+    input: Mutex<&'io mut (dyn Read + Send)>,
+    output: Mutex<&'io mut (dyn Write + Send)>,
+    /// Compile requests which arrived while another compilation was waiting
+    /// for a response from the host
+    queued: Mutex<VecDeque<(u32, Vec<u8>)>>,
+    next_request_id: AtomicU32,
+    /// Whether the connection has been closed, either because of a protocol
+    /// error or because the input ended during a compilation
+    closed: AtomicBool,
+    io_error: Mutex<Option<io::Error>>,
+}
+
+/// Locks `mutex`, ignoring poisoning, since a panic while it was held can't
+/// leave the data it guards in an inconsistent state
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl fmt::Debug for Connection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connection")
+            .field("next_request_id", &self.next_request_id)
+            .field("closed", &self.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'io> Connection<'io> {
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    fn read(&self) -> Option<(u32, Vec<u8>)> {
+        if self.is_closed() {
+            return None;
+        }
+
+        let packet = read_packet(&mut *lock(&self.input));
+
+        match packet {
+            Ok(Some(packet)) => Some(packet),
+            Ok(None) => None,
+            Err(err) => {
+                *lock(&self.io_error) = Some(err);
+                self.close();
+                None
+            }
+        }
+    }
+
+    /// The next message outside of a compilation
+    fn next_message(&self) -> Option<(u32, Vec<u8>)> {
+        let queued = lock(&self.queued).pop_front();
+        queued.or_else(|| self.read())
+    }
+
+    /// Sends an `OutboundMessage` built by `build`
+    fn send(&self, compilation_id: u32, build: impl FnOnce(&mut Encoder)) {
+        if self.is_closed() {
+            return;
+        }
+
+        let mut message = Encoder::new();
+        build(&mut message);
+
+        let result = write_packet(&mut *lock(&self.output), compilation_id, &message.finish());
+
+        if let Err(err) = result {
+            *lock(&self.io_error) = Some(err);
+            self.close();
+        }
+    }
+
+    fn protocol_error(&self, compilation_id: u32, kind: ProtocolErrorType, id: u32, message: &str) {
+        self.send(compilation_id, |outbound| {
+            outbound.message(1, |error| {
+                error
+                    .uint32(1, kind as u32)
+                    .uint32(2, id)
+                    .string(3, message);
+            });
+        });
+        self.close();
+    }
+
+    fn version_response(&self, compilation_id: u32, request: &[u8]) {
+        let id = match message_id(request) {
+            Ok(id) => id,
+            Err(message) => {
+                return self.protocol_error(
+                    compilation_id,
+                    ProtocolErrorType::Parse,
+                    NO_ID,
+                    &message,
+                )
+            }
+        };
+
+        self.send(compilation_id, |outbound| {
+            outbound.message(8, |response| {
+                response
+                    .string(1, PROTOCOL_VERSION)
+                    .string(2, env!("CARGO_PKG_VERSION"))
+                    .string(3, env!("CARGO_PKG_VERSION"))
+                    .string(4, "grass")
+                    .uint32(5, id);
+            });
+        });
+    }
+
+    /// Sends a request to the host as the field `request_field` of an
+    /// `OutboundMessage`, and waits for the response in the field
+    /// `response_field` of an `InboundMessage`
+    ///
+    /// Version requests received in the meantime are answered immediately,
+    /// and compile requests are queued until the current one is finished.
+    fn request(
+        &self,
+        compilation_id: u32,
+        request_field: u32,
+        response_field: u32,
+        build: impl FnOnce(&mut Encoder),
+    ) -> Result<Vec<u8>, String> {
+        let id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+
+        self.send(compilation_id, |outbound| {
+            outbound.message(request_field, |request| {
+                request.uint32(1, id);
+                build(request);
+            });
+        });
+
+        while let Some((inbound_id, payload)) = self.read() {
+            let (field, message) = match inbound_message(&payload) {
+                Ok(message) => message,
+                Err(message) => {
+                    self.protocol_error(inbound_id, ProtocolErrorType::Parse, NO_ID, &message);
+                    break;
+                }
+            };
+
+            match field {
+                1 => lock(&self.queued).push_back((inbound_id, payload.clone())),
+                6 => self.version_response(inbound_id, message),
+                _ if field == response_field && inbound_id == compilation_id => {
+                    match message_id(message) {
+                        Ok(response_id) if response_id == id => return Ok(message.to_vec()),
+                        Ok(response_id) => self.protocol_error(
+                            inbound_id,
+                            ProtocolErrorType::Params,
+                            response_id,
+                            &format!(
+                                "Response ID {} doesn't match any outstanding requests.",
+                                response_id
+                            ),
+                        ),
+                        Err(message) => self.protocol_error(
+                            inbound_id,
+                            ProtocolErrorType::Parse,
+                            NO_ID,
+                            &message,
+                        ),
+                    }
+                }
+                _ => self.protocol_error(
+                    inbound_id,
+                    ProtocolErrorType::Params,
+                    NO_ID,
+                    &format!(
+                        "Expected response {} for compilation {}, but got message {}.",
+                        response_field, compilation_id, field
+                    ),
+                ),
+            }
+        }
+
+        self.close();
+        Err("The connection to the host was closed.".to_owned())
+    }
+}
+
+/// The input of a `CompileRequest`
+enum Input {
+    String {
+        source: String,
+        url: String,
+        syntax: InputSyntax,
+        importer: Option<ImporterSpec>,
+    },
+    Path(PathBuf),
+}
+
+/// An importer in a `CompileRequest`
+enum ImporterSpec {
+    Path(PathBuf),
+    Importer(u32),
+    FileImporter(u32),
+    NodePackage(PathBuf),
+}
+
+fn syntax_from_id(id: u32) -> InputSyntax {
+    match id {
+        1 => InputSyntax::Sass,
+        2 => InputSyntax::Css,
+        _ => InputSyntax::Scss,
+    }
+}
+
+fn importer_spec(message: &[u8]) -> Result<Option<ImporterSpec>, String> {
+    let mut decoder = Decoder::new(message);
+    let mut spec = None;
+
+    while let Some((number, field)) = decoder.next_field()? {
+        spec = match number {
+            1 => Some(ImporterSpec::Path(PathBuf::from(field.as_string()?))),
+            2 => Some(ImporterSpec::Importer(field.as_u32()?)),
+            3 => Some(ImporterSpec::FileImporter(field.as_u32()?)),
+            5 => {
+                let mut decoder = Decoder::new(field.as_bytes()?);
+                let mut entry_point = String::new();
+                while let Some((number, field)) = decoder.next_field()? {
+                    if number == 1 {
+                        entry_point = field.as_string()?;
+                    }
+                }
+                Some(ImporterSpec::NodePackage(PathBuf::from(entry_point)))
+            }
+            _ => spec,
+        };
+    }
+
+    Ok(spec)
+}
+
+struct CompileRequest {
+    input: Option<Input>,
+    style: OutputStyle,
+    source_map: bool,
+    source_map_include_sources: bool,
+    importers: Vec<ImporterSpec>,
+    global_functions: Vec<String>,
+    alert_ascii: bool,
+    verbose: bool,
+    charset: bool,
+    silent: bool,
+    fatal_deprecations: Vec<String>,
+    silence_deprecations: Vec<String>,
+    future_deprecations: Vec<String>,
+}
+
+impl CompileRequest {
+    fn decode(message: &[u8]) -> Result<Self, String> {
+        let mut request = Self {
+            input: None,
+            style: OutputStyle::Expanded,
+            source_map: false,
+            source_map_include_sources: false,
+            importers: Vec::new(),
+            global_functions: Vec::new(),
+            alert_ascii: false,
+            verbose: false,
+            charset: false,
+            silent: false,
+            fatal_deprecations: Vec::new(),
+            silence_deprecations: Vec::new(),
+            future_deprecations: Vec::new(),
+        };
+
+        let mut decoder = Decoder::new(message);
+        while let Some((number, field)) = decoder.next_field()? {
+            match number {
+                2 => request.input = Some(Self::string_input(field.as_bytes()?)?),
+                3 => request.input = Some(Input::Path(PathBuf::from(field.as_string()?))),
+                4 => {
+                    request.style = if field.as_u32()? == 1 {
+                        OutputStyle::Compressed
+                    } else {
+                        OutputStyle::Expanded
+                    }
+                }
+                5 => request.source_map = field.as_bool()?,
+                6 => request.importers.extend(importer_spec(field.as_bytes()?)?),
+                7 => request.global_functions.push(field.as_string()?),
+                9 => request.alert_ascii = field.as_bool()?,
+                10 => request.verbose = field.as_bool()?,
+                12 => request.source_map_include_sources = field.as_bool()?,
+                13 => request.charset = field.as_bool()?,
+                14 => request.silent = field.as_bool()?,
+                15 => request.fatal_deprecations.push(field.as_string()?),
+                16 => request.silence_deprecations.push(field.as_string()?),
+                17 => request.future_deprecations.push(field.as_string()?),
+                _ => {}
+            }
+        }
+
+        Ok(request)
+    }
+
+    fn string_input(message: &[u8]) -> Result<Input, String> {
+        let mut source = String::new();
+        let mut url = String::new();
+        let mut syntax = InputSyntax::Scss;
+        let mut importer = None;
+
+        let mut decoder = Decoder::new(message);
+        while let Some((number, field)) = decoder.next_field()? {
+            match number {
+                1 => source = field.as_string()?,
+                2 => url = field.as_string()?,
+                3 => syntax = syntax_from_id(field.as_u32()?),
+                4 => importer = importer_spec(field.as_bytes()?)?,
+                _ => {}
+            }
+        }
+
+        Ok(Input::String {
+            source,
+            url,
+            syntax,
+            importer,
+        })
+    }
+}
+
+/// The outcome of a compilation, sent as a `CompileResponse`
+struct CompileResponse {
+    /// The CSS, and its source map as JSON if one was requested
+    result: Result<(String, Option<String>), Box<crate::Error>>,
+    loaded_urls: Vec<String>,
+    /// Maps file names in error spans back to their URLs
+    urls: Urls,
+}
+
+impl CompileResponse {
+    fn encode(&self, message: &mut Encoder) {
+        match &self.result {
+            Ok((css, source_map)) => {
+                message.message(2, |success| {
+                    success.string(1, css);
+
+                    if let Some(source_map) = source_map {
+                        success.string(2, source_map);
+                    }
+                });
+            }
+            Err(err) => {
+                let formatted = err.to_string();
+
+                message.message(3, |failure| match (**err).clone().kind() {
+                    ErrorKind::ParseError {
+                        message,
+                        loc,
+                        stack,
+                        ..
+                    } => {
+                        let mut stack_trace = String::new();
+                        write_stack_trace(&mut stack_trace, &stack, "").unwrap();
+
+                        failure
+                            .string(1, &message)
+                            .message(2, |span| self.urls.encode_span(span, &loc))
+                            .string(3, &stack_trace)
+                            .string(4, &formatted);
+                    }
+                    _ => {
+                        let message = formatted.trim_end();
+                        failure
+                            .string(1, message.strip_prefix("Error: ").unwrap_or(message))
+                            .string(4, &formatted);
+                    }
+                });
+            }
+        }
+
+        for url in &self.loaded_urls {
+            message.string_always(4, url);
+        }
+    }
+}
+
+/// Converts the names of files in the `CodeMap` into URLs
+#[derive(Debug, Default)]
+struct Urls {
+    /// The name and URL of the entry point, if it was passed as a string
+    /// without a `file:` URL
+    entry: Option<(String, String)>,
+    /// The canonical URLs of stylesheets loaded by host importers, which are
+    /// used as their names
+    canonical: Mutex<HashSet<String>>,
+}
+
+impl Urls {
+    fn url(&self, name: &str) -> String {
+        match &self.entry {
+            Some((entry_name, url)) if entry_name == name => return url.clone(),
+            _ => {}
+        }
+
+        if lock(&self.canonical).contains(name) {
+            name.to_owned()
+        } else {
+            file_url(Path::new(name))
+        }
+    }
+
+    /// Encodes a `SourceSpan`
+    fn encode_span(&self, span: &mut Encoder, loc: &SpanLoc) {
+        let file = &loc.file;
+        let offset = |line: usize, column: usize| {
+            let line_span = file.line_span(line);
+            let column = file
+                .source_slice(line_span)
+                .char_indices()
+                .nth(column)
+                .map_or(line_span.len() as usize, |(i, _)| i);
+            (line_span.low() - file.span.low()) as usize + column
+        };
+
+        let start = offset(loc.begin.line, loc.begin.column);
+        let end = offset(loc.end.line, loc.end.column);
+
+        let context_span = file
+            .line_span(loc.begin.line)
+            .merge(file.line_span(loc.end.line));
+
+        span.string(1, &file.source()[start..end])
+            .message(2, |location| {
+                location
+                    .uint32(1, start as u32)
+                    .uint32(2, loc.begin.line as u32)
+                    .uint32(3, loc.begin.column as u32);
+            })
+            .message(3, |location| {
+                location
+                    .uint32(1, end as u32)
+                    .uint32(2, loc.end.line as u32)
+                    .uint32(3, loc.end.column as u32);
+            })
+            .string(4, &self.url(file.name()))
+            .string(5, file.source_slice(context_span));
+    }
+}
+
+fn compile(connection: &Connection, compilation_id: u32, message: &[u8]) -> CompileResponse {
+    let mut urls = Urls::default();
+
+    let request = match CompileRequest::decode(message) {
+        Ok(request) => request,
+        Err(message) => {
+            connection.protocol_error(compilation_id, ProtocolErrorType::Parse, NO_ID, &message);
+            return CompileResponse {
+                result: Ok((String::new(), None)),
+                loaded_urls: Vec::new(),
+                urls,
+            };
+        }
+    };
+
+    let mut input = request.input;
+    let mut specs = Vec::new();
+
+    let name = match &mut input {
+        Some(Input::String { url, importer, .. }) => {
+            specs.extend(importer.take());
+
+            match file_url_to_path(url) {
+                Some(path) => path,
+                None => {
+                    let name = if url.is_empty() {
+                        "stdin"
+                    } else {
+                        url.as_str()
+                    };
+                    urls.entry = Some((name.to_owned(), url.clone()));
+                    PathBuf::from(name)
+                }
+            }
+        }
+        Some(Input::Path(path)) => path.clone(),
+        None => {
+            connection.protocol_error(
+                compilation_id,
+                ProtocolErrorType::Params,
+                NO_ID,
+                "CompileRequest.input is not set.",
+            );
+            return CompileResponse {
+                result: Ok((String::new(), None)),
+                loaded_urls: Vec::new(),
+                urls,
+            };
+        }
+    };
+
+    specs.extend(request.importers);
+
+    let compilation = Compilation {
+        connection,
+        id: compilation_id,
+        unicode: !request.alert_ascii,
+        loaded_urls: Mutex::new(Vec::new()),
+        urls,
+    };
+
+    let function_host = HostFunctions {
+        compilation: &compilation,
+        functions: request
+            .global_functions
+            .into_iter()
+            .map(|signature| {
+                let name = signature
+                    .split('(')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_owned();
+                HostFunction::new(HostFunctionId::Name(name), signature)
+            })
+            .collect(),
+        compiler_functions: RefCell::new(Vec::new()),
+        argument_lists: RefCell::new(Vec::new()),
+    };
+
+    let host_importers = specs
+        .iter()
+        .map(|spec| match spec {
+            ImporterSpec::Importer(id) | ImporterSpec::FileImporter(id) => Some(HostImporter {
+                compilation: &compilation,
+                id: *id,
+            }),
+            _ => None,
+        })
+        .collect::<Vec<Option<HostImporter>>>();
+
+    let mut options = Options::default()
+        .style(request.style)
+        .quiet(request.silent)
+        .verbose(request.verbose)
+        .allows_charset(request.charset)
+        .unicode_error_messages(compilation.unicode)
+        .fs(&compilation)
+        .logger(&compilation);
+
+    for (spec, importer) in specs.iter().zip(&host_importers) {
+        options = match (spec, importer) {
+            (ImporterSpec::Path(path), _) => options.load_path(path),
+            (ImporterSpec::NodePackage(path), _) => options.node_package_importer(path),
+            (ImporterSpec::Importer(_), Some(importer)) => options.importer(importer),
+            (ImporterSpec::FileImporter(_), Some(importer)) => options.file_importer(importer),
+            _ => options,
+        };
+    }
+
+    let deprecations = |ids: &[String]| {
+        ids.iter()
+            .filter_map(|id| Deprecation::from_id(id))
+            .collect::<Vec<Deprecation>>()
+    };
+
+    for deprecation in deprecations(&request.fatal_deprecations) {
+        options = options.fatal_deprecation(deprecation);
+    }
+
+    for deprecation in deprecations(&request.silence_deprecations) {
+        options = options.silence_deprecation(deprecation);
+    }
+
+    for deprecation in deprecations(&request.future_deprecations) {
+        options = options.future_deprecation(deprecation);
+    }
+
+    let result = match input {
+        Some(Input::String { source, syntax, .. }) => {
+            if compilation.urls.entry.is_none() {
+                compilation.record_url(file_url(&name));
+            }
+
+            crate::compile_with_function_host(
+                source,
+                &name,
+                &options.input_syntax(syntax),
+                &function_host,
+                request.source_map,
+            )
+        }
+        _ => compilation.read_source(&name).and_then(|source| {
+            crate::compile_with_function_host(
+                source,
+                &name,
+                &options,
+                &function_host,
+                request.source_map,
+            )
+        }),
+    };
+
+    let result = result.map(|(mut css, source_map)| {
+        if css.ends_with('\n') {
+            css.pop();
+        }
+
+        let source_map = source_map.map(|mut source_map| {
+            source_map.map_sources(|name| compilation.urls.url(name));
+            source_map.to_json(None, request.source_map_include_sources)
+        });
+
+        (css, source_map)
+    });
+
+    CompileResponse {
+        result,
+        loaded_urls: compilation
+            .loaded_urls
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner),
+        urls: compilation.urls,
+    }
+}
+
+/// The state of a single compilation, which implements the file system and
+/// logger used by the compiler
+struct Compilation<'a, 'io> {
+    connection: &'a Connection<'io>,
+    id: u32,
+    unicode: bool,
+    loaded_urls: Mutex<Vec<String>>,
+    urls: Urls,
+}
+
+impl fmt::Debug for Compilation<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compilation")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The host functions of a single compilation
+///
+/// Unlike the rest of a [`Compilation`], this holds Sass values, which can't
+/// be shared between threads, so it is passed to the compiler directly rather
+/// than through its [`Options`].
+struct HostFunctions<'a, 'io> {
+    compilation: &'a Compilation<'a, 'io>,
+    functions: Vec<HostFunction>,
+    /// Functions passed to the host as `CompilerFunction`s, indexed by ID
+    compiler_functions: RefCell<Vec<SassFunction>>,
+    /// Argument lists passed to the host, indexed by ID minus one, since an ID
+    /// of zero refers to a new argument list
+    argument_lists: RefCell<Vec<ArgList>>,
+}
+
+impl fmt::Debug for HostFunctions<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostFunctions")
+            .field("functions", &self.functions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Compilation<'_, '_> {
+    /// Reads the stylesheet at `path`, like [`crate::from_path`]
+    fn read_source(&self, path: &Path) -> crate::Result<String> {
+        Ok(String::from_utf8(self.read(path)?)?)
+    }
+
+    fn record_url(&self, url: String) {
+        let mut loaded_urls = lock(&self.loaded_urls);
+        if !loaded_urls.contains(&url) {
+            loaded_urls.push(url);
+        }
+    }
+
+    fn log(
+        &self,
+        kind: LogEventType,
+        deprecation: Option<Deprecation>,
+        message: &str,
+        location: Option<&SpanLoc>,
+        trace: &[StackFrame],
+    ) {
+        let mut formatted = String::new();
+        let mut stack_trace = String::new();
+        write_stack_trace(&mut stack_trace, trace, "").unwrap();
+
+        match (kind, location) {
+            (LogEventType::Debug, Some(location)) => {
+                formatted = format!(
+                    "{}:{} DEBUG: {}\n",
+                    location.file.name(),
+                    location.begin.line + 1,
+                    message
+                );
+            }
+            (_, Some(location)) => {
+                let header = match deprecation {
+                    Some(deprecation) => format!("Deprecation Warning [{}]", deprecation),
+                    None => "Warning".to_owned(),
+                };
+                write_diagnostic(
+                    &mut formatted,
+                    &header,
+                    message,
+                    location,
+                    self.unicode,
+                    trace,
+                )
+                .unwrap();
+            }
+            (_, None) => formatted = format!("Warning: {}\n", message),
+        }
+
+        self.connection.send(self.id, |outbound| {
+            outbound.message(3, |event| {
+                event.uint32(2, kind as u32).string(3, message);
+
+                if let Some(location) = location {
+                    event.message(4, |span| self.urls.encode_span(span, location));
+                }
+
+                event.string(5, &stack_trace).string(6, &formatted);
+
+                if let Some(deprecation) = deprecation {
+                    event.string(7, deprecation.id());
+                }
+            });
+        });
+    }
+}
+
+impl Fs for Compilation<'_, '_> {
+    fn is_dir(&self, path: &Path) -> bool {
+        StdFs.is_dir(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        StdFs.is_file(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let contents = StdFs.read(path)?;
+
+        let is_stylesheet = path
+            .extension()
+            .is_some_and(|ext| ext == "scss" || ext == "sass" || ext == "css");
+
+        if is_stylesheet {
+            self.record_url(file_url(path));
+        }
+
+        Ok(contents)
+    }
+}
+
+impl Logger for Compilation<'_, '_> {
+    fn debug(&self, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+        self.log(LogEventType::Debug, None, message, Some(&location), trace);
+    }
+
+    fn warn(&self, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+        self.log(LogEventType::Warning, None, message, Some(&location), trace);
+    }
+
+    fn deprecation(
+        &self,
+        deprecation: Deprecation,
+        location: SpanLoc,
+        message: &str,
+        trace: &[StackFrame],
+    ) {
+        self.log(
+            LogEventType::DeprecationWarning,
+            Some(deprecation),
+            message,
+            Some(&location),
+            trace,
+        );
+    }
+
+    fn warn_without_span(&self, message: &str) {
+        self.log(LogEventType::Warning, None, message, None, &[]);
+    }
+}
+
+impl FunctionHost for HostFunctions<'_, '_> {
+    fn global_functions(&self) -> &[HostFunction] {
+        &self.functions
+    }
+
+    fn call(
+        &self,
+        function: &HostFunction,
+        arguments: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, String> {
+        let compilation = self.compilation;
+        let response = compilation
+            .connection
+            .request(compilation.id, 7, 5, |request| {
+                match &function.id {
+                    HostFunctionId::Name(name) => request.string_always(2, name),
+                    HostFunctionId::Id(id) => request.uint32_always(3, *id),
+                };
+
+                for argument in &arguments {
+                    request.message(4, |value| self.encode_value(value, argument, span));
+                }
+            })?;
+
+        let mut result = Err("FunctionCallResponse.result is not set.".to_owned());
+
+        let mut decoder = Decoder::new(&response);
+        while let Some((number, field)) = decoder.next_field()? {
+            match number {
+                2 => result = self.decode_value(field.as_bytes()?, span),
+                3 => result = Err(field.as_string()?),
+                4 => {
+                    for id in packed_uint32(field)? {
+                        if let Some(argument_list) = self
+                            .argument_lists
+                            .borrow()
+                            .get((id as usize).wrapping_sub(1))
+                        {
+                            argument_list.keywords();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        result
+    }
+}
+
+/// Decodes a repeated `uint32` field, which may or may not be packed
+fn packed_uint32(field: Field) -> Result<Vec<u32>, String> {
+    match field {
+        Field::Bytes(bytes) => {
+            let mut values = Vec::new();
+            let mut rest = bytes;
+
+            while !rest.is_empty() {
+                let mut value = 0_u32;
+                let mut len = 0;
+                for (i, &byte) in rest.iter().enumerate().take(5) {
+                    value |= u32::from(byte & 0x7f) << (7 * i);
+                    len = i + 1;
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                }
+                values.push(value);
+                rest = &rest[len..];
+            }
+
+            Ok(values)
+        }
+        field => Ok(vec![field.as_u32()?]),
+    }
+}
+
+/// A host importer or file importer, identified by its ID
+#[derive(Debug)]
+struct HostImporter<'a, 'io> {
+    compilation: &'a Compilation<'a, 'io>,
+    id: u32,
+}
+
+impl HostImporter<'_, '_> {
+    /// Decodes a response whose result is a URL in field 2 or an error in
+    /// field 3
+    fn url_response(response: &[u8]) -> Result<Option<String>, String> {
+        let mut url = None;
+
+        let mut decoder = Decoder::new(response);
+        while let Some((number, field)) = decoder.next_field()? {
+            match number {
+                2 => url = Some(field.as_string()?),
+                3 => return Err(field.as_string()?),
+                _ => {}
+            }
+        }
+
+        Ok(url)
+    }
+
+    fn encode_context(request: &mut Encoder, context: &ImportContext) {
+        request.bool(5, context.from_import);
+
+        if let Some(containing_url) = context.containing_url {
+            request.string(6, containing_url);
+        }
+    }
+}
+
+impl Importer for HostImporter<'_, '_> {
+    fn canonicalize(&self, url: &str, context: &ImportContext) -> Result<Option<String>, String> {
+        let compilation = self.compilation;
+        let response = compilation
+            .connection
+            .request(compilation.id, 4, 2, |request| {
+                request.uint32(3, self.id).string(4, url);
+                Self::encode_context(request, context);
+            })?;
+
+        Self::url_response(&response)
+    }
+
+    fn load(&self, canonical_url: &str) -> Result<ImporterResult, String> {
+        let compilation = self.compilation;
+        let response = compilation
+            .connection
+            .request(compilation.id, 5, 3, |request| {
+                request.uint32(3, self.id).string(4, canonical_url);
+            })?;
+
+        let mut result = None;
+
+        let mut decoder = Decoder::new(&response);
+        while let Some((number, field)) = decoder.next_field()? {
+            match number {
+                2 => {
+                    let mut contents = String::new();
+                    let mut syntax = InputSyntax::Scss;
+
+                    let mut decoder = Decoder::new(field.as_bytes()?);
+                    while let Some((number, field)) = decoder.next_field()? {
+                        match number {
+                            1 => contents = field.as_string()?,
+                            2 => syntax = syntax_from_id(field.as_u32()?),
+                            _ => {}
+                        }
+                    }
+
+                    result = Some(ImporterResult::new(contents, syntax));
+                }
+                3 => return Err(field.as_string()?),
+                _ => {}
+            }
+        }
+
+        let result = result.ok_or_else(|| "Can't find stylesheet to import.".to_owned())?;
+
+        lock(&compilation.urls.canonical).insert(canonical_url.to_owned());
+        compilation.record_url(canonical_url.to_owned());
+
+        Ok(result)
+    }
+}
+
+impl FileImporter for HostImporter<'_, '_> {
+    fn find_file(&self, url: &str, context: &ImportContext) -> Result<Option<PathBuf>, String> {
+        let compilation = self.compilation;
+        let response = compilation
+            .connection
+            .request(compilation.id, 6, 4, |request| {
+                request.uint32(3, self.id).string(4, url);
+                Self::encode_context(request, context);
+            })?;
+
+        match Self::url_response(&response)? {
+            Some(url) => match file_url_to_path(&url) {
+                Some(path) => Ok(Some(path)),
+                None => Err(format!(
+                    "The file importer must return a file: URL, was \"{}\"",
+                    url
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+}
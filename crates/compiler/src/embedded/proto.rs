@@ -0,0 +1,256 @@
+//! A minimal protocol buffers encoder and decoder, and the framing of packets
+//! in the embedded protocol
+
+use std::io::{self, Read, Write};
+
+/// A field decoded from a message
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Field<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    /// A `fixed32` or `float`, which no message in the protocol uses
+    Fixed32,
+}
+
+impl<'a> Field<'a> {
+    pub fn as_u32(self) -> Result<u32, String> {
+        match self {
+            Field::Varint(v) => Ok(v as u32),
+            _ => Err("Expected a varint field.".to_owned()),
+        }
+    }
+
+    pub fn as_bool(self) -> Result<bool, String> {
+        self.as_u32().map(|v| v != 0)
+    }
+
+    pub fn as_f64(self) -> Result<f64, String> {
+        match self {
+            Field::Fixed64(v) => Ok(f64::from_bits(v)),
+            _ => Err("Expected a double field.".to_owned()),
+        }
+    }
+
+    pub fn as_bytes(self) -> Result<&'a [u8], String> {
+        match self {
+            Field::Bytes(bytes) => Ok(bytes),
+            _ => Err("Expected a length-delimited field.".to_owned()),
+        }
+    }
+
+    pub fn as_string(self) -> Result<String, String> {
+        String::from_utf8(self.as_bytes()?.to_vec()).map_err(|_| "Invalid UTF-8.".to_owned())
+    }
+}
+
+/// Iterates over the fields of an encoded message
+pub(crate) struct Decoder<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0_u64;
+
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self
+                .buf
+                .split_first()
+                .ok_or_else(|| "Truncated varint.".to_owned())?;
+            self.buf = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err("Varint is too long.".to_owned())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.buf.len() < len {
+            return Err("Truncated message.".to_owned());
+        }
+
+        let (taken, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(taken)
+    }
+
+    /// The next field number and value, or `None` at the end of the message
+    pub fn next_field(&mut self) -> Result<Option<(u32, Field<'a>)>, String> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        let key = self.varint()?;
+        let number = (key >> 3) as u32;
+
+        let field = match key & 0x7 {
+            0 => Field::Varint(self.varint()?),
+            1 => Field::Fixed64(u64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            2 => {
+                let len = self.varint()? as usize;
+                Field::Bytes(self.take(len)?)
+            }
+            5 => {
+                self.take(4)?;
+                Field::Fixed32
+            }
+            wire_type => return Err(format!("Unknown wire type {}.", wire_type)),
+        };
+
+        Ok(Some((number, field)))
+    }
+}
+
+/// Builds an encoded message. As in proto3, fields with default values are
+/// omitted unless they are part of a `oneof`
+#[derive(Default)]
+pub(crate) struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn key(&mut self, number: u32, wire_type: u8) {
+        self.varint(u64::from(number) << 3 | u64::from(wire_type));
+    }
+
+    pub fn uint32(&mut self, number: u32, value: u32) -> &mut Self {
+        if value != 0 {
+            self.key(number, 0);
+            self.varint(u64::from(value));
+        }
+        self
+    }
+
+    /// Encodes a varint even if it's zero, for `oneof` fields
+    pub fn uint32_always(&mut self, number: u32, value: u32) -> &mut Self {
+        self.key(number, 0);
+        self.varint(u64::from(value));
+        self
+    }
+
+    pub fn bool(&mut self, number: u32, value: bool) -> &mut Self {
+        self.uint32(number, u32::from(value))
+    }
+
+    pub fn double(&mut self, number: u32, value: f64) -> &mut Self {
+        if value != 0.0 || value.is_sign_negative() {
+            self.key(number, 1);
+            self.buf.extend_from_slice(&value.to_bits().to_le_bytes());
+        }
+        self
+    }
+
+    pub fn string(&mut self, number: u32, value: &str) -> &mut Self {
+        if !value.is_empty() {
+            self.bytes(number, value.as_bytes());
+        }
+        self
+    }
+
+    /// Encodes a string even if it's empty, for `oneof` and `optional` fields
+    pub fn string_always(&mut self, number: u32, value: &str) -> &mut Self {
+        self.bytes(number, value.as_bytes())
+    }
+
+    pub fn message(&mut self, number: u32, build: impl FnOnce(&mut Encoder)) -> &mut Self {
+        let mut inner = Encoder::new();
+        build(&mut inner);
+        self.bytes(number, &inner.buf)
+    }
+
+    fn bytes(&mut self, number: u32, bytes: &[u8]) -> &mut Self {
+        self.key(number, 2);
+        self.varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+}
+
+/// Reads a packet, consisting of a varint length followed by a varint
+/// compilation ID and an encoded `InboundMessage`, or `None` at the end of
+/// the input
+pub(crate) fn read_packet(input: &mut impl Read) -> io::Result<Option<(u32, Vec<u8>)>> {
+    let len = match read_varint(input)? {
+        Some(len) => len as usize,
+        None => return Ok(None),
+    };
+
+    let mut packet = vec![0; len];
+    input.read_exact(&mut packet)?;
+
+    let mut id = 0_u64;
+    let mut consumed = 0;
+    for (i, &byte) in packet.iter().enumerate().take(5) {
+        id |= u64::from(byte & 0x7f) << (7 * i);
+        consumed = i + 1;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    Ok(Some((id as u32, packet.split_off(consumed))))
+}
+
+fn read_varint(input: &mut impl Read) -> io::Result<Option<u64>> {
+    let mut value = 0_u64;
+    let mut byte = [0];
+
+    for shift in (0..64).step_by(7) {
+        if input.read(&mut byte)? == 0 {
+            if shift == 0 {
+                return Ok(None);
+            }
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "varint is too long",
+    ))
+}
+
+/// Writes an encoded `OutboundMessage` for the compilation `id`
+pub(crate) fn write_packet(output: &mut impl Write, id: u32, message: &[u8]) -> io::Result<()> {
+    let mut header = Encoder::new();
+    header.varint(u64::from(id));
+    let id = header.finish();
+
+    let mut len = Encoder::new();
+    len.varint((id.len() + message.len()) as u64);
+
+    output.write_all(&len.finish())?;
+    output.write_all(&id)?;
+    output.write_all(message)?;
+    output.flush()
+}
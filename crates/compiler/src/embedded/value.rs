@@ -0,0 +1,428 @@
+//! Conversion between Sass values and the `Value` message of the embedded
+//! protocol
+
+use std::{
+    collections::BTreeMap,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use codemap::{Span, Spanned};
+
+use crate::{
+    color::Color,
+    common::{Brackets, Identifier, ListSeparator, QuoteKind},
+    unit::Unit,
+    value::{
+        ArgList, HostFunction, HostFunctionId, Number, SassFunction, SassMap, SassNumber, Value,
+    },
+};
+
+use super::{
+    proto::{Decoder, Encoder, Field},
+    HostFunctions,
+};
+
+/// The `ListSeparator` values
+const fn separator_id(separator: ListSeparator) -> u32 {
+    match separator {
+        ListSeparator::Comma => 0,
+        ListSeparator::Space => 1,
+        ListSeparator::Slash => 2,
+        ListSeparator::Undecided => 3,
+    }
+}
+
+fn separator_from_id(id: u32) -> Result<ListSeparator, String> {
+    Ok(match id {
+        0 => ListSeparator::Comma,
+        1 => ListSeparator::Space,
+        2 => ListSeparator::Slash,
+        3 => ListSeparator::Undecided,
+        _ => return Err(format!("Unknown list separator {}.", id)),
+    })
+}
+
+/// Reads a numeric field, which is a `uint32` for the RGB channels of colors
+/// and a `double` otherwise
+fn number_field(field: Field) -> Result<f64, String> {
+    match field {
+        Field::Varint(..) => field.as_u32().map(f64::from),
+        _ => field.as_f64(),
+    }
+}
+
+impl HostFunctions<'_, '_> {
+    /// Encodes `value` as the fields of a `Value` message
+    pub(super) fn encode_value(&self, message: &mut Encoder, value: &Value, span: Span) {
+        match value {
+            Value::True => {
+                message.uint32_always(7, 0);
+            }
+            Value::False => {
+                message.uint32_always(7, 1);
+            }
+            Value::Null => {
+                message.uint32_always(7, 2);
+            }
+            Value::String(text, quotes) => {
+                message.message(1, |string| {
+                    string
+                        .string(1, text)
+                        .bool(2, matches!(quotes, QuoteKind::Quoted));
+                });
+            }
+            Value::Dimension(SassNumber { num, unit, .. }) => {
+                let (numerators, denominators) = unit.clone().numer_and_denom();
+
+                message.message(2, |number| {
+                    number.double(1, num.0);
+                    for unit in numerators {
+                        number.string(2, &unit.to_string());
+                    }
+                    for unit in denominators {
+                        number.string(3, &unit.to_string());
+                    }
+                });
+            }
+            Value::Color(color) => {
+                message.message(3, |rgb| {
+                    rgb.uint32(1, color.red().0.round() as u32)
+                        .uint32(2, color.green().0.round() as u32)
+                        .uint32(3, color.blue().0.round() as u32)
+                        .double(4, color.alpha().0);
+                });
+            }
+            Value::List(elems, separator, brackets) => {
+                message.message(5, |list| {
+                    list.uint32(1, separator_id(*separator))
+                        .bool(2, matches!(brackets, Brackets::Bracketed));
+                    for elem in elems {
+                        list.message(3, |value| self.encode_value(value, elem, span));
+                    }
+                });
+            }
+            Value::Map(map) => {
+                message.message(6, |message| {
+                    for (key, value) in map.iter() {
+                        message.message(1, |entry| {
+                            entry
+                                .message(1, |message| self.encode_value(message, &key.node, span))
+                                .message(2, |message| self.encode_value(message, value, span));
+                        });
+                    }
+                });
+            }
+            Value::ArgList(arglist) => {
+                let id = {
+                    let mut argument_lists = self.argument_lists.borrow_mut();
+                    argument_lists.push(arglist.clone());
+                    argument_lists.len() as u32
+                };
+
+                message.message(10, |message| {
+                    message
+                        .uint32(1, id)
+                        .uint32(2, separator_id(arglist.separator));
+                    for elem in &arglist.elems {
+                        message.message(3, |value| self.encode_value(value, elem, span));
+                    }
+                    for (name, value) in arglist.peek_keywords() {
+                        message.message(4, |entry| {
+                            entry
+                                .string(1, name.as_str())
+                                .message(2, |message| self.encode_value(message, value, span));
+                        });
+                    }
+                });
+            }
+            Value::FunctionRef(function) => match &**function {
+                SassFunction::Host(HostFunction {
+                    id: HostFunctionId::Id(id),
+                    signature,
+                    ..
+                }) => {
+                    message.message(9, |message| {
+                        message.uint32(1, *id).string(2, signature);
+                    });
+                }
+                function => {
+                    let id = {
+                        let mut compiler_functions = self.compiler_functions.borrow_mut();
+                        compiler_functions.push(function.clone());
+                        compiler_functions.len() as u32 - 1
+                    };
+
+                    message.message(8, |message| {
+                        message.uint32(1, id);
+                    });
+                }
+            },
+            Value::Calculation(..) => {
+                // calculations aren't part of version 2 of the protocol, so
+                // they're passed as unquoted strings
+                let text = value.inspect(span).unwrap_or_default();
+                message.message(1, |string| {
+                    string.string(1, &text);
+                });
+            }
+        }
+    }
+
+    /// Decodes a `Value` message
+    pub(super) fn decode_value(&self, message: &[u8], span: Span) -> Result<Value, String> {
+        let mut decoder = Decoder::new(message);
+        let mut value = None;
+
+        while let Some((number, field)) = decoder.next_field()? {
+            value = Some(match number {
+                1 => self.decode_string(field.as_bytes()?)?,
+                2 => self.decode_number(field.as_bytes()?)?,
+                3 | 4 | 11 => self.decode_color(number, field.as_bytes()?)?,
+                5 => self.decode_list(field.as_bytes()?, span)?,
+                6 => self.decode_map(field.as_bytes()?, span)?,
+                7 => match field.as_u32()? {
+                    0 => Value::True,
+                    1 => Value::False,
+                    2 => Value::Null,
+                    id => return Err(format!("Unknown singleton value {}.", id)),
+                },
+                8 => {
+                    let id = self.message_fields(field.as_bytes()?, |number, field, id| {
+                        if number == 1 {
+                            *id = field.as_u32()?;
+                        }
+                        Ok(())
+                    })?;
+
+                    match self.compiler_functions.borrow().get(id as usize) {
+                        Some(function) => Value::FunctionRef(Box::new(function.clone())),
+                        None => return Err(format!("CompilerFunction.id {} doesn't exist.", id)),
+                    }
+                }
+                9 => {
+                    let (id, signature) = self.message_fields(
+                        field.as_bytes()?,
+                        |number, field, (id, signature): &mut (u32, String)| {
+                            match number {
+                                1 => *id = field.as_u32()?,
+                                2 => *signature = field.as_string()?,
+                                _ => {}
+                            }
+                            Ok(())
+                        },
+                    )?;
+
+                    Value::FunctionRef(Box::new(SassFunction::Host(HostFunction::new(
+                        HostFunctionId::Id(id),
+                        signature,
+                    ))))
+                }
+                10 => self.decode_argument_list(field.as_bytes()?, span)?,
+                _ => return Err(format!("Unknown value type {}.", number)),
+            });
+        }
+
+        value.ok_or_else(|| "Value.value is not set.".to_owned())
+    }
+
+    /// Folds the fields of `message` into a default value of `T`
+    fn message_fields<T: Default>(
+        &self,
+        message: &[u8],
+        mut visit: impl FnMut(u32, Field, &mut T) -> Result<(), String>,
+    ) -> Result<T, String> {
+        let mut result = T::default();
+        let mut decoder = Decoder::new(message);
+
+        while let Some((number, field)) = decoder.next_field()? {
+            visit(number, field, &mut result)?;
+        }
+
+        Ok(result)
+    }
+
+    fn decode_string(&self, message: &[u8]) -> Result<Value, String> {
+        let (text, quoted) = self.message_fields(
+            message,
+            |number, field, (text, quoted): &mut (String, bool)| {
+                match number {
+                    1 => *text = field.as_string()?,
+                    2 => *quoted = field.as_bool()?,
+                    _ => {}
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(Value::String(
+            text,
+            if quoted {
+                QuoteKind::Quoted
+            } else {
+                QuoteKind::None
+            },
+        ))
+    }
+
+    fn decode_number(&self, message: &[u8]) -> Result<Value, String> {
+        let (value, numerators, denominators) = self.message_fields(
+            message,
+            |number, field, (value, numerators, denominators): &mut (f64, Vec<Unit>, Vec<Unit>)| {
+                match number {
+                    1 => *value = field.as_f64()?,
+                    2 => numerators.push(Unit::from(field.as_string()?)),
+                    3 => denominators.push(Unit::from(field.as_string()?)),
+                    _ => {}
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(Value::Dimension(SassNumber {
+            num: Number(value),
+            unit: Unit::new(numerators, denominators),
+            as_slash: None,
+        }))
+    }
+
+    /// Decodes an `RgbColor`, `HslColor` or `HwbColor`, whose channels are
+    /// all in fields 1 to 4
+    fn decode_color(&self, kind: u32, message: &[u8]) -> Result<Value, String> {
+        let [first, second, third, alpha] =
+            self.message_fields(message, |number, field, channels: &mut [f64; 4]| {
+                if let Some(channel) = channels.get_mut(number as usize - 1) {
+                    *channel = number_field(field)?;
+                }
+                Ok(())
+            })?;
+
+        let (first, second, third, alpha) =
+            (Number(first), Number(second), Number(third), Number(alpha));
+
+        let color = match kind {
+            3 => Color::from_rgba(first, second, third, alpha),
+            4 => Color::from_hsla(first, second / Number(100.0), third / Number(100.0), alpha),
+            _ => Color::from_hwb(first, second, third, alpha),
+        };
+
+        Ok(Value::Color(Arc::new(color)))
+    }
+
+    fn decode_list(&self, message: &[u8], span: Span) -> Result<Value, String> {
+        let (separator, brackets, elems) = self.message_fields(
+            message,
+            |number, field, (separator, brackets, elems): &mut (u32, bool, Vec<Value>)| {
+                match number {
+                    1 => *separator = field.as_u32()?,
+                    2 => *brackets = field.as_bool()?,
+                    3 => elems.push(self.decode_value(field.as_bytes()?, span)?),
+                    _ => {}
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(Value::List(
+            elems.into(),
+            separator_from_id(separator)?,
+            if brackets {
+                Brackets::Bracketed
+            } else {
+                Brackets::None
+            },
+        ))
+    }
+
+    fn decode_map(&self, message: &[u8], span: Span) -> Result<Value, String> {
+        let entries = self.message_fields(
+            message,
+            |number, field, entries: &mut Vec<(Spanned<Value>, Value)>| {
+                if number == 1 {
+                    let (key, value) = self.decode_entry(field.as_bytes()?, span)?;
+                    entries.push((Spanned { node: key, span }, value));
+                }
+                Ok(())
+            },
+        )?;
+
+        let mut map = SassMap::new();
+        for (key, value) in entries {
+            map.insert(key, value);
+        }
+
+        Ok(Value::Map(map))
+    }
+
+    /// Decodes a `Map.Entry`
+    fn decode_entry(&self, message: &[u8], span: Span) -> Result<(Value, Value), String> {
+        let (key, value) = self.message_fields(
+            message,
+            |number, field, (key, value): &mut (Option<Value>, Option<Value>)| {
+                match number {
+                    1 => *key = Some(self.decode_value(field.as_bytes()?, span)?),
+                    2 => *value = Some(self.decode_value(field.as_bytes()?, span)?),
+                    _ => {}
+                }
+                Ok(())
+            },
+        )?;
+
+        match (key, value) {
+            (Some(key), Some(value)) => Ok((key, value)),
+            _ => Err("Map.Entry is missing a key or value.".to_owned()),
+        }
+    }
+
+    fn decode_argument_list(&self, message: &[u8], span: Span) -> Result<Value, String> {
+        #[derive(Default)]
+        struct Fields {
+            id: u32,
+            separator: u32,
+            elems: Vec<Value>,
+            keywords: BTreeMap<Identifier, Value>,
+        }
+
+        let fields = self.message_fields(message, |number, field, fields: &mut Fields| {
+            match number {
+                1 => fields.id = field.as_u32()?,
+                2 => fields.separator = field.as_u32()?,
+                3 => fields
+                    .elems
+                    .push(self.decode_value(field.as_bytes()?, span)?),
+                4 => {
+                    let (name, value) = self.message_fields(
+                        field.as_bytes()?,
+                        |number, field, (name, value): &mut (String, Option<Value>)| {
+                            match number {
+                                1 => *name = field.as_string()?,
+                                2 => *value = Some(self.decode_value(field.as_bytes()?, span)?),
+                                _ => {}
+                            }
+                            Ok(())
+                        },
+                    )?;
+
+                    let value = value
+                        .ok_or_else(|| "ArgumentList keyword is missing a value.".to_owned())?;
+                    fields.keywords.insert(Identifier::from(name), value);
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+
+        if fields.id != 0 {
+            return match self.argument_lists.borrow().get(fields.id as usize - 1) {
+                Some(arglist) => Ok(Value::ArgList(arglist.clone())),
+                None => Err(format!("ArgumentList.id {} doesn't exist.", fields.id)),
+            };
+        }
+
+        Ok(Value::ArgList(ArgList::new(
+            fields.elems,
+            Arc::new(AtomicBool::new(false)),
+            fields.keywords,
+            separator_from_id(fields.separator)?,
+        )))
+    }
+}
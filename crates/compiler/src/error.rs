@@ -25,6 +25,12 @@ pub type SassResult<T> = Result<T, Box<SassError>>;
 /// ./input.scss:308:17
 ///```
 ///
+/// If the error occurred inside a mixin, function, or module, the location is
+/// followed by a Sass stack trace, e.g.
+///```text
+///   _mixins.scss 12:3  button-variant()
+///   input.scss 4:3     root stylesheet
+///```
 #[derive(Debug, Clone)]
 pub struct SassError {
     kind: SassErrorKind,
@@ -38,10 +44,12 @@ impl SassError {
                 message,
                 loc,
                 unicode,
+                stack,
             } => PublicSassErrorKind::ParseError {
                 message,
                 loc,
                 unicode,
+                stack,
             },
             SassErrorKind::FromUtf8Error(s) => PublicSassErrorKind::FromUtf8Error(s),
             SassErrorKind::IoError(io) => PublicSassErrorKind::IoError(io),
@@ -49,19 +57,42 @@ impl SassError {
         }
     }
 
-    pub(crate) fn raw(self) -> (String, Span) {
+    /// The message, span, and stack trace of a raw error, or the error itself
+    /// if it has no span, e.g. an `io::Error`
+    pub(crate) fn raw(self) -> Result<(String, Span, Option<Vec<RawStackFrame>>), Self> {
         match self.kind {
-            SassErrorKind::Raw(string, span) => (string, span),
-            e => unreachable!("unable to get raw of {:?}", e),
+            SassErrorKind::Raw(string, span, trace) => Ok((string, span, trace)),
+            kind => Err(Self { kind }),
+        }
+    }
+
+    /// Attach a Sass stack trace to this error if it does not already have one
+    ///
+    /// Errors bubble up through every frame of the stack, so only the innermost
+    /// frame (the first to see the error) should record the trace
+    pub(crate) fn with_trace(
+        mut self: Box<Self>,
+        trace: impl FnOnce(Span) -> Vec<RawStackFrame>,
+    ) -> Box<Self> {
+        if let SassErrorKind::Raw(_, span, ref mut existing @ None) = self.kind {
+            *existing = Some(trace(span));
         }
+
+        self
     }
 
-    pub(crate) const fn from_loc(message: String, loc: SpanLoc, unicode: bool) -> Self {
+    pub(crate) const fn from_loc(
+        message: String,
+        loc: SpanLoc,
+        unicode: bool,
+        stack: Vec<StackFrame>,
+    ) -> Self {
         SassError {
             kind: SassErrorKind::ParseError {
                 message,
                 loc,
                 unicode,
+                stack,
             },
         }
     }
@@ -87,6 +118,10 @@ pub enum PublicSassErrorKind {
         ///
         /// This is configurable with [`crate::Options::unicode_error_messages`]
         unicode: bool,
+
+        /// The Sass stack trace at the point the error was thrown, starting
+        /// with the innermost frame and ending with the root stylesheet.
+        stack: Vec<StackFrame>,
     },
 
     /// Sass was unable to find the entry-point file.
@@ -99,16 +134,72 @@ pub enum PublicSassErrorKind {
     FromUtf8Error(String),
 }
 
+/// A single entry in a Sass stack trace
+#[derive(Debug, Clone)]
+pub struct StackFrame {
+    /// The name of the member being executed in this frame, e.g. `foo()` for
+    /// a function or mixin, `@content`, `@use`, or `root stylesheet`
+    pub member: String,
+
+    /// The location within this frame that was being executed
+    pub loc: SpanLoc,
+}
+
+impl StackFrame {
+    pub(crate) fn new(member: String, loc: SpanLoc) -> Self {
+        Self { member, loc }
+    }
+}
+
+/// A stack frame which has not yet been resolved against the `CodeMap`
+pub(crate) type RawStackFrame = (String, Span);
+
+/// Format a Sass stack trace in the same style as `dart-sass`, with every
+/// line prefixed by `indent`
+pub(crate) fn write_stack_trace(
+    f: &mut impl fmt::Write,
+    stack: &[StackFrame],
+    indent: &str,
+) -> fmt::Result {
+    let locations = stack
+        .iter()
+        .map(|frame| {
+            format!(
+                "{} {}:{}",
+                frame.loc.file.name(),
+                frame.loc.begin.line + 1,
+                frame.loc.begin.column + 1
+            )
+        })
+        .collect::<Vec<String>>();
+
+    let width = locations.iter().map(String::len).max().unwrap_or(0);
+
+    for (location, frame) in locations.iter().zip(stack) {
+        writeln!(
+            f,
+            "{}{:width$}  {}",
+            indent,
+            location,
+            frame.member,
+            width = width
+        )?;
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 enum SassErrorKind {
     /// A raw error with no additional metadata
-    /// It contains only a `String` message and
-    /// a span
-    Raw(String, Span),
+    /// It contains only a `String` message, a span, and the
+    /// Sass stack trace if one has been recorded
+    Raw(String, Span, Option<Vec<RawStackFrame>>),
     ParseError {
         message: String,
         loc: SpanLoc,
         unicode: bool,
+        stack: Vec<StackFrame>,
     },
     // we put `IoError`s in an `Arc` to allow them to be cloneable
     IoError(Arc<io::Error>),
@@ -121,55 +212,73 @@ impl Display for SassError {
     // TODO: integrate with codemap-diagnostics
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (message, loc, unicode) = match &self.kind {
+        let (message, loc, unicode, stack) = match &self.kind {
             SassErrorKind::ParseError {
                 message,
                 loc,
                 unicode,
-            } => (message, loc, *unicode),
+                stack,
+            } => (message, loc, *unicode, stack),
             SassErrorKind::FromUtf8Error(..) => return writeln!(f, "Error: Invalid UTF-8."),
             SassErrorKind::IoError(s) => return writeln!(f, "Error: {}", s),
-            SassErrorKind::Raw(..) => unreachable!(),
+            // raw errors are converted before being returned to users, but we
+            // can still print their message without a location
+            SassErrorKind::Raw(message, ..) => return writeln!(f, "Error: {}", message),
         };
 
-        let first_bar = if unicode { '╷' } else { ',' };
-        let second_bar = if unicode { '│' } else { '|' };
-        let third_bar = if unicode { '│' } else { '|' };
-        let fourth_bar = if unicode { '╵' } else { '\'' };
+        write_diagnostic(f, "Error", message, loc, unicode, stack)
+    }
+}
+
+/// Writes a diagnostic in the format of errors, with the line containing
+/// `loc` underlined and followed by the Sass stack trace
+pub(crate) fn write_diagnostic(
+    f: &mut impl fmt::Write,
+    header: &str,
+    message: &str,
+    loc: &SpanLoc,
+    unicode: bool,
+    stack: &[StackFrame],
+) -> fmt::Result {
+    let first_bar = if unicode { '╷' } else { ',' };
+    let second_bar = if unicode { '│' } else { '|' };
+    let third_bar = if unicode { '│' } else { '|' };
+    let fourth_bar = if unicode { '╵' } else { '\'' };
 
-        let line = loc.begin.line + 1;
-        let col = loc.begin.column + 1;
-        writeln!(f, "Error: {}", message)?;
-        let padding = vec![' '; format!("{}", line).len() + 1]
+    let line = loc.begin.line + 1;
+    let col = loc.begin.column + 1;
+    writeln!(f, "{}: {}", header, message)?;
+    let padding = vec![' '; format!("{}", line).len() + 1]
+        .iter()
+        .collect::<String>();
+    writeln!(f, "{}{}", padding, first_bar)?;
+    writeln!(
+        f,
+        "{} {} {}",
+        line,
+        second_bar,
+        loc.file.source_line(loc.begin.line)
+    )?;
+    writeln!(
+        f,
+        "{}{} {}{}",
+        padding,
+        third_bar,
+        vec![' '; loc.begin.column].iter().collect::<String>(),
+        vec!['^'; loc.end.column.max(loc.begin.column) - loc.begin.column.min(loc.end.column)]
             .iter()
-            .collect::<String>();
-        writeln!(f, "{}{}", padding, first_bar)?;
-        writeln!(
-            f,
-            "{} {} {}",
-            line,
-            second_bar,
-            loc.file.source_line(loc.begin.line)
-        )?;
-        writeln!(
-            f,
-            "{}{} {}{}",
-            padding,
-            third_bar,
-            vec![' '; loc.begin.column].iter().collect::<String>(),
-            vec!['^'; loc.end.column.max(loc.begin.column) - loc.begin.column.min(loc.end.column)]
-                .iter()
-                .collect::<String>()
-        )?;
-        writeln!(f, "{}{}", padding, fourth_bar)?;
+            .collect::<String>()
+    )?;
+    writeln!(f, "{}{}", padding, fourth_bar)?;
 
-        if unicode {
-            writeln!(f, "./{}:{}:{}", loc.file.name(), line, col)?;
-        } else {
-            writeln!(f, "  {} {}:{}  root stylesheet", loc.file.name(), line, col)?;
-        }
-        Ok(())
+    if unicode && stack.len() <= 1 {
+        writeln!(f, "./{}:{}:{}", loc.file.name(), line, col)?;
+    } else if stack.is_empty() {
+        writeln!(f, "  {} {}:{}  root stylesheet", loc.file.name(), line, col)?;
+    } else {
+        write_stack_trace(f, stack, "  ")?;
     }
+    Ok(())
 }
 
 impl From<io::Error> for Box<SassError> {
@@ -197,7 +306,7 @@ impl From<(&str, Span)> for Box<SassError> {
     #[inline]
     fn from(error: (&str, Span)) -> Box<SassError> {
         Box::new(SassError {
-            kind: SassErrorKind::Raw(error.0.to_owned(), error.1),
+            kind: SassErrorKind::Raw(error.0.to_owned(), error.1, None),
         })
     }
 }
@@ -206,7 +315,7 @@ impl From<(String, Span)> for Box<SassError> {
     #[inline]
     fn from(error: (String, Span)) -> Box<SassError> {
         Box::new(SassError {
-            kind: SassErrorKind::Raw(error.0, error.1),
+            kind: SassErrorKind::Raw(error.0, error.1, None),
         })
     }
 }
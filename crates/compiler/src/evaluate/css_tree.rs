@@ -79,28 +79,32 @@ impl CssTree {
 
     fn add_child_to_parent(&self, child: CssStmt, parent_idx: CssTreeIdx) {
         RefMut::map(self.stmts[parent_idx.0].borrow_mut(), |parent| {
-            match parent {
-                Some(CssStmt::RuleSet { body, .. }) => body.push(child),
-                Some(CssStmt::Style(..) | CssStmt::Comment(..) | CssStmt::Import(..)) | None => {
-                    unreachable!()
-                }
-                Some(CssStmt::Media(media, ..)) => {
-                    media.body.push(child);
-                }
-                Some(CssStmt::UnknownAtRule(at_rule, ..)) => {
-                    at_rule.body.push(child);
-                }
-                Some(CssStmt::Supports(supports, ..)) => {
-                    supports.body.push(child);
-                }
-                Some(CssStmt::KeyframesRuleSet(keyframes)) => {
-                    keyframes.body.push(child);
-                }
-            };
+            push_child(parent.as_mut().unwrap(), child);
             parent
         });
     }
 
+    /// The top-level statements, in the order they were added
+    pub fn root_children(&self) -> &[CssTreeIdx] {
+        self.parent_to_child
+            .get(&Self::ROOT)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// A copy of the statement at `idx` with its children applied, as it would
+    /// be returned by [`CssTree::finish`]
+    pub fn clone_subtree(&self, idx: CssTreeIdx) -> Option<CssStmt> {
+        let mut stmt = self.get(idx).clone()?;
+
+        for &child in self.parent_to_child.get(&idx).into_iter().flatten() {
+            if let Some(child) = self.clone_subtree(child) {
+                push_child(&mut stmt, child);
+            }
+        }
+
+        Some(stmt)
+    }
+
     pub fn add_child(&mut self, child: CssStmt, parent_idx: CssTreeIdx) -> CssTreeIdx {
         let child_idx = self.add_stmt_inner(child);
         self.parent_to_child
@@ -146,3 +150,24 @@ impl CssTree {
         idx
     }
 }
+
+fn push_child(parent: &mut CssStmt, child: CssStmt) {
+    match parent {
+        CssStmt::RuleSet { body, .. } => body.push(child),
+        CssStmt::Style(..) | CssStmt::Comment(..) | CssStmt::Import(..) => {
+            unreachable!()
+        }
+        CssStmt::Media(media, ..) => {
+            media.body.push(child);
+        }
+        CssStmt::UnknownAtRule(at_rule, ..) => {
+            at_rule.body.push(child);
+        }
+        CssStmt::Supports(supports, ..) => {
+            supports.body.push(child);
+        }
+        CssStmt::KeyframesRuleSet(keyframes) => {
+            keyframes.body.push(child);
+        }
+    }
+}
@@ -5,5 +5,6 @@ pub(crate) use visitor::*;
 mod bin_op;
 mod css_tree;
 mod env;
+mod node_package;
 mod scope;
 mod visitor;
@@ -0,0 +1,417 @@
+//! Resolution of `pkg:` URLs to files in `node_modules`, following the
+//! algorithm used by `dart-sass`'s `NodePackageImporter`
+//!
+//! <https://github.com/sass/sass/blob/main/accepted/node-package-importer.d.ts.md>
+
+use std::path::{Path, PathBuf};
+
+use crate::{utils::Json, Fs};
+
+const CONDITIONS: [&str; 3] = ["sass", "style", "default"];
+
+/// The outcome of resolving a `pkg:` URL
+#[derive(Debug)]
+pub(crate) enum PackageResolution {
+    /// The URL resolved to exactly this file through the package's `exports`,
+    /// `sass`, or `style` fields
+    File(PathBuf),
+
+    /// The URL should be resolved like a relative import of this path,
+    /// trying extensions, partials and index files
+    Import(PathBuf),
+
+    /// No package with this name could be found
+    NotFound,
+}
+
+pub(crate) struct NodePackageImporter<'a> {
+    pub fs: &'a dyn Fs,
+    pub entry_point_directory: &'a Path,
+}
+
+impl<'a> NodePackageImporter<'a> {
+    /// Resolve `url`, the part of a `pkg:` URL after the scheme, from a
+    /// stylesheet at `containing_file`
+    pub fn resolve(&self, url: &str, containing_file: &Path) -> Result<PackageResolution, String> {
+        if url.is_empty() {
+            return Err("pkg: URL must not have an empty path.".to_owned());
+        }
+
+        if url.starts_with('/') {
+            return Err(format!(
+                "pkg: URL \"pkg:{}\" must not be an absolute path.",
+                url
+            ));
+        }
+
+        if url.starts_with('.') {
+            return Err(format!("pkg: URL \"pkg:{}\" must not begin with '.'.", url));
+        }
+
+        let (package_name, subpath) = split_package_name(url)?;
+
+        let base_directory = containing_file
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty() && self.fs.is_dir(dir))
+            .unwrap_or(self.entry_point_directory);
+
+        let package_root = match self
+            .find_package_root(package_name, base_directory)
+            .or_else(|| self.find_package_root(package_name, self.entry_point_directory))
+        {
+            Some(root) => root,
+            None => return Ok(PackageResolution::NotFound),
+        };
+
+        let manifest_path = package_root.join("package.json");
+        let manifest = if self.fs.is_file(&manifest_path) {
+            let bytes = self
+                .fs
+                .read(&manifest_path)
+                .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+            let text = String::from_utf8_lossy(&bytes);
+            Json::parse(&text)
+                .map_err(|e| format!("Failed to parse {}: {}", manifest_path.display(), e))?
+        } else {
+            Json::Object(Vec::new())
+        };
+
+        if let Some(exports) = manifest.get("exports") {
+            if let Some(path) =
+                self.resolve_exports(&package_root, subpath, exports, package_name)?
+            {
+                if !has_sass_extension(&path) {
+                    return Err(format!(
+                        "The export for '{}' in '{}' resolved to '{}', which is not a '.scss', '.sass', or '.css' file.",
+                        subpath.unwrap_or("root"),
+                        package_name,
+                        path.display()
+                    ));
+                }
+
+                return Ok(PackageResolution::File(path));
+            }
+        }
+
+        match subpath {
+            Some(subpath) => Ok(PackageResolution::Import(package_root.join(subpath))),
+            None => {
+                for field in ["sass", "style"] {
+                    if let Some(value) = manifest.get(field).and_then(Json::as_str) {
+                        let path = package_root.join(value);
+                        if has_sass_extension(&path) {
+                            return Ok(PackageResolution::File(path));
+                        }
+                    }
+                }
+
+                Ok(PackageResolution::Import(package_root.join("index")))
+            }
+        }
+    }
+
+    /// Walk up from `directory` looking for `node_modules/<package_name>`
+    fn find_package_root(&self, package_name: &str, directory: &Path) -> Option<PathBuf> {
+        let mut directory = Some(directory);
+
+        while let Some(dir) = directory {
+            let root = dir.join("node_modules").join(package_name);
+
+            if self.fs.is_dir(&root) {
+                return Some(root);
+            }
+
+            directory = dir.parent();
+        }
+
+        None
+    }
+
+    fn resolve_exports(
+        &self,
+        package_root: &Path,
+        subpath: Option<&str>,
+        exports: &Json,
+        package_name: &str,
+    ) -> Result<Option<PathBuf>, String> {
+        if let Some(path) = self.exports_resolve(
+            package_root,
+            &exports_to_check(subpath, false),
+            exports,
+            subpath,
+            package_name,
+        )? {
+            return Ok(Some(path));
+        }
+
+        if matches!(subpath, Some(subpath) if Path::new(subpath).extension().is_some()) {
+            return Ok(None);
+        }
+
+        self.exports_resolve(
+            package_root,
+            &exports_to_check(subpath, true),
+            exports,
+            subpath,
+            package_name,
+        )
+    }
+
+    fn exports_resolve(
+        &self,
+        package_root: &Path,
+        variants: &[Option<String>],
+        exports: &Json,
+        subpath: Option<&str>,
+        package_name: &str,
+    ) -> Result<Option<PathBuf>, String> {
+        if let Json::Object(entries) = exports {
+            let has_subpath_keys = entries.iter().any(|(key, _)| key.starts_with('.'));
+            let has_condition_keys = entries.iter().any(|(key, _)| !key.starts_with('.'));
+
+            if has_subpath_keys && has_condition_keys {
+                return Err(format!(
+                    "`exports` in {} can not have both conditions and paths at the same level.\nFound {}.",
+                    package_name,
+                    entries
+                        .iter()
+                        .map(|(key, _)| format!("\"{}\"", key))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ));
+            }
+        }
+
+        let mut matches = Vec::new();
+
+        for variant in variants {
+            if let Some(path) = self.resolve_variant(package_root, variant.as_deref(), exports)? {
+                matches.push(path);
+            }
+        }
+
+        match matches.len() {
+            0 => Ok(None),
+            1 => Ok(matches.pop()),
+            _ => Err(format!(
+                "Unable to determine which of multiple potential resolutions found for {} in {} should be used. \n\nFound:\n{}",
+                subpath.unwrap_or("root"),
+                package_name,
+                matches
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )),
+        }
+    }
+
+    fn resolve_variant(
+        &self,
+        package_root: &Path,
+        variant: Option<&str>,
+        exports: &Json,
+    ) -> Result<Option<PathBuf>, String> {
+        let variant = match variant {
+            Some(variant) => variant,
+            None => {
+                return match main_export(exports) {
+                    Some(main) => self.target_resolve(main, package_root, None),
+                    None => Ok(None),
+                }
+            }
+        };
+
+        let entries = match exports {
+            Json::Object(entries) if entries.iter().any(|(key, _)| key.starts_with('.')) => entries,
+            _ => return Ok(None),
+        };
+
+        let match_key = format!("./{}", variant.replace('\\', "/"));
+
+        if let Some(target) = exports.get(&match_key) {
+            if *target != Json::Null && !match_key.contains('*') {
+                return self.target_resolve(target, package_root, None);
+            }
+        }
+
+        let mut expansion_keys: Vec<&(String, Json)> = entries
+            .iter()
+            .filter(|(key, _)| key.matches('*').count() == 1)
+            .collect();
+        expansion_keys.sort_by(|(a, _), (b, _)| compare_expansion_keys(a, b));
+
+        for (expansion_key, target) in expansion_keys {
+            let (pattern_base, pattern_trailer) = expansion_key.split_once('*').unwrap();
+
+            if !match_key.starts_with(pattern_base) || match_key == pattern_base {
+                continue;
+            }
+
+            if pattern_trailer.is_empty()
+                || (match_key.ends_with(pattern_trailer) && match_key.len() >= expansion_key.len())
+            {
+                if *target == Json::Null {
+                    continue;
+                }
+
+                let pattern_match =
+                    &match_key[pattern_base.len()..match_key.len() - pattern_trailer.len()];
+
+                return self.target_resolve(target, package_root, Some(pattern_match));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn target_resolve(
+        &self,
+        target: &Json,
+        package_root: &Path,
+        pattern_match: Option<&str>,
+    ) -> Result<Option<PathBuf>, String> {
+        match target {
+            Json::String(target) if !target.starts_with("./") => Err(format!(
+                "Export '{}' must be a path relative to the package root at '{}'.",
+                target,
+                package_root.display()
+            )),
+            Json::String(target) => match pattern_match {
+                Some(pattern_match) => {
+                    let path = package_root.join(target.replacen('*', pattern_match, 1));
+                    Ok(if self.fs.is_file(&path) {
+                        Some(path)
+                    } else {
+                        None
+                    })
+                }
+                None => Ok(Some(package_root.join(target))),
+            },
+            Json::Object(conditions) => {
+                for (condition, value) in conditions {
+                    if !CONDITIONS.contains(&condition.as_str()) {
+                        continue;
+                    }
+
+                    if let Some(path) = self.target_resolve(value, package_root, pattern_match)? {
+                        return Ok(Some(path));
+                    }
+                }
+
+                Ok(None)
+            }
+            Json::Array(targets) => {
+                for value in targets {
+                    if let Some(path) = self.target_resolve(value, package_root, pattern_match)? {
+                        return Ok(Some(path));
+                    }
+                }
+
+                Ok(None)
+            }
+            Json::Null => Ok(None),
+            _ => Err(format!(
+                "Invalid 'exports' value in {}.",
+                package_root.join("package.json").display()
+            )),
+        }
+    }
+}
+
+/// Split a URL such as `@scope/name/sub/path` into the package name and
+/// subpath
+fn split_package_name(url: &str) -> Result<(&str, Option<&str>), String> {
+    let name_segments = if url.starts_with('@') { 2 } else { 1 };
+
+    let split_at = url
+        .match_indices('/')
+        .nth(name_segments - 1)
+        .map(|(idx, _)| idx);
+
+    let (name, subpath) = match split_at {
+        Some(idx) => (&url[..idx], Some(&url[idx + 1..])),
+        None => (url, None),
+    };
+
+    if name_segments == 2 && !name.contains('/') {
+        return Err(format!(
+            "pkg: URL \"pkg:{}\" has an invalid scoped package name.",
+            url
+        ));
+    }
+
+    Ok((name, subpath.filter(|subpath| !subpath.is_empty())))
+}
+
+fn has_sass_extension(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("scss" | "sass" | "css")
+    )
+}
+
+/// The export keys to look for, given a subpath
+fn exports_to_check(subpath: Option<&str>, add_index: bool) -> Vec<Option<String>> {
+    let subpath = match (subpath, add_index) {
+        (None, false) => return vec![None],
+        (None, true) => "index".to_owned(),
+        (Some(subpath), true) => format!("{}/index", subpath),
+        (Some(subpath), false) => subpath.to_owned(),
+    };
+
+    let mut paths = if has_sass_extension(Path::new(&subpath)) {
+        vec![subpath.clone()]
+    } else {
+        vec![
+            subpath.clone(),
+            format!("{}.scss", subpath),
+            format!("{}.sass", subpath),
+            format!("{}.css", subpath),
+        ]
+    };
+
+    let (dirname, basename) = match subpath.rsplit_once('/') {
+        Some((dirname, basename)) => (Some(dirname), basename),
+        None => (None, subpath.as_str()),
+    };
+
+    if !basename.starts_with('_') {
+        let partials: Vec<String> = paths
+            .iter()
+            .map(|path| {
+                let file_name = path.rsplit('/').next().unwrap();
+                match dirname {
+                    Some(dirname) => format!("{}/_{}", dirname, file_name),
+                    None => format!("_{}", file_name),
+                }
+            })
+            .collect();
+        paths.extend(partials);
+    }
+
+    paths.into_iter().map(Some).collect()
+}
+
+/// The export for the package root, if any
+fn main_export(exports: &Json) -> Option<&Json> {
+    match exports {
+        Json::String(..) | Json::Array(..) => Some(exports),
+        Json::Object(entries) if !entries.iter().any(|(key, _)| key.starts_with('.')) => {
+            Some(exports)
+        }
+        Json::Object(..) => exports.get(".").filter(|main| **main != Json::Null),
+        _ => None,
+    }
+}
+
+/// Order pattern keys from most to least specific, as described in the Node
+/// documentation for `PATTERN_KEY_COMPARE`
+fn compare_expansion_keys(a: &str, b: &str) -> std::cmp::Ordering {
+    let base_length_a = a.find('*').map_or(a.len(), |idx| idx + 1);
+    let base_length_b = b.find('*').map_or(b.len(), |idx| idx + 1);
+
+    base_length_b
+        .cmp(&base_length_a)
+        .then_with(|| b.len().cmp(&a.len()))
+}
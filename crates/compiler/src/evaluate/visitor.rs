@@ -1,21 +1,28 @@
 use std::{
-    cell::{Cell, RefCell},
-    collections::{BTreeMap, BTreeSet, HashSet},
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     ffi::OsStr,
     fmt,
     iter::FromIterator,
     mem,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
 };
 
-use codemap::{CodeMap, Span, Spanned};
+use codemap::{CodeMap, File, Span, Spanned};
 use indexmap::IndexSet;
 
 use crate::{
+    analysis::{LoadedStyleSheet, Recorder},
     ast::*,
     builtin::{
+        is_legacy_color_function,
         meta::if_arguments,
+        module_equivalent,
         modules::{
             declare_module_color, declare_module_list, declare_module_map, declare_module_math,
             declare_module_meta, declare_module_selector, declare_module_string, Module,
@@ -23,7 +30,9 @@ use crate::{
         GLOBAL_FUNCTIONS,
     },
     common::{unvendor, BinaryOp, Identifier, ListSeparator, QuoteKind, UnaryOp},
-    error::{SassError, SassResult},
+    compiler::{CachedModule, ConfigurationKey, Load, ModuleCache, StyleSheetCache},
+    error::{RawStackFrame, SassError, SassResult, StackFrame},
+    importer::{file_url, CustomImporter},
     interner::InternedString,
     lexer::Lexer,
     parse::{
@@ -34,18 +43,19 @@ use crate::{
         ComplexSelectorComponent, ExtendRule, ExtendedSelector, ExtensionStore, SelectorList,
         SelectorParser,
     },
-    utils::{to_sentence, trim_ascii},
+    utils::{look_up_span, may_produce_non_ascii, to_sentence, trim_ascii, PersistentVec},
     value::{
-        ArgList, CalculationArg, CalculationName, Number, SassCalculation, SassFunction, SassMap,
-        SassNumber, UserDefinedFunction, Value,
+        ArgList, CalculationArg, CalculationName, FunctionHost, HostFunction, Number,
+        SassCalculation, SassFunction, SassMap, SassNumber, UserDefinedFunction, Value,
     },
-    ContextFlags, InputSyntax, Options,
+    ContextFlags, Deprecation, ImportContext, Importer, InputSyntax, Options,
 };
 
 use super::{
     bin_op::{add, cmp, div, mul, rem, single_eq, sub},
     css_tree::{CssTree, CssTreeIdx},
     env::Environment,
+    node_package::{NodePackageImporter, PackageResolution},
 };
 
 trait UserDefinedCallable {
@@ -75,7 +85,7 @@ impl UserDefinedCallable for Arc<AstFunctionDecl> {
 
 impl UserDefinedCallable for AstMixin {
     fn name(&self) -> Identifier {
-        self.name
+        self.name.node
     }
 
     fn arguments(&self) -> &ArgumentDeclaration {
@@ -93,12 +103,42 @@ impl UserDefinedCallable for Arc<CallableContentBlock> {
     }
 }
 
+/// The number of warnings for a single deprecation to emit before omitting
+/// the rest, unless [`Options::verbose`] is set
+const MAX_DEPRECATION_REPETITIONS: usize = 5;
+
 #[derive(Debug, Clone)]
 pub(crate) struct CallableContentBlock {
     content: AstContentBlock,
     env: Environment,
 }
 
+/// A module being executed while compiling with a [`crate::Compiler`], which
+/// collects what is needed to reuse the module in later compilations
+#[derive(Debug, Default)]
+struct ModuleFrame {
+    /// The number of top-level CSS statements emitted before the module
+    root_start: usize,
+    /// The number of hoisted plain CSS `@import`s emitted before the module
+    imports_start: usize,
+    /// The configuration the module was executed with, or `None` if it can't
+    /// be compared with later configurations
+    configuration: Option<ConfigurationKey>,
+    /// The names of the configured variables, before the module used any
+    configured: Vec<Identifier>,
+    loads: Vec<Load>,
+    upstream: Vec<CachedModule>,
+    /// The CSS of each cached upstream module, along with the index among the
+    /// top-level statements of its first statement
+    upstream_css: Vec<(usize, Vec<Arc<[CssStmt]>>)>,
+    /// Upstream modules which were executed but not cached, and whose state
+    /// must be restored along with this module's
+    uncached: Vec<Arc<RefCell<Module>>>,
+    /// Whether the module can't be reused, e.g. because it called
+    /// `math.random()`
+    uncacheable: bool,
+}
+
 pub(crate) struct Visitor<'a> {
     pub declaration_name: Option<String>,
     pub flags: ContextFlags,
@@ -124,6 +164,50 @@ pub(crate) struct Visitor<'a> {
     /// has been seen in the past. In the majority of cases, files are imported
     /// at most once.
     files_seen: BTreeSet<PathBuf>,
+    /// The name of the member currently being executed, e.g. `foo()` or
+    /// `root stylesheet`
+    member: String,
+    /// The caller frames of the current member. Each entry holds the name of
+    /// the calling member and the span of the invocation
+    stack: Vec<RawStackFrame>,
+    /// The number of times each deprecation warning has been emitted, used to
+    /// omit repetitive warnings
+    deprecation_counts: HashMap<Deprecation, usize>,
+    /// Information collected for [`crate::analysis`], if the stylesheet is
+    /// being analyzed rather than compiled
+    pub recorder: Option<Recorder>,
+    /// The canonical URLs of the stylesheets loaded by custom importers
+    importer_urls: HashSet<PathBuf>,
+    /// Functions implemented by the program embedding the compiler, such as a
+    /// host speaking the embedded protocol
+    pub function_host: Option<&'a dyn FunctionHost>,
+    /// The parsed signatures of host functions, keyed by their source
+    host_signatures: HashMap<String, Arc<AstFunctionDecl>>,
+    /// The number of user-defined mixins, functions, and content blocks
+    /// currently being executed, checked against [`Options::max_call_depth`]
+    call_depth: usize,
+    /// The total number of loop iterations so far, checked against
+    /// [`Options::max_loop_iterations`]
+    loop_iterations: usize,
+    /// The URLs of the stylesheets currently being loaded, used to detect
+    /// stylesheets which load themselves
+    active_modules: HashSet<PathBuf>,
+    /// Stylesheets parsed by previous compilations, if compiling with a
+    /// [`crate::Compiler`]
+    pub cache: Option<&'a mut StyleSheetCache>,
+    /// Modules executed by previous compilations, if compiling with a
+    /// [`crate::Compiler`]
+    modules: Option<&'a mut ModuleCache>,
+    /// The modules currently being executed, if caching modules. The first
+    /// frame belongs to the root stylesheet, which is never cached
+    module_frames: Vec<ModuleFrame>,
+    /// What the URLs loaded by cached modules resolve to in this compilation,
+    /// keyed by the stylesheet they were loaded from, the URL, and whether
+    /// they were loaded by `@import`
+    resolved_loads: HashMap<(PathBuf, String, bool), Option<PathBuf>>,
+    /// Whether any stylesheet loaded by this compilation could produce
+    /// non-ASCII output, in which case the output may need a `@charset` rule
+    pub may_emit_non_ascii: bool,
 }
 
 impl<'a> Visitor<'a> {
@@ -140,6 +224,9 @@ impl<'a> Visitor<'a> {
 
         let current_import_path = path.to_path_buf();
 
+        let mut active_modules = HashSet::new();
+        active_modules.insert(current_import_path.clone());
+
         Self {
             declaration_name: None,
             style_rule_ignoring_at_root: None,
@@ -160,9 +247,91 @@ impl<'a> Visitor<'a> {
             map,
             import_cache: BTreeMap::new(),
             files_seen: BTreeSet::new(),
+            member: "root stylesheet".to_owned(),
+            stack: Vec::new(),
+            deprecation_counts: HashMap::new(),
+            importer_urls: HashSet::new(),
+            function_host: None,
+            host_signatures: HashMap::new(),
+            recorder: None,
+            call_depth: 0,
+            loop_iterations: 0,
+            active_modules,
+            cache: None,
+            modules: None,
+            module_frames: Vec::new(),
+            resolved_loads: HashMap::new(),
+            may_emit_non_ascii: false,
+        }
+    }
+
+    /// Reuses the modules in `modules` where possible, and adds the modules
+    /// executed by this compilation to it once [`Visitor::store_modules`] is
+    /// called
+    pub fn cache_modules(&mut self, modules: &'a mut ModuleCache) {
+        modules.loaded_files.clear();
+        self.modules = Some(modules);
+        // reused modules may come from stylesheets this compilation never loads
+        self.may_emit_non_ascii = true;
+        self.module_frames.push(ModuleFrame::default());
+    }
+
+    /// Moves the modules executed or reused by this compilation into the
+    /// module cache, including those loaded before an error
+    pub fn store_modules(&mut self) {
+        if let Some(modules) = self.modules.as_deref_mut() {
+            for frame in self.module_frames.drain(..) {
+                for module in frame.upstream {
+                    modules.insert(module);
+                }
+            }
+        }
+    }
+
+    /// Prevents the module being executed from being reused, since executing
+    /// it again may produce a different result
+    #[cfg_attr(not(feature = "random"), allow(dead_code))]
+    pub fn mark_uncacheable(&mut self) {
+        if let Some(frame) = self.module_frames.last_mut() {
+            frame.uncacheable = true;
         }
     }
 
+    /// Returns an error if compilation was cancelled through
+    /// [`Options::cancellation_token`] or has passed [`Options::deadline`]
+    fn check_cancelled(&self, span: Span) -> SassResult<()> {
+        if let Some(token) = &self.options.cancellation_token {
+            if token.is_cancelled() {
+                return Err(("Compilation was cancelled.", span).into());
+            }
+        }
+
+        if let Some(deadline) = self.options.deadline {
+            if Instant::now() >= deadline {
+                return Err(("Compilation exceeded its deadline.", span).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records a single iteration of an `@each`, `@for`, or `@while` loop
+    fn count_loop_iteration(&mut self, span: Span) -> SassResult<()> {
+        self.loop_iterations += 1;
+
+        if let Some(max) = self.options.max_loop_iterations {
+            if self.loop_iterations > max {
+                return Err((
+                    format!("Maximum number of loop iterations ({}) exceeded.", max),
+                    span,
+                )
+                    .into());
+            }
+        }
+
+        self.check_cancelled(span)
+    }
+
     pub fn visit_stylesheet(&mut self, mut style_sheet: StyleSheet) -> SassResult<()> {
         let was_in_plain_css = self.is_plain_css;
         self.is_plain_css = style_sheet.is_plain_css;
@@ -192,7 +361,7 @@ impl<'a> Visitor<'a> {
     fn visit_return_rule(&mut self, ret: AstReturn) -> SassResult<Option<Value>> {
         let val = self.visit_expr(ret.val)?;
 
-        Ok(Some(self.without_slash(val)))
+        Ok(Some(self.without_slash(val, ret.span)?))
     }
 
     // todo: we really don't have to return Option<Value> from all of these children
@@ -255,6 +424,7 @@ impl<'a> Visitor<'a> {
 
             self.load_module(
                 forward_rule.url.as_path(),
+                "@forward",
                 Some(Arc::clone(&new_configuration)),
                 false,
                 forward_rule.span,
@@ -304,6 +474,7 @@ impl<'a> Visitor<'a> {
             let url = forward_rule.url.clone();
             self.load_module(
                 url.as_path(),
+                "@forward",
                 None,
                 false,
                 forward_rule.span,
@@ -347,7 +518,7 @@ impl<'a> Visitor<'a> {
 
             // todo: superfluous clone?
             let value = self.visit_expr(variable.expr.node.clone())?;
-            let value = self.without_slash(value);
+            let value = self.without_slash(value, variable.expr.span)?;
 
             new_values.insert(
                 variable.name.node,
@@ -500,6 +671,7 @@ impl<'a> Visitor<'a> {
                         selector,
                         body: Vec::new(),
                         is_group_end: false,
+                        span: supports_rule.span,
                     };
 
                     visitor.with_parent(
@@ -593,6 +765,7 @@ impl<'a> Visitor<'a> {
     pub fn load_module(
         &mut self,
         url: &Path,
+        stack_frame: &str,
         configuration: Option<Arc<RefCell<Configuration>>>,
         names_in_errors: bool,
         span: Span,
@@ -638,14 +811,256 @@ impl<'a> Visitor<'a> {
             return Ok(());
         }
 
-        // todo: decide on naming convention for style_sheet vs stylesheet
-        let stylesheet = self.load_style_sheet(url.to_string_lossy().as_ref(), false, span)?;
+        self.with_stack_frame(stack_frame.to_owned(), span, |visitor| {
+            // todo: decide on naming convention for style_sheet vs stylesheet
+            let (stylesheet, canonical) =
+                visitor.load_canonical_style_sheet(url.to_string_lossy().as_ref(), false, span)?;
 
-        let module = self.execute(stylesheet.clone(), configuration, names_in_errors)?;
+            // without a configuration of its own, the module inherits the
+            // current configuration
+            let effective_configuration = configuration
+                .as_ref()
+                .map_or_else(|| Arc::clone(&visitor.configuration), Arc::clone);
 
-        callback(self, module, stylesheet)?;
+            if let Some(module) = visitor.reuse_module(&canonical, &effective_configuration) {
+                return callback(visitor, module, stylesheet);
+            }
 
-        Ok(())
+            if !visitor.active_modules.insert(stylesheet.url.clone()) {
+                return Err(("Module loop: this module is already being loaded.", span).into());
+            }
+
+            let url = stylesheet.url.clone();
+            visitor.enter_module(&effective_configuration);
+            let module = visitor.execute(stylesheet.clone(), configuration, names_in_errors);
+            visitor.exit_module(canonical, &effective_configuration, module.as_ref().ok());
+            visitor.active_modules.remove(&url);
+
+            callback(visitor, module?, stylesheet)
+        })
+    }
+
+    /// Reuses a module executed by a previous compilation, if it was executed
+    /// from the same stylesheet with the same configuration, and nothing it
+    /// loaded has changed since
+    fn reuse_module(
+        &mut self,
+        canonical: &Path,
+        configuration: &Arc<RefCell<Configuration>>,
+    ) -> Option<Arc<RefCell<Module>>> {
+        self.modules.as_ref()?;
+
+        let file = Arc::clone(self.cache.as_deref()?.file(canonical)?);
+        let key = ConfigurationKey::new(&(**configuration).borrow(), self.span_before)?;
+
+        loop {
+            let cached = self.modules.as_deref_mut()?.take(canonical, &file, &key)?;
+
+            if self.is_unchanged(&cached) {
+                return Some(self.replay_module(cached, configuration));
+            }
+
+            // the upstream modules of a module which has changed may still be
+            // reused by the new version of that module
+            for upstream in cached.upstream {
+                self.modules.as_deref_mut()?.insert(upstream);
+            }
+        }
+    }
+
+    /// Whether `cached`, and every module it loaded, would produce the same
+    /// result if executed again
+    fn is_unchanged(&mut self, cached: &CachedModule) -> bool {
+        cached.loads.iter().all(|load| self.load_is_unchanged(load))
+            && cached
+                .upstream
+                .iter()
+                .all(|upstream| self.is_unchanged(upstream))
+    }
+
+    /// Whether `load` still resolves to the same, unchanged file
+    fn load_is_unchanged(&mut self, load: &Load) -> bool {
+        let key = (load.base.clone(), load.url.clone(), load.for_import);
+
+        let canonical = match self.resolved_loads.get(&key) {
+            Some(canonical) => canonical.clone(),
+            None => {
+                let base = mem::replace(&mut self.current_import_path, load.base.clone());
+                let canonical = self
+                    .import_like_node(&load.url, load.for_import, self.span_before)
+                    .ok()
+                    .map(|(_, canonical)| canonical);
+                self.current_import_path = base;
+
+                self.resolved_loads.insert(key, canonical.clone());
+                canonical
+            }
+        };
+
+        let is_unchanged = canonical.as_ref() == Some(&load.canonical)
+            && self
+                .cache
+                .as_deref()
+                .and_then(|cache| cache.file(&load.canonical))
+                .is_some_and(|file| Arc::ptr_eq(file, &load.file));
+
+        if is_unchanged {
+            if let Some(modules) = self.modules.as_deref_mut() {
+                modules.loaded_files.insert(load.canonical.clone());
+            }
+        }
+
+        is_unchanged
+    }
+
+    /// Reproduces the effects of executing `cached`, returning its module
+    fn replay_module(
+        &mut self,
+        cached: CachedModule,
+        configuration: &Arc<RefCell<Configuration>>,
+    ) -> Arc<RefCell<Module>> {
+        for (module, state) in &cached.states {
+            (**module).borrow_mut().restore(state.clone());
+        }
+
+        for &name in &cached.consumed {
+            (**configuration).borrow_mut().remove(name);
+        }
+
+        let root_start = self.css_tree.root_children().len();
+
+        for stmt in cached.css.iter().flat_map(|chunk| chunk.iter()) {
+            self.css_tree.add_stmt(stmt.clone(), None);
+        }
+
+        self.import_nodes.extend(cached.imports.iter().cloned());
+
+        let module = Arc::clone(&cached.module);
+
+        let frame = self.module_frames.last_mut().unwrap();
+        frame.upstream_css.push((root_start, cached.css.clone()));
+        frame.upstream.push(cached);
+
+        module
+    }
+
+    /// Starts collecting what is needed to cache the module about to be
+    /// executed, if caching modules
+    fn enter_module(&mut self, configuration: &Arc<RefCell<Configuration>>) {
+        if self.modules.is_none() {
+            return;
+        }
+
+        let configuration = (**configuration).borrow();
+
+        self.module_frames.push(ModuleFrame {
+            root_start: self.css_tree.root_children().len(),
+            imports_start: self.import_nodes.len(),
+            configuration: ConfigurationKey::new(&configuration, self.span_before),
+            configured: configuration.values.keys(),
+            ..ModuleFrame::default()
+        });
+    }
+
+    /// Caches the module which has just been executed, or if it can't be
+    /// cached, makes what it loaded part of the module which loaded it
+    fn exit_module(
+        &mut self,
+        canonical: PathBuf,
+        configuration: &Arc<RefCell<Configuration>>,
+        module: Option<&Arc<RefCell<Module>>>,
+    ) {
+        if self.modules.is_none() {
+            return;
+        }
+
+        let mut frame = self.module_frames.pop().unwrap();
+
+        let file = self
+            .cache
+            .as_deref()
+            .and_then(|cache| cache.file(&canonical))
+            .map(Arc::clone);
+
+        let (module, file, key) = match (module, file, frame.configuration.take()) {
+            (Some(module), Some(file), Some(key)) if !frame.uncacheable => (module, file, key),
+            (module, ..) => {
+                let parent = self.module_frames.last_mut().unwrap();
+                parent.loads.append(&mut frame.loads);
+                parent.upstream.append(&mut frame.upstream);
+                parent.upstream_css.append(&mut frame.upstream_css);
+                parent.uncached.append(&mut frame.uncached);
+                parent.uncached.extend(module.map(Arc::clone));
+                parent.uncacheable |= frame.uncacheable;
+                return;
+            }
+        };
+
+        let remaining = (**configuration).borrow().values.keys();
+
+        let states = frame
+            .upstream
+            .iter()
+            .flat_map(|upstream| upstream.states.iter().map(|(module, _)| module))
+            .chain(&frame.uncached)
+            .chain(Some(module))
+            .filter_map(|module| Some((Arc::clone(module), (**module).borrow().state()?)))
+            .collect();
+
+        let cached = CachedModule {
+            canonical,
+            file,
+            configuration: key,
+            module: Arc::clone(module),
+            css: self.module_css(&frame),
+            imports: self.import_nodes[frame.imports_start..].to_vec(),
+            consumed: frame
+                .configured
+                .into_iter()
+                .filter(|name| !remaining.contains(name))
+                .collect(),
+            states,
+            loads: frame.loads,
+            upstream: frame.upstream,
+        };
+
+        let parent = self.module_frames.last_mut().unwrap();
+        parent
+            .upstream_css
+            .push((frame.root_start, cached.css.clone()));
+        parent.upstream.push(cached);
+    }
+
+    /// The top-level CSS emitted by the module executed in `frame`, sharing
+    /// the chunks emitted by its cached upstream modules
+    fn module_css(&self, frame: &ModuleFrame) -> Vec<Arc<[CssStmt]>> {
+        let root_children = self.css_tree.root_children();
+
+        let mut css = Vec::new();
+        let mut own = Vec::new();
+        let mut upstream_css = frame.upstream_css.iter().peekable();
+        let mut idx = frame.root_start;
+
+        while idx < root_children.len() {
+            if let Some((_, chunks)) = upstream_css.next_if(|(start, _)| *start == idx) {
+                if !own.is_empty() {
+                    css.push(Arc::from(mem::take(&mut own)));
+                }
+
+                css.extend(chunks.iter().cloned());
+                idx += chunks.iter().map(|chunk| chunk.len()).sum::<usize>();
+                continue;
+            }
+
+            own.extend(self.css_tree.clone_subtree(root_children[idx]));
+            idx += 1;
+        }
+
+        if !own.is_empty() {
+            css.push(Arc::from(own));
+        }
+
+        css
     }
 
     fn visit_use_rule(&mut self, use_rule: AstUseRule) -> SassResult<()> {
@@ -656,7 +1071,7 @@ impl<'a> Visitor<'a> {
 
             for var in use_rule.configuration {
                 let value = self.visit_expr(var.expr.node)?;
-                let value = self.without_slash(value);
+                let value = self.without_slash(value, var.expr.span)?;
                 values.insert(
                     var.name.node,
                     ConfiguredValue::explicit(value, var.name.span.merge(var.expr.span)),
@@ -675,6 +1090,7 @@ impl<'a> Visitor<'a> {
 
         self.load_module(
             &use_rule.url,
+            "@use",
             Some(Arc::clone(&configuration)),
             false,
             span,
@@ -734,7 +1150,7 @@ impl<'a> Visitor<'a> {
     /// <https://sass-lang.com/documentation/at-rules/import#finding-the-file>
     /// <https://sass-lang.com/documentation/at-rules/import#load-paths>
     #[allow(clippy::cognitive_complexity)]
-    fn find_import(&self, path: &Path) -> Option<PathBuf> {
+    pub(crate) fn find_import(&self, path: &Path) -> Option<PathBuf> {
         let path_buf = if path.is_absolute() {
             path.into()
         } else {
@@ -807,9 +1223,10 @@ impl<'a> Visitor<'a> {
         &mut self,
         lexer: Lexer,
         path: &Path,
+        syntax: InputSyntax,
         span_before: Span,
     ) -> SassResult<StyleSheet> {
-        match InputSyntax::for_path(path) {
+        match syntax {
             InputSyntax::Scss => {
                 ScssParser::new(lexer, self.map, self.options, span_before, path).__parse()
             }
@@ -825,10 +1242,70 @@ impl<'a> Visitor<'a> {
     fn import_like_node(
         &mut self,
         url: &str,
-        _for_import: bool,
+        for_import: bool,
         span: Span,
-    ) -> SassResult<StyleSheet> {
-        if let Some(name) = self.find_import(url.as_ref()) {
+    ) -> SassResult<(StyleSheet, PathBuf)> {
+        let mut found = match (
+            url.strip_prefix("pkg:"),
+            &self.options.node_package_importer,
+        ) {
+            (Some(package_url), Some(entry_point_directory)) => {
+                let importer = NodePackageImporter {
+                    fs: self.options.fs,
+                    entry_point_directory,
+                };
+
+                match importer
+                    .resolve(package_url, &self.current_import_path)
+                    .map_err(|message| (message, span))?
+                {
+                    PackageResolution::File(path) => Some(path),
+                    PackageResolution::Import(path) => self.find_import(&path),
+                    PackageResolution::NotFound => None,
+                }
+            }
+            _ => self.find_import(url.as_ref()),
+        };
+
+        if found.is_none() {
+            let containing_url = if self.importer_urls.contains(&self.current_import_path) {
+                self.current_import_path.to_string_lossy().into_owned()
+            } else {
+                file_url(&self.current_import_path)
+            };
+
+            let context = ImportContext {
+                containing_url: Some(&containing_url),
+                from_import: for_import,
+            };
+
+            for &importer in &self.options.importers {
+                match importer {
+                    CustomImporter::Importer(importer) => {
+                        if let Some(canonical_url) = importer
+                            .canonicalize(url, &context)
+                            .map_err(|message| (message, span))?
+                        {
+                            return self.load_from_importer(importer, canonical_url, span);
+                        }
+                    }
+                    CustomImporter::File(importer) => {
+                        if let Some(path) = importer
+                            .find_file(url, &context)
+                            .map_err(|message| (message, span))?
+                        {
+                            found = self.find_import(&path);
+                        }
+                    }
+                }
+
+                if found.is_some() {
+                    break;
+                }
+            }
+        }
+
+        if let Some(name) = found {
             // assumption: most users use regular file paths for their imports.
             // we do support importing syntactically invalid paths and paths that
             // do not exist through the `Options::fs` API, so we fallback to the
@@ -836,33 +1313,105 @@ impl<'a> Visitor<'a> {
             let canonical = std::fs::canonicalize(&name).unwrap_or_else(|_| name.to_path_buf());
 
             if let Some(style_sheet) = self.import_cache.get(&canonical) {
-                return Ok(style_sheet.clone());
+                return Ok((style_sheet.clone(), canonical));
             }
 
-            let file = self.map.add_file(
-                name.to_string_lossy().into(),
-                String::from_utf8(self.options.fs.read(&name)?)?,
-            );
+            let contents = String::from_utf8(self.options.fs.read(&name)?)?;
+            let syntax = InputSyntax::for_path(&name);
 
-            let old_is_use_allowed = self.flags.is_use_allowed();
-            self.flags.set(ContextFlags::IS_USE_ALLOWED, true);
+            let style_sheet = self.parse_loaded(&name, canonical.clone(), contents, syntax)?;
 
-            let style_sheet =
-                self.parse_file(Lexer::new_from_file(&file), &name, file.span.subspan(0, 0))?;
+            return Ok((style_sheet, canonical));
+        }
 
-            self.flags
-                .set(ContextFlags::IS_USE_ALLOWED, old_is_use_allowed);
+        Err(("Can't find stylesheet to import.", span).into())
+    }
 
-            if self.files_seen.contains(&canonical) {
-                self.import_cache.insert(canonical, style_sheet.clone());
-            } else {
-                self.files_seen.insert(canonical);
-            }
+    fn load_from_importer(
+        &mut self,
+        importer: &dyn Importer,
+        canonical_url: String,
+        span: Span,
+    ) -> SassResult<(StyleSheet, PathBuf)> {
+        let canonical = PathBuf::from(canonical_url);
 
-            return Ok(style_sheet);
+        if let Some(style_sheet) = self.import_cache.get(&canonical) {
+            return Ok((style_sheet.clone(), canonical));
         }
 
-        Err(("Can't find stylesheet to import.", span).into())
+        let result = importer
+            .load(&canonical.to_string_lossy())
+            .map_err(|message| (message, span))?;
+
+        self.importer_urls.insert(canonical.clone());
+
+        let style_sheet = self.parse_loaded(
+            &canonical.clone(),
+            canonical.clone(),
+            result.contents,
+            result.syntax,
+        )?;
+
+        Ok((style_sheet, canonical))
+    }
+
+    /// Parses a stylesheet that was loaded from `name`, caching it by its
+    /// canonical path if it has been loaded before
+    fn parse_loaded(
+        &mut self,
+        name: &Path,
+        canonical: PathBuf,
+        contents: String,
+        syntax: InputSyntax,
+    ) -> SassResult<StyleSheet> {
+        self.may_emit_non_ascii |= may_produce_non_ascii(&contents);
+
+        if let Some((_, style_sheet)) = self
+            .cache
+            .as_deref()
+            .and_then(|cache| cache.get(&canonical, &contents, syntax))
+        {
+            return Ok(style_sheet.clone());
+        }
+
+        let file = self.add_file(name.to_string_lossy().into(), contents);
+
+        let old_is_use_allowed = self.flags.is_use_allowed();
+        self.flags.set(ContextFlags::IS_USE_ALLOWED, true);
+
+        let style_sheet = self.parse_file(
+            Lexer::new_from_file(&file),
+            name,
+            syntax,
+            file.span.subspan(0, 0),
+        )?;
+
+        self.flags
+            .set(ContextFlags::IS_USE_ALLOWED, old_is_use_allowed);
+
+        if let Some(cache) = self.cache.as_deref_mut() {
+            cache.insert(canonical.clone(), file, syntax, style_sheet.clone());
+        }
+
+        if self.files_seen.contains(&canonical) {
+            self.import_cache.insert(canonical, style_sheet.clone());
+        } else {
+            self.files_seen.insert(canonical);
+        }
+
+        Ok(style_sheet)
+    }
+
+    /// Adds a file to the code map, recording its size if compiling with a
+    /// [`crate::Compiler`]
+    fn add_file(&mut self, name: String, contents: String) -> Arc<File> {
+        let file = self.map.add_file(name, contents);
+
+        if let Some(cache) = self.cache.as_deref_mut() {
+            cache.record_file(&file);
+        }
+
+        file
     }
 
     pub fn load_style_sheet(
@@ -872,25 +1421,107 @@ impl<'a> Visitor<'a> {
         for_import: bool,
         span: Span,
     ) -> SassResult<StyleSheet> {
+        self.load_canonical_style_sheet(url, for_import, span)
+            .map(|(style_sheet, _)| style_sheet)
+    }
+
+    /// Loads the stylesheet at `url`, along with the canonical path it was
+    /// loaded from
+    fn load_canonical_style_sheet(
+        &mut self,
+        url: &str,
+        for_import: bool,
+        span: Span,
+    ) -> SassResult<(StyleSheet, PathBuf)> {
         // todo: import cache
-        self.import_like_node(url, for_import, span)
+        let (style_sheet, canonical) = self.import_like_node(url, for_import, span)?;
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.loaded.push(LoadedStyleSheet {
+                from: self.current_import_path.clone(),
+                url: url.to_owned(),
+                style_sheet: style_sheet.clone(),
+            });
+        }
+
+        self.record_load(url, for_import, &canonical);
+
+        Ok((style_sheet, canonical))
     }
 
-    fn visit_dynamic_import_rule(&mut self, dynamic_import: &AstSassImport) -> SassResult<()> {
-        let stylesheet = self.load_style_sheet(&dynamic_import.url, true, dynamic_import.span)?;
-
-        // If the imported stylesheet doesn't use any modules, we can inject its
-        // CSS directly into the current stylesheet. If it does use modules, we
-        // need to put its CSS into an intermediate [ModifiableCssStylesheet] so
-        // that we can hermetically resolve `@extend`s before injecting it.
-        if stylesheet.uses.is_empty() && stylesheet.forwards.is_empty() {
-            self.visit_stylesheet(stylesheet)?;
-            return Ok(());
+    /// Records that the module being executed loaded `url`, so that a later
+    /// compilation can check whether it still resolves to the same file
+    fn record_load(&mut self, url: &str, for_import: bool, canonical: &Path) {
+        let modules = match self.modules.as_deref_mut() {
+            Some(modules) => modules,
+            None => return,
+        };
+
+        modules.loaded_files.insert(canonical.to_path_buf());
+
+        self.resolved_loads.insert(
+            (self.current_import_path.clone(), url.to_owned(), for_import),
+            Some(canonical.to_path_buf()),
+        );
+
+        let file = self
+            .cache
+            .as_deref()
+            .and_then(|cache| cache.file(canonical))
+            .map(Arc::clone);
+
+        let frame = self.module_frames.last_mut().unwrap();
+
+        match file {
+            Some(file) => frame.loads.push(Load {
+                base: self.current_import_path.clone(),
+                url: url.to_owned(),
+                for_import,
+                canonical: canonical.to_path_buf(),
+                file,
+            }),
+            // loaded through a custom importer or in some other way that
+            // can't be checked for changes
+            None => frame.uncacheable = true,
         }
+    }
+
+    fn visit_dynamic_import_rule(&mut self, dynamic_import: &AstSassImport) -> SassResult<()> {
+        self.emit_deprecation(
+            Deprecation::Import,
+            "Sass @import rules are deprecated and will be removed in Dart Sass 3.0.0.\n\nMore info and automated migrator: https://sass-lang.com/d/import",
+            dynamic_import.span,
+        )?;
+
+        self.with_stack_frame("@import".to_owned(), dynamic_import.span, |visitor| {
+            let stylesheet =
+                visitor.load_style_sheet(&dynamic_import.url, true, dynamic_import.span)?;
 
-        // this todo should be unreachable, as we currently do not push
-        // to stylesheet.uses or stylesheet.forwards
-        todo!()
+            // If the imported stylesheet doesn't use any modules, we can inject its
+            // CSS directly into the current stylesheet. If it does use modules, we
+            // need to put its CSS into an intermediate [ModifiableCssStylesheet] so
+            // that we can hermetically resolve `@extend`s before injecting it.
+            if stylesheet.uses.is_empty() && stylesheet.forwards.is_empty() {
+                let url = stylesheet.url.clone();
+
+                if !visitor.active_modules.insert(url.clone()) {
+                    return Err(("This file is already being loaded.", dynamic_import.span).into());
+                }
+
+                let result = visitor.visit_stylesheet(stylesheet);
+                visitor.active_modules.remove(&url);
+
+                return result;
+            }
+
+            // this should be unreachable, as we currently do not push to
+            // stylesheet.uses or stylesheet.forwards
+            Err((
+                "Importing stylesheets which load modules is not yet supported.",
+                dynamic_import.span,
+            )
+                .into())
+        })
     }
 
     fn visit_static_import_rule(&mut self, static_import: AstPlainCssImport) -> SassResult<()> {
@@ -919,13 +1550,11 @@ impl<'a> Visitor<'a> {
 
         let message = self.visit_expr(debug_rule.value)?;
 
-        let loc = self.map.look_up_span(debug_rule.span);
-        eprintln!(
-            "{}:{} DEBUG: {}",
-            loc.file.name(),
-            loc.begin.line + 1,
-            message.inspect(debug_rule.span)?
-        );
+        let loc = look_up_span(self.map, debug_rule.span);
+        let trace = self.resolved_stack_trace(debug_rule.span);
+        self.options
+            .logger
+            .debug(loc, message.inspect(debug_rule.span)?.as_ref(), &trace);
 
         Ok(None)
     }
@@ -1182,16 +1811,15 @@ impl<'a> Visitor<'a> {
         let list = self.parse_selector_from_string(&target_text, false, true, extend_rule.span)?;
 
         for complex in list.components {
-            if complex.components.len() != 1 || !complex.components.first().unwrap().is_compound() {
-                // If the selector was a compound selector but not a simple
-                // selector, emit a more explicit error.
-                return Err(("complex selectors may not be extended.", extend_rule.span).into());
-            }
-
-            let compound = match complex.components.first() {
-                Some(ComplexSelectorComponent::Compound(c)) => c,
-                Some(..) | None => todo!(),
+            let compound = match complex.components.as_slice() {
+                [ComplexSelectorComponent::Compound(compound)] => compound,
+                _ => {
+                    return Err(("complex selectors may not be extended.", extend_rule.span).into())
+                }
             };
+
+            // If the selector was a compound selector but not a simple
+            // selector, emit a more explicit error.
             if compound.components.len() != 1 {
                 return Err((
                     format!(
@@ -1281,6 +1909,7 @@ impl<'a> Visitor<'a> {
         };
 
         let children = media_rule.body;
+        let span = media_rule.span;
 
         let query = merged_queries.clone().unwrap_or_else(|| queries1.clone());
 
@@ -1316,6 +1945,7 @@ impl<'a> Visitor<'a> {
                                 selector,
                                 body: Vec::new(),
                                 is_group_end: false,
+                                span,
                             };
 
                             visitor.with_parent(
@@ -1430,6 +2060,7 @@ impl<'a> Visitor<'a> {
                         selector,
                         body: Vec::new(),
                         is_group_end: false,
+                        span: unknown_at_rule.span,
                     };
 
                     visitor.with_parent(
@@ -1463,14 +2094,119 @@ impl<'a> Visitor<'a> {
         if self.options.quiet {
             return;
         }
-        let loc = self.map.look_up_span(span);
-        eprintln!(
-            "Warning: {}\n    ./{}:{}:{}",
-            message,
-            loc.file.name(),
-            loc.begin.line + 1,
-            loc.begin.column + 1
-        );
+        let loc = look_up_span(self.map, span);
+        let trace = self.resolved_stack_trace(span);
+        self.options.logger.warn(loc, message, &trace);
+    }
+
+    /// Emit a warning for the use of a deprecated feature, or an error if the
+    /// user has made the deprecation fatal
+    pub fn emit_deprecation(
+        &mut self,
+        deprecation: Deprecation,
+        message: &str,
+        span: Span,
+    ) -> SassResult<()> {
+        if self.options.is_fatal(deprecation) {
+            return Err((
+                format!(
+                    "{}\n\nThis is only an error because you've set the {} deprecation to be fatal.\nRemove this setting if you need to keep using this feature.",
+                    message, deprecation
+                ),
+                span,
+            )
+                .into());
+        }
+
+        if self.options.quiet || !self.options.is_deprecation_active(deprecation) {
+            return Ok(());
+        }
+
+        let count = self.deprecation_counts.entry(deprecation).or_insert(0);
+        *count += 1;
+
+        if !self.options.verbose && *count > MAX_DEPRECATION_REPETITIONS {
+            return Ok(());
+        }
+
+        let loc = look_up_span(self.map, span);
+        let trace = self.resolved_stack_trace(span);
+        self.options
+            .logger
+            .deprecation(deprecation, loc, message, &trace);
+
+        Ok(())
+    }
+
+    /// Emit a summary of the deprecation warnings which were omitted because
+    /// they were repetitive
+    pub fn emit_deprecation_summary(&self) {
+        let omitted: usize = self
+            .deprecation_counts
+            .values()
+            .map(|count| count.saturating_sub(MAX_DEPRECATION_REPETITIONS))
+            .sum();
+
+        if omitted > 0 && !self.options.verbose {
+            self.options.logger.warn_without_span(&format!(
+                "{} repetitive deprecation warnings omitted.\nRun in verbose mode to see all warnings.",
+                omitted
+            ));
+        }
+    }
+
+    fn warn_for_global_builtin(&mut self, name: &str, span: Span) -> SassResult<()> {
+        if is_legacy_color_function(name) {
+            return self.emit_deprecation(
+                Deprecation::ColorFunctions,
+                &format!("{}() is deprecated.\n\nRecommendation: color.adjust() or color.scale()\n\nMore info: https://sass-lang.com/d/color-functions", name),
+                span,
+            );
+        }
+
+        match module_equivalent(name) {
+            Some(equivalent) => self.emit_deprecation(
+                Deprecation::GlobalBuiltin,
+                &format!("Global built-in functions are deprecated and will be removed in Dart Sass 3.0.0.\nUse {} instead.\n\nMore info and automated migrator: https://sass-lang.com/d/import", equivalent),
+                span,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs `callback` in a new Sass stack frame named `member`, invoked from
+    /// `span`
+    pub fn with_stack_frame<T>(
+        &mut self,
+        member: String,
+        span: Span,
+        callback: impl FnOnce(&mut Self) -> SassResult<T>,
+    ) -> SassResult<T> {
+        let old_member = mem::replace(&mut self.member, member);
+        self.stack.push((old_member, span));
+
+        let result = callback(self).map_err(|err| err.with_trace(|span| self.stack_trace(span)));
+
+        let (old_member, _) = self.stack.pop().unwrap();
+        self.member = old_member;
+
+        result
+    }
+
+    /// The current Sass stack trace, innermost frame first, with the current
+    /// member located at `span`
+    fn stack_trace(&self, span: Span) -> Vec<RawStackFrame> {
+        let mut trace = Vec::with_capacity(self.stack.len() + 1);
+        trace.push((self.member.clone(), span));
+        trace.extend(self.stack.iter().rev().cloned());
+        trace
+    }
+
+    fn resolved_stack_trace(&self, span: Span) -> Vec<StackFrame> {
+        self.stack_trace(span)
+            .into_iter()
+            .map(|(member, span)| StackFrame::new(member, look_up_span(self.map, span)))
+            .collect()
     }
 
     fn visit_warn_rule(&mut self, warn_rule: AstWarn) -> SassResult<()> {
@@ -1669,7 +2405,7 @@ impl<'a> Visitor<'a> {
 
     fn visit_mixin_decl(&mut self, mixin: AstMixin) {
         self.env.insert_mixin(
-            mixin.name,
+            mixin.name.node,
             Mixin::UserDefined(mixin, self.env.new_closure()),
         );
     }
@@ -1683,8 +2419,10 @@ impl<'a> Visitor<'a> {
         let mut result = None;
 
         'outer: for val in list {
+            self.count_loop_iteration(each_stmt.span)?;
+
             if each_stmt.variables.len() == 1 {
-                let val = self.without_slash(val);
+                let val = self.without_slash(val, each_stmt.list_span)?;
                 self.env
                     .scopes_mut()
                     .insert_var_last(each_stmt.variables[0], val);
@@ -1694,7 +2432,7 @@ impl<'a> Visitor<'a> {
                         .into_iter()
                         .chain(std::iter::once(Value::Null).cycle()),
                 ) {
-                    let val = self.without_slash(val);
+                    let val = self.without_slash(val, each_stmt.list_span)?;
                     self.env.scopes_mut().insert_var_last(var, val);
                 }
             }
@@ -1761,6 +2499,8 @@ impl<'a> Visitor<'a> {
 
         let mut i = from;
         'outer: while i != to {
+            self.count_loop_iteration(from_span.merge(to_span))?;
+
             self.env.scopes_mut().insert_var_last(
                 for_stmt.variable.node,
                 Value::Dimension(SassNumber {
@@ -1791,9 +2531,11 @@ impl<'a> Visitor<'a> {
             let mut result = None;
 
             'outer: while visitor
-                .visit_expr(while_stmt.condition.clone())?
+                .visit_expr(while_stmt.condition.node.clone())?
                 .is_truthy()
             {
+                visitor.count_loop_iteration(while_stmt.condition.span)?;
+
                 for stmt in while_stmt.body.clone() {
                     let val = visitor.visit_stmt(stmt)?;
                     if val.is_some() {
@@ -1810,7 +2552,7 @@ impl<'a> Visitor<'a> {
     fn visit_if_stmt(&mut self, if_stmt: AstIf) -> SassResult<Option<Value>> {
         let mut clause: Option<Vec<AstStmt>> = if_stmt.else_clause;
         for clause_to_check in if_stmt.if_clauses {
-            if self.visit_expr(clause_to_check.condition)?.is_truthy() {
+            if self.visit_expr(clause_to_check.condition.node)?.is_truthy() {
                 clause = Some(clause_to_check.body);
                 break;
             }
@@ -1871,10 +2613,12 @@ impl<'a> Visitor<'a> {
                         ..
                     }) | None
                 ) {
+                    let value = var_override.unwrap().value;
+                    self.record_variable_value(decl.span, &value);
                     self.env.insert_var(
                         name,
                         None,
-                        var_override.unwrap().value,
+                        value,
                         true,
                         self.flags.in_semi_global_scope(),
                     )?;
@@ -1886,13 +2630,29 @@ impl<'a> Visitor<'a> {
                 let value = self.env.get_var(name, decl.namespace).unwrap();
 
                 if value != Value::Null {
+                    self.record_variable_value(decl.span, &value);
                     return Ok(None);
                 }
             }
         }
 
+        if decl.is_global
+            && decl.namespace.is_none()
+            && !(*self.env.global_vars()).borrow().contains_key(&decl.name)
+        {
+            let message = if self.env.at_root() {
+                "As of Dart Sass 2.0.0, !global assignments won't be able to declare new variables.\n\nSince this assignment is at the root of the stylesheet, the !global flag is\nunnecessary and can safely be removed.".to_owned()
+            } else {
+                format!("As of Dart Sass 2.0.0, !global assignments won't be able to declare new variables.\n\nRecommendation: add `${}: null` at the stylesheet root.", decl.name)
+            };
+
+            self.emit_deprecation(Deprecation::NewGlobal, &message, decl.span)?;
+        }
+
         let value = self.visit_expr(decl.value)?;
-        let value = self.without_slash(value);
+        let value = self.without_slash(value, decl.span)?;
+
+        self.record_variable_value(decl.span, &value);
 
         self.env.insert_var(
             name,
@@ -1905,6 +2665,12 @@ impl<'a> Visitor<'a> {
         Ok(None)
     }
 
+    fn record_variable_value(&mut self, span: Span, value: &Value) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.variable_values.insert(span, value.clone());
+        }
+    }
+
     fn interpolation_to_value(
         &mut self,
         interpolation: Interpolation,
@@ -1969,19 +2735,28 @@ impl<'a> Visitor<'a> {
     }
 
     #[allow(clippy::unused_self)]
-    fn without_slash(&mut self, v: Value) -> Value {
-        match v {
-            Value::Dimension(SassNumber { .. }) if v.as_slash().is_some() => {
-                // todo: emit warning. we don't currently because it can be quite loud
-                // self.emit_warning(
-                //     Cow::Borrowed("Using / for division is deprecated and will be removed at some point in the future"),
-                //     self.span_before,
-                // );
+    fn without_slash(&mut self, v: Value, span: Span) -> SassResult<Value> {
+        if let Some(as_slash) = v.as_slash() {
+            fn recommendation(numerator: &SassNumber, denominator: &SassNumber) -> String {
+                let format = |number: &SassNumber| match &number.as_slash {
+                    Some(as_slash) => recommendation(&as_slash.0, &as_slash.1),
+                    None => format!("{}{}", number.num.inspect(), number.unit),
+                };
+
+                format!("math.div({}, {})", format(numerator), format(denominator))
             }
-            _ => {}
+
+            self.emit_deprecation(
+                Deprecation::SlashDiv,
+                &format!(
+                    "Using / for division is deprecated and will be removed in Dart Sass 2.0.0.\n\nRecommendation: {}\n\nMore info and automated migrator: https://sass-lang.com/d/slash-div",
+                    recommendation(&as_slash.0, &as_slash.1)
+                ),
+                span,
+            )?;
         }
 
-        v.without_slash()
+        Ok(v.without_slash())
     }
 
     fn eval_maybe_args(
@@ -2004,14 +2779,14 @@ impl<'a> Visitor<'a> {
 
         for expr in arguments.positional {
             let val = self.visit_expr(expr)?;
-            positional.push(self.without_slash(val));
+            positional.push(self.without_slash(val, span)?);
         }
 
         let mut named = BTreeMap::new();
 
         for (key, expr) in arguments.named {
             let val = self.visit_expr(expr)?;
-            named.insert(key, self.without_slash(val));
+            named.insert(key, self.without_slash(val, span)?);
         }
 
         if arguments.rest.is_none() {
@@ -2029,31 +2804,31 @@ impl<'a> Visitor<'a> {
         let mut separator = ListSeparator::Undecided;
 
         match rest {
-            Value::Map(rest) => self.add_rest_map(&mut named, rest)?,
+            Value::Map(rest) => self.add_rest_map(&mut named, rest, span)?,
             Value::List(elems, list_separator, _) => {
                 let mut list = elems
                     .into_iter()
-                    .map(|e| self.without_slash(e))
-                    .collect::<Vec<_>>();
+                    .map(|e| self.without_slash(e, span))
+                    .collect::<SassResult<Vec<_>>>()?;
                 positional.append(&mut list);
                 separator = list_separator;
             }
             Value::ArgList(arglist) => {
                 // todo: superfluous clone
                 for (&key, value) in arglist.keywords() {
-                    named.insert(key, self.without_slash(value.clone()));
+                    named.insert(key, self.without_slash(value.clone(), span)?);
                 }
 
                 let mut list = arglist
                     .elems
                     .into_iter()
-                    .map(|e| self.without_slash(e))
-                    .collect::<Vec<_>>();
+                    .map(|e| self.without_slash(e, span))
+                    .collect::<SassResult<Vec<_>>>()?;
                 positional.append(&mut list);
                 separator = arglist.separator;
             }
             _ => {
-                positional.push(self.without_slash(rest));
+                positional.push(self.without_slash(rest, span)?);
             }
         }
 
@@ -2069,7 +2844,7 @@ impl<'a> Visitor<'a> {
 
         match self.visit_expr(arguments.keyword_rest.unwrap())? {
             Value::Map(keyword_rest) => {
-                self.add_rest_map(&mut named, keyword_rest)?;
+                self.add_rest_map(&mut named, keyword_rest, span)?;
 
                 Ok(ArgumentResult {
                     positional,
@@ -2096,11 +2871,12 @@ impl<'a> Visitor<'a> {
         &mut self,
         named: &mut BTreeMap<Identifier, Value>,
         rest: SassMap,
+        span: Span,
     ) -> SassResult<()> {
         for (key, val) in rest {
             match key.node {
                 Value::String(text, ..) => {
-                    let val = self.without_slash(val);
+                    let val = self.without_slash(val, span)?;
                     named.insert(Identifier::from(text), val);
                 }
                 _ => {
@@ -2137,113 +2913,127 @@ impl<'a> Visitor<'a> {
             name.push_str("()");
         }
 
-        self.with_environment(env.new_closure(), |visitor| {
-            visitor.with_scope(false, true, move |visitor| {
-                func.arguments().verify(
-                    evaluated.positional.len(),
-                    &evaluated.named,
-                    evaluated.span,
-                )?;
-
-                let declared_arguments = &func.arguments().args;
-                let min_len = evaluated.positional.len().min(declared_arguments.len());
-
-                let positional_len = evaluated.positional.len();
-
-                #[allow(clippy::needless_range_loop)]
-                for i in (0..min_len).rev() {
-                    visitor.env.scopes_mut().insert_var_last(
-                        declared_arguments[i].name,
-                        evaluated.positional.remove(i),
-                    );
-                }
+        if let Some(max) = self.options.max_call_depth {
+            if self.call_depth >= max {
+                return Err((format!("Maximum call depth ({}) exceeded.", max), span).into());
+            }
+        }
 
-                // todo: better name for var
-                let additional_declared_args = if declared_arguments.len() > positional_len {
-                    &declared_arguments[positional_len..declared_arguments.len()]
-                } else {
-                    &[]
-                };
+        self.check_cancelled(span)?;
 
-                for argument in additional_declared_args {
-                    let name = argument.name;
-                    let value = evaluated.named.remove(&argument.name).map_or_else(
-                        || {
-                            // todo: superfluous clone
-                            let v = visitor.visit_expr(argument.default.clone().unwrap())?;
-                            Ok(visitor.without_slash(v))
-                        },
-                        SassResult::Ok,
+        self.call_depth += 1;
+        let result = self.with_stack_frame(name, span, |visitor| {
+            visitor.with_environment(env.new_closure(), |visitor| {
+                visitor.with_scope(false, true, move |visitor| {
+                    func.arguments().verify(
+                        evaluated.positional.len(),
+                        &evaluated.named,
+                        evaluated.span,
                     )?;
-                    visitor.env.scopes_mut().insert_var_last(name, value);
-                }
 
-                let were_keywords_accessed = Arc::new(Cell::new(false));
+                    let declared_arguments = &func.arguments().args;
+                    let min_len = evaluated.positional.len().min(declared_arguments.len());
 
-                let num_named_args = evaluated.named.len();
+                    let positional_len = evaluated.positional.len();
+
+                    #[allow(clippy::needless_range_loop)]
+                    for i in (0..min_len).rev() {
+                        visitor.env.scopes_mut().insert_var_last(
+                            declared_arguments[i].name,
+                            evaluated.positional.remove(i),
+                        );
+                    }
 
-                let has_arg_list = if let Some(rest_arg) = func.arguments().rest {
-                    let rest = if !evaluated.positional.is_empty() {
-                        evaluated.positional
+                    // todo: better name for var
+                    let additional_declared_args = if declared_arguments.len() > positional_len {
+                        &declared_arguments[positional_len..declared_arguments.len()]
                     } else {
-                        Vec::new()
+                        &[]
                     };
 
-                    let arg_list = Value::ArgList(ArgList::new(
-                        rest,
-                        Arc::clone(&were_keywords_accessed),
-                        // todo: superfluous clone
-                        evaluated.named.clone(),
-                        if evaluated.separator == ListSeparator::Undecided {
-                            ListSeparator::Comma
+                    for argument in additional_declared_args {
+                        let name = argument.name;
+                        let value = evaluated.named.remove(&argument.name).map_or_else(
+                            || {
+                                // todo: superfluous clone
+                                let v = visitor.visit_expr(argument.default.clone().unwrap())?;
+                                visitor.without_slash(v, span)
+                            },
+                            SassResult::Ok,
+                        )?;
+                        visitor.env.scopes_mut().insert_var_last(name, value);
+                    }
+
+                    let were_keywords_accessed = Arc::new(AtomicBool::new(false));
+
+                    let num_named_args = evaluated.named.len();
+
+                    let has_arg_list = if let Some(rest_arg) = func.arguments().rest {
+                        let rest = if !evaluated.positional.is_empty() {
+                            evaluated.positional
                         } else {
-                            ListSeparator::Space
-                        },
-                    ));
+                            Vec::new()
+                        };
 
-                    visitor.env.scopes_mut().insert_var_last(rest_arg, arg_list);
+                        let arg_list = Value::ArgList(ArgList::new(
+                            rest,
+                            Arc::clone(&were_keywords_accessed),
+                            // todo: superfluous clone
+                            evaluated.named.clone(),
+                            if evaluated.separator == ListSeparator::Undecided {
+                                ListSeparator::Comma
+                            } else {
+                                ListSeparator::Space
+                            },
+                        ));
 
-                    true
-                } else {
-                    false
-                };
+                        visitor.env.scopes_mut().insert_var_last(rest_arg, arg_list);
 
-                let val = run(func, visitor)?;
+                        true
+                    } else {
+                        false
+                    };
 
-                if !has_arg_list || num_named_args == 0 {
-                    return Ok(val);
-                }
+                    let val = run(func, visitor)?;
 
-                if (*were_keywords_accessed).get() {
-                    return Ok(val);
-                }
+                    if !has_arg_list || num_named_args == 0 {
+                        return Ok(val);
+                    }
 
-                let argument_word = if num_named_args == 1 {
-                    "argument"
-                } else {
-                    "arguments"
-                };
+                    if were_keywords_accessed.load(Ordering::Relaxed) {
+                        return Ok(val);
+                    }
 
-                let argument_names = to_sentence(
-                    evaluated
-                        .named
-                        .keys()
-                        .map(|key| format!("${key}", key = key))
-                        .collect(),
-                    "or",
-                );
+                    let argument_word = if num_named_args == 1 {
+                        "argument"
+                    } else {
+                        "arguments"
+                    };
 
-                Err((
-                    format!(
-                        "No {argument_word} named {argument_names}.",
-                        argument_word = argument_word,
-                        argument_names = argument_names
-                    ),
-                    span,
-                )
-                    .into())
+                    let argument_names = to_sentence(
+                        evaluated
+                            .named
+                            .keys()
+                            .map(|key| format!("${key}", key = key))
+                            .collect(),
+                        "or",
+                    );
+
+                    Err((
+                        format!(
+                            "No {argument_word} named {argument_names}.",
+                            argument_word = argument_word,
+                            argument_names = argument_names
+                        ),
+                        span,
+                    )
+                        .into())
+                })
             })
-        })
+        });
+        self.call_depth -= 1;
+
+        result
     }
 
     pub(crate) fn run_function_callable(
@@ -2269,7 +3059,7 @@ impl<'a> Visitor<'a> {
             SassFunction::Builtin(func, _name) => {
                 let evaluated = self.eval_maybe_args(arguments, span)?;
                 let val = func.0(evaluated, self)?;
-                Ok(self.without_slash(val))
+                self.without_slash(val, span)
             }
             SassFunction::UserDefined(UserDefinedFunction { function, env, .. }) => self
                 .run_user_defined_callable(arguments, function, &env, span, |function, visitor| {
@@ -2283,6 +3073,41 @@ impl<'a> Visitor<'a> {
 
                     Err(("Function finished without @return.", span).into())
                 }),
+            SassFunction::Host(function) => {
+                let env = self.env.clone();
+                let signature = self.parse_host_signature(&function.signature, span)?;
+
+                self.run_user_defined_callable(
+                    arguments,
+                    signature,
+                    &env,
+                    span,
+                    |signature, visitor| {
+                        let names = signature
+                            .arguments
+                            .args
+                            .iter()
+                            .map(|argument| argument.name)
+                            .chain(signature.arguments.rest);
+
+                        let mut values = Vec::new();
+                        for name in names {
+                            values.push(visitor.env.get_var(Spanned { node: name, span }, None)?);
+                        }
+
+                        let host = match visitor.function_host {
+                            Some(host) => host,
+                            None => return Err(("Undefined function.", span).into()),
+                        };
+
+                        let value = host
+                            .call(&function, values, span)
+                            .map_err(|message| (message, span))?;
+
+                        visitor.without_slash(value, span)
+                    },
+                )
+            }
             SassFunction::Plain { name } => {
                 let has_named;
                 let mut rest = None;
@@ -2341,6 +3166,63 @@ impl<'a> Visitor<'a> {
         }
     }
 
+    /// Parses the signature of a host function, such as `foo($a, $b: 1)`, as
+    /// the declaration of a function with an empty body
+    fn parse_host_signature(
+        &mut self,
+        signature: &str,
+        span: Span,
+    ) -> SassResult<Arc<AstFunctionDecl>> {
+        if let Some(decl) = self.host_signatures.get(signature) {
+            return Ok(Arc::clone(decl));
+        }
+
+        let source = if signature.contains('(') {
+            format!("@function {} {{}}", signature)
+        } else {
+            format!("@function {}() {{}}", signature)
+        };
+
+        let file = self.add_file(signature.to_owned(), source);
+        let path = PathBuf::from(signature);
+        let style_sheet = ScssParser::new(
+            Lexer::new_from_file(&file),
+            self.map,
+            self.options,
+            file.span.subspan(0, 0),
+            &path,
+        )
+        .__parse()?;
+
+        match style_sheet.body.into_iter().next() {
+            Some(AstStmt::FunctionDecl(decl)) => {
+                let decl = Arc::new(decl);
+                self.host_signatures
+                    .insert(signature.to_owned(), Arc::clone(&decl));
+                Ok(decl)
+            }
+            _ => Err((format!("Invalid signature \"{}\".", signature), span).into()),
+        }
+    }
+
+    /// The global function with this name implemented by the program
+    /// embedding the compiler, if any
+    pub(crate) fn host_function(
+        &self,
+        name: Identifier,
+        namespace: Option<Spanned<Identifier>>,
+    ) -> Option<HostFunction> {
+        if namespace.is_some() {
+            return None;
+        }
+
+        self.function_host?
+            .global_functions()
+            .iter()
+            .find(|function| function.name == name)
+            .cloned()
+    }
+
     fn visit_list_expr(&mut self, list: ListExpr) -> SassResult<Value> {
         let elems = list
             .elems
@@ -2349,7 +3231,7 @@ impl<'a> Visitor<'a> {
                 let value = self.visit_expr(e.node)?;
                 Ok(value)
             })
-            .collect::<SassResult<Vec<_>>>()?;
+            .collect::<SassResult<PersistentVec<_>>>()?;
 
         Ok(Value::List(elems, list.separator, list.brackets))
     }
@@ -2360,7 +3242,12 @@ impl<'a> Visitor<'a> {
         let func = match self.env.get_fn(name, func_call.namespace)? {
             Some(func) => func,
             None => {
-                if let Some(f) = GLOBAL_FUNCTIONS.get(name.as_str()) {
+                if let Some(f) = self.host_function(name, func_call.namespace) {
+                    SassFunction::Host(f)
+                } else if let Some(f) = GLOBAL_FUNCTIONS.get(name.as_str()) {
+                    if func_call.namespace.is_none() {
+                        self.warn_for_global_builtin(name.as_str(), func_call.span)?;
+                    }
                     SassFunction::Builtin(f.clone(), name)
                 } else {
                     if func_call.namespace.is_some() {
@@ -2590,7 +3477,8 @@ impl<'a> Visitor<'a> {
     }
 
     fn visit_ternary(&mut self, if_expr: Ternary) -> SassResult<Value> {
-        if_arguments().verify(if_expr.0.positional.len(), &if_expr.0.named, if_expr.0.span)?;
+        let span = if_expr.0.span;
+        if_arguments().verify(if_expr.0.positional.len(), &if_expr.0.named, span)?;
 
         let mut positional = if_expr.0.positional;
         let mut named = if_expr.0.named;
@@ -2619,7 +3507,7 @@ impl<'a> Visitor<'a> {
             self.visit_expr(if_false)?
         };
 
-        Ok(self.without_slash(value))
+        self.without_slash(value, span)
     }
 
     fn visit_string(&mut self, mut text: Interpolation, quote: QuoteKind) -> SassResult<Value> {
@@ -2757,13 +3645,17 @@ impl<'a> Visitor<'a> {
                         span,
                     );
                 } else if left_is_number && right_is_number {
-                    // todo: emit warning here. it prints too frequently, so we do not currently
-                    // self.emit_warning(
-                    //     Cow::Borrowed(format!(
-                    //         "Using / for division outside of calc() is deprecated"
-                    //     )),
-                    //     span,
-                    // );
+                    let left_text = left.inspect(span)?;
+                    let right_text = right.inspect(span)?;
+                    self.emit_deprecation(
+                        Deprecation::SlashDiv,
+                        &format!(
+                            "Using / for division outside of calc() is deprecated and will be removed in Dart Sass 2.0.0.\n\nRecommendation: math.div({left}, {right}) or calc({left} / {right})\n\nMore info and automated migrator: https://sass-lang.com/d/slash-div",
+                            left = left_text,
+                            right = right_text,
+                        ),
+                        span,
+                    )?;
                 }
 
                 div(left, right, self.options, span)?
@@ -2785,6 +3677,8 @@ impl<'a> Visitor<'a> {
     }
 
     pub fn visit_ruleset(&mut self, ruleset: AstRuleSet) -> SassResult<Option<Value>> {
+        self.check_cancelled(ruleset.span)?;
+
         if self.declaration_name.is_some() {
             return Err((
                 "Style rules may not be used within nested declarations.",
@@ -2853,6 +3747,7 @@ impl<'a> Visitor<'a> {
             selector: selector.clone(),
             body: Vec::new(),
             is_group_end: false,
+            span: ruleset.selector_span,
         };
 
         let old_at_root_excluding_style_rule = self.flags.at_root_excluding_style_rule();
@@ -2947,6 +3842,7 @@ impl<'a> Visitor<'a> {
                         property: InternedString::get_or_intern(&name),
                         value: Box::new(value),
                         declared_as_custom_property: is_custom_property,
+                        span: style.span,
                     }),
                     self.parent,
                 );
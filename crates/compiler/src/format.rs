@@ -0,0 +1,1363 @@
+//! A formatter for Sass stylesheets, in the spirit of `rustfmt`
+//!
+//! [`format()`] parses a stylesheet and re-emits it with consistent
+//! indentation, spacing and line breaks, in the syntax it was written in.
+//! [`convert`] does the same, but writes the stylesheet in another syntax.
+//!
+//! ```
+//! # use grass_compiler as grass;
+//! use grass::format::{format, FormatOptions};
+//!
+//! let formatted = format(
+//!     "a{color:red;&:hover{color : blue}}".to_owned(),
+//!     "input.scss".as_ref(),
+//!     &FormatOptions::default(),
+//! )?;
+//!
+//! assert_eq!("a {\n  color: red;\n  &:hover {\n    color: blue;\n  }\n}\n", formatted);
+//! # Ok::<(), Box<grass::Error>>(())
+//! ```
+//!
+//! Only the whitespace between tokens is changed. Selectors, expressions and
+//! at-rule parameters are otherwise kept exactly as written, so formatting a
+//! stylesheet never changes the CSS it compiles to. The one exception is the
+//! indented syntax's `=` and `+` shorthands, which are written out as `@mixin`
+//! and `@include`.
+//!
+//! Comments are preserved, as are blank lines between statements, although
+//! runs of blank lines are collapsed into one. Comma-separated lists that
+//! don't fit within the line width are broken onto one line per element. This
+//! is never done for the indented syntax, which doesn't allow expressions to
+//! span multiple lines. Selector lists written over multiple lines are kept on
+//! one line per selector.
+//!
+//! When converting to the indented syntax, `//` comments within expressions
+//! are written as `/* */` comments, since the expression must fit on one line.
+//!
+//! This API is experimental, and may change in minor releases.
+
+use std::{borrow::Cow, path::Path};
+
+use codemap::{Pos, Span};
+
+use crate::{ast::*, parse_stylesheet, InputSyntax, NullFs, NullLogger, Options, Result};
+
+/// Configuration for formatting
+///
+/// The simplest usage is `grass::format::FormatOptions::default()`, which
+/// indents with two spaces and breaks lines longer than 80 characters; however,
+/// a builder pattern is also exposed to offer more control.
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    pub(crate) indent_width: usize,
+    pub(crate) use_tabs: bool,
+    pub(crate) line_width: usize,
+    pub(crate) input_syntax: Option<InputSyntax>,
+}
+
+impl Default for FormatOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            use_tabs: false,
+            line_width: 80,
+            input_syntax: None,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// The number of spaces to indent each level of nesting by
+    ///
+    /// When indenting with tabs, this is the width a tab is assumed to take up
+    /// when measuring lines.
+    ///
+    /// By default, this value is `2`
+    #[must_use]
+    #[inline]
+    pub const fn indent_width(mut self, indent_width: usize) -> Self {
+        self.indent_width = indent_width;
+        self
+    }
+
+    /// Whether to indent with tabs rather than spaces
+    ///
+    /// By default, this value is `false`
+    #[must_use]
+    #[inline]
+    pub const fn use_tabs(mut self, use_tabs: bool) -> Self {
+        self.use_tabs = use_tabs;
+        self
+    }
+
+    /// The width lines should fit within, in characters
+    ///
+    /// Lines are only broken between the elements of comma-separated lists, so
+    /// lines without one may be longer than this.
+    ///
+    /// By default, this value is `80`
+    #[must_use]
+    #[inline]
+    pub const fn line_width(mut self, line_width: usize) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// The syntax the stylesheet is written in
+    ///
+    /// Stylesheets are always formatted in the syntax they are written in.
+    ///
+    /// By default, the syntax is inferred from the extension of the path passed
+    /// to [`format()`], which is the indented syntax for `.sass` files, plain CSS
+    /// for `.css` files, and SCSS otherwise.
+    #[must_use]
+    #[inline]
+    pub const fn input_syntax(mut self, syntax: InputSyntax) -> Self {
+        self.input_syntax = Some(syntax);
+        self
+    }
+}
+
+/// Format a stylesheet, returning an error if it fails to parse
+///
+/// `path` is only used to infer the input syntax and in error messages; the
+/// file system is never accessed.
+pub fn format(input: String, path: &Path, options: &FormatOptions) -> Result<String> {
+    reformat(input, path, None, options)
+}
+
+/// Format a stylesheet in the syntax `to`, returning an error if it fails to
+/// parse
+///
+/// This is used to migrate stylesheets between SCSS and the indented syntax.
+/// The converted stylesheet compiles to the same CSS as the original. Plain
+/// CSS is written like SCSS, so converting to [`InputSyntax::Css`] only
+/// produces valid CSS if the stylesheet doesn't use any Sass features.
+///
+/// ```
+/// # use grass_compiler as grass;
+/// use grass::{format::{convert, FormatOptions}, InputSyntax};
+///
+/// let converted = convert(
+///     "a {\n  color: red;\n}\n".to_owned(),
+///     "input.scss".as_ref(),
+///     InputSyntax::Sass,
+///     &FormatOptions::default(),
+/// )?;
+///
+/// assert_eq!("a\n  color: red\n", converted);
+/// # Ok::<(), Box<grass::Error>>(())
+/// ```
+pub fn convert(
+    input: String,
+    path: &Path,
+    to: InputSyntax,
+    options: &FormatOptions,
+) -> Result<String> {
+    reformat(input, path, Some(to), options)
+}
+
+fn reformat(
+    input: String,
+    path: &Path,
+    output_syntax: Option<InputSyntax>,
+    options: &FormatOptions,
+) -> Result<String> {
+    let input_syntax = options
+        .input_syntax
+        .unwrap_or_else(|| InputSyntax::for_path(path));
+    let output_syntax = output_syntax.unwrap_or(input_syntax);
+
+    let parse_options = Options::default()
+        .fs(&NullFs)
+        .logger(&NullLogger)
+        .input_syntax(input_syntax);
+
+    let mut map = codemap::CodeMap::new();
+    let (stylesheet, empty_span) = parse_stylesheet(&mut map, input, path, &parse_options)?;
+    let file = map.find_file(empty_span.low());
+
+    let mut formatter = Formatter {
+        source: file.source(),
+        base: file.span.low(),
+        input_indented: input_syntax == InputSyntax::Sass,
+        indented: output_syntax == InputSyntax::Sass,
+        indent: if options.use_tabs {
+            "\t".to_owned()
+        } else {
+            " ".repeat(options.indent_width)
+        },
+        indent_width: options.indent_width,
+        line_width: if output_syntax == InputSyntax::Sass {
+            usize::MAX
+        } else {
+            options.line_width
+        },
+        out: String::new(),
+    };
+
+    formatter.write_body(&stylesheet.body, 0);
+
+    Ok(formatter.out)
+}
+
+/// A tree describing where lines may be broken, which is laid out to fit
+/// within the line width
+#[derive(Debug)]
+enum Doc {
+    Text(String),
+    /// A `//` comment, which must be followed by a line break
+    Comment(String),
+    /// A space, or a line break if the enclosing group is broken
+    Line,
+    /// Nothing, or a line break if the enclosing group is broken
+    SoftLine,
+    HardLine,
+    /// Docs whose lines are either all broken, or not broken at all
+    Group(Vec<Doc>),
+    /// Docs which are indented by one more level after a line break
+    Indent(Vec<Doc>),
+}
+
+impl Doc {
+    /// The width of these docs if no lines are broken, or `None` if they
+    /// contain a line break that can't be avoided
+    fn flat_width(docs: &[Doc]) -> Option<usize> {
+        docs.iter().try_fold(0, |width, doc| {
+            Some(
+                width
+                    + match doc {
+                        Doc::Text(text) => text.chars().count(),
+                        Doc::Line => 1,
+                        Doc::SoftLine => 0,
+                        Doc::Comment(..) | Doc::HardLine => return None,
+                        Doc::Group(docs) | Doc::Indent(docs) => Doc::flat_width(docs)?,
+                    },
+            )
+        })
+    }
+
+    /// The width of the text following a group, up until the next place a
+    /// line may be broken
+    fn rest_width(docs: &[Doc], broken: bool, rest: usize) -> usize {
+        let mut width = 0;
+
+        for doc in docs {
+            match doc {
+                Doc::Text(text) => width += text.chars().count(),
+                Doc::Line | Doc::SoftLine if broken => return width,
+                Doc::Line => width += 1,
+                Doc::SoftLine => {}
+                Doc::Comment(..) | Doc::HardLine => return width,
+                Doc::Group(docs) | Doc::Indent(docs) => match Doc::flat_width(docs) {
+                    Some(flat) => width += flat,
+                    None => return width,
+                },
+            }
+        }
+
+        width + rest
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Whitespace,
+    /// Text which is kept as written, including strings, interpolation, loud
+    /// comments and raw `url()`s
+    Text,
+    Open,
+    Close,
+    Comma,
+    SilentComment,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
+
+/// Characters which always form a token on their own
+fn is_punctuation(c: char) -> bool {
+    matches!(
+        c,
+        '(' | ')' | '[' | ']' | ',' | '{' | '}' | ';' | ':' | '>' | '+' | '~' | '\\' | '"' | '\''
+    )
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+fn char_len(s: &str, i: usize) -> usize {
+    s[i..].chars().next().map_or(1, char::len_utf8)
+}
+
+/// Returns the end of the interpolation starting at `i`
+fn scan_interpolation(s: &str, i: usize) -> usize {
+    let mut depth = 0;
+    let mut i = i + 1;
+
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            b'"' | b'\'' => {
+                i = scan_string(s, i);
+                continue;
+            }
+            b'\\' => i += 1,
+            _ => {}
+        }
+        i += char_len(s, i);
+    }
+
+    s.len()
+}
+
+/// Returns the end of the quoted string starting at `i`
+fn scan_string(s: &str, i: usize) -> usize {
+    let quote = s.as_bytes()[i];
+    let mut i = i + 1;
+
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'\\' => i += 1,
+            b'#' if s[i..].starts_with("#{") => {
+                i = scan_interpolation(s, i);
+                continue;
+            }
+            b'\n' => return i,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+        i += char_len(s, i);
+    }
+
+    s.len()
+}
+
+/// Returns the end of the unquoted `url()` starting at `i`, if there is one
+fn scan_raw_url(s: &str, i: usize) -> Option<usize> {
+    if !s.get(i..i + 4)?.eq_ignore_ascii_case("url(")
+        || s[..i].chars().next_back().is_some_and(is_name_char)
+    {
+        return None;
+    }
+
+    let mut i = i + 4;
+
+    if s[i..].trim_start().starts_with(['"', '\''].as_slice()) {
+        return None;
+    }
+
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b')' => return Some(i + 1),
+            b'\\' => i += 1,
+            b'#' if s[i..].starts_with("#{") => {
+                i = scan_interpolation(s, i);
+                continue;
+            }
+            b'"' | b'\'' | b'(' => return None,
+            _ => {}
+        }
+        i += char_len(s, i);
+    }
+
+    None
+}
+
+fn next_token(s: &str, start: usize) -> Token {
+    let rest = &s[start..];
+    let c = rest.chars().next().unwrap();
+
+    let (kind, end) = if c.is_whitespace() {
+        let len = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        (TokenKind::Whitespace, start + len)
+    } else if rest.starts_with("//") {
+        (
+            TokenKind::SilentComment,
+            start + rest.find(['\n', '\r'].as_slice()).unwrap_or(rest.len()),
+        )
+    } else if let Some(comment) = rest.strip_prefix("/*") {
+        (
+            TokenKind::Text,
+            comment
+                .find("*/")
+                .map_or(s.len(), |end| start + 2 + end + 2),
+        )
+    } else if rest.starts_with("#{") {
+        (TokenKind::Text, scan_interpolation(s, start))
+    } else if let Some(end) = scan_raw_url(s, start) {
+        (TokenKind::Text, end)
+    } else {
+        match c {
+            '"' | '\'' => (TokenKind::Text, scan_string(s, start)),
+            '\\' => (
+                TokenKind::Text,
+                (start + 1 + rest[1..].chars().next().map_or(0, char::len_utf8)).min(s.len()),
+            ),
+            '(' | '[' => (TokenKind::Open, start + 1),
+            ')' | ']' => (TokenKind::Close, start + 1),
+            ',' => (TokenKind::Comma, start + 1),
+            c if is_punctuation(c) => (TokenKind::Text, start + 1),
+            _ => {
+                let run_start = start + c.len_utf8();
+                let mut end = run_start;
+
+                for (i, c) in s[run_start..].char_indices() {
+                    let at = run_start + i;
+                    if c.is_whitespace()
+                        || is_punctuation(c)
+                        || s[at..].starts_with("//")
+                        || s[at..].starts_with("/*")
+                        || s[at..].starts_with("#{")
+                    {
+                        end = at;
+                        break;
+                    }
+                    end = at + c.len_utf8();
+                }
+
+                (TokenKind::Text, end)
+            }
+        }
+    };
+
+    Token { kind, start, end }
+}
+
+fn tokenize(s: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < s.len() {
+        let token = next_token(s, i);
+        i = token.end;
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+/// An element of a comma-separated list
+#[derive(Default)]
+struct Item {
+    docs: Vec<Doc>,
+    /// A `//` comment which follows this element
+    comment: Option<String>,
+    space_before_next: bool,
+}
+
+impl Item {
+    fn push(&mut self, doc: Doc) {
+        if let Some(comment) = self.comment.take() {
+            self.docs.push(Doc::Comment(comment));
+            self.docs.push(Doc::HardLine);
+        } else if self.space_before_next && !self.docs.is_empty() {
+            self.docs.push(Doc::Text(" ".to_owned()));
+        }
+
+        self.space_before_next = false;
+        self.docs.push(doc);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.docs.is_empty() && self.comment.is_none()
+    }
+}
+
+/// Builds docs for an expression or at-rule parameters, normalizing the
+/// whitespace between tokens
+struct ValueBuilder<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+    cursor: usize,
+    indented: bool,
+}
+
+impl<'a> ValueBuilder<'a> {
+    fn build(text: &'a str, indented: bool) -> Vec<Doc> {
+        let mut tokens = tokenize(text);
+
+        // a trailing comment is written after the statement instead
+        while matches!(
+            tokens.last(),
+            Some(Token {
+                kind: TokenKind::Whitespace | TokenKind::SilentComment,
+                ..
+            })
+        ) {
+            tokens.pop();
+        }
+
+        let mut builder = Self {
+            text,
+            tokens,
+            cursor: 0,
+            indented,
+        };
+
+        let (items, leading_comment) = builder.list(false);
+        Self::join(items, leading_comment, false)
+    }
+
+    fn list(&mut self, nested: bool) -> (Vec<Item>, Option<String>) {
+        let mut items = vec![Item::default()];
+        let mut leading_comment: Option<String> = None;
+
+        while let Some(token) = self.tokens.get(self.cursor).copied() {
+            self.cursor += 1;
+            let text = &self.text[token.start..token.end];
+            let item = items.last_mut().unwrap();
+
+            match token.kind {
+                TokenKind::Whitespace => item.space_before_next = true,
+                TokenKind::Close if nested => {
+                    self.cursor -= 1;
+                    break;
+                }
+                TokenKind::Comma => items.push(Item::default()),
+                TokenKind::SilentComment if self.indented => {
+                    item.push(Doc::Text(loud_comment(text)));
+                }
+                TokenKind::SilentComment => {
+                    let comment = text.trim_end().to_owned();
+
+                    if item.is_empty() {
+                        let len = items.len();
+                        match items.get_mut(len.wrapping_sub(2)) {
+                            Some(previous) if previous.comment.is_none() => {
+                                previous.comment = Some(comment);
+                            }
+                            Some(..) | None if leading_comment.is_none() => {
+                                leading_comment = Some(comment);
+                            }
+                            _ => items.last_mut().unwrap().comment = Some(comment),
+                        }
+                    } else {
+                        if let Some(previous) = item.comment.take() {
+                            item.docs.push(Doc::Comment(previous));
+                            item.docs.push(Doc::HardLine);
+                        }
+                        item.comment = Some(comment);
+                    }
+                }
+                TokenKind::Open => {
+                    let (inner, inner_comment) = self.list(true);
+                    let close = match self.tokens.get(self.cursor) {
+                        Some(close) => {
+                            self.cursor += 1;
+                            &self.text[close.start..close.end]
+                        }
+                        None => "",
+                    };
+
+                    let mut docs = vec![Doc::Text(text.to_owned())];
+                    docs.extend(Self::join(inner, inner_comment, true));
+                    docs.push(Doc::Text(close.to_owned()));
+
+                    let item = items.last_mut().unwrap();
+                    for doc in docs {
+                        item.push(doc);
+                    }
+                }
+                TokenKind::Text | TokenKind::Close => item.push(Doc::Text(text.to_owned())),
+            }
+        }
+
+        (items, leading_comment)
+    }
+
+    /// Joins the elements of a list, which may be broken onto separate lines if
+    /// there is more than one of them
+    fn join(mut items: Vec<Item>, leading_comment: Option<String>, nested: bool) -> Vec<Doc> {
+        if items.len() == 1 && leading_comment.is_none() && items[0].comment.is_none() {
+            return items.pop().unwrap().docs;
+        }
+
+        let trailing_comma = items.len() > 1 && items.last().is_some_and(Item::is_empty);
+        if trailing_comma {
+            items.pop();
+        }
+
+        let mut inner = Vec::new();
+
+        if let Some(comment) = leading_comment {
+            inner.push(Doc::Comment(comment));
+            inner.push(Doc::SoftLine);
+        } else if nested {
+            inner.push(Doc::SoftLine);
+        }
+
+        let last = items.len() - 1;
+        for (i, item) in items.into_iter().enumerate() {
+            inner.extend(item.docs);
+
+            if i < last || trailing_comma {
+                inner.push(Doc::Text(",".to_owned()));
+            }
+
+            if let Some(comment) = item.comment {
+                inner.push(Doc::Comment(comment));
+            }
+
+            if i < last {
+                inner.push(Doc::Line);
+            }
+        }
+
+        let mut group = vec![Doc::Indent(inner)];
+        if nested {
+            group.push(Doc::SoftLine);
+        }
+
+        vec![Doc::Group(group)]
+    }
+}
+
+/// Writes a `//` comment as a `/* */` comment, for places in the indented
+/// syntax that must fit on one line
+fn loud_comment(comment: &str) -> String {
+    format!("/*{} */", comment[2..].trim_end())
+}
+
+/// Normalizes the whitespace in a selector list, returning each complex
+/// selector separately along with whether the list was written over multiple
+/// lines
+fn selectors(text: &str, indented: bool) -> (Vec<String>, bool) {
+    let mut selectors = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0_usize;
+    let mut space = false;
+    let mut is_multiline = false;
+
+    for token in tokenize(text) {
+        let text = &text[token.start..token.end];
+
+        match token.kind {
+            TokenKind::Whitespace => {
+                if depth == 0 && current.is_empty() && !selectors.is_empty() && text.contains('\n')
+                {
+                    is_multiline = true;
+                }
+                space = true;
+            }
+            TokenKind::SilentComment if indented => {
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(&loud_comment(text));
+                space = false;
+            }
+            TokenKind::Comma if depth == 0 => {
+                selectors.push(std::mem::take(&mut current));
+                space = false;
+            }
+            TokenKind::Comma => {
+                current.push_str(", ");
+                space = false;
+            }
+            TokenKind::Close => {
+                depth = depth.saturating_sub(1);
+                current.push_str(text);
+                space = false;
+            }
+            TokenKind::Text if depth == 0 && matches!(text, ">" | "+" | "~") => {
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(text);
+                current.push(' ');
+                space = false;
+            }
+            TokenKind::Text | TokenKind::Open | TokenKind::SilentComment => {
+                if space && !current.is_empty() && !current.ends_with([' ', '(', '['].as_slice()) {
+                    current.push(' ');
+                }
+                current.push_str(text.trim_end());
+                if token.kind == TokenKind::SilentComment {
+                    current.push('\n');
+                }
+                if token.kind == TokenKind::Open {
+                    depth += 1;
+                }
+                space = false;
+            }
+        }
+    }
+
+    selectors.push(current);
+
+    let selectors = selectors
+        .into_iter()
+        .map(|selector| selector.trim().to_owned())
+        .collect();
+
+    (selectors, is_multiline)
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    base: Pos,
+    /// Whether the stylesheet is written in the indented syntax
+    input_indented: bool,
+    /// Whether the stylesheet is being written in the indented syntax
+    indented: bool,
+    indent: String,
+    indent_width: usize,
+    line_width: usize,
+    out: String,
+}
+
+impl<'a> Formatter<'a> {
+    fn offset(&self, pos: Pos) -> usize {
+        (pos - self.base) as usize
+    }
+
+    fn text(&self, span: Span) -> &'a str {
+        &self.source[self.offset(span.low())..self.offset(span.high())]
+    }
+
+    /// The end of the header of the statement starting at `start`, which is
+    /// its `{` or `;` for SCSS, and the end of the line for the indented syntax
+    fn header_end(&self, start: usize) -> usize {
+        let mut depth = 0_usize;
+        let mut i = start;
+
+        while i < self.source.len() {
+            let token = next_token(self.source, i);
+            let text = &self.source[token.start..token.end];
+
+            match token.kind {
+                TokenKind::Whitespace
+                    if self.input_indented && depth == 0 && text.contains('\n') =>
+                {
+                    return i
+                }
+                TokenKind::Open => depth += 1,
+                TokenKind::Close => depth = depth.saturating_sub(1),
+                TokenKind::Text
+                    if !self.input_indented && depth == 0 && matches!(text, "{" | ";" | "}") =>
+                {
+                    return i
+                }
+                _ => {}
+            }
+
+            i = token.end;
+        }
+
+        self.source.len()
+    }
+
+    fn header(&self, start: Pos) -> &'a str {
+        let start = self.offset(start);
+        self.source[start..self.header_end(start)].trim()
+    }
+
+    /// The `//` comment at the end of the header of the statement starting at
+    /// `start`, if there is one
+    fn trailing_comment(&self, start: Pos) -> Option<&'a str> {
+        let header = self.header(start);
+
+        tokenize(header)
+            .into_iter()
+            .rev()
+            .find(|token| token.kind != TokenKind::Whitespace)
+            .filter(|token| token.kind == TokenKind::SilentComment)
+            .map(|token| header[token.start..token.end].trim_end())
+    }
+
+    fn line_start(&self, offset: usize) -> usize {
+        self.source[..offset].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn is_blank_line_before(&self, pos: Pos) -> bool {
+        let line_start = self.line_start(self.offset(pos));
+
+        if line_start == 0 {
+            return false;
+        }
+
+        let previous_line_start = self.line_start(line_start - 1);
+        self.source[previous_line_start..line_start]
+            .trim()
+            .is_empty()
+    }
+
+    /// Whether some other statement precedes `pos` on its line
+    ///
+    /// The keyword of a statement which starts after it, as returned by
+    /// [`stmt_start`], is ignored.
+    fn is_trailing(&self, pos: Pos) -> bool {
+        let offset = self.offset(pos);
+        let before = self.source[self.line_start(offset)..offset].trim_end();
+        let before = before
+            .trim_end_matches(is_name_char)
+            .strip_suffix('@')
+            .or_else(|| before.strip_suffix(['=', '+'].as_slice()))
+            .unwrap_or(before);
+
+        !before.trim().is_empty()
+    }
+
+    fn column(&self) -> usize {
+        let line = &self.out[self.out.rfind('\n').map_or(0, |i| i + 1)..];
+        line.chars()
+            .map(|c| if c == '\t' { self.indent_width } else { 1 })
+            .sum()
+    }
+
+    fn write_indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str(&self.indent);
+        }
+    }
+
+    fn newline(&mut self, depth: usize) {
+        self.out.push('\n');
+        self.write_indent(depth);
+    }
+
+    fn print(&mut self, docs: &[Doc], depth: usize, broken: bool, rest: usize) {
+        for (i, doc) in docs.iter().enumerate() {
+            match doc {
+                Doc::Text(text) => self.out.push_str(text),
+                Doc::Comment(comment) => {
+                    if !self.out.ends_with([' ', '\n', '\t'].as_slice()) {
+                        self.out.push(' ');
+                    }
+                    self.out.push_str(comment);
+                }
+                Doc::Line if broken => self.newline(depth),
+                Doc::Line => self.out.push(' '),
+                Doc::SoftLine if broken => self.newline(depth),
+                Doc::SoftLine => {}
+                Doc::HardLine => self.newline(depth),
+                Doc::Indent(inner) => {
+                    let rest = Doc::rest_width(&docs[i + 1..], broken, rest);
+                    self.print(inner, depth + 1, broken, rest);
+                }
+                Doc::Group(inner) => {
+                    let rest = Doc::rest_width(&docs[i + 1..], broken, rest);
+                    let fits = Doc::flat_width(inner).is_some_and(|width| {
+                        self.column().saturating_add(width).saturating_add(rest) <= self.line_width
+                    });
+                    self.print(inner, depth, !fits, rest);
+                }
+            }
+        }
+    }
+
+    fn write_comment(&mut self, comment: Option<&str>) {
+        if let Some(comment) = comment {
+            self.out.push(' ');
+            self.out.push_str(comment);
+        }
+    }
+
+    /// Writes a statement without children, at the start of a line
+    fn write_line(&mut self, header: &[Doc], comment: Option<&str>, depth: usize) {
+        self.write_indent(depth);
+        if self.indented {
+            self.print(header, depth, false, 0);
+        } else {
+            self.print(header, depth, false, 1);
+            self.out.push(';');
+        }
+        self.write_comment(comment);
+        self.out.push('\n');
+    }
+
+    /// Writes a statement with children, with the header starting at the
+    /// current position
+    fn write_block(
+        &mut self,
+        header: &[Doc],
+        comment: Option<&str>,
+        body: &[AstStmt],
+        depth: usize,
+    ) {
+        if self.indented {
+            self.print(header, depth, false, 0);
+            self.write_comment(comment);
+            self.out.push('\n');
+            self.write_body(body, depth + 1);
+            return;
+        }
+
+        self.print(header, depth, false, 2);
+
+        if body.is_empty() && comment.is_none() {
+            self.out.push_str(" {}\n");
+            return;
+        }
+
+        self.out.push_str(" {");
+        self.write_comment(comment);
+        self.out.push('\n');
+        self.write_body(body, depth + 1);
+        self.write_indent(depth);
+        self.out.push_str("}\n");
+    }
+
+    fn write_body(&mut self, body: &[AstStmt], depth: usize) {
+        for (i, stmt) in body.iter().enumerate() {
+            let start = stmt_start(stmt);
+
+            if !self.out.is_empty() && self.is_trailing(start) {
+                if let Some(comment) = self.comment(stmt) {
+                    // in the indented syntax, only single-line `//` comments
+                    // may follow a statement
+                    if !self.indented
+                        || (matches!(stmt, AstStmt::SilentComment(..))
+                            && !comment.trim_end().contains('\n'))
+                    {
+                        self.out.pop();
+                        self.out.push(' ');
+                        self.write_comment_text(&comment, start, depth, true);
+                        continue;
+                    }
+                }
+            }
+
+            if i > 0 && !self.is_trailing(start) && self.is_blank_line_before(start) {
+                self.out.push('\n');
+            }
+
+            self.write_stmt(stmt, depth);
+        }
+    }
+
+    /// The text of a comment, in the syntax being written
+    fn comment(&self, stmt: &AstStmt) -> Option<Cow<'a, str>> {
+        match stmt {
+            // the indented syntax ends `//` comments with their indentation
+            // rather than prefixing each line with `//`
+            AstStmt::SilentComment(AstSilentComment { text, .. })
+                if self.input_indented && !self.indented =>
+            {
+                Some(Cow::Owned(text.clone()))
+            }
+            AstStmt::LoudComment(AstLoudComment { span, .. })
+                if self.input_indented && !self.indented =>
+            {
+                Some(Cow::Owned(self.indented_loud_comment(*span)))
+            }
+            // a closing `*/` on its own line would end up in the comment
+            AstStmt::LoudComment(AstLoudComment { span, .. })
+                if self.indented && !self.input_indented =>
+            {
+                let text = self.text(*span).trim_end();
+                Some(match text.rsplit_once('\n') {
+                    Some((rest, "*/")) => Cow::Borrowed(rest.trim_end()),
+                    Some((rest, last)) if last.trim() == "*/" => Cow::Borrowed(rest.trim_end()),
+                    _ => Cow::Borrowed(text),
+                })
+            }
+            AstStmt::SilentComment(AstSilentComment { span, .. })
+            | AstStmt::LoudComment(AstLoudComment { span, .. }) => {
+                Some(Cow::Borrowed(self.text(*span)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Writes a `/* */` comment from the indented syntax the way it's emitted
+    /// in CSS, with lines after the first prefixed with ` * ` and their
+    /// indentation past the `/*` kept
+    fn indented_loud_comment(&self, span: Span) -> String {
+        let offset = self.offset(span.low());
+        let column = self.source[self.line_start(offset)..offset].chars().count();
+
+        let mut lines = self.text(span).trim_end().lines();
+        let mut comment = lines.next().unwrap_or_default().trim_end().to_owned();
+        let mut is_first_line = comment == "/*";
+        let mut blank_lines = 0;
+
+        for line in lines {
+            let content = line.trim_start();
+
+            if content.is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+
+            for _ in 0..blank_lines {
+                comment.push_str("\n *");
+            }
+            blank_lines = 0;
+
+            let indentation = line.len() - content.len() - column;
+
+            if is_first_line {
+                comment.push(' ');
+                is_first_line = false;
+            } else {
+                comment.push_str("\n * ");
+            }
+
+            for _ in 3..indentation {
+                comment.push(' ');
+            }
+
+            comment.push_str(content.trim_end());
+        }
+
+        if !comment.ends_with("*/") {
+            comment.push_str(" */");
+        }
+
+        comment
+    }
+
+    /// Writes a comment, reindenting any lines after the first
+    fn write_comment_text(&mut self, text: &str, start: Pos, depth: usize, is_trailing: bool) {
+        let offset = self.offset(start);
+        let column = self.source[self.line_start(offset)..offset].chars().count();
+
+        let mut lines = text.trim_end().lines();
+
+        if !is_trailing {
+            self.write_indent(depth);
+        }
+        self.out
+            .push_str(lines.next().unwrap_or_default().trim_end());
+        self.out.push('\n');
+
+        for line in lines {
+            let indentation = line.len() - line.trim_start().len();
+            let line = line[indentation.min(column)..].trim_end();
+
+            if line.is_empty() {
+                self.out.push('\n');
+                continue;
+            }
+
+            // after a trailing comment, the rest is indented as though the
+            // comment started its own line
+            if is_trailing {
+                self.write_indent(depth);
+                self.out.push_str(line.trim_start());
+            } else {
+                self.write_indent(depth);
+                // in the indented syntax, a `/* */` comment ends at the first
+                // line that isn't indented past it
+                if self.indented && text.starts_with("/*") && !line.starts_with(char::is_whitespace)
+                {
+                    self.out.push_str(&self.indent);
+                }
+                self.out.push_str(line);
+            }
+            self.out.push('\n');
+        }
+    }
+
+    fn value(&self, span: Span) -> Vec<Doc> {
+        ValueBuilder::build(self.text(span), self.indented)
+    }
+
+    /// The header of an at-rule, with the whitespace after its name and within
+    /// its parameters normalized
+    fn at_rule(&self, start: Pos) -> Vec<Doc> {
+        let header = self.header(start);
+
+        let name_end = header[1..]
+            .find(|c: char| !is_name_char(c))
+            .map_or(header.len(), |i| i + 1);
+        let (name, params) = header.split_at(name_end);
+
+        let mut docs = vec![Doc::Text(name.to_owned())];
+
+        if params.starts_with(char::is_whitespace) && !params.trim().is_empty() {
+            docs.push(Doc::Text(" ".to_owned()));
+        }
+
+        docs.extend(ValueBuilder::build(params.trim(), self.indented));
+        docs
+    }
+
+    /// A header that starts with `keyword`, followed by the source text from
+    /// `start` to the end of the header
+    fn keyword(&self, keyword: &str, start: Pos) -> Vec<Doc> {
+        let mut docs = vec![Doc::Text(keyword.to_owned())];
+        docs.extend(ValueBuilder::build(self.header(start), self.indented));
+        docs
+    }
+
+    fn selector(&self, span: Span) -> Vec<Doc> {
+        let mut docs = Vec::new();
+        let (selectors, is_multiline) = selectors(self.text(span), self.indented);
+
+        for (i, selector) in selectors.into_iter().enumerate() {
+            if i > 0 {
+                docs.push(Doc::Text(",".to_owned()));
+                docs.push(if is_multiline {
+                    Doc::HardLine
+                } else {
+                    Doc::Line
+                });
+            }
+            docs.push(Doc::Text(selector));
+        }
+
+        vec![Doc::Group(docs)]
+    }
+
+    /// Finds the offset of the `:` separating a declaration's name from its
+    /// value
+    fn find_colon(&self, start: Pos) -> usize {
+        let start = self.offset(start);
+        // skip the first character, to allow the `:prop: val` hack
+        let mut i = start + char_len(self.source, start);
+
+        while i < self.source.len() {
+            let token = next_token(self.source, i);
+            if &self.source[token.start..token.end] == ":" {
+                return i;
+            }
+            i = token.end;
+        }
+
+        self.source.len()
+    }
+
+    /// Writes `@else` or `@else if`, following the previous clause
+    fn write_else(&mut self, depth: usize) {
+        if self.indented {
+            self.write_indent(depth);
+        } else {
+            self.out.pop();
+            self.out.push(' ');
+        }
+    }
+
+    fn write_stmt(&mut self, stmt: &AstStmt, depth: usize) {
+        let start = stmt_start(stmt);
+
+        match stmt {
+            AstStmt::SilentComment(..) | AstStmt::LoudComment(..) => {
+                let comment = self.comment(stmt).unwrap();
+                self.write_comment_text(&comment, start, depth, false);
+            }
+            AstStmt::RuleSet(rule) => {
+                self.write_indent(depth);
+                let header = self.selector(rule.selector_span);
+                self.write_block(&header, self.trailing_comment(start), &rule.body, depth);
+            }
+            AstStmt::Style(style) => {
+                let colon = self.find_colon(start);
+                let name = self.source[self.offset(start)..colon].trim();
+
+                let mut header = vec![Doc::Text(format!("{}:", name))];
+
+                match &style.value {
+                    Some(value) if style.is_custom_property() => {
+                        let value = self.source[colon + 1..self.offset(value.span.high())].trim();
+                        if !value.is_empty() {
+                            header.push(Doc::Text(format!(" {}", value)));
+                        }
+                    }
+                    Some(value) => {
+                        header.push(Doc::Text(" ".to_owned()));
+                        header.extend(self.value(value.span));
+                    }
+                    None => {}
+                }
+
+                let comment = self.trailing_comment(start);
+
+                if style.body.is_empty() {
+                    self.write_line(&header, comment, depth);
+                } else {
+                    self.write_indent(depth);
+                    self.write_block(&header, comment, &style.body, depth);
+                }
+            }
+            AstStmt::VariableDecl(..) => {
+                let colon = self.find_colon(start);
+                let name: String = self.source[self.offset(start)..colon]
+                    .split_whitespace()
+                    .collect();
+
+                let offset = self.offset(start);
+                let value = self.source[colon + 1..self.header_end(offset)].trim();
+
+                let mut header = vec![Doc::Text(format!("{}: ", name))];
+                header.extend(ValueBuilder::build(value, self.indented));
+
+                self.write_line(&header, self.trailing_comment(start), depth);
+            }
+            AstStmt::If(rule) => {
+                for (i, clause) in rule.if_clauses.iter().enumerate() {
+                    if i == 0 {
+                        self.write_indent(depth);
+                    } else {
+                        self.write_else(depth);
+                    }
+
+                    let mut header = vec![Doc::Text(
+                        if i == 0 { "@if " } else { "@else if " }.to_owned(),
+                    )];
+                    header.extend(self.value(clause.condition.span));
+
+                    let comment = self.trailing_comment(clause.condition.span.low());
+                    self.write_block(&header, comment, &clause.body, depth);
+                }
+
+                if let Some(body) = &rule.else_clause {
+                    self.write_else(depth);
+                    self.write_block(&[Doc::Text("@else".to_owned())], None, body, depth);
+                }
+            }
+            AstStmt::For(rule) => {
+                self.write_indent(depth);
+                let header = self.keyword("@for ", start);
+                self.write_block(&header, self.trailing_comment(start), &rule.body, depth);
+            }
+            AstStmt::While(rule) => {
+                let mut header = vec![Doc::Text("@while ".to_owned())];
+                header.extend(self.value(rule.condition.span));
+
+                self.write_indent(depth);
+                self.write_block(&header, self.trailing_comment(start), &rule.body, depth);
+            }
+            AstStmt::Each(AstEach { body, .. })
+            | AstStmt::Media(AstMedia { body, .. })
+            | AstStmt::Supports(AstSupportsRule { children: body, .. }) => {
+                self.write_indent(depth);
+                let header = self.at_rule(start);
+                self.write_block(&header, self.trailing_comment(start), body, depth);
+            }
+            AstStmt::AtRootRule(rule) => {
+                self.write_indent(depth);
+
+                match rule.children.as_slice() {
+                    // `@at-root` followed directly by a selector
+                    [AstStmt::RuleSet(child)]
+                        if rule.query.is_none()
+                            && child.selector_span.low() < self.header_end_pos(start) =>
+                    {
+                        let mut header = vec![Doc::Text("@at-root ".to_owned())];
+                        header.extend(self.selector(child.selector_span));
+                        let comment = self.trailing_comment(start);
+                        self.write_block(&header, comment, &child.body, depth);
+                    }
+                    children => {
+                        let header = self.at_rule(start);
+                        self.write_block(&header, self.trailing_comment(start), children, depth);
+                    }
+                }
+            }
+            AstStmt::UnknownAtRule(AstUnknownAtRule { children, .. }) => {
+                let header = self.at_rule(start);
+                let comment = self.trailing_comment(start);
+
+                match children {
+                    Some(children) => {
+                        self.write_indent(depth);
+                        self.write_block(&header, comment, children, depth);
+                    }
+                    None => self.write_line(&header, comment, depth),
+                }
+            }
+            AstStmt::Extend(..)
+            | AstStmt::ImportRule(..)
+            | AstStmt::Use(..)
+            | AstStmt::Forward(..)
+            | AstStmt::ContentRule(..) => {
+                let header = self.at_rule(start);
+                self.write_line(&header, self.trailing_comment(start), depth);
+            }
+            AstStmt::Return(..)
+            | AstStmt::Debug(..)
+            | AstStmt::Warn(..)
+            | AstStmt::ErrorRule(..) => {
+                let keyword = match stmt {
+                    AstStmt::Return(..) => "@return ",
+                    AstStmt::Debug(..) => "@debug ",
+                    AstStmt::Warn(..) => "@warn ",
+                    _ => "@error ",
+                };
+                let header = self.keyword(keyword, start);
+                self.write_line(&header, self.trailing_comment(start), depth);
+            }
+            AstStmt::Mixin(AstMixin { body, .. })
+            | AstStmt::FunctionDecl(AstFunctionDecl { children: body, .. }) => {
+                let keyword = if matches!(stmt, AstStmt::Mixin(..)) {
+                    "@mixin "
+                } else {
+                    "@function "
+                };
+
+                self.write_indent(depth);
+                let header = self.keyword(keyword, start);
+                self.write_block(&header, self.trailing_comment(start), body, depth);
+            }
+            AstStmt::Include(include) => {
+                let header = self.keyword("@include ", start);
+                let comment = self.trailing_comment(start);
+
+                match &include.content {
+                    Some(content) => {
+                        self.write_indent(depth);
+                        self.write_block(&header, comment, &content.body, depth);
+                    }
+                    None => self.write_line(&header, comment, depth),
+                }
+            }
+        }
+    }
+
+    fn header_end_pos(&self, start: Pos) -> Pos {
+        self.base + self.header_end(self.offset(start)) as u64
+    }
+}
+
+/// The position where a statement starts, or for rules written with a
+/// keyword that isn't included in their span, where the text following the
+/// keyword starts
+fn stmt_start(stmt: &AstStmt) -> Pos {
+    match stmt {
+        AstStmt::If(AstIf { span, .. })
+        | AstStmt::Each(AstEach { span, .. })
+        | AstStmt::Media(AstMedia { span, .. })
+        | AstStmt::Return(AstReturn { span, .. })
+        | AstStmt::RuleSet(AstRuleSet { span, .. })
+        | AstStmt::Style(AstStyle { span, .. })
+        | AstStmt::VariableDecl(AstVariableDecl { span, .. })
+        | AstStmt::LoudComment(AstLoudComment { span, .. })
+        | AstStmt::SilentComment(AstSilentComment { span, .. })
+        | AstStmt::ContentRule(AstContentRule { span, .. })
+        | AstStmt::Warn(AstWarn { span, .. })
+        | AstStmt::UnknownAtRule(AstUnknownAtRule { span, .. })
+        | AstStmt::ErrorRule(AstErrorRule { span, .. })
+        | AstStmt::Extend(AstExtendRule { span, .. })
+        | AstStmt::AtRootRule(AstAtRootRule { span, .. })
+        | AstStmt::Debug(AstDebugRule { span, .. })
+        | AstStmt::ImportRule(AstImportRule { span, .. })
+        | AstStmt::Use(AstUseRule { span, .. })
+        | AstStmt::Forward(AstForwardRule { span, .. })
+        | AstStmt::Supports(AstSupportsRule { span, .. }) => span.low(),
+        AstStmt::For(rule) => rule.variable.span.low(),
+        AstStmt::While(rule) => rule.condition.span.low(),
+        AstStmt::FunctionDecl(AstFunctionDecl { name, .. })
+        | AstStmt::Mixin(AstMixin { name, .. }) => name.span.low(),
+        AstStmt::Include(include) => include
+            .namespace
+            .as_ref()
+            .map_or(include.name.span.low(), |namespace| namespace.span.low()),
+    }
+}
@@ -11,7 +11,11 @@ use std::{
 /// trait (`is_absolute`, `parent`, `join`, *&c.*); but that would infect too many other APIs to be
 /// desirable, so we live with it as it is—which is also acceptable, because the motivating example
 /// use case is mostly using this as an optimisation over the real platform underneath.
-pub trait Fs: std::fmt::Debug {
+///
+/// File systems must be [`Sync`], so that [`crate::Options`] can be shared between threads
+/// compiling in parallel. Use a [`std::sync::Mutex`] rather than a [`std::cell::RefCell`] for
+/// any state that is updated while reading.
+pub trait Fs: std::fmt::Debug + Sync {
     /// Returns `true` if the path exists on disk and is pointing at a directory.
     fn is_dir(&self, path: &Path) -> bool;
     /// Returns `true` if the path exists on disk and is pointing at a regular file.
@@ -0,0 +1,108 @@
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
+
+use crate::InputSyntax;
+
+/// Information about the rule loading a stylesheet, passed to custom importers
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug)]
+pub struct ImportContext<'a> {
+    /// The canonical URL of the stylesheet containing the rule, or a `file:`
+    /// URL if it was loaded from the file system
+    pub containing_url: Option<&'a str>,
+
+    /// Whether the stylesheet is being loaded by `@import` rather than `@use`
+    /// or `@forward`
+    pub from_import: bool,
+}
+
+/// A stylesheet loaded by an [`Importer`]
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct ImporterResult {
+    pub contents: String,
+    pub syntax: InputSyntax,
+}
+
+impl ImporterResult {
+    #[must_use]
+    pub const fn new(contents: String, syntax: InputSyntax) -> Self {
+        Self { contents, syntax }
+    }
+}
+
+/// A trait to allow loading stylesheets from sources other than the file
+/// system, such as a bundler's virtual modules
+///
+/// Importers are consulted in the order they were added to [`crate::Options`],
+/// for URLs that aren't found relative to the current stylesheet or in a load
+/// path.
+///
+/// Like [`crate::Fs`], importers must be [`Sync`], so that [`crate::Options`]
+/// can be shared between threads.
+pub trait Importer: Debug + Sync {
+    /// Returns the canonical URL of the stylesheet `url` refers to, or `None`
+    /// if this importer doesn't recognize it
+    ///
+    /// Stylesheets are cached by their canonical URL, which is also used as
+    /// their file name in errors and as the containing URL of the rules they
+    /// contain.
+    fn canonicalize(&self, url: &str, context: &ImportContext) -> Result<Option<String>, String>;
+
+    /// Loads the stylesheet at a URL returned by [`Importer::canonicalize`]
+    fn load(&self, canonical_url: &str) -> Result<ImporterResult, String>;
+}
+
+/// A trait to allow redirecting URLs to the file system
+///
+/// Unlike an [`Importer`], this only returns a path, which is resolved like a
+/// load path, so partials, extensions and index files are found as usual.
+pub trait FileImporter: Debug + Sync {
+    /// Returns the path `url` refers to, or `None` if this importer doesn't
+    /// recognize it
+    fn find_file(&self, url: &str, context: &ImportContext) -> Result<Option<PathBuf>, String>;
+}
+
+/// An importer added to [`crate::Options`], in the order it is consulted
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum CustomImporter<'a> {
+    Importer(&'a dyn Importer),
+    File(&'a dyn FileImporter),
+}
+
+/// The characters which are escaped in the paths of `file:` URLs
+const ESCAPED: [(char, &str); 4] = [('%', "%25"), (' ', "%20"), ('#', "%23"), ('?', "%3F")];
+
+/// The `file:` URL of `path`, resolved against the current directory
+pub(crate) fn file_url(path: &Path) -> String {
+    let path = std::env::current_dir().map_or_else(|_| path.to_path_buf(), |dir| dir.join(path));
+    let mut path = path.to_string_lossy().replace('\\', "/");
+
+    for (c, escaped) in ESCAPED {
+        path = path.replace(c, escaped);
+    }
+
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path)
+    }
+}
+
+/// The path of a `file:` URL, or `None` if `url` isn't one
+pub(crate) fn file_url_to_path(url: &str) -> Option<PathBuf> {
+    let mut path = url.strip_prefix("file://")?.to_owned();
+
+    // drive letters on windows follow the leading slash
+    if path.as_bytes().get(2) == Some(&b':') {
+        path.remove(0);
+    }
+
+    for (c, escaped) in ESCAPED.iter().rev() {
+        path = path.replace(escaped, &c.to_string());
+    }
+
+    Some(PathBuf::from(path))
+}
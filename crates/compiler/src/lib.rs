@@ -80,106 +80,265 @@ grass input.scss
     unknown_lints,
 )]
 
-use std::path::Path;
+use std::{io::Write, path::Path, sync::Arc};
 
 use parse::{CssParser, SassParser, StylesheetParser};
 use serializer::Serializer;
 #[cfg(feature = "wasm-exports")]
 use wasm_bindgen::prelude::*;
 
-use codemap::CodeMap;
+use codemap::{CodeMap, File, Span};
 
+pub use codemap::SpanLoc;
+
+pub use crate::compiler::Compiler;
+pub use crate::deprecation::Deprecation;
 pub use crate::error::{
-    PublicSassErrorKind as ErrorKind, SassError as Error, SassResult as Result,
+    PublicSassErrorKind as ErrorKind, SassError as Error, SassResult as Result, StackFrame,
 };
 pub use crate::fs::{Fs, NullFs, StdFs};
-pub use crate::options::{InputSyntax, Options, OutputStyle};
+pub use crate::importer::{FileImporter, ImportContext, Importer, ImporterResult};
+pub use crate::logger::{Logger, NullLogger, StdLogger};
+pub use crate::options::{CancellationToken, InputSyntax, Options, OutputStyle};
+pub use crate::source_map::SourceMap;
+use crate::{
+    ast::{CssStmt, StyleSheet},
+    compiler::{ModuleCache, StyleSheetCache},
+    evaluate::Visitor,
+    lexer::Lexer,
+    parse::ScssParser,
+    utils::{look_up_span, may_produce_non_ascii},
+    value::FunctionHost,
+};
 pub(crate) use crate::{context_flags::ContextFlags, lexer::Token};
-use crate::{evaluate::Visitor, lexer::Lexer, parse::ScssParser};
 
+pub mod analysis;
 mod ast;
 mod builtin;
 mod color;
 mod common;
+mod compiler;
 mod context_flags;
+mod deprecation;
+pub mod embedded;
 mod error;
 mod evaluate;
+pub mod format;
 mod fs;
+mod importer;
 mod interner;
 mod lexer;
+pub mod lint;
+mod logger;
+pub mod migrate;
 mod options;
 mod parse;
 mod selector;
 mod serializer;
+mod source_map;
 mod unit;
-mod utils;
+pub mod utils;
 mod value;
+#[cfg(feature = "wasm-exports")]
+pub mod wasm;
 
 fn raw_to_parse_error(map: &CodeMap, err: Error, unicode: bool) -> Box<Error> {
-    let (message, span) = err.raw();
-    Box::new(Error::from_loc(message, map.look_up_span(span), unicode))
+    let (message, span, trace) = match err.raw() {
+        Ok(raw) => raw,
+        Err(err) => return Box::new(err),
+    };
+    let trace = trace.unwrap_or_else(|| vec![("root stylesheet".to_owned(), span)]);
+    let stack = trace
+        .into_iter()
+        .map(|(member, span)| StackFrame::new(member, look_up_span(map, span)))
+        .collect();
+    Box::new(Error::from_loc(
+        message,
+        look_up_span(map, span),
+        unicode,
+        stack,
+    ))
 }
 
-fn from_string_with_file_name<P: AsRef<Path>>(
+/// Parse `input` as the file at `path`, returning the stylesheet and an empty
+/// span at the start of the file
+fn parse_stylesheet(
+    map: &mut CodeMap,
     input: String,
-    file_name: P,
+    path: &Path,
     options: &Options,
-) -> Result<String> {
-    let mut map = CodeMap::new();
-    let path = file_name.as_ref();
+) -> Result<(StyleSheet, Span)> {
     let file = map.add_file(path.to_string_lossy().into_owned(), input);
+    let stylesheet = parse_file(map, &file, path, options)?;
+
+    Ok((stylesheet, file.span.subspan(0, 0)))
+}
+
+/// Parse `file`, which has already been added to `map`, as the file at `path`
+fn parse_file(
+    map: &mut CodeMap,
+    file: &Arc<File>,
+    path: &Path,
+    options: &Options,
+) -> Result<StyleSheet> {
     let empty_span = file.span.subspan(0, 0);
-    let lexer = Lexer::new_from_file(&file);
+    let lexer = Lexer::new_from_file(file);
 
     let input_syntax = options
         .input_syntax
         .unwrap_or_else(|| InputSyntax::for_path(path));
 
     let stylesheet = match input_syntax {
-        InputSyntax::Scss => {
-            ScssParser::new(lexer, &mut map, options, empty_span, file_name.as_ref()).__parse()
-        }
-        InputSyntax::Sass => {
-            SassParser::new(lexer, &mut map, options, empty_span, file_name.as_ref()).__parse()
-        }
-        InputSyntax::Css => {
-            CssParser::new(lexer, &mut map, options, empty_span, file_name.as_ref()).__parse()
-        }
+        InputSyntax::Scss => ScssParser::new(lexer, map, options, empty_span, path).__parse(),
+        InputSyntax::Sass => SassParser::new(lexer, map, options, empty_span, path).__parse(),
+        InputSyntax::Css => CssParser::new(lexer, map, options, empty_span, path).__parse(),
     };
 
-    let stylesheet = match stylesheet {
-        Ok(v) => v,
-        Err(e) => return Err(raw_to_parse_error(&map, *e, options.unicode_error_messages)),
-    };
+    stylesheet.map_err(|e| raw_to_parse_error(map, *e, options.unicode_error_messages))
+}
 
-    let mut visitor = Visitor::new(path, options, &mut map, empty_span);
-    match visitor.visit_stylesheet(stylesheet) {
-        Ok(_) => {}
-        Err(e) => return Err(raw_to_parse_error(&map, *e, options.unicode_error_messages)),
+/// Like [`parse_stylesheet`], but reusing the stylesheet in `cache` if it was
+/// parsed from the same input, and caching it otherwise
+fn parse_cached_stylesheet(
+    map: &mut CodeMap,
+    cache: &mut StyleSheetCache,
+    input: String,
+    path: &Path,
+    options: &Options,
+) -> Result<(StyleSheet, Span)> {
+    let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let syntax = options
+        .input_syntax
+        .unwrap_or_else(|| InputSyntax::for_path(path));
+
+    if let Some((file, stylesheet)) = cache.get(&canonical, &input, syntax) {
+        return Ok((stylesheet.clone(), file.span.subspan(0, 0)));
     }
-    let stmts = visitor.finish();
 
-    let mut serializer = Serializer::new(options, &map, false, empty_span);
+    let file = map.add_file(path.to_string_lossy().into_owned(), input);
+    cache.record_file(&file);
 
-    let mut prev_was_group_end = false;
-    let mut prev_requires_semicolon = false;
-    for stmt in stmts {
-        if stmt.is_invisible() {
-            continue;
-        }
+    let stylesheet = parse_file(map, &file, path, options)?;
+    let empty_span = file.span.subspan(0, 0);
+    cache.insert(canonical, file, syntax, stylesheet.clone());
+
+    Ok((stylesheet, empty_span))
+}
+
+fn compile_with_file_name(
+    input: String,
+    path: &Path,
+    options: &Options,
+    source_map: bool,
+) -> Result<(String, Option<SourceMap>)> {
+    compile_with_cache(
+        &mut CodeMap::new(),
+        None,
+        None,
+        input,
+        path,
+        options,
+        source_map,
+    )
+}
 
-        let is_group_end = stmt.is_group_end();
-        let requires_semicolon = Serializer::requires_semicolon(&stmt);
+/// Compile `input` as the file at `path`, calling `function_host` to run the
+/// functions implemented by the program embedding the compiler
+fn compile_with_function_host(
+    input: String,
+    path: &Path,
+    options: &Options,
+    function_host: &dyn FunctionHost,
+    source_map: bool,
+) -> Result<(String, Option<SourceMap>)> {
+    value::with_precision(options.precision, || {
+        compile_with_cache(
+            &mut CodeMap::new(),
+            None,
+            Some(function_host),
+            input,
+            path,
+            options,
+            source_map,
+        )
+    })
+}
+
+/// Compile `input` as the file at `path`, adding the files it loads to `map`
+///
+/// If `cache` is given, the stylesheets and modules in it are reused, and any
+/// stylesheets which are parsed or modules which are executed are added to it.
+/// Its stylesheets must have been parsed into `map`.
+fn compile_with_cache(
+    map: &mut CodeMap,
+    cache: Option<(&mut StyleSheetCache, &mut ModuleCache)>,
+    function_host: Option<&dyn FunctionHost>,
+    input: String,
+    path: &Path,
+    options: &Options,
+    source_map: bool,
+) -> Result<(String, Option<SourceMap>)> {
+    let (stmts, empty_span, _) = evaluate(map, cache, function_host, input, path, options)?;
 
+    let mut serializer = Serializer::new(options, map, false, empty_span);
+
+    if source_map {
+        serializer = serializer.with_source_map();
+    }
+
+    for stmt in &stmts {
         serializer
-            .visit_group(stmt, prev_was_group_end, prev_requires_semicolon)
-            .map_err(|e| raw_to_parse_error(&map, *e, options.unicode_error_messages))?;
+            .visit_group(stmt)
+            .map_err(|e| raw_to_parse_error(map, *e, options.unicode_error_messages))?;
+    }
+
+    Ok(serializer.finish())
+}
+
+/// Parse and evaluate `input` as the file at `path`, returning the top-level
+/// CSS statements, an empty span at the start of the file, and whether the
+/// output could contain non-ASCII characters
+fn evaluate<'a>(
+    map: &'a mut CodeMap,
+    cache: Option<(&'a mut StyleSheetCache, &'a mut ModuleCache)>,
+    function_host: Option<&'a dyn FunctionHost>,
+    input: String,
+    path: &Path,
+    options: &'a Options<'a>,
+) -> Result<(Vec<CssStmt>, Span, bool)> {
+    let may_emit_non_ascii = may_produce_non_ascii(&input) || function_host.is_some();
+
+    let (mut cache, modules) = match cache {
+        Some((cache, modules)) => (Some(cache), Some(modules)),
+        None => (None, None),
+    };
+
+    let (stylesheet, empty_span) = match cache.as_deref_mut() {
+        Some(cache) => parse_cached_stylesheet(map, cache, input, path, options)?,
+        None => parse_stylesheet(map, input, path, options)?,
+    };
+
+    let mut visitor = Visitor::new(path, options, map, empty_span);
+    visitor.cache = cache;
+    visitor.function_host = function_host;
+    visitor.may_emit_non_ascii = may_emit_non_ascii;
+
+    if let Some(modules) = modules {
+        visitor.cache_modules(modules);
+    }
+
+    let result = visitor.visit_stylesheet(stylesheet);
+    visitor.store_modules();
 
-        prev_was_group_end = is_group_end;
-        prev_requires_semicolon = requires_semicolon;
+    if let Err(e) = result {
+        return Err(raw_to_parse_error(map, *e, options.unicode_error_messages));
     }
 
-    Ok(serializer.finish(prev_requires_semicolon))
+    visitor.emit_deprecation_summary();
+
+    let may_emit_non_ascii = visitor.may_emit_non_ascii;
+
+    Ok((visitor.finish(), empty_span, may_emit_non_ascii))
 }
 
 /// Compile CSS from a path
@@ -213,6 +372,146 @@ pub fn from_string(input: String, options: &Options) -> Result<String> {
     from_string_with_file_name(input, "stdin", options)
 }
 
+/// Compile CSS from a string, as if it were read from the file at `path`
+///
+/// `path` is used to resolve relative imports, to name the file in error
+/// messages, and to infer the input syntax if [`Options::input_syntax`] is not
+/// set. The file itself is never read.
+///
+/// ```
+/// # use grass_compiler as grass;
+/// fn main() -> Result<(), Box<grass::Error>> {
+///     let css = grass::from_string_with_file_name(
+///         "a\n  color: red".to_string(),
+///         "input.sass",
+///         &grass::Options::default(),
+///     )?;
+///     assert_eq!(css, "a {\n  color: red;\n}\n");
+///     Ok(())
+/// }
+/// ```
+#[inline]
+pub fn from_string_with_file_name<P: AsRef<Path>>(
+    input: String,
+    path: P,
+    options: &Options,
+) -> Result<String> {
+    value::with_precision(options.precision, || {
+        compile_with_file_name(input, path.as_ref(), options, false)
+    })
+    .map(|(css, _)| css)
+}
+
+/// Compile CSS from a string, writing it to `writer` rather than returning it
+///
+/// The output is written one top-level rule at a time, so that large
+/// stylesheets never need to be held in memory as a single string. Since
+/// `writer` is written to once per rule, callers writing to a file or socket
+/// should wrap it in a [`std::io::BufWriter`].
+///
+/// If the stylesheets being compiled contain non-ASCII characters or escapes,
+/// the output may need a `@charset` rule or byte-order mark. In that case,
+/// output is held back until the first non-ASCII character is produced, or
+/// until compilation finishes if there is none.
+///
+/// If an error occurs, some CSS may already have been written.
+///
+/// ```
+/// # use grass_compiler as grass;
+/// fn main() -> Result<(), Box<grass::Error>> {
+///     let mut css = Vec::new();
+///     grass::compile_to_writer(
+///         "a { b { color: &; } }".to_string(),
+///         &grass::Options::default(),
+///         &mut css,
+///     )?;
+///     assert_eq!(css, b"a b {\n  color: a b;\n}\n");
+///     Ok(())
+/// }
+/// ```
+#[inline]
+pub fn compile_to_writer(input: String, options: &Options, writer: &mut dyn Write) -> Result<()> {
+    value::with_precision(options.precision, || {
+        let mut map = CodeMap::new();
+        let (stmts, empty_span, may_emit_non_ascii) =
+            evaluate(&mut map, None, None, input, Path::new("stdin"), options)?;
+
+        let to_error = |e: Box<Error>| raw_to_parse_error(&map, *e, options.unicode_error_messages);
+
+        let mut serializer = Serializer::new(options, &map, false, empty_span);
+
+        // Until a non-ASCII character is seen, it isn't known whether the
+        // output needs a charset prefix, so it can't be written yet
+        let mut is_streaming = !may_emit_non_ascii;
+        let mut checked_len = 0;
+
+        for stmt in &stmts {
+            serializer.visit_group(stmt).map_err(to_error)?;
+
+            if !is_streaming {
+                if serializer.is_ascii_after(checked_len) {
+                    checked_len = serializer.buffered_len();
+                    continue;
+                }
+
+                writer.write_all(Serializer::charset_prefix(options, true).as_bytes())?;
+                is_streaming = true;
+            }
+
+            serializer.write_to(writer)?;
+        }
+
+        serializer.finish_to(writer)?;
+
+        Ok(())
+    })
+}
+
+/// Compile CSS from a string, along with a source map mapping the CSS back to
+/// the Sass it was compiled from
+///
+/// See [`SourceMap`] for an example.
+#[inline]
+pub fn from_string_with_source_map(
+    input: String,
+    options: &Options,
+) -> Result<(String, SourceMap)> {
+    compile_with_source_map(input, Path::new("stdin"), options)
+}
+
+/// Compile CSS from a path, along with a source map mapping the CSS back to
+/// the Sass it was compiled from
+///
+/// ```no_run
+/// # use grass_compiler as grass;
+/// fn main() -> Result<(), Box<grass::Error>> {
+///     let (css, source_map) =
+///         grass::from_path_with_source_map("input.scss", &grass::Options::default())?;
+///     let json = source_map.to_json(Some("input.css"), false);
+///     Ok(())
+/// }
+/// ```
+#[inline]
+pub fn from_path_with_source_map<P: AsRef<Path>>(
+    p: P,
+    options: &Options,
+) -> Result<(String, SourceMap)> {
+    let input = String::from_utf8(options.fs.read(p.as_ref())?)?;
+    compile_with_source_map(input, p.as_ref(), options)
+}
+
+fn compile_with_source_map(
+    input: String,
+    path: &Path,
+    options: &Options,
+) -> Result<(String, SourceMap)> {
+    let (css, source_map) = value::with_precision(options.precision, || {
+        compile_with_file_name(input, path, options, true)
+    })?;
+
+    Ok((css, source_map.unwrap()))
+}
+
 #[cfg(feature = "wasm-exports")]
 #[wasm_bindgen(js_name = from_string)]
 pub fn from_string_js(input: String) -> std::result::Result<String, String> {
@@ -0,0 +1,806 @@
+//! Static checks for common mistakes in a stylesheet
+//!
+//! [`lint`] parses and evaluates a stylesheet the same way
+//! [`crate::from_string_with_file_name`] does, but rather than producing CSS it
+//! reports:
+//!
+//! - variables, mixins and functions declared at the root of the stylesheet
+//!   with a private name, starting with `-` or `_`, which are never used
+//! - local variables which are never used
+//! - `@use` rules whose namespace is never referenced, unless the module emits
+//!   CSS
+//! - local variables which shadow a global variable, which is usually a
+//!   missing `!global`, and `!global` flags at the root of the stylesheet,
+//!   where they have no effect
+//! - properties declared more than once in the same style rule. Consecutive
+//!   declarations with different values are allowed, since these are commonly
+//!   used as fallbacks
+//! - `@extend` rules whose target doesn't appear in any selector
+//!
+//! ```
+//! # use grass_compiler as grass;
+//! use grass::lint::{lint, LintKind};
+//!
+//! let lints = lint(
+//!     "@use \"sass:math\";\n@mixin -unused { a: b; }".to_owned(),
+//!     "input.scss".as_ref(),
+//!     &grass::Options::default(),
+//! )?;
+//!
+//! assert_eq!(LintKind::UnusedUse, lints[0].kind);
+//! assert_eq!(LintKind::UnusedMember, lints[1].kind);
+//! assert_eq!("Private mixin -unused is never used.", lints[1].message);
+//! # Ok::<(), Box<grass::Error>>(())
+//! ```
+//!
+//! Lints are reported in source order. Their `Display` implementation mirrors
+//! that of errors.
+//!
+//! This API is experimental, and may change in minor releases.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+use codemap::{CodeMap, Span, SpanLoc, Spanned};
+
+use crate::{
+    analysis::{prefix, LoadedStyleSheet, Recorder},
+    ast::*,
+    common::Identifier,
+    error::write_diagnostic,
+    evaluate::Visitor,
+    parse_stylesheet, raw_to_parse_error, value, Options, Result,
+};
+
+/// A kind of mistake reported by [`lint`]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LintKind {
+    /// A private variable, mixin or function which is never used
+    UnusedMember,
+
+    /// A local variable which is never used
+    UnusedVariable,
+
+    /// A `@use` rule whose namespace is never referenced
+    UnusedUse,
+
+    /// A local variable which shadows a global variable, or a `!global` flag
+    /// which has no effect
+    ShadowedGlobal,
+
+    /// A property declared more than once in the same style rule
+    DuplicateProperty,
+
+    /// An `@extend` rule whose target doesn't appear in any selector
+    UnmatchedExtend,
+}
+
+impl LintKind {
+    /// Every kind of lint
+    pub const ALL: [LintKind; 6] = [
+        LintKind::UnusedMember,
+        LintKind::UnusedVariable,
+        LintKind::UnusedUse,
+        LintKind::ShadowedGlobal,
+        LintKind::DuplicateProperty,
+        LintKind::UnmatchedExtend,
+    ];
+
+    /// The stable identifier of this lint, e.g. `unused-use`
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            LintKind::UnusedMember => "unused-member",
+            LintKind::UnusedVariable => "unused-variable",
+            LintKind::UnusedUse => "unused-use",
+            LintKind::ShadowedGlobal => "shadowed-global",
+            LintKind::DuplicateProperty => "duplicate-property",
+            LintKind::UnmatchedExtend => "unmatched-extend",
+        }
+    }
+
+    /// Look up a lint by its identifier
+    #[must_use]
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|kind| kind.id() == id)
+    }
+}
+
+impl Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+/// A mistake found by [`lint`]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Lint {
+    pub kind: LintKind,
+
+    pub message: String,
+
+    /// The location of the offending declaration or rule
+    pub location: SpanLoc,
+
+    unicode: bool,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_diagnostic(
+            f,
+            &format!("Warning [{}]", self.kind),
+            &self.message,
+            &self.location,
+            self.unicode,
+            &[],
+        )
+    }
+}
+
+/// Lint `input` as if it were the contents of the file at `path`, returning an
+/// error if it fails to compile
+///
+/// Imports are resolved relative to `path` and read through [`Options::fs`],
+/// and warnings are reported to [`Options::logger`], just as they are during
+/// compilation.
+pub fn lint(input: String, path: &Path, options: &Options) -> Result<Vec<Lint>> {
+    value::with_precision(options.precision, || {
+        lint_with_file_name(input, path, options)
+    })
+}
+
+fn lint_with_file_name(input: String, path: &Path, options: &Options) -> Result<Vec<Lint>> {
+    let mut map = CodeMap::new();
+    let (stylesheet, empty_span) = parse_stylesheet(&mut map, input, path, options)?;
+
+    let mut visitor = Visitor::new(path, options, &mut map, empty_span);
+    visitor.recorder = Some(Recorder::default());
+
+    if let Err(e) = visitor.visit_stylesheet(stylesheet.clone()) {
+        return Err(raw_to_parse_error(&map, *e, options.unicode_error_messages));
+    }
+
+    let mut unmatched_extends: Vec<(String, Span)> = Vec::new();
+    for (target, span) in visitor.extender.unsatisfied_extensions() {
+        if !unmatched_extends.iter().any(|(_, other)| *other == span) {
+            unmatched_extends.push((target.to_string(), span));
+        }
+    }
+
+    let recorder = visitor.recorder.take().unwrap_or_default();
+    drop(visitor);
+
+    let mut linter = Linter {
+        map: &map,
+        loaded: &recorder.loaded,
+        path,
+        lints: Vec::new(),
+        scopes: vec![Scope::default()],
+        globals: HashSet::new(),
+        members: Vec::new(),
+        used_members: HashSet::new(),
+        uses: Vec::new(),
+    };
+
+    for stmt in &stylesheet.body {
+        if let AstStmt::VariableDecl(decl) = stmt {
+            if decl.namespace.is_none() {
+                linter.globals.insert(decl.name);
+            }
+        }
+    }
+
+    linter.visit_body(&stylesheet.body);
+    linter.finish();
+
+    for (target, span) in unmatched_extends {
+        linter.lints.push((
+            LintKind::UnmatchedExtend,
+            format!(
+                "The target selector {} was not found, so this @extend has no effect.",
+                target
+            ),
+            span,
+        ));
+    }
+
+    let mut lints = linter.lints;
+    lints.sort_by_key(|(_, _, span)| span.low());
+
+    Ok(lints
+        .into_iter()
+        .map(|(kind, message, span)| Lint {
+            kind,
+            message,
+            location: map.look_up_span(span),
+            unicode: options.unicode_error_messages,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum MemberKind {
+    Variable,
+    Mixin,
+    Function,
+}
+
+impl Display for MemberKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MemberKind::Variable => "variable",
+            MemberKind::Mixin => "mixin",
+            MemberKind::Function => "function",
+        })
+    }
+}
+
+/// A local variable
+struct Variable {
+    /// The span of the variable's first declaration, or `None` for parameters
+    /// and loop variables, which are never reported as unused
+    span: Option<Span>,
+    is_used: bool,
+}
+
+#[derive(Default)]
+struct Scope {
+    variables: HashMap<Identifier, Variable>,
+
+    /// Whether assigning to a global variable in this scope assigns it rather
+    /// than declaring a local variable, as it does in control flow rules at
+    /// the root of a stylesheet
+    is_semi_global: bool,
+}
+
+/// A `@use` rule with a namespace
+struct Use {
+    namespace: Identifier,
+    url: String,
+    span: Span,
+    is_used: bool,
+}
+
+struct Linter<'a> {
+    map: &'a CodeMap,
+    loaded: &'a [LoadedStyleSheet],
+    path: &'a Path,
+    lints: Vec<(LintKind, String, Span)>,
+    /// Lexical scopes. The first is the root of the stylesheet, whose
+    /// variables are tracked in `members` instead
+    scopes: Vec<Scope>,
+    /// The names of every variable declared at the root of the stylesheet
+    globals: HashSet<Identifier>,
+    /// The private members declared at the root of the stylesheet
+    members: Vec<(MemberKind, Identifier, Span)>,
+    /// The members referenced without a namespace that don't refer to a local
+    /// variable
+    used_members: HashSet<(MemberKind, Identifier)>,
+    uses: Vec<Use>,
+}
+
+impl<'a> Linter<'a> {
+    fn finish(&mut self) {
+        for &(kind, name, span) in &self.members {
+            if !self.used_members.contains(&(kind, name)) {
+                self.lints.push((
+                    LintKind::UnusedMember,
+                    format!(
+                        "Private {} {}{} is never used.",
+                        kind,
+                        if kind == MemberKind::Variable {
+                            "$"
+                        } else {
+                            ""
+                        },
+                        name
+                    ),
+                    span,
+                ));
+            }
+        }
+
+        for i in 0..self.uses.len() {
+            let module = &self.uses[i];
+            if module.is_used || self.emits_css(self.path, &module.url, &mut HashSet::new()) {
+                continue;
+            }
+
+            let module = &self.uses[i];
+            self.lints.push((
+                LintKind::UnusedUse,
+                format!(
+                    "The module \"{}\" is never used through its namespace {}.",
+                    module.url, module.namespace
+                ),
+                module.span,
+            ));
+        }
+    }
+
+    /// Whether the module loaded by `url` from `from` may emit CSS, in which
+    /// case it is worth loading even if none of its members are used
+    fn emits_css(&self, from: &Path, url: &str, visited: &mut HashSet<PathBuf>) -> bool {
+        if url.starts_with("sass:") {
+            return false;
+        }
+
+        let loaded = match self
+            .loaded
+            .iter()
+            .find(|loaded| loaded.from == from && loaded.url == url)
+        {
+            Some(loaded) => loaded,
+            None => return true,
+        };
+
+        if !visited.insert(loaded.style_sheet.url.clone()) {
+            return false;
+        }
+
+        loaded.style_sheet.body.iter().any(|stmt| match stmt {
+            AstStmt::VariableDecl(..)
+            | AstStmt::FunctionDecl(..)
+            | AstStmt::Mixin(..)
+            | AstStmt::SilentComment(..)
+            | AstStmt::Debug(..)
+            | AstStmt::Warn(..)
+            | AstStmt::ErrorRule(..) => false,
+            AstStmt::Use(AstUseRule { url, .. }) | AstStmt::Forward(AstForwardRule { url, .. }) => {
+                self.emits_css(&loaded.style_sheet.url, &url.to_string_lossy(), visited)
+            }
+            _ => true,
+        })
+    }
+
+    fn visit_body(&mut self, body: &[AstStmt]) {
+        self.check_duplicate_properties(body);
+
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    /// Visits `callback` in a new scope, reporting the local variables that
+    /// were never used
+    fn scoped(&mut self, is_control_flow: bool, callback: impl FnOnce(&mut Self)) {
+        let is_semi_global = is_control_flow
+            && (self.scopes.len() == 1 || self.scopes.last().unwrap().is_semi_global);
+
+        self.scopes.push(Scope {
+            variables: HashMap::new(),
+            is_semi_global,
+        });
+
+        callback(self);
+
+        let scope = self.scopes.pop().unwrap();
+
+        for (name, variable) in scope.variables {
+            if let (Some(span), false) = (variable.span, variable.is_used) {
+                self.lints.push((
+                    LintKind::UnusedVariable,
+                    format!("Local variable ${} is never used.", name),
+                    span,
+                ));
+            }
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &AstStmt) {
+        match stmt {
+            AstStmt::If(if_rule) => {
+                for clause in &if_rule.if_clauses {
+                    self.visit_expr(&clause.condition.node);
+                    self.scoped(true, |this| this.visit_body(&clause.body));
+                }
+
+                if let Some(body) = &if_rule.else_clause {
+                    self.scoped(true, |this| this.visit_body(body));
+                }
+            }
+            AstStmt::For(for_rule) => {
+                self.visit_expr(&for_rule.from.node);
+                self.visit_expr(&for_rule.to.node);
+                self.scoped(true, |this| {
+                    this.declare_parameter(for_rule.variable.node);
+                    this.visit_body(&for_rule.body);
+                });
+            }
+            AstStmt::Each(each_rule) => {
+                self.visit_expr(&each_rule.list);
+                self.scoped(true, |this| {
+                    for &variable in &each_rule.variables {
+                        this.declare_parameter(variable);
+                    }
+                    this.visit_body(&each_rule.body);
+                });
+            }
+            AstStmt::While(while_rule) => {
+                self.visit_expr(&while_rule.condition.node);
+                self.scoped(true, |this| this.visit_body(&while_rule.body));
+            }
+            AstStmt::Return(AstReturn { val, .. })
+            | AstStmt::Warn(AstWarn { value: val, .. })
+            | AstStmt::ErrorRule(AstErrorRule { value: val, .. })
+            | AstStmt::Debug(AstDebugRule { value: val, .. }) => self.visit_expr(val),
+            AstStmt::RuleSet(rule_set) => {
+                self.visit_interpolation(&rule_set.selector);
+                self.scoped(false, |this| this.visit_body(&rule_set.body));
+            }
+            AstStmt::Style(style) => {
+                self.visit_interpolation(&style.name);
+                if let Some(value) = &style.value {
+                    self.visit_expr(&value.node);
+                }
+                self.scoped(false, |this| this.visit_body(&style.body));
+            }
+            AstStmt::Media(media) => {
+                self.visit_interpolation(&media.query);
+                self.scoped(false, |this| this.visit_body(&media.body));
+            }
+            AstStmt::Supports(supports) => {
+                self.visit_supports_condition(&supports.condition);
+                self.scoped(false, |this| this.visit_body(&supports.children));
+            }
+            AstStmt::AtRootRule(at_root) => {
+                if let Some(query) = &at_root.query {
+                    self.visit_interpolation(&query.node);
+                }
+                self.scoped(false, |this| this.visit_body(&at_root.children));
+            }
+            AstStmt::UnknownAtRule(rule) => {
+                self.visit_interpolation(&rule.name);
+                if let Some(value) = &rule.value {
+                    self.visit_interpolation(value);
+                }
+                if let Some(children) = &rule.children {
+                    self.scoped(false, |this| this.visit_body(children));
+                }
+            }
+            AstStmt::Extend(extend) => self.visit_interpolation(&extend.value),
+            AstStmt::LoudComment(comment) => self.visit_interpolation(&comment.text),
+            AstStmt::SilentComment(..) => {}
+            AstStmt::Include(include) => {
+                self.visit_argument_invocation(&include.args);
+                self.reference(MemberKind::Mixin, include.namespace, include.name.node);
+
+                if let Some(content) = &include.content {
+                    self.scoped(false, |this| {
+                        this.declare_arguments(&content.args);
+                        this.visit_body(&content.body);
+                    });
+                }
+            }
+            AstStmt::ContentRule(content) => self.visit_argument_invocation(&content.args),
+            AstStmt::VariableDecl(decl) => {
+                self.visit_expr(&decl.value);
+                self.assign(decl);
+            }
+            AstStmt::FunctionDecl(function) => {
+                self.declare_member(MemberKind::Function, function.name);
+                self.scoped(false, |this| {
+                    this.declare_arguments(&function.arguments);
+                    this.visit_body(&function.children);
+                });
+            }
+            AstStmt::Mixin(mixin) => {
+                self.declare_member(MemberKind::Mixin, mixin.name);
+                self.scoped(false, |this| {
+                    this.declare_arguments(&mixin.args);
+                    this.visit_body(&mixin.body);
+                });
+            }
+            AstStmt::ImportRule(import_rule) => {
+                for import in &import_rule.imports {
+                    if let AstImport::Plain(import) = import {
+                        self.visit_interpolation(&import.url);
+                        if let Some(modifiers) = &import.modifiers {
+                            self.visit_interpolation(modifiers);
+                        }
+                    }
+                }
+            }
+            AstStmt::Use(use_rule) => {
+                for variable in &use_rule.configuration {
+                    self.visit_expr(&variable.expr.node);
+                }
+
+                if let Some(namespace) = &use_rule.namespace {
+                    self.uses.push(Use {
+                        namespace: Identifier::from(namespace.trim_start_matches("sass:")),
+                        url: use_rule.url.to_string_lossy().into_owned(),
+                        span: use_rule.span,
+                        is_used: false,
+                    });
+                }
+            }
+            AstStmt::Forward(forward_rule) => {
+                for variable in &forward_rule.configuration {
+                    self.visit_expr(&variable.expr.node);
+                }
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &AstExpr) {
+        match expr {
+            AstExpr::BinaryOp(op) => {
+                self.visit_expr(&op.lhs);
+                self.visit_expr(&op.rhs);
+            }
+            AstExpr::Calculation { args, .. } => {
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+            }
+            AstExpr::FunctionCall(call) => {
+                self.visit_argument_invocation(&call.arguments);
+                self.reference(MemberKind::Function, call.namespace, call.name);
+            }
+            AstExpr::If(ternary) => self.visit_argument_invocation(&ternary.0),
+            AstExpr::InterpolatedFunction(function) => {
+                self.visit_interpolation(&function.name);
+                self.visit_argument_invocation(&function.arguments);
+            }
+            AstExpr::List(list) => {
+                for elem in &list.elems {
+                    self.visit_expr(&elem.node);
+                }
+            }
+            AstExpr::Map(map) => {
+                for (key, value) in &map.0 {
+                    self.visit_expr(&key.node);
+                    self.visit_expr(value);
+                }
+            }
+            AstExpr::Paren(inner) | AstExpr::UnaryOp(_, inner, _) => self.visit_expr(inner),
+            AstExpr::String(StringExpr(interpolation, ..), ..) => {
+                self.visit_interpolation(interpolation);
+            }
+            AstExpr::Supports(condition) => self.visit_supports_condition(condition),
+            AstExpr::Variable { name, namespace } => {
+                self.reference(MemberKind::Variable, *namespace, name.node);
+            }
+            AstExpr::True
+            | AstExpr::False
+            | AstExpr::Null
+            | AstExpr::Color(..)
+            | AstExpr::Number { .. }
+            | AstExpr::ParentSelector => {}
+        }
+    }
+
+    fn visit_interpolation(&mut self, interpolation: &Interpolation) {
+        for part in &interpolation.contents {
+            if let InterpolationPart::Expr(expr) = part {
+                self.visit_expr(&expr.node);
+            }
+        }
+    }
+
+    fn visit_argument_invocation(&mut self, arguments: &ArgumentInvocation) {
+        for arg in &arguments.positional {
+            self.visit_expr(arg);
+        }
+
+        for arg in arguments.named.values() {
+            self.visit_expr(arg);
+        }
+
+        for arg in arguments.rest.iter().chain(&arguments.keyword_rest) {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_supports_condition(&mut self, condition: &AstSupportsCondition) {
+        match condition {
+            AstSupportsCondition::Anything { contents } => self.visit_interpolation(contents),
+            AstSupportsCondition::Declaration { name, value } => {
+                self.visit_expr(name);
+                self.visit_expr(value);
+            }
+            AstSupportsCondition::Function { name, args } => {
+                self.visit_interpolation(name);
+                self.visit_interpolation(args);
+            }
+            AstSupportsCondition::Interpolation(expr) => self.visit_expr(expr),
+            AstSupportsCondition::Negation(inner) => self.visit_supports_condition(inner),
+            AstSupportsCondition::Operation { left, right, .. } => {
+                self.visit_supports_condition(left);
+                self.visit_supports_condition(right);
+            }
+        }
+    }
+
+    fn declare_arguments(&mut self, arguments: &ArgumentDeclaration) {
+        for arg in &arguments.args {
+            if let Some(default) = &arg.default {
+                self.visit_expr(default);
+            }
+            self.declare_parameter(arg.name);
+        }
+
+        if let Some(rest) = arguments.rest {
+            self.declare_parameter(rest);
+        }
+    }
+
+    fn declare_parameter(&mut self, name: Identifier) {
+        self.scopes.last_mut().unwrap().variables.insert(
+            name,
+            Variable {
+                span: None,
+                is_used: false,
+            },
+        );
+    }
+
+    fn declare_member(&mut self, kind: MemberKind, name: Spanned<Identifier>) {
+        if self.scopes.len() == 1
+            && !name.node.is_public()
+            && !self
+                .members
+                .iter()
+                .any(|&(other_kind, other, _)| other_kind == kind && other == name.node)
+        {
+            self.members.push((kind, name.node, name.span));
+        }
+    }
+
+    fn assign(&mut self, decl: &AstVariableDecl) {
+        if let Some(namespace) = decl.namespace {
+            self.use_namespace(namespace.node);
+            return;
+        }
+
+        let name_span = prefix(decl.span, 0, 1 + decl.name.as_str().len() as u64);
+
+        if self.scopes.len() == 1 {
+            if decl.is_global {
+                self.lints.push((
+                    LintKind::ShadowedGlobal,
+                    format!(
+                        "!global has no effect at the root of a stylesheet, where ${} is already global.",
+                        decl.name
+                    ),
+                    decl.span,
+                ));
+            }
+
+            self.declare_member(
+                MemberKind::Variable,
+                Spanned {
+                    node: decl.name,
+                    span: name_span,
+                },
+            );
+            return;
+        }
+
+        if decl.is_global {
+            return;
+        }
+
+        // assigning to a variable declared in an enclosing local scope
+        // assigns that variable
+        if self.scopes[1..]
+            .iter()
+            .any(|scope| scope.variables.contains_key(&decl.name))
+        {
+            return;
+        }
+
+        if self.globals.contains(&decl.name) {
+            if self.scopes.last().unwrap().is_semi_global {
+                return;
+            }
+
+            self.lints.push((
+                LintKind::ShadowedGlobal,
+                format!(
+                    "This declares a local variable which shadows the global ${}. Use !global to assign to the global variable instead.",
+                    decl.name
+                ),
+                name_span,
+            ));
+        }
+
+        self.scopes.last_mut().unwrap().variables.insert(
+            decl.name,
+            Variable {
+                span: Some(name_span),
+                is_used: false,
+            },
+        );
+    }
+
+    fn reference(
+        &mut self,
+        kind: MemberKind,
+        namespace: Option<Spanned<Identifier>>,
+        name: Identifier,
+    ) {
+        if let Some(namespace) = namespace {
+            self.use_namespace(namespace.node);
+            return;
+        }
+
+        if kind == MemberKind::Variable {
+            let local = self.scopes[1..]
+                .iter_mut()
+                .rev()
+                .find_map(|scope| scope.variables.get_mut(&name));
+
+            if let Some(variable) = local {
+                variable.is_used = true;
+                return;
+            }
+        }
+
+        self.used_members.insert((kind, name));
+    }
+
+    fn use_namespace(&mut self, namespace: Identifier) {
+        for module in &mut self.uses {
+            if module.namespace == namespace {
+                module.is_used = true;
+            }
+        }
+    }
+
+    fn value_text(&self, style: &AstStyle) -> &'a str {
+        match &style.value {
+            Some(value) => self
+                .map
+                .find_file(value.span.low())
+                .source_slice(value.span)
+                .trim(),
+            None => "",
+        }
+    }
+
+    /// Reports properties declared more than once directly within `body`
+    fn check_duplicate_properties(&mut self, body: &[AstStmt]) {
+        let mut declared: HashMap<&str, (usize, &AstStyle)> = HashMap::new();
+
+        let declarations = body
+            .iter()
+            .filter(|stmt| !matches!(stmt, AstStmt::SilentComment(..) | AstStmt::LoudComment(..)))
+            .enumerate();
+
+        for (i, stmt) in declarations {
+            let style = match stmt {
+                AstStmt::Style(style) if style.value.is_some() => style,
+                _ => continue,
+            };
+
+            let name = match style.name.as_plain() {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if let Some((previous_index, previous)) = declared.insert(name, (i, style)) {
+                let is_fallback =
+                    previous_index + 1 == i && self.value_text(previous) != self.value_text(style);
+
+                if !is_fallback {
+                    self.lints.push((
+                        LintKind::DuplicateProperty,
+                        format!(
+                            "The property {} is already declared in this style rule.",
+                            name
+                        ),
+                        prefix(style.span, 0, name.len() as u64),
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::{Debug, Write};
+
+use codemap::SpanLoc;
+
+use crate::{
+    error::{write_stack_trace, StackFrame},
+    Deprecation,
+};
+
+/// A trait to allow replacing logging mechanisms
+///
+/// Loggers must be [`Sync`], since a single logger may receive messages from
+/// several compilations running on different threads.
+pub trait Logger: Debug + Sync {
+    /// Logs message from a `@debug` statement
+    ///
+    /// `trace` is the Sass stack trace at the point of the statement, starting
+    /// with the innermost frame
+    fn debug(&self, location: SpanLoc, message: &str, trace: &[StackFrame]);
+
+    /// Logs message from a `@warn` statement or a warning emitted by the compiler
+    ///
+    /// `trace` is the Sass stack trace at the point of the warning, starting
+    /// with the innermost frame
+    fn warn(&self, location: SpanLoc, message: &str, trace: &[StackFrame]);
+
+    /// Logs a warning for the use of a deprecated feature
+    ///
+    /// By default, this forwards to [`Logger::warn`]
+    #[inline]
+    fn deprecation(
+        &self,
+        deprecation: Deprecation,
+        location: SpanLoc,
+        message: &str,
+        trace: &[StackFrame],
+    ) {
+        let _ = deprecation;
+        self.warn(location, message, trace);
+    }
+
+    /// Logs a warning which is not associated with a location in a stylesheet,
+    /// such as the number of repetitive deprecation warnings that were omitted
+    #[inline]
+    fn warn_without_span(&self, message: &str) {
+        eprintln!("Warning: {}", message);
+    }
+}
+
+/// Logs events to standard error
+///
+/// This is the default logger. It mirrors the format used by `dart-sass`.
+#[derive(Debug)]
+pub struct StdLogger;
+
+impl Logger for StdLogger {
+    #[inline]
+    fn debug(&self, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+        let mut out = format!(
+            "{}:{} DEBUG: {}\n",
+            location.file.name(),
+            location.begin.line + 1,
+            message
+        );
+
+        if trace.len() > 1 {
+            write_stack_trace(&mut out, trace, "    ").unwrap();
+        }
+
+        eprint!("{}", out);
+    }
+
+    #[inline]
+    fn warn(&self, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+        warn_with_header("Warning", location, message, trace);
+    }
+
+    #[inline]
+    fn deprecation(
+        &self,
+        deprecation: Deprecation,
+        location: SpanLoc,
+        message: &str,
+        trace: &[StackFrame],
+    ) {
+        warn_with_header(
+            &format!("Deprecation Warning [{}]", deprecation),
+            location,
+            message,
+            trace,
+        );
+    }
+}
+
+fn warn_with_header(header: &str, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+    let mut out = format!("{}: {}\n", header, message);
+
+    if trace.len() > 1 {
+        write_stack_trace(&mut out, trace, "    ").unwrap();
+    } else {
+        writeln!(
+            out,
+            "    ./{}:{}:{}",
+            location.file.name(),
+            location.begin.line + 1,
+            location.begin.column + 1
+        )
+        .unwrap();
+    }
+
+    eprint!("{}", out);
+}
+
+/// Discards all log events
+#[derive(Debug)]
+pub struct NullLogger;
+
+impl Logger for NullLogger {
+    #[inline]
+    fn debug(&self, _location: SpanLoc, _message: &str, _trace: &[StackFrame]) {}
+
+    #[inline]
+    fn warn(&self, _location: SpanLoc, _message: &str, _trace: &[StackFrame]) {}
+
+    #[inline]
+    fn warn_without_span(&self, _message: &str) {}
+}
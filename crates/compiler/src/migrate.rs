@@ -0,0 +1,731 @@
+//! Migration of stylesheets from `@import` to the module system
+//!
+//! [`migrate`] replaces each `@import` of a Sass file with a `@use` rule, and
+//! prefixes every reference to a member declared by an imported file with the
+//! namespace of the `@use` rule that now loads it:
+//!
+//! ```
+//! # use grass_compiler as grass;
+//! use grass::{migrate::{migrate, MigrateOptions}, Fs};
+//! # use std::{io, path::Path};
+//! # #[derive(Debug)]
+//! # struct TestFs;
+//! # impl Fs for TestFs {
+//! #     fn is_dir(&self, _: &Path) -> bool { false }
+//! #     fn is_file(&self, path: &Path) -> bool { path == Path::new("_theme.scss") || path == Path::new("input.scss") }
+//! #     fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+//! #         Ok(if path == Path::new("input.scss") {
+//! #             b"@import \"theme\";\na { color: $primary; }\n".to_vec()
+//! #         } else {
+//! #             b"$primary: red !default;\n".to_vec()
+//! #         })
+//! #     }
+//! # }
+//! let files = migrate(
+//!     "input.scss".as_ref(),
+//!     &grass::Options::default().fs(&TestFs),
+//!     &MigrateOptions::default(),
+//! )?;
+//!
+//! assert_eq!(
+//!     "@use \"theme\";\na { color: theme.$primary; }\n",
+//!     files[0].migrated
+//! );
+//! # Ok::<(), Box<grass::Error>>(())
+//! ```
+//!
+//! Variables declared before an `@import` which configure `!default` variables
+//! of the imported file are passed along with a `with` clause, and variables
+//! declared after it which assign a variable of the imported file assign that
+//! module's variable instead.
+//!
+//! With [`MigrateOptions::migrate_deps`], the imported files are migrated as
+//! well, and an imported file gains a `@forward` rule for each file it imports
+//! whose members are referenced through it.
+//!
+//! Migration fails if a file references a private member of another file, or
+//! contains an `@import` nested within another rule or following rules which
+//! aren't allowed before `@use`. Global built-in functions are left as they
+//! are.
+//!
+//! This API is experimental, and may change in minor releases.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use codemap::{CodeMap, LineCol, Span};
+
+use crate::{
+    analysis::{analyze, SymbolKind},
+    ast::*,
+    common::Identifier,
+    evaluate::Visitor,
+    parse_stylesheet, raw_to_parse_error, Error, InputSyntax, Options, Result,
+};
+
+/// Configuration for [`migrate`]
+#[derive(Debug, Clone, Default)]
+pub struct MigrateOptions {
+    migrate_deps: bool,
+}
+
+impl MigrateOptions {
+    /// Whether to migrate the files imported by the stylesheet, and the files
+    /// they import, as well as the stylesheet itself
+    ///
+    /// By default, only the stylesheet itself is migrated.
+    #[must_use]
+    pub const fn migrate_deps(mut self, migrate_deps: bool) -> Self {
+        self.migrate_deps = migrate_deps;
+        self
+    }
+}
+
+/// A file changed by [`migrate`]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct MigratedFile {
+    pub path: PathBuf,
+
+    /// The contents of the file before migration
+    pub original: String,
+
+    /// The contents of the file after migration
+    pub migrated: String,
+}
+
+/// Migrate the stylesheet at `path` from `@import` to `@use` and `@forward`,
+/// returning the files that changed
+///
+/// Files are read through [`Options::fs`], and nothing is written.
+pub fn migrate(
+    path: &Path,
+    options: &Options,
+    migrate_options: &MigrateOptions,
+) -> Result<Vec<MigratedFile>> {
+    let mut graph = Graph {
+        files: Vec::new(),
+        indices: HashMap::new(),
+    };
+
+    graph.load(path, options)?;
+
+    let migrated: Vec<usize> = if migrate_options.migrate_deps {
+        (0..graph.files.len()).collect()
+    } else {
+        vec![0]
+    };
+
+    let mut plans = Vec::new();
+    let mut forwards: HashMap<usize, HashSet<usize>> = HashMap::new();
+
+    for &file in &migrated {
+        let plan = graph.plan(file, options)?;
+
+        if migrate_options.migrate_deps {
+            for &(from, to) in &plan.deep_members {
+                graph.forward_chain(from, to, &mut forwards);
+            }
+        }
+
+        plans.push((file, plan));
+    }
+
+    Ok(plans
+        .into_iter()
+        .filter_map(|(file, plan)| {
+            let migrated = graph.render(file, &plan, forwards.get(&file));
+            let file = &graph.files[file];
+
+            if migrated == file.source {
+                return None;
+            }
+
+            Some(MigratedFile {
+                path: file.path.clone(),
+                original: file.source.clone(),
+                migrated,
+            })
+        })
+        .collect())
+}
+
+/// A stylesheet in the `@import` graph
+struct File {
+    path: PathBuf,
+    source: String,
+    syntax: InputSyntax,
+    map: CodeMap,
+    /// The span of the whole file within `map`
+    span: Span,
+    style_sheet: StyleSheet,
+    /// The `@import`s of Sass files at the root of the stylesheet, in source
+    /// order
+    imports: Vec<Import>,
+}
+
+struct Import {
+    url: String,
+    /// The index of the imported file
+    file: usize,
+}
+
+/// How a file will be rewritten
+struct Plan {
+    /// The namespace of the `@use` rule replacing each import
+    namespaces: Vec<String>,
+    /// Whether any member is referenced through each import's namespace
+    is_referenced: Vec<bool>,
+    /// The variables declared before each import which configure it
+    configuration: Vec<Vec<String>>,
+    /// The byte offsets at which a namespace is inserted
+    insertions: Vec<(usize, String)>,
+    /// Imported files whose members are referenced, but which are only
+    /// reachable through another import, along with that import's file
+    deep_members: Vec<(usize, usize)>,
+}
+
+struct Graph {
+    files: Vec<File>,
+    indices: HashMap<PathBuf, usize>,
+}
+
+impl Graph {
+    /// Loads the stylesheet at `path` along with every file it imports,
+    /// returning its index
+    fn load(&mut self, path: &Path, options: &Options) -> Result<usize> {
+        if let Some(&index) = self.indices.get(path) {
+            return Ok(index);
+        }
+
+        let source = String::from_utf8(options.fs.read(path)?)?;
+        let syntax = match (self.files.is_empty(), options.input_syntax) {
+            (true, Some(syntax)) => syntax,
+            _ => InputSyntax::for_path(path),
+        };
+
+        let file_options = Options {
+            input_syntax: Some(syntax),
+            ..options.clone()
+        };
+
+        let mut map = CodeMap::new();
+        let (style_sheet, empty_span) =
+            parse_stylesheet(&mut map, source.clone(), path, &file_options)?;
+
+        let mut urls = Vec::new();
+        for stmt in &style_sheet.body {
+            if let AstStmt::ImportRule(import_rule) = stmt {
+                for import in &import_rule.imports {
+                    if let AstImport::Sass(import) = import {
+                        urls.push((import.url.clone(), import.span));
+                    }
+                }
+            }
+        }
+
+        let resolved = {
+            let visitor = Visitor::new(path, &file_options, &mut map, empty_span);
+            urls.iter()
+                .map(|(url, span)| (visitor.find_import(url.as_ref()), *span))
+                .collect::<Vec<_>>()
+        };
+
+        let index = self.files.len();
+        self.indices.insert(path.to_path_buf(), index);
+        self.files.push(File {
+            path: path.to_path_buf(),
+            source,
+            syntax,
+            span: map.find_file(empty_span.low()).span,
+            map,
+            style_sheet,
+            imports: Vec::new(),
+        });
+
+        for ((url, _), (resolved, span)) in urls.into_iter().zip(resolved) {
+            let resolved = match resolved {
+                Some(resolved) => resolved,
+                None => {
+                    return Err(self.error(
+                        index,
+                        "Can't find stylesheet to import.",
+                        span,
+                        options,
+                    ))
+                }
+            };
+
+            let file = self.load(&resolved, options)?;
+            self.files[index].imports.push(Import { url, file });
+        }
+
+        Ok(index)
+    }
+
+    fn error(&self, file: usize, message: &str, span: Span, options: &Options) -> Box<Error> {
+        raw_to_parse_error(
+            &self.files[file].map,
+            *Box::<Error>::from((message, span)),
+            options.unicode_error_messages,
+        )
+    }
+
+    /// The files reachable from `file` through `@import`, including itself
+    fn closure(&self, file: usize) -> HashSet<usize> {
+        let mut visited = HashSet::new();
+        let mut queue = vec![file];
+
+        while let Some(file) = queue.pop() {
+            if visited.insert(file) {
+                queue.extend(self.files[file].imports.iter().map(|import| import.file));
+            }
+        }
+
+        visited
+    }
+
+    /// Requests that each file on the shortest import path from `from` to
+    /// `to` forwards the next one
+    fn forward_chain(&self, from: usize, to: usize, forwards: &mut HashMap<usize, HashSet<usize>>) {
+        let mut previous = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(file) = queue.pop_front() {
+            if file == to {
+                break;
+            }
+
+            for import in &self.files[file].imports {
+                if import.file != from && !previous.contains_key(&import.file) {
+                    previous.insert(import.file, file);
+                    queue.push_back(import.file);
+                }
+            }
+        }
+
+        let mut file = to;
+        while let Some(&importer) = previous.get(&file) {
+            forwards.entry(importer).or_default().insert(file);
+            file = importer;
+        }
+    }
+
+    fn plan(&self, index: usize, options: &Options) -> Result<Plan> {
+        let file = &self.files[index];
+
+        check_import_positions(&file.style_sheet.body, true)
+            .map_err(|(message, span)| self.error(index, message, span, options))?;
+
+        let file_options = Options {
+            input_syntax: Some(file.syntax),
+            ..options.clone()
+        };
+
+        let analysis = analyze(file.source.clone(), &file.path, &file_options);
+        if let Some(error) = analysis.error {
+            return Err(error);
+        }
+
+        let closures: Vec<HashSet<usize>> = file
+            .imports
+            .iter()
+            .map(|import| self.closure(import.file))
+            .collect();
+
+        let mut taken: HashSet<String> = file
+            .style_sheet
+            .body
+            .iter()
+            .filter_map(|stmt| match stmt {
+                AstStmt::Use(AstUseRule {
+                    namespace: Some(namespace),
+                    ..
+                }) => Some(namespace.trim_start_matches("sass:").to_owned()),
+                _ => None,
+            })
+            .collect();
+
+        let namespaces = file
+            .imports
+            .iter()
+            .map(|import| {
+                let base = default_namespace(&import.url);
+                let mut namespace = base.clone();
+                let mut n = 2;
+                while !taken.insert(namespace.clone()) {
+                    namespace = format!("{}{}", base, n);
+                    n += 1;
+                }
+                namespace
+            })
+            .collect();
+
+        let mut plan = Plan {
+            namespaces,
+            is_referenced: vec![false; file.imports.len()],
+            configuration: vec![Vec::new(); file.imports.len()],
+            insertions: Vec::new(),
+            deep_members: Vec::new(),
+        };
+
+        // the root variable declarations which assign a variable of an
+        // imported file, keyed by the position of their name
+        let mut reassigned: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut imports_seen = 0;
+
+        for stmt in &file.style_sheet.body {
+            match stmt {
+                AstStmt::ImportRule(import_rule) => {
+                    imports_seen += import_rule
+                        .imports
+                        .iter()
+                        .filter(|import| import.is_dynamic())
+                        .count();
+                }
+                AstStmt::VariableDecl(decl)
+                    if decl.namespace.is_none() && decl.name.is_public() =>
+                {
+                    let configured = (imports_seen..file.imports.len()).find(|&import| {
+                        closures[import]
+                            .iter()
+                            .any(|&other| self.declares_default(other, decl.name))
+                    });
+
+                    if let Some(import) = configured {
+                        plan.configuration[import].push(decl.name.to_string());
+                        self.reference_member(index, import, decl.name, &closures, &mut plan);
+                        continue;
+                    }
+
+                    let assigned = (0..imports_seen).rev().find(|&import| {
+                        closures[import]
+                            .iter()
+                            .any(|&other| self.declares_variable(other, decl.name))
+                    });
+
+                    if let Some(import) = assigned {
+                        let start = decl.span.low() - file.span.low();
+                        let position = file.map.look_up_pos(decl.span.low()).position;
+                        reassigned.insert((position.line, position.column), import);
+                        plan.insertions
+                            .push((start as usize, plan.namespaces[import].clone()));
+                        self.reference_member(index, import, decl.name, &closures, &mut plan);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for reference in &analysis.references {
+            if reference.namespace.is_some() || reference.kind == SymbolKind::Namespace {
+                continue;
+            }
+
+            let definition = match &reference.definition {
+                Some(definition) => definition,
+                None => continue,
+            };
+
+            let offset = offset(&file.source, reference.location.begin);
+
+            if Path::new(definition.location.file.name()) == file.path {
+                let begin = definition.location.begin;
+                if let Some(&import) = reassigned.get(&(begin.line, begin.column)) {
+                    plan.insertions
+                        .push((offset, plan.namespaces[import].clone()));
+                }
+                continue;
+            }
+
+            let declared_in = match self.indices.get(Path::new(definition.location.file.name())) {
+                Some(&declared_in) => declared_in,
+                None => continue,
+            };
+
+            let import = match file
+                .imports
+                .iter()
+                .position(|import| import.file == declared_in)
+                .or_else(|| {
+                    closures
+                        .iter()
+                        .position(|closure| closure.contains(&declared_in))
+                }) {
+                Some(import) => import,
+                None => continue,
+            };
+
+            let name = Identifier::from(reference.name.as_str());
+            if !name.is_public() {
+                let span = file.span.subspan(
+                    offset as u64,
+                    (offset
+                        + reference.name.len()
+                        + usize::from(reference.kind == SymbolKind::Variable))
+                        as u64,
+                );
+
+                return Err(self.error(
+                    index,
+                    &format!(
+                        "This refers to the private {} {} from {}, which can't be used from another module.",
+                        kind_name(reference.kind),
+                        reference.name,
+                        definition.location.file.name()
+                    ),
+                    span,
+                    options,
+                ));
+            }
+
+            plan.insertions
+                .push((offset, plan.namespaces[import].clone()));
+            self.reference_member(index, import, name, &closures, &mut plan);
+
+            if file.imports[import].file != declared_in {
+                plan.deep_members
+                    .push((file.imports[import].file, declared_in));
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Records that a member declared somewhere in the closure of `import` is
+    /// referenced through its namespace
+    fn reference_member(
+        &self,
+        index: usize,
+        import: usize,
+        name: Identifier,
+        closures: &[HashSet<usize>],
+        plan: &mut Plan,
+    ) {
+        plan.is_referenced[import] = true;
+
+        let direct = self.files[index].imports[import].file;
+        if let Some(&declared_in) = closures[import]
+            .iter()
+            .find(|&&other| other != direct && self.declares_variable(other, name))
+        {
+            if !self.declares_variable(direct, name) {
+                plan.deep_members.push((direct, declared_in));
+            }
+        }
+    }
+
+    fn declares_variable(&self, file: usize, name: Identifier) -> bool {
+        self.root_variables(file).any(|decl| decl.name == name)
+    }
+
+    fn declares_default(&self, file: usize, name: Identifier) -> bool {
+        self.root_variables(file)
+            .any(|decl| decl.name == name && decl.is_guarded)
+    }
+
+    fn root_variables(&self, file: usize) -> impl Iterator<Item = &AstVariableDecl> {
+        self.files[file]
+            .style_sheet
+            .body
+            .iter()
+            .filter_map(|stmt| match stmt {
+                AstStmt::VariableDecl(decl) if decl.namespace.is_none() => Some(decl),
+                _ => None,
+            })
+    }
+
+    fn render(&self, index: usize, plan: &Plan, forwards: Option<&HashSet<usize>>) -> String {
+        let file = &self.files[index];
+        let base = file.span.low();
+        let semicolon = if file.syntax == InputSyntax::Sass {
+            ""
+        } else {
+            ";"
+        };
+
+        let mut edits: Vec<(usize, usize, String)> = plan
+            .insertions
+            .iter()
+            .map(|(offset, namespace)| (*offset, *offset, format!("{}.", namespace)))
+            .collect();
+
+        let mut import_index = 0;
+
+        for stmt in &file.style_sheet.body {
+            let import_rule = match stmt {
+                AstStmt::ImportRule(import_rule) => import_rule,
+                _ => continue,
+            };
+
+            let mut lines = Vec::new();
+
+            for import in &import_rule.imports {
+                match import {
+                    AstImport::Sass(sass_import) => {
+                        let import = &file.imports[import_index];
+                        let namespace = &plan.namespaces[import_index];
+                        let configuration = &plan.configuration[import_index];
+                        let is_forwarded =
+                            forwards.is_some_and(|forwards| forwards.contains(&import.file));
+
+                        if is_forwarded {
+                            lines.push(format!("@forward \"{}\"{}", sass_import.url, semicolon));
+                        }
+
+                        if !is_forwarded
+                            || plan.is_referenced[import_index]
+                            || !configuration.is_empty()
+                        {
+                            let mut line = format!("@use \"{}\"", sass_import.url);
+
+                            if *namespace != default_namespace(&sass_import.url) {
+                                line.push_str(&format!(" as {}", namespace));
+                            }
+
+                            if !configuration.is_empty() {
+                                let configuration: Vec<String> = configuration
+                                    .iter()
+                                    .map(|name| format!("${}: ${}", name, name))
+                                    .collect();
+                                line.push_str(&format!(" with ({})", configuration.join(", ")));
+                            }
+
+                            line.push_str(semicolon);
+                            lines.push(line);
+                        }
+
+                        import_index += 1;
+                    }
+                    AstImport::Plain(plain_import) => {
+                        let text = &file.source[(plain_import.span.low() - base) as usize
+                            ..(plain_import.span.high() - base) as usize];
+                        lines.push(format!("@import {}{}", text, semicolon));
+                    }
+                }
+            }
+
+            let start = (import_rule.span.low() - base) as usize;
+            let mut end = (import_rule.span.high() - base) as usize;
+            if file.source[end..].starts_with(';') {
+                end += 1;
+            }
+
+            edits.push((start, end, lines.join("\n")));
+        }
+
+        edits.sort_by_key(|&(start, end, _)| std::cmp::Reverse((start, end)));
+
+        let mut migrated = file.source.clone();
+        for (start, end, replacement) in edits {
+            migrated.replace_range(start..end, &replacement);
+        }
+
+        migrated
+    }
+}
+
+/// Checks that every `@import` in `body` can be replaced by `@use`, returning
+/// the offending import otherwise
+fn check_import_positions(
+    body: &[AstStmt],
+    is_root: bool,
+) -> std::result::Result<(), (&'static str, Span)> {
+    let mut allows_use = is_root;
+
+    for stmt in body {
+        let children: Vec<&[AstStmt]> = match stmt {
+            AstStmt::ImportRule(import_rule) => {
+                for import in &import_rule.imports {
+                    match import {
+                        AstImport::Sass(import) if !is_root => {
+                            return Err((
+                                "Nested @import rules can't be migrated to @use.",
+                                import.span,
+                            ))
+                        }
+                        AstImport::Sass(import) if !allows_use => {
+                            return Err((
+                                "This @import can't be migrated to @use, because it follows rules which aren't allowed before @use.",
+                                import.span,
+                            ))
+                        }
+                        AstImport::Sass(..) => {}
+                        AstImport::Plain(..) => allows_use = false,
+                    }
+                }
+                continue;
+            }
+            AstStmt::VariableDecl(..)
+            | AstStmt::Use(..)
+            | AstStmt::Forward(..)
+            | AstStmt::SilentComment(..)
+            | AstStmt::LoudComment(..) => continue,
+            AstStmt::If(if_rule) => if_rule
+                .if_clauses
+                .iter()
+                .map(|clause| clause.body.as_slice())
+                .chain(if_rule.else_clause.as_deref())
+                .collect(),
+            AstStmt::For(AstFor { body, .. })
+            | AstStmt::Each(AstEach { body, .. })
+            | AstStmt::While(AstWhile { body, .. })
+            | AstStmt::RuleSet(AstRuleSet { body, .. })
+            | AstStmt::Style(AstStyle { body, .. })
+            | AstStmt::Media(AstMedia { body, .. })
+            | AstStmt::Mixin(AstMixin { body, .. }) => vec![body],
+            AstStmt::Supports(AstSupportsRule { children, .. })
+            | AstStmt::AtRootRule(AstAtRootRule { children, .. })
+            | AstStmt::FunctionDecl(AstFunctionDecl { children, .. }) => vec![children],
+            AstStmt::UnknownAtRule(rule) => rule.children.as_deref().into_iter().collect(),
+            AstStmt::Include(include) => include
+                .content
+                .iter()
+                .map(|content| content.body.as_slice())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        allows_use = false;
+
+        for body in children {
+            check_import_positions(body, false)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The namespace `@use` gives a module loaded from `url` without `as`
+fn default_namespace(url: &str) -> String {
+    let base_name = url.rsplit('/').next().unwrap_or(url);
+    let base_name = base_name.strip_prefix('_').unwrap_or(base_name);
+    base_name.split('.').next().unwrap_or(base_name).to_owned()
+}
+
+fn kind_name(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Variable => "variable",
+        SymbolKind::Mixin => "mixin",
+        SymbolKind::Function => "function",
+        SymbolKind::Namespace => "namespace",
+    }
+}
+
+/// The byte offset of `position` within `source`
+fn offset(source: &str, position: LineCol) -> usize {
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(position.line)
+        .map(str::len)
+        .sum();
+
+    line_start
+        + source[line_start..]
+            .chars()
+            .take(position.column)
+            .map(char::len_utf8)
+            .sum::<usize>()
+}
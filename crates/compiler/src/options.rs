@@ -1,20 +1,45 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
-use crate::{Fs, StdFs};
+use crate::{
+    importer::CustomImporter,
+    value::{DEFAULT_PRECISION, MAX_PRECISION},
+    Deprecation, FileImporter, Fs, Importer, Logger, StdFs, StdLogger,
+};
 
 /// Configuration for Sass compilation
 ///
 /// The simplest usage is `grass::Options::default()`; however, a builder pattern
 /// is also exposed to offer more control.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Options<'a> {
     pub(crate) fs: &'a dyn Fs,
+    pub(crate) logger: &'a dyn Logger,
     pub(crate) style: OutputStyle,
     pub(crate) load_paths: Vec<PathBuf>,
+    pub(crate) node_package_importer: Option<PathBuf>,
+    pub(crate) importers: Vec<CustomImporter<'a>>,
     pub(crate) allows_charset: bool,
     pub(crate) unicode_error_messages: bool,
     pub(crate) quiet: bool,
     pub(crate) input_syntax: Option<InputSyntax>,
+    pub(crate) precision: u8,
+    pub(crate) verbose: bool,
+    pub(crate) silence_deprecations: HashSet<Deprecation>,
+    pub(crate) fatal_deprecations: HashSet<Deprecation>,
+    pub(crate) future_deprecations: HashSet<Deprecation>,
+    pub(crate) max_call_depth: Option<usize>,
+    pub(crate) max_loop_iterations: Option<usize>,
+    pub(crate) max_output_size: Option<usize>,
+    pub(crate) cancellation_token: Option<CancellationToken>,
+    pub(crate) deadline: Option<Instant>,
 }
 
 impl Default for Options<'_> {
@@ -22,12 +47,25 @@ impl Default for Options<'_> {
     fn default() -> Self {
         Self {
             fs: &StdFs,
+            logger: &StdLogger,
             style: OutputStyle::Expanded,
             load_paths: Vec::new(),
+            node_package_importer: None,
+            importers: Vec::new(),
             allows_charset: true,
             unicode_error_messages: true,
             quiet: false,
             input_syntax: None,
+            precision: DEFAULT_PRECISION,
+            verbose: false,
+            silence_deprecations: HashSet::new(),
+            fatal_deprecations: HashSet::new(),
+            future_deprecations: HashSet::new(),
+            max_call_depth: None,
+            max_loop_iterations: None,
+            max_output_size: None,
+            cancellation_token: None,
+            deadline: None,
         }
     }
 }
@@ -44,6 +82,17 @@ impl<'a> Options<'a> {
         self
     }
 
+    /// This option allows you to control how Sass emits `@debug` and `@warn`
+    /// messages, as well as any warnings produced by the compiler itself.
+    ///
+    /// By default, it uses [`StdLogger`], which prints messages to standard error.
+    #[must_use]
+    #[inline]
+    pub fn logger(mut self, logger: &'a dyn Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
     /// `grass` currently offers 2 different output styles
     ///
     ///  - [`OutputStyle::Expanded`] writes each selector and declaration on its own line.
@@ -108,6 +157,49 @@ impl<'a> Options<'a> {
         self
     }
 
+    /// Resolve `pkg:` URLs, such as `@use "pkg:bootstrap"`, to packages in
+    /// `node_modules` directories, like Node.js does.
+    ///
+    /// Packages are found by walking up from the directory of the stylesheet
+    /// containing the `pkg:` URL, or from `entry_point_directory` if that
+    /// fails. Within a package, the `exports` field of its `package.json` is
+    /// consulted using the `sass`, `style`, and `default` conditions, followed
+    /// by the `sass` and `style` fields, followed by an `index` file.
+    ///
+    /// This matches the behavior of `NodePackageImporter` in `dart-sass`.
+    ///
+    /// By default, `pkg:` URLs are not supported.
+    #[must_use]
+    #[inline]
+    pub fn node_package_importer<P: AsRef<Path>>(mut self, entry_point_directory: P) -> Self {
+        self.node_package_importer = Some(entry_point_directory.as_ref().to_owned());
+        self
+    }
+
+    /// Load stylesheets whose URLs aren't found on the file system with a
+    /// custom [`Importer`].
+    ///
+    /// This method will append a single importer to the list. Importers added
+    /// with this method and [`Options::file_importer`] are consulted in the
+    /// order they were added.
+    #[must_use]
+    #[inline]
+    pub fn importer(mut self, importer: &'a dyn Importer) -> Self {
+        self.importers.push(CustomImporter::Importer(importer));
+        self
+    }
+
+    /// Redirect URLs which aren't found on the file system to other paths with
+    /// a custom [`FileImporter`].
+    ///
+    /// See [`Options::importer`] for more information about custom importers
+    #[must_use]
+    #[inline]
+    pub fn file_importer(mut self, importer: &'a dyn FileImporter) -> Self {
+        self.importers.push(CustomImporter::File(importer));
+        self
+    }
+
     /// This flag tells Sass whether to emit a `@charset`
     /// declaration or a UTF-8 byte-order mark.
     ///
@@ -150,9 +242,186 @@ impl<'a> Options<'a> {
         self
     }
 
+    /// The number of digits after the decimal point to emit when serializing
+    /// numbers
+    ///
+    /// This also controls how close two numbers must be to be considered
+    /// equal, and how numbers are rounded by functions such as `round()`.
+    /// Numbers are compared to one more digit than is emitted.
+    ///
+    /// By default, this value is `10`, matching `dart-sass`. `libsass` used
+    /// a default of `5`. Values above `16` exceed the precision of the
+    /// underlying floating point numbers and are clamped.
+    #[must_use]
+    #[inline]
+    pub const fn precision(mut self, precision: u8) -> Self {
+        self.precision = if precision > MAX_PRECISION {
+            MAX_PRECISION
+        } else {
+            precision
+        };
+        self
+    }
+
+    /// By default, Sass only emits the first few warnings for each kind of
+    /// deprecation, followed by a count of the warnings that were omitted.
+    ///
+    /// This flag tells Sass to emit every deprecation warning, even when they
+    /// are repetitive.
+    #[must_use]
+    #[inline]
+    pub const fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Don't emit warnings for the given deprecation.
+    ///
+    /// This method may be called multiple times to silence multiple deprecations.
+    #[must_use]
+    #[inline]
+    pub fn silence_deprecation(mut self, deprecation: Deprecation) -> Self {
+        self.silence_deprecations.insert(deprecation);
+        self
+    }
+
+    /// Treat uses of the given deprecated feature as errors rather than warnings.
+    ///
+    /// This takes precedence over [`Options::silence_deprecation`] and
+    /// [`Options::quiet`], and implicitly opts in to future deprecations.
+    #[must_use]
+    #[inline]
+    pub fn fatal_deprecation(mut self, deprecation: Deprecation) -> Self {
+        self.fatal_deprecations.insert(deprecation);
+        self
+    }
+
+    /// Opt in to warnings for a deprecation which is not yet active by default.
+    ///
+    /// See [`Deprecation::is_future`] for more information. This has no effect
+    /// on deprecations which are already active.
+    #[must_use]
+    #[inline]
+    pub fn future_deprecation(mut self, deprecation: Deprecation) -> Self {
+        self.future_deprecations.insert(deprecation);
+        self
+    }
+
+    /// The maximum number of nested mixin, function, and `@content` calls
+    ///
+    /// Exceeding this limit is an error, reported at the call which exceeded
+    /// it. This protects against unbounded recursion in untrusted stylesheets,
+    /// which would otherwise overflow the stack. Note that deeply nested calls
+    /// may still overflow small thread stacks before reaching a large limit.
+    ///
+    /// By default, there is no limit.
+    #[must_use]
+    #[inline]
+    pub const fn max_call_depth(mut self, max_call_depth: usize) -> Self {
+        self.max_call_depth = Some(max_call_depth);
+        self
+    }
+
+    /// The maximum total number of iterations of `@each`, `@for`, and `@while`
+    /// loops over the whole compilation
+    ///
+    /// Exceeding this limit is an error, reported at the loop which exceeded
+    /// it. By default, there is no limit.
+    #[must_use]
+    #[inline]
+    pub const fn max_loop_iterations(mut self, max_loop_iterations: usize) -> Self {
+        self.max_loop_iterations = Some(max_loop_iterations);
+        self
+    }
+
+    /// The maximum size of the generated CSS, in bytes
+    ///
+    /// Exceeding this limit is an error, reported at the style rule or
+    /// declaration which exceeded it. By default, there is no limit.
+    ///
+    /// This limits the size of the output, not the memory used to produce it.
+    /// The whole stylesheet is evaluated before any CSS is generated, so a
+    /// stylesheet which emits a very large number of rules will hold all of
+    /// them in memory before this limit is checked. To bound the size of the
+    /// evaluated stylesheet, also set [`Options::max_loop_iterations`] and
+    /// [`Options::max_call_depth`].
+    #[must_use]
+    #[inline]
+    pub const fn max_output_size(mut self, max_output_size: usize) -> Self {
+        self.max_output_size = Some(max_output_size);
+        self
+    }
+
+    /// Stop compilation with an error once `token` is cancelled
+    ///
+    /// Cancellation is cooperative: it's checked at each style rule, loop
+    /// iteration, and mixin or function call, so compilation stops shortly after
+    /// [`CancellationToken::cancel`] is called from another thread.
+    ///
+    /// ```
+    /// # use grass_compiler as grass;
+    /// let token = grass::CancellationToken::new();
+    /// let options = grass::Options::default().cancellation_token(token.clone());
+    ///
+    /// token.cancel();
+    ///
+    /// let err = grass::from_string("a { b: c; }".to_owned(), &options).unwrap_err();
+    /// assert!(err.to_string().contains("Compilation was cancelled."));
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Stop compilation with an error if it's still running at `deadline`
+    ///
+    /// This is checked at the same points as [`Options::cancellation_token`].
+    #[must_use]
+    #[inline]
+    pub const fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
     pub(crate) fn is_compressed(&self) -> bool {
         matches!(self.style, OutputStyle::Compressed)
     }
+
+    pub(crate) fn is_fatal(&self, deprecation: Deprecation) -> bool {
+        self.fatal_deprecations.contains(&deprecation)
+    }
+
+    /// Whether warnings should be emitted for the given deprecation
+    pub(crate) fn is_deprecation_active(&self, deprecation: Deprecation) -> bool {
+        if self.silence_deprecations.contains(&deprecation) {
+            return false;
+        }
+
+        !deprecation.is_future() || self.future_deprecations.contains(&deprecation)
+    }
+}
+
+/// A flag used to cancel a compilation from another thread
+///
+/// See [`Options::cancellation_token`]. Clones of a token share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every compilation using this token
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
 /// Useful when parsing Sass from sources other than the file system
@@ -101,7 +101,7 @@ impl<'a> StylesheetParser<'a> for CssParser<'a> {
             Some("import") => self.parse_css_import_rule(start),
             Some("media") => self.parse_media_rule(start),
             Some("-moz-document") => self._parse_moz_document_rule(name),
-            Some("supports") => self.parse_supports_rule(),
+            Some("supports") => self.parse_supports_rule(start),
             _ => self.unknown_at_rule(name, start),
         }
     }
@@ -125,7 +125,7 @@ impl<'a> CssParser<'a> {
         }
     }
 
-    fn parse_css_import_rule(&mut self, _start: usize) -> SassResult<AstStmt> {
+    fn parse_css_import_rule(&mut self, start: usize) -> SassResult<AstStmt> {
         let url_start = self.toks.cursor();
 
         let url = if self.toks.next_char_is('u') || self.toks.next_char_is('U') {
@@ -150,6 +150,7 @@ impl<'a> CssParser<'a> {
                 modifiers,
                 span: self.toks.span_from(url_start),
             })],
+            span: self.toks.span_from(start),
         }))
     }
 
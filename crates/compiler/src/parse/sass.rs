@@ -50,7 +50,8 @@ impl<'a> BaseParser<'a> for SassParser<'a> {
                     return Err(("expected */.", self.toks.prev_span()).into())
                 }
                 Some(Token { kind: '*', .. }) => {}
-                _ => continue,
+                Some(..) => continue,
+                None => return Err(("expected more input.", self.toks.current_span()).into()),
             }
 
             loop {
@@ -202,8 +203,9 @@ impl<'a> StylesheetParser<'a> for SassParser<'a> {
                 statements.push(child);
             }
 
-            let indentation = self.read_indentation()?;
-            assert_eq!(indentation, 0);
+            if self.read_indentation()? != 0 {
+                return Err(("Expected no indentation.", self.toks.current_span()).into());
+            }
         }
 
         Ok(statements)
@@ -301,7 +303,7 @@ impl<'a> StylesheetParser<'a> for SassParser<'a> {
                 buffer.add_char(' ');
             }
 
-            while self.toks.peek().is_some() {
+            loop {
                 match self.toks.peek() {
                     Some(Token {
                         kind: '\n' | '\r', ..
@@ -318,7 +320,7 @@ impl<'a> StylesheetParser<'a> for SassParser<'a> {
                         buffer.add_char(kind);
                         self.toks.next();
                     }
-                    None => todo!(),
+                    None => break,
                 }
             }
 
@@ -378,7 +378,10 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
 
         self.flags_mut().set(ContextFlags::FOUND_CONTENT_RULE, true);
 
-        Ok(AstStmt::ContentRule(AstContentRule { args }))
+        Ok(AstStmt::ContentRule(AstContentRule {
+            args,
+            span: self.toks_mut().span_from(start),
+        }))
     }
 
     fn parse_debug_rule(&mut self) -> SassResult<AstStmt> {
@@ -394,6 +397,7 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
     fn parse_each_rule(
         &mut self,
         child: fn(&mut Self) -> SassResult<AstStmt>,
+        start: usize,
     ) -> SassResult<AstStmt> {
         let was_in_control_directive = self.flags().in_control_flow();
         self.flags_mut().set(ContextFlags::IN_CONTROL_FLOW, true);
@@ -409,7 +413,7 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
         self.expect_identifier("in", false)?;
         self.whitespace()?;
 
-        let list = self.parse_expression(None, None, None)?.node;
+        let list = self.parse_expression(None, None, None)?;
 
         let body = self.with_children(child)?.node;
 
@@ -418,8 +422,10 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
 
         Ok(AstStmt::Each(AstEach {
             variables,
-            list,
+            list: list.node,
+            list_span: list.span,
             body,
+            span: self.toks_mut().span_from(start),
         }))
     }
 
@@ -626,11 +632,11 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
 
         return match self.plain_at_rule_name()?.as_str() {
             "debug" => self.parse_debug_rule(),
-            "each" => self.parse_each_rule(Self::function_child),
+            "each" => self.parse_each_rule(Self::function_child, start),
             "else" => self.parse_disallowed_at_rule(start),
             "error" => self.parse_error_rule(),
             "for" => self.parse_for_rule(Self::function_child),
-            "if" => self.parse_if_rule(Self::function_child),
+            "if" => self.parse_if_rule(Self::function_child, start),
             "return" => self.parse_return_rule(),
             "warn" => self.parse_warn_rule(),
             "while" => self.parse_while_rule(Self::function_child),
@@ -641,12 +647,13 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
     fn parse_if_rule(
         &mut self,
         child: fn(&mut Self) -> SassResult<AstStmt>,
+        start: usize,
     ) -> SassResult<AstStmt> {
         let if_indentation = self.current_indentation();
 
         let was_in_control_directive = self.flags().in_control_flow();
         self.flags_mut().set(ContextFlags::IN_CONTROL_FLOW, true);
-        let condition = self.parse_expression(None, None, None)?.node;
+        let condition = self.parse_expression(None, None, None)?;
         let body = self.parse_children(child)?;
         self.whitespace_without_comments();
 
@@ -658,7 +665,7 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
             self.whitespace()?;
             if self.scan_identifier("if", false)? {
                 self.whitespace()?;
-                let condition = self.parse_expression(None, None, None)?.node;
+                let condition = self.parse_expression(None, None, None)?;
                 let body = self.parse_children(child)?;
                 clauses.push(AstIfClause { condition, body });
             } else {
@@ -674,6 +681,7 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
         Ok(AstStmt::If(AstIf {
             if_clauses: clauses,
             else_clause: last_clause,
+            span: self.toks_mut().span_from(start),
         }))
     }
 
@@ -902,7 +910,10 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
             }
         }
 
-        Ok(AstStmt::ImportRule(AstImportRule { imports }))
+        Ok(AstStmt::ImportRule(AstImportRule {
+            imports,
+            span: self.toks_mut().span_from(start),
+        }))
     }
 
     fn parse_public_identifier(&mut self) -> SassResult<String> {
@@ -1068,7 +1079,12 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
     }
 
     fn parse_mixin_rule(&mut self, start: usize) -> SassResult<AstStmt> {
+        let name_start = self.toks().cursor();
         let name = Identifier::from(self.parse_identifier(true, false)?);
+        let name = Spanned {
+            node: name,
+            span: self.toks_mut().span_from(name_start),
+        };
         self.whitespace()?;
         let args = if self.toks_mut().next_char_is('(') {
             self.parse_argument_declaration()?
@@ -1368,7 +1384,7 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
         Ok(condition)
     }
 
-    fn parse_supports_rule(&mut self) -> SassResult<AstStmt> {
+    fn parse_supports_rule(&mut self, start: usize) -> SassResult<AstStmt> {
         let condition = self.parse_supports_condition()?;
         self.whitespace()?;
         let children = self.with_children(Self::parse_statement)?;
@@ -1376,7 +1392,7 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
         Ok(AstStmt::Supports(AstSupportsRule {
             condition,
             children: children.node,
-            span: children.span,
+            span: self.toks_mut().span_from(start),
         }))
     }
 
@@ -1396,7 +1412,7 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
         let was_in_control_directive = self.flags().in_control_flow();
         self.flags_mut().set(ContextFlags::IN_CONTROL_FLOW, true);
 
-        let condition = self.parse_expression(None, None, None)?.node;
+        let condition = self.parse_expression(None, None, None)?;
 
         let body = self.with_children(child)?.node;
 
@@ -1520,7 +1536,12 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
             });
         }
 
-        let base_name = url
+        // the path of a `pkg:` URL begins with the package name, so the
+        // scheme must not become part of the namespace
+        let url_path = url.to_string_lossy();
+        let url_path = Path::new(url_path.strip_prefix("pkg:").unwrap_or(&url_path));
+
+        let base_name = url_path
             .file_name()
             .map_or_else(OsString::new, ToOwned::to_owned);
         let base_name = base_name.to_string_lossy();
@@ -1683,7 +1704,7 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
             Some("at-root") => self.parse_at_root_rule(start),
             Some("content") => self.parse_content_rule(start),
             Some("debug") => self.parse_debug_rule(),
-            Some("each") => self.parse_each_rule(child),
+            Some("each") => self.parse_each_rule(child, start),
             Some("else") | Some("return") => self.parse_disallowed_at_rule(start),
             Some("error") => self.parse_error_rule(),
             Some("extend") => self.parse_extend_rule(start),
@@ -1697,14 +1718,14 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
                 self.parse_forward_rule(start)
             }
             Some("function") => self.parse_function_rule(start),
-            Some("if") => self.parse_if_rule(child),
+            Some("if") => self.parse_if_rule(child, start),
             Some("import") => self.parse_import_rule(start),
             Some("include") => self.parse_include_rule(),
             Some("media") => self.parse_media_rule(start),
             Some("mixin") => self.parse_mixin_rule(start),
             // todo: support -moz-document
             // Some("-moz-document") => self.parse_moz_document_rule(name),
-            Some("supports") => self.parse_supports_rule(),
+            Some("supports") => self.parse_supports_rule(start),
             Some("use") => {
                 self.flags_mut()
                     .set(ContextFlags::IS_USE_ALLOWED, was_use_allowed);
@@ -2512,11 +2533,11 @@ pub(crate) trait StylesheetParser<'a>: BaseParser<'a> + Sized {
         match name.as_str() {
             "content" => self.parse_content_rule(start),
             "debug" => self.parse_debug_rule(),
-            "each" => self.parse_each_rule(Self::parse_declaration_child),
+            "each" => self.parse_each_rule(Self::parse_declaration_child, start),
             "else" => self.parse_disallowed_at_rule(start),
             "error" => self.parse_error_rule(),
             "for" => self.parse_for_rule(Self::parse_declaration_child),
-            "if" => self.parse_if_rule(Self::parse_declaration_child),
+            "if" => self.parse_if_rule(Self::parse_declaration_child, start),
             "include" => self.parse_include_rule(),
             "warn" => self.parse_warn_rule(),
             "while" => self.parse_while_rule(Self::parse_declaration_child),
@@ -166,6 +166,10 @@ impl ComplexSelector {
             }
 
             if remaining1 == 1 {
+                let last = match other.components.last() {
+                    Some(ComplexSelectorComponent::Compound(c)) => c,
+                    Some(ComplexSelectorComponent::Combinator(..)) | None => return false,
+                };
                 let parents = other
                     .components
                     .iter()
@@ -173,10 +177,7 @@ impl ComplexSelector {
                     .skip(i2)
                     .cloned()
                     .collect();
-                return compound1.is_super_selector(
-                    other.components.last().unwrap().as_compound(),
-                    &Some(parents),
-                );
+                return compound1.is_super_selector(last, &Some(parents));
             }
 
             let mut after_super_selector = i2 + 1;
@@ -327,14 +328,7 @@ impl ComplexSelectorComponent {
     ) -> SassResult<Option<Vec<ComplexSelector>>> {
         match self {
             Self::Compound(c) => c.resolve_parent_selectors(parent),
-            Self::Combinator(..) => todo!(),
-        }
-    }
-
-    pub fn as_compound(&self) -> &CompoundSelector {
-        match self {
-            Self::Compound(c) => c,
-            Self::Combinator(..) => unreachable!(),
+            Self::Combinator(..) => Ok(None),
         }
     }
 }
@@ -162,12 +162,9 @@ impl ExtensionStore {
         let compound_targets = targets
             .components
             .into_iter()
-            .map(|complex| {
-                if complex.components.len() == 1 {
-                    Ok(complex.components.first().unwrap().as_compound().clone())
-                } else {
-                    Err((format!("Can't extend complex selector {}.", complex), span).into())
-                }
+            .map(|complex| match complex.components.as_slice() {
+                [ComplexSelectorComponent::Compound(compound)] => Ok(compound.clone()),
+                _ => Err((format!("Can't extend complex selector {}.", complex), span).into()),
             })
             .collect::<SassResult<Vec<CompoundSelector>>>()?;
 
@@ -935,6 +932,15 @@ impl ExtensionStore {
     /// The `media_context` defines the media query context in which the extension
     /// is defined. It can only extend selectors within the same context. A `None`
     /// context indicates no media queries.
+    /// The targets and spans of `@extend` rules whose target doesn't appear in
+    /// any selector in the stylesheet
+    pub fn unsatisfied_extensions(&self) -> impl Iterator<Item = (&SimpleSelector, Span)> + '_ {
+        self.extensions
+            .iter()
+            .filter(|(target, _)| !self.selectors.contains_key(target))
+            .flat_map(|(target, sources)| sources.values().map(move |source| (target, source.span)))
+    }
+
     pub fn add_extension(
         &mut self,
         extender: SelectorList,
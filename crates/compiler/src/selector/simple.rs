@@ -224,8 +224,8 @@ impl SimpleSelector {
     }
 
     fn unify_universal(self, mut compound: Vec<Self>) -> Option<Vec<Self>> {
-        if let Self::Universal(..) | Self::Type(..) = compound[0] {
-            let mut unified = vec![self.unify_universal_and_element(&compound[0])?];
+        if let Some(first @ (Self::Universal(..) | Self::Type(..))) = compound.first() {
+            let mut unified = vec![self.unify_universal_and_element(first)?];
             unified.extend(compound.into_iter().skip(1));
             return Some(unified);
         }
@@ -250,42 +250,21 @@ impl SimpleSelector {
     ///
     /// If no such selector can be produced, returns `None`.
     fn unify_universal_and_element(&self, other: &Self) -> Option<Self> {
-        let namespace1;
-        let name1;
-        if let SimpleSelector::Type(name) = self.clone() {
-            namespace1 = name.namespace;
-            name1 = name.ident;
-        } else if let SimpleSelector::Universal(namespace) = self.clone() {
-            namespace1 = namespace;
-            name1 = String::new();
-        } else {
-            todo!("ArgumentError.value(selector1, 'selector1', 'must be a UniversalSelector or a TypeSelector')")
-        }
+        let (namespace1, name1) = self.namespace_and_name()?;
+        let (namespace2, name2) = other.namespace_and_name()?;
 
-        let namespace2;
-        let mut name2 = String::new();
-
-        if let SimpleSelector::Universal(namespace) = other {
-            namespace2 = namespace.clone();
-        } else if let SimpleSelector::Type(name) = other {
-            namespace2 = name.namespace.clone();
-            name2 = name.ident.clone();
-        } else {
-            todo!("ArgumentError.value(selector2, 'selector2', 'must be a UniversalSelector or a TypeSelector');")
-        }
-
-        let namespace = if namespace1 == namespace2 || namespace2 == Namespace::Asterisk {
-            namespace1
-        } else if namespace1 == Namespace::Asterisk {
-            namespace2
+        let namespace = if namespace1 == namespace2 || *namespace2 == Namespace::Asterisk {
+            namespace1.clone()
+        } else if *namespace1 == Namespace::Asterisk {
+            namespace2.clone()
         } else {
             return None;
         };
 
         let name = if name1 == name2 || name2.is_empty() {
-            name1
+            name1.to_owned()
         } else if name1.is_empty() || name1 == "*" {
-            name2
+            name2.to_owned()
         } else {
             return None;
         };
@@ -300,9 +279,19 @@ impl SimpleSelector {
         })
     }
 
+    /// The namespace and element name of a `SimpleSelector::Type`, or the
+    /// namespace and an empty name of a `SimpleSelector::Universal`
+    fn namespace_and_name(&self) -> Option<(&Namespace, &str)> {
+        match self {
+            Self::Type(name) => Some((&name.namespace, &name.ident)),
+            Self::Universal(namespace) => Some((namespace, "")),
+            _ => None,
+        }
+    }
+
     fn unify_type(self, mut compound: Vec<Self>) -> Option<Vec<Self>> {
-        if let Self::Universal(..) | Self::Type(..) = compound[0] {
-            let mut unified = vec![self.unify_universal_and_element(&compound[0])?];
+        if let Some(first @ (Self::Universal(..) | Self::Type(..))) = compound.first() {
+            let mut unified = vec![self.unify_universal_and_element(first)?];
             unified.extend(compound.into_iter().skip(1));
             Some(unified)
         } else {
@@ -366,16 +355,12 @@ impl SimpleSelector {
             {
                 if SUBSELECTOR_PSEUDOS.contains(&unvendor(name)) {
                     return sel.components.iter().all(|complex| {
-                        if complex.components.len() != 1 {
-                            return false;
-                        };
-                        complex
-                            .components
-                            .first()
-                            .unwrap()
-                            .as_compound()
-                            .components
-                            .contains(self)
+                        match complex.components.as_slice() {
+                            [ComplexSelectorComponent::Compound(compound)] => {
+                                compound.components.contains(self)
+                            }
+                            _ => false,
+                        }
                     });
                 }
                 false
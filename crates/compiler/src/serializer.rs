@@ -1,4 +1,4 @@
-use std::io::Write;
+use std::io::{self, Write};
 
 use codemap::{CodeMap, Span};
 
@@ -11,10 +11,11 @@ use crate::{
         Combinator, ComplexSelector, ComplexSelectorComponent, CompoundSelector, Namespace, Pseudo,
         SelectorList, SimpleSelector,
     },
+    source_map::SourceMap,
     utils::hex_char_for,
     value::{
-        fuzzy_equals, ArgList, CalculationArg, CalculationName, SassCalculation, SassFunction,
-        SassMap, SassNumber, Value,
+        fuzzy_equals, ArgList, CalculationArg, CalculationName, Number, SassCalculation,
+        SassFunction, SassMap, SassNumber, Value,
     },
     Options,
 };
@@ -128,8 +129,16 @@ pub(crate) struct Serializer<'a> {
     // todo: use this field
     _quote: bool,
     buffer: Vec<u8>,
+    /// The number of bytes already written out of `buffer` by
+    /// [`Serializer::write_to`]
+    flushed: usize,
     map: &'a CodeMap,
     span: Span,
+    /// Byte offsets into the output paired with the span that produced the
+    /// text at that offset, if a source map is being generated
+    source_map_marks: Option<Vec<(usize, Span)>>,
+    prev_was_group_end: bool,
+    prev_requires_semicolon: bool,
 }
 
 impl<'a> Serializer<'a> {
@@ -141,8 +150,36 @@ impl<'a> Serializer<'a> {
             indent_width: 2,
             options,
             buffer: Vec::new(),
+            flushed: 0,
             map,
             span,
+            source_map_marks: None,
+            prev_was_group_end: false,
+            prev_requires_semicolon: false,
+        }
+    }
+
+    pub fn with_source_map(mut self) -> Self {
+        self.source_map_marks = Some(Vec::new());
+        self
+    }
+
+    fn mark(&mut self, span: Span) {
+        if let Some(marks) = &mut self.source_map_marks {
+            marks.push((self.flushed + self.buffer.len(), span));
+        }
+    }
+
+    /// Returns an error if the CSS written so far is larger than
+    /// [`Options::max_output_size`]
+    fn check_output_size(&self, span: Span) -> SassResult<()> {
+        match self.options.max_output_size {
+            Some(max) if self.flushed + self.buffer.len() > max => Err((
+                format!("Output exceeded the maximum size of {} bytes.", max),
+                span,
+            )
+                .into()),
+            _ => Ok(()),
         }
     }
 
@@ -556,93 +593,118 @@ impl<'a> Serializer<'a> {
     }
 
     fn write_float(&mut self, float: f64) {
-        if float.is_infinite() && float.is_sign_negative() {
-            self.buffer.extend_from_slice(b"-Infinity");
-            return;
-        } else if float.is_infinite() {
-            self.buffer.extend_from_slice(b"Infinity");
-            return;
-        }
-
-        // todo: can optimize away intermediate buffer
-        let mut buffer = String::with_capacity(3);
-
-        if float < 0.0 {
-            buffer.push('-');
-        }
+        let formatted = Number(float).to_string(self.options.is_compressed());
 
-        let num = float.abs();
+        self.buffer.extend_from_slice(formatted.as_bytes());
+    }
 
-        if self.options.is_compressed() && num < 1.0 {
-            buffer.push_str(
-                format!("{:.10}", num)[1..]
-                    .trim_end_matches('0')
-                    .trim_end_matches('.'),
-            );
-        } else {
-            buffer.push_str(
-                format!("{:.10}", num)
-                    .trim_end_matches('0')
-                    .trim_end_matches('.'),
-            );
-        }
+    /// Whether any CSS has been written so far
+    fn is_empty(&self) -> bool {
+        self.flushed == 0 && self.buffer.is_empty()
+    }
 
-        if buffer.is_empty() || buffer == "-" || buffer == "-0" {
-            buffer = "0".to_owned();
+    /// Serializes a top-level statement, separating it from the previous one
+    pub fn visit_group(&mut self, stmt: &CssStmt) -> SassResult<()> {
+        if stmt.is_invisible() {
+            return Ok(());
         }
 
-        self.buffer.append(&mut buffer.into_bytes());
-    }
-
-    pub fn visit_group(
-        &mut self,
-        stmt: CssStmt,
-        prev_was_group_end: bool,
-        prev_requires_semicolon: bool,
-    ) -> SassResult<()> {
-        if prev_requires_semicolon {
+        if self.prev_requires_semicolon {
             self.buffer.push(b';');
         }
 
-        if !self.buffer.is_empty() {
+        if !self.is_empty() {
             self.write_optional_newline();
         }
 
-        if prev_was_group_end && !self.buffer.is_empty() {
+        if self.prev_was_group_end && !self.is_empty() {
             self.write_optional_newline();
         }
 
         self.visit_stmt(stmt)?;
 
-        Ok(())
+        self.prev_was_group_end = stmt.is_group_end();
+        self.prev_requires_semicolon = Self::requires_semicolon(stmt);
+
+        self.check_output_size(self.span)
     }
 
-    fn finish_for_expr(self) -> String {
-        // SAFETY: todo
-        unsafe { String::from_utf8_unchecked(self.buffer) }
+    /// The number of bytes serialized but not yet written
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
     }
 
-    pub fn finish(mut self, prev_requires_semicolon: bool) -> String {
-        let is_not_ascii = self.buffer.iter().any(|&c| !c.is_ascii());
+    /// Whether the bytes serialized but not yet written are ASCII, ignoring
+    /// the first `start`, which have already been checked
+    pub fn is_ascii_after(&self, start: usize) -> bool {
+        self.buffer[start..].is_ascii()
+    }
 
-        if prev_requires_semicolon {
+    /// The text which must precede the output, if it contains any non-ASCII
+    /// characters
+    pub fn charset_prefix(options: &Options, is_not_ascii: bool) -> &'static str {
+        if is_not_ascii && options.is_compressed() {
+            "\u{FEFF}"
+        } else if is_not_ascii {
+            "@charset \"UTF-8\";\n"
+        } else {
+            ""
+        }
+    }
+
+    /// Writes the CSS serialized so far to `writer`
+    pub fn write_to(&mut self, writer: &mut dyn Write) -> io::Result<()> {
+        writer.write_all(&self.buffer)?;
+        self.flushed += self.buffer.len();
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Terminates the last top-level statement
+    fn write_end(&mut self) {
+        if self.prev_requires_semicolon {
             self.buffer.push(b';');
         }
 
-        if !self.buffer.is_empty() {
+        if !self.is_empty() {
             self.write_optional_newline();
         }
+    }
+
+    fn finish_for_expr(self) -> String {
+        // SAFETY: todo
+        unsafe { String::from_utf8_unchecked(self.buffer) }
+    }
+
+    pub fn finish(mut self) -> (String, Option<SourceMap>) {
+        let is_not_ascii = !self.buffer.is_ascii();
+
+        self.write_end();
 
         // SAFETY: todo
         let mut as_string = unsafe { String::from_utf8_unchecked(self.buffer) };
 
-        if is_not_ascii && self.options.is_compressed() {
-            as_string.insert(0, '\u{FEFF}');
-        } else if is_not_ascii {
-            as_string.insert_str(0, "@charset \"UTF-8\";\n");
-        }
+        let prefix = Self::charset_prefix(self.options, is_not_ascii);
+
+        as_string.insert_str(0, prefix);
+
+        let source_map = self.source_map_marks.map(|mut marks| {
+            for (offset, _) in &mut marks {
+                *offset += prefix.len();
+            }
+
+            SourceMap::new(self.map, &as_string, &marks)
+        });
+
+        (as_string, source_map)
+    }
 
-        as_string
+    /// Terminates the last top-level statement and writes the remaining CSS
+    /// to `writer`
+    pub fn finish_to(mut self, writer: &mut dyn Write) -> io::Result<()> {
+        self.write_end();
+        self.write_to(writer)?;
+        writer.flush()
     }
 
     fn write_indentation(&mut self) {
@@ -689,16 +751,18 @@ impl<'a> Serializer<'a> {
         }
     }
 
-    fn visit_list(
+    fn visit_list<'v>(
         &mut self,
-        list_elems: &[Value],
+        list_elems: impl ExactSizeIterator<Item = &'v Value>,
         sep: ListSeparator,
         brackets: Brackets,
         span: Span,
     ) -> SassResult<()> {
+        let len = list_elems.len();
+
         if brackets == Brackets::Bracketed {
             self.buffer.push(b'[');
-        } else if list_elems.is_empty() {
+        } else if len == 0 {
             if !self.inspect {
                 return Err(("() isn't a valid CSS value.", span).into());
             }
@@ -708,7 +772,7 @@ impl<'a> Serializer<'a> {
         }
 
         let is_singleton = self.inspect
-            && list_elems.len() == 1
+            && len == 1
             && (sep == ListSeparator::Comma || sep == ListSeparator::Slash);
 
         if is_singleton && brackets != Brackets::Bracketed {
@@ -717,10 +781,10 @@ impl<'a> Serializer<'a> {
 
         let (mut x, mut y);
         let elems: &mut dyn Iterator<Item = &Value> = if self.inspect {
-            x = list_elems.iter();
+            x = list_elems;
             &mut x
         } else {
-            y = list_elems.iter().filter(|elem| !elem.is_blank());
+            y = list_elems.filter(|elem| !elem.is_blank());
             &mut y
         };
 
@@ -925,7 +989,12 @@ impl<'a> Serializer<'a> {
     }
 
     fn visit_arglist(&mut self, arglist: &ArgList, span: Span) -> SassResult<()> {
-        self.visit_list(&arglist.elems, ListSeparator::Comma, Brackets::None, span)
+        self.visit_list(
+            arglist.elems.iter(),
+            ListSeparator::Comma,
+            Brackets::None,
+            span,
+        )
     }
 
     fn visit_value(&mut self, value: &Value, span: Span) -> SassResult<()> {
@@ -933,7 +1002,9 @@ impl<'a> Serializer<'a> {
             Value::Dimension(num) => self.visit_number(num)?,
             Value::Color(color) => self.visit_color(color),
             Value::Calculation(calc) => self.visit_calculation(calc)?,
-            Value::List(elems, sep, brackets) => self.visit_list(elems, *sep, *brackets, span)?,
+            Value::List(elems, sep, brackets) => {
+                self.visit_list(elems.iter(), *sep, *brackets, span)?;
+            }
             Value::True => self.buffer.extend_from_slice(b"true"),
             Value::False => self.buffer.extend_from_slice(b"false"),
             Value::Null => {
@@ -951,11 +1022,12 @@ impl<'a> Serializer<'a> {
         Ok(())
     }
 
-    fn write_style(&mut self, style: Style) -> SassResult<()> {
+    fn write_style(&mut self, style: &Style) -> SassResult<()> {
         if !self.options.is_compressed() {
             self.write_indentation();
         }
 
+        self.mark(style.span);
         self.buffer
             .extend_from_slice(style.property.resolve_ref().as_bytes());
         self.buffer.push(b':');
@@ -967,10 +1039,10 @@ impl<'a> Serializer<'a> {
 
         self.visit_value(&style.value.node, style.value.span)?;
 
-        Ok(())
+        self.check_output_size(style.span)
     }
 
-    fn write_import(&mut self, import: &str, modifiers: Option<String>) -> SassResult<()> {
+    fn write_import(&mut self, import: &str, modifiers: Option<&str>) -> SassResult<()> {
         self.write_indentation();
         self.buffer.extend_from_slice(b"@import ");
         write!(&mut self.buffer, "{}", import)?;
@@ -1019,7 +1091,7 @@ impl<'a> Serializer<'a> {
         }
     }
 
-    fn write_children(&mut self, mut children: Vec<CssStmt>) -> SassResult<()> {
+    fn write_children(&mut self, children: &[CssStmt]) -> SassResult<()> {
         if self.options.is_compressed() {
             self.buffer.push(b'{');
         } else {
@@ -1028,10 +1100,13 @@ impl<'a> Serializer<'a> {
 
         self.indentation += self.indent_width;
 
-        let last = children.pop();
+        let (last, children) = match children.split_last() {
+            Some((last, children)) => (Some(last), children),
+            None => (None, children),
+        };
 
         for child in children {
-            let needs_semicolon = Self::requires_semicolon(&child);
+            let needs_semicolon = Self::requires_semicolon(child);
             let did_write = self.visit_stmt(child)?;
 
             if !did_write {
@@ -1046,7 +1121,7 @@ impl<'a> Serializer<'a> {
         }
 
         if let Some(last) = last {
-            let needs_semicolon = Self::requires_semicolon(&last);
+            let needs_semicolon = Self::requires_semicolon(last);
             let did_write = self.visit_stmt(last)?;
 
             if did_write {
@@ -1082,7 +1157,7 @@ impl<'a> Serializer<'a> {
         }
     }
 
-    fn write_supports_rule(&mut self, supports_rule: SupportsRule) -> SassResult<()> {
+    fn write_supports_rule(&mut self, supports_rule: &SupportsRule) -> SassResult<()> {
         self.write_indentation();
         self.buffer.extend_from_slice(b"@supports");
 
@@ -1092,21 +1167,28 @@ impl<'a> Serializer<'a> {
                 .extend_from_slice(supports_rule.params.as_bytes());
         }
 
-        self.write_children(supports_rule.body)?;
+        self.write_children(&supports_rule.body)?;
 
         Ok(())
     }
 
     /// Returns whether or not text was written
-    fn visit_stmt(&mut self, stmt: CssStmt) -> SassResult<bool> {
+    fn visit_stmt(&mut self, stmt: &CssStmt) -> SassResult<bool> {
         if stmt.is_invisible() {
             return Ok(false);
         }
 
         match stmt {
-            CssStmt::RuleSet { selector, body, .. } => {
+            CssStmt::RuleSet {
+                selector,
+                body,
+                span,
+                ..
+            } => {
                 self.write_indentation();
+                self.mark(*span);
                 self.write_selector_list(&selector.as_selector_list());
+                self.check_output_size(*span)?;
 
                 self.write_children(body)?;
             }
@@ -1126,7 +1208,7 @@ impl<'a> Serializer<'a> {
                     self.write_media_query(last);
                 }
 
-                self.write_children(media_rule.body)?;
+                self.write_children(&media_rule.body)?;
             }
             CssStmt::UnknownAtRule(unknown_at_rule, ..) => {
                 self.write_indentation();
@@ -1146,25 +1228,27 @@ impl<'a> Serializer<'a> {
                     return Ok(true);
                 }
 
-                self.write_children(unknown_at_rule.body)?;
+                self.write_children(&unknown_at_rule.body)?;
             }
             CssStmt::Style(style) => self.write_style(style)?,
-            CssStmt::Comment(comment, span) => self.write_comment(&comment, span)?,
+            CssStmt::Comment(comment, span) => self.write_comment(comment, *span)?,
             CssStmt::KeyframesRuleSet(keyframes_rule_set) => {
                 self.write_indentation();
                 // todo: i bet we can do something like write_with_separator to avoid extra allocation
                 let selector = keyframes_rule_set
                     .selector
-                    .into_iter()
+                    .iter()
                     .map(|s| s.to_string())
                     .collect::<Vec<String>>()
                     .join(", ");
 
                 self.buffer.extend_from_slice(selector.as_bytes());
 
-                self.write_children(keyframes_rule_set.body)?;
+                self.write_children(&keyframes_rule_set.body)?;
+            }
+            CssStmt::Import(import, modifier) => {
+                self.write_import(import, modifier.as_deref())?;
             }
-            CssStmt::Import(import, modifier) => self.write_import(&import, modifier)?,
             CssStmt::Supports(supports_rule, _) => self.write_supports_rule(supports_rule)?,
         }
 
@@ -0,0 +1,214 @@
+//! Source maps, mapping generated CSS back to the Sass it was compiled from
+//!
+//! Mappings are emitted at the start of each selector and declaration, which
+//! is the granularity browser dev tools use when showing where a style came
+//! from.
+
+use codemap::{CodeMap, Span};
+
+use crate::utils::write_json_string;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A [version 3 source map](https://sourcemaps.info/spec.html) for a compiled
+/// stylesheet
+///
+/// Sources are named the same way as files in error messages: by their path,
+/// `"stdin"` for the root stylesheet of [`crate::from_string`], or the
+/// canonical URL for stylesheets loaded by an [`crate::Importer`].
+///
+/// ```
+/// # use grass_compiler as grass;
+/// # fn main() -> Result<(), Box<grass::Error>> {
+/// let (css, source_map) = grass::from_string_with_source_map(
+///     "a {\n  color: red;\n}".to_owned(),
+///     &grass::Options::default(),
+/// )?;
+///
+/// assert_eq!(css, "a {\n  color: red;\n}\n");
+/// assert_eq!(source_map.sources(), ["stdin"]);
+/// assert_eq!(source_map.mappings(), "AAAA;EACE");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    sources_content: Vec<String>,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Builds a source map for `css`, where each entry of `marks` is a byte
+    /// offset into `css` and the span of the Sass that produced it
+    pub(crate) fn new(map: &CodeMap, css: &str, marks: &[(usize, Span)]) -> Self {
+        let mut source_map = Self {
+            sources: Vec::new(),
+            sources_content: Vec::new(),
+            mappings: String::new(),
+        };
+
+        let mut marks = marks.iter().peekable();
+
+        let mut column = 0;
+
+        // the previous segment's fields, which the next segment is relative to
+        let mut prev_column = 0;
+        let mut prev_source = 0;
+        let mut prev_source_line = 0;
+        let mut prev_source_column = 0;
+        let mut line_has_segment = false;
+
+        for (offset, c) in css.char_indices().chain(std::iter::once((css.len(), '\0'))) {
+            let mut prev_offset = None;
+            while let Some(&&(mark_offset, span)) = marks.peek() {
+                if mark_offset > offset {
+                    break;
+                }
+
+                marks.next();
+
+                // only the first mark at a position is meaningful
+                if prev_offset == Some(mark_offset) {
+                    continue;
+                }
+                prev_offset = Some(mark_offset);
+
+                let loc = map.look_up_pos(span.low());
+                let source = source_map.source_index(loc.file.name(), loc.file.source());
+                let source_line = loc.position.line as i64;
+                let source_column = loc
+                    .file
+                    .source_line(loc.position.line)
+                    .chars()
+                    .take(loc.position.column)
+                    .map(char::len_utf16)
+                    .sum::<usize>() as i64;
+
+                if line_has_segment {
+                    source_map.mappings.push(',');
+                }
+                line_has_segment = true;
+
+                write_vlq(&mut source_map.mappings, column - prev_column);
+                write_vlq(&mut source_map.mappings, source - prev_source);
+                write_vlq(&mut source_map.mappings, source_line - prev_source_line);
+                write_vlq(&mut source_map.mappings, source_column - prev_source_column);
+
+                prev_column = column;
+                prev_source = source;
+                prev_source_line = source_line;
+                prev_source_column = source_column;
+            }
+
+            if c == '\n' {
+                source_map.mappings.push(';');
+                column = 0;
+                prev_column = 0;
+                line_has_segment = false;
+            } else {
+                column += c.len_utf16() as i64;
+            }
+        }
+
+        let trimmed = source_map.mappings.trim_end_matches(';').len();
+        source_map.mappings.truncate(trimmed);
+
+        source_map
+    }
+
+    fn source_index(&mut self, name: &str, content: &str) -> i64 {
+        match self.sources.iter().position(|source| source == name) {
+            Some(idx) => idx as i64,
+            None => {
+                self.sources.push(name.to_owned());
+                self.sources_content.push(content.to_owned());
+                self.sources.len() as i64 - 1
+            }
+        }
+    }
+
+    /// Replaces the name of each source with `url(name)`
+    pub(crate) fn map_sources(&mut self, mut url: impl FnMut(&str) -> String) {
+        for source in &mut self.sources {
+            *source = url(source);
+        }
+    }
+
+    /// The names of the stylesheets referenced by this source map
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    /// The contents of each of [`SourceMap::sources`]
+    pub fn sources_content(&self) -> &[String] {
+        &self.sources_content
+    }
+
+    /// The Base64 VLQ encoded mappings
+    pub fn mappings(&self) -> &str {
+        &self.mappings
+    }
+
+    /// Serializes this source map as JSON
+    ///
+    /// `file` is the name of the generated CSS file, if known. If
+    /// `include_sources` is true, the contents of each source are embedded in
+    /// the source map, so that it can be used without access to the Sass files.
+    pub fn to_json(&self, file: Option<&str>, include_sources: bool) -> String {
+        let mut json = String::from("{\"version\":3");
+
+        if let Some(file) = file {
+            json.push_str(",\"file\":");
+            write_json_string(&mut json, file);
+        }
+
+        json.push_str(",\"sources\":");
+        write_json_array(&mut json, &self.sources);
+
+        if include_sources {
+            json.push_str(",\"sourcesContent\":");
+            write_json_array(&mut json, &self.sources_content);
+        }
+
+        json.push_str(",\"names\":[],\"mappings\":");
+        write_json_string(&mut json, &self.mappings);
+        json.push('}');
+
+        json
+    }
+}
+
+fn write_vlq(out: &mut String, value: i64) {
+    let mut vlq = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+
+    loop {
+        let mut digit = vlq & 0b11111;
+        vlq >>= 5;
+
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+
+        out.push(BASE64[digit as usize] as char);
+
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+fn write_json_array(out: &mut String, strings: &[String]) {
+    out.push('[');
+    for (idx, s) in strings.iter().enumerate() {
+        if idx != 0 {
+            out.push(',');
+        }
+        write_json_string(out, s);
+    }
+    out.push(']');
+}
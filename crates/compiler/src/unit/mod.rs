@@ -163,6 +163,29 @@ impl Unit {
         matches!(self, Unit::Complex(complex) if complex.numer.len() != 1 || !complex.denom.is_empty())
     }
 
+    /// The unit which every unit comparable to this one can be converted to
+    pub fn canonical(&self) -> Unit {
+        match self.kind() {
+            UnitKind::Absolute => Unit::Px,
+            UnitKind::Angle => Unit::Deg,
+            UnitKind::Time => Unit::S,
+            UnitKind::Frequency => Unit::Hz,
+            UnitKind::Resolution => Unit::Dppx,
+            UnitKind::FontRelative
+            | UnitKind::ViewportRelative
+            | UnitKind::Other
+            | UnitKind::None => self.clone(),
+        }
+    }
+
+    /// Whether numbers with this unit can be converted to other units
+    pub fn is_convertible(&self) -> bool {
+        !matches!(
+            self.kind(),
+            UnitKind::FontRelative | UnitKind::ViewportRelative | UnitKind::Other | UnitKind::None
+        )
+    }
+
     pub fn comparable(&self, other: &Unit) -> bool {
         if other == &Unit::None {
             return true;
@@ -0,0 +1,166 @@
+use std::{fmt, sync::Arc};
+
+const BITS: u32 = 6;
+const MASK: u64 = (1 << BITS) - 1;
+
+/// A map from 64-bit hashes to values which is cheap to clone, and which only
+/// copies the nodes along the path to an entry when it is inserted or removed
+///
+/// Each node holds up to 64 entries, indexed by 6 bits of the hash and stored
+/// compactly using a bitmap of which are present, as in a hash array mapped
+/// trie.
+pub(crate) struct HashTrie<V> {
+    root: Arc<Node<V>>,
+}
+
+#[derive(Clone)]
+struct Node<V> {
+    bitmap: u64,
+    entries: Vec<Entry<V>>,
+}
+
+#[derive(Clone)]
+enum Entry<V> {
+    Leaf(u64, V),
+    Branch(Arc<Node<V>>),
+}
+
+impl<V> Node<V> {
+    const fn new() -> Self {
+        Self {
+            bitmap: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// The bit for `hash` in the bitmap of a node at `shift`, and the position
+    /// its entry would have in `entries`
+    fn position(&self, hash: u64, shift: u32) -> (u64, usize) {
+        let bit = 1 << ((hash >> shift) & MASK);
+        (bit, (self.bitmap & (bit - 1)).count_ones() as usize)
+    }
+}
+
+impl<V: Clone> Node<V> {
+    fn insert(&mut self, shift: u32, hash: u64, value: V) {
+        let (bit, idx) = self.position(hash, shift);
+
+        if self.bitmap & bit == 0 {
+            self.bitmap |= bit;
+            self.entries.insert(idx, Entry::Leaf(hash, value));
+            return;
+        }
+
+        match &mut self.entries[idx] {
+            Entry::Leaf(existing, old) if *existing == hash => *old = value,
+            Entry::Leaf(..) => {
+                let mut branch = Node::new();
+
+                if let Entry::Leaf(existing, old) =
+                    std::mem::replace(&mut self.entries[idx], Entry::Branch(Arc::new(Node::new())))
+                {
+                    branch.insert(shift + BITS, existing, old);
+                }
+
+                branch.insert(shift + BITS, hash, value);
+                self.entries[idx] = Entry::Branch(Arc::new(branch));
+            }
+            Entry::Branch(child) => Arc::make_mut(child).insert(shift + BITS, hash, value),
+        }
+    }
+
+    fn remove(&mut self, shift: u32, hash: u64) -> Option<V> {
+        let (bit, idx) = self.position(hash, shift);
+
+        if self.bitmap & bit == 0 {
+            return None;
+        }
+
+        match &mut self.entries[idx] {
+            Entry::Leaf(existing, ..) if *existing == hash => {
+                self.bitmap &= !bit;
+
+                match self.entries.remove(idx) {
+                    Entry::Leaf(_, value) => Some(value),
+                    Entry::Branch(..) => unreachable!(),
+                }
+            }
+            Entry::Leaf(..) => None,
+            Entry::Branch(child) => {
+                let child = Arc::make_mut(child);
+                let removed = child.remove(shift + BITS, hash);
+
+                // a branch left with a single leaf is replaced by that leaf, so
+                // that lookups don't pass through it
+                if child.entries.len() == 1 && matches!(child.entries[0], Entry::Leaf(..)) {
+                    let leaf = child.entries.pop().unwrap();
+                    self.entries[idx] = leaf;
+                }
+
+                removed
+            }
+        }
+    }
+}
+
+impl<V> HashTrie<V> {
+    pub fn new() -> Self {
+        Self {
+            root: Arc::new(Node::new()),
+        }
+    }
+
+    pub fn get(&self, hash: u64) -> Option<&V> {
+        let mut node = &*self.root;
+        let mut shift = 0;
+
+        loop {
+            let (bit, idx) = node.position(hash, shift);
+
+            if node.bitmap & bit == 0 {
+                return None;
+            }
+
+            match &node.entries[idx] {
+                Entry::Leaf(existing, value) => return (*existing == hash).then_some(value),
+                Entry::Branch(child) => {
+                    node = child;
+                    shift += BITS;
+                }
+            }
+        }
+    }
+}
+
+impl<V: Clone> HashTrie<V> {
+    /// Inserts `value` for `hash`, replacing any existing value
+    pub fn insert(&mut self, hash: u64, value: V) {
+        Arc::make_mut(&mut self.root).insert(0, hash, value);
+    }
+
+    pub fn remove(&mut self, hash: u64) -> Option<V> {
+        self.get(hash)?;
+
+        Arc::make_mut(&mut self.root).remove(0, hash)
+    }
+}
+
+impl<V> Clone for HashTrie<V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: Arc::clone(&self.root),
+        }
+    }
+}
+
+impl<V> Default for HashTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> fmt::Debug for HashTrie<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashTrie").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,251 @@
+//! A minimal JSON parser, sufficient for reading `package.json` files, and
+//! the string escaping used when writing JSON
+
+use std::{fmt::Write, iter::Peekable, str::Chars};
+
+/// The deepest nesting of arrays and objects that is parsed, so that
+/// malicious or broken manifests can't overflow the stack
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Keys are kept in source order, which is significant for conditional
+    /// exports
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut parser = JsonParser {
+            chars: s.chars().peekable(),
+            depth: 0,
+        };
+
+        let value = parser.parse_value()?;
+        parser.whitespace();
+
+        match parser.chars.next() {
+            Some(c) => Err(format!("unexpected character {:?}", c)),
+            None => Ok(value),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// The number of arrays and objects currently being parsed
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected {:?}, found {:?}", expected, c)),
+            None => Err(format!("expected {:?}, found end of input", expected)),
+        }
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<(), String> {
+        word.chars().try_for_each(|c| self.expect(c))
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        self.whitespace();
+
+        match self.chars.peek() {
+            Some('{') => self.parse_nested(Self::parse_object),
+            Some('[') => self.parse_nested(Self::parse_array),
+            Some('"') => Ok(Json::String(self.parse_string()?)),
+            Some('t') => self.expect_word("true").map(|()| Json::Bool(true)),
+            Some('f') => self.expect_word("false").map(|()| Json::Bool(false)),
+            Some('n') => self.expect_word("null").map(|()| Json::Null),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some(c) => Err(format!("unexpected character {:?}", c)),
+            None => Err("unexpected end of input".to_owned()),
+        }
+    }
+
+    fn parse_nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Json, String>,
+    ) -> Result<Json, String> {
+        if self.depth == MAX_DEPTH {
+            return Err("arrays and objects are nested too deeply".to_owned());
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn parse_object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        self.whitespace();
+
+        let mut entries = Vec::new();
+
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+            return Ok(Json::Object(entries));
+        }
+
+        loop {
+            self.whitespace();
+            let key = self.parse_string()?;
+            self.whitespace();
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            entries.push((key, value));
+            self.whitespace();
+
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(entries)),
+                Some(c) => return Err(format!("expected ',' or '}}', found {:?}", c)),
+                None => return Err("unexpected end of input".to_owned()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        self.whitespace();
+
+        let mut elems = Vec::new();
+
+        if self.chars.peek() == Some(&']') {
+            self.chars.next();
+            return Ok(Json::Array(elems));
+        }
+
+        loop {
+            elems.push(self.parse_value()?);
+            self.whitespace();
+
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(elems)),
+                Some(c) => return Err(format!("expected ',' or ']', found {:?}", c)),
+                None => return Err("unexpected end of input".to_owned()),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut buffer = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(buffer),
+                Some('\\') => match self.chars.next() {
+                    Some('"') => buffer.push('"'),
+                    Some('\\') => buffer.push('\\'),
+                    Some('/') => buffer.push('/'),
+                    Some('b') => buffer.push('\u{8}'),
+                    Some('f') => buffer.push('\u{c}'),
+                    Some('n') => buffer.push('\n'),
+                    Some('r') => buffer.push('\r'),
+                    Some('t') => buffer.push('\t'),
+                    Some('u') => buffer.push(self.parse_unicode_escape()?),
+                    Some(c) => return Err(format!("invalid escape {:?}", c)),
+                    None => return Err("unexpected end of input".to_owned()),
+                },
+                Some(c) => buffer.push(c),
+                None => return Err("unterminated string".to_owned()),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let mut value = 0;
+
+        for _ in 0..4 {
+            let digit = self
+                .chars
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| "invalid unicode escape".to_owned())?;
+            value = value * 16 + digit;
+        }
+
+        Ok(value)
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.parse_hex4()?;
+
+        let code_point = if (0xD800..0xDC00).contains(&high) {
+            self.expect_word("\\u")?;
+            let low = self.parse_hex4()?;
+            0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+        } else {
+            high
+        };
+
+        Ok(char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn parse_number(&mut self) -> Result<Json, String> {
+        let mut buffer = String::new();
+
+        while let Some(&c) = self.chars.peek() {
+            if !matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9') {
+                break;
+            }
+            buffer.push(c);
+            self.chars.next();
+        }
+
+        buffer
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| format!("invalid number {:?}", buffer))
+    }
+}
+
+/// Appends `s` to `out` as a quoted JSON string
+pub fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
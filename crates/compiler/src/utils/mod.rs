@@ -1,11 +1,47 @@
+//! Helpers shared between the compiler and the `grass` command line
+
+use codemap::{CodeMap, Span, SpanLoc};
+
 pub(crate) use chars::*;
+pub(crate) use hash_trie::HashTrie;
+pub use json::write_json_string;
+pub(crate) use json::Json;
 pub(crate) use map_view::*;
+pub(crate) use persistent_vec::PersistentVec;
 pub(crate) use strings::*;
 
 mod chars;
+mod hash_trie;
+mod json;
 mod map_view;
+mod persistent_vec;
 mod strings;
 
+/// Looks up the location of `span`, widening it to the nearest character
+/// boundaries
+///
+/// Spans computed from evaluated text, such as selectors containing escapes,
+/// may not line up with the characters of the original source, which would
+/// cause [`CodeMap::look_up_span`] to panic
+pub(crate) fn look_up_span(map: &CodeMap, span: Span) -> SpanLoc {
+    let file = map.find_file(span.low());
+    let source = file.source();
+    let file_start = file.span.low();
+
+    let mut low = ((span.low() - file_start) as usize).min(source.len());
+    let mut high = ((span.high() - file_start) as usize).clamp(low, source.len());
+
+    while !source.is_char_boundary(low) {
+        low -= 1;
+    }
+
+    while !source.is_char_boundary(high) {
+        high += 1;
+    }
+
+    map.look_up_span(file.span.subspan(low as u64, high as u64))
+}
+
 #[allow(clippy::case_sensitive_file_extension_comparisons)]
 pub(crate) fn is_plain_css_import(url: &str) -> bool {
     if url.len() < 5 {
@@ -0,0 +1,394 @@
+use std::{
+    fmt,
+    iter::FromIterator,
+    ops::{Index, IndexMut},
+    sync::Arc,
+    vec::IntoIter,
+};
+
+const BITS: usize = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+/// A vector which is cheap to clone, and which can be pushed to and modified
+/// without copying all of its elements
+///
+/// Elements are stored in a tree of 32-element chunks, with the last chunk
+/// kept outside the tree so that most pushes only touch it, in the same manner
+/// as Clojure's persistent vectors. Clones share their chunks, and a chunk is
+/// only copied when a clone which shares it is modified.
+pub(crate) struct PersistentVec<T> {
+    len: usize,
+    /// The amount `index` is shifted by to find the child of `root` it is in
+    shift: usize,
+    root: Arc<Node<T>>,
+    tail: Arc<Vec<T>>,
+}
+
+#[derive(Clone)]
+enum Node<T> {
+    Branch(Vec<Arc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T> Node<T> {
+    fn extend_cloned(&self, vec: &mut Vec<T>)
+    where
+        T: Clone,
+    {
+        match self {
+            Node::Branch(children) => {
+                for child in children {
+                    child.extend_cloned(vec);
+                }
+            }
+            Node::Leaf(elems) => vec.extend_from_slice(elems),
+        }
+    }
+
+    fn drain_into(node: Arc<Self>, vec: &mut Vec<T>)
+    where
+        T: Clone,
+    {
+        match Arc::try_unwrap(node) {
+            Ok(Node::Branch(children)) => {
+                for child in children {
+                    Self::drain_into(child, vec);
+                }
+            }
+            Ok(Node::Leaf(elems)) => vec.extend(elems),
+            Err(node) => node.extend_cloned(vec),
+        }
+    }
+}
+
+impl<T> PersistentVec<T> {
+    pub fn new() -> Self {
+        Self {
+            len: 0,
+            shift: BITS,
+            root: Arc::new(Node::Branch(Vec::new())),
+            tail: Arc::new(Vec::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        Some(&self.chunk(index)[index & MASK])
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            vec: self,
+            chunk: &[],
+            front: 0,
+            back: self.len,
+        }
+    }
+
+    fn tail_offset(&self) -> usize {
+        self.len - self.tail.len()
+    }
+
+    /// The chunk of 32 elements containing `index`
+    fn chunk(&self, index: usize) -> &[T] {
+        if index >= self.tail_offset() {
+            return &self.tail;
+        }
+
+        let mut node = &*self.root;
+        let mut shift = self.shift;
+
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = &children[(index >> shift) & MASK];
+                    shift -= BITS;
+                }
+                Node::Leaf(elems) => return elems,
+            }
+        }
+    }
+}
+
+impl<T: Clone> PersistentVec<T> {
+    pub fn push(&mut self, value: T) {
+        if self.tail.len() < WIDTH {
+            Arc::make_mut(&mut self.tail).push(value);
+            self.len += 1;
+            return;
+        }
+
+        let tail = std::mem::replace(&mut self.tail, Arc::new(vec![value]));
+        let leaf = Arc::new(Node::Leaf(
+            Arc::try_unwrap(tail).unwrap_or_else(|tail| (*tail).clone()),
+        ));
+
+        // the tree is full, so it gains a level
+        if (self.len >> BITS) > (1 << self.shift) {
+            let old_root = std::mem::replace(&mut self.root, Arc::new(Node::Branch(Vec::new())));
+            self.root = Arc::new(Node::Branch(vec![
+                old_root,
+                Self::new_path(self.shift, leaf),
+            ]));
+            self.shift += BITS;
+        } else {
+            Self::push_leaf(
+                Arc::make_mut(&mut self.root),
+                self.shift,
+                self.len - WIDTH,
+                leaf,
+            );
+        }
+
+        self.len += 1;
+    }
+
+    /// Adds `leaf` to the tree rooted at `node`, as the chunk starting at
+    /// `index`
+    fn push_leaf(node: &mut Node<T>, shift: usize, index: usize, leaf: Arc<Node<T>>) {
+        let children = match node {
+            Node::Branch(children) => children,
+            Node::Leaf(..) => unreachable!("leaves are only found at the bottom of the tree"),
+        };
+
+        if shift == BITS {
+            children.push(leaf);
+            return;
+        }
+
+        let child_index = (index >> shift) & MASK;
+
+        if child_index < children.len() {
+            Self::push_leaf(
+                Arc::make_mut(&mut children[child_index]),
+                shift - BITS,
+                index,
+                leaf,
+            );
+        } else {
+            children.push(Self::new_path(shift - BITS, leaf));
+        }
+    }
+
+    /// Wraps `leaf` in branches until it can be the child of a node at `shift`
+    fn new_path(shift: usize, leaf: Arc<Node<T>>) -> Arc<Node<T>> {
+        if shift == 0 {
+            leaf
+        } else {
+            Arc::new(Node::Branch(vec![Self::new_path(shift - BITS, leaf)]))
+        }
+    }
+
+    /// A mutable reference to the element at `index`, copying the chunk it is
+    /// in if that chunk is shared with another vector
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let tail_offset = self.tail_offset();
+
+        if index >= tail_offset {
+            return Some(&mut Arc::make_mut(&mut self.tail)[index - tail_offset]);
+        }
+
+        let mut node = Arc::make_mut(&mut self.root);
+        let mut shift = self.shift;
+
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = Arc::make_mut(&mut children[(index >> shift) & MASK]);
+                    shift -= BITS;
+                }
+                Node::Leaf(elems) => return Some(&mut elems[index & MASK]),
+            }
+        }
+    }
+
+    /// Moves the elements out of this vector, cloning only those which are
+    /// shared with another vector
+    pub fn into_vec(self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len);
+
+        Node::drain_into(self.root, &mut vec);
+        vec.extend(Arc::try_unwrap(self.tail).unwrap_or_else(|tail| (*tail).clone()));
+
+        vec
+    }
+}
+
+impl<T> Clone for PersistentVec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            len: self.len,
+            shift: self.shift,
+            root: Arc::clone(&self.root),
+            tail: Arc::clone(&self.tail),
+        }
+    }
+}
+
+impl<T> Default for PersistentVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PersistentVec<T> {}
+
+impl<T> Index<usize> for PersistentVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(elem) => elem,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                self.len, index
+            ),
+        }
+    }
+}
+
+impl<T: Clone> IndexMut<usize> for PersistentVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+
+        match self.get_mut(index) {
+            Some(elem) => elem,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            ),
+        }
+    }
+}
+
+impl<T: Clone> From<Vec<T>> for PersistentVec<T> {
+    fn from(vec: Vec<T>) -> Self {
+        if vec.len() <= WIDTH {
+            return Self {
+                len: vec.len(),
+                shift: BITS,
+                root: Arc::new(Node::Branch(Vec::new())),
+                tail: Arc::new(vec),
+            };
+        }
+
+        vec.into_iter().collect()
+    }
+}
+
+impl<T: Clone> FromIterator<T> for PersistentVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T: Clone> Extend<T> for PersistentVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T: Clone> IntoIterator for PersistentVec<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub(crate) struct Iter<'a, T> {
+    vec: &'a PersistentVec<T>,
+    /// The chunk containing `front`, once it has been looked up
+    chunk: &'a [T],
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        if self.front & MASK == 0 || self.chunk.is_empty() {
+            self.chunk = self.vec.chunk(self.front);
+        }
+
+        let elem = &self.chunk[self.front & MASK];
+        self.front += 1;
+
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.back -= 1;
+
+        self.vec.get(self.back)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter")
+            .field("front", &self.front)
+            .field("back", &self.back)
+            .finish()
+    }
+}
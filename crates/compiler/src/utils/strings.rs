@@ -31,3 +31,9 @@ pub(crate) fn is_ident(s: &str) -> bool {
     }
     true
 }
+
+/// Whether compiling `source` could produce non-ASCII CSS. Sources made up of
+/// ASCII characters without escapes can only ever produce ASCII output
+pub(crate) fn may_produce_non_ascii(source: &str) -> bool {
+    !source.is_ascii() || source.contains('\\')
+}
@@ -1,4 +1,10 @@
-use std::{cell::Cell, collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use crate::common::{Identifier, ListSeparator};
 
@@ -7,7 +13,7 @@ use super::Value;
 #[derive(Debug, Clone)]
 pub(crate) struct ArgList {
     pub elems: Vec<Value>,
-    were_keywords_accessed: Arc<Cell<bool>>,
+    were_keywords_accessed: Arc<AtomicBool>,
     // todo: special wrapper around this field to avoid having to make it private?
     keywords: BTreeMap<Identifier, Value>,
     pub separator: ListSeparator,
@@ -26,12 +32,12 @@ impl Eq for ArgList {}
 impl ArgList {
     pub fn new(
         elems: Vec<Value>,
-        were_keywords_accessed: Arc<Cell<bool>>,
+        were_keywords_accessed: Arc<AtomicBool>,
         keywords: BTreeMap<Identifier, Value>,
         separator: ListSeparator,
     ) -> Self {
         debug_assert!(
-            !(*were_keywords_accessed).get(),
+            !were_keywords_accessed.load(Ordering::Relaxed),
             "expected args to initialize with unaccessed keywords"
         );
 
@@ -56,12 +62,18 @@ impl ArgList {
     }
 
     pub fn keywords(&self) -> &BTreeMap<Identifier, Value> {
-        (*self.were_keywords_accessed).set(true);
+        self.were_keywords_accessed.store(true, Ordering::Relaxed);
+        &self.keywords
+    }
+
+    /// The keywords passed to this argument list, without marking them as
+    /// accessed
+    pub fn peek_keywords(&self) -> &BTreeMap<Identifier, Value> {
         &self.keywords
     }
 
     pub fn into_keywords(self) -> BTreeMap<Identifier, Value> {
-        (*self.were_keywords_accessed).set(true);
+        self.were_keywords_accessed.store(true, Ordering::Relaxed);
         self.keywords
     }
 }
@@ -1,66 +1,227 @@
-use std::{slice::Iter, vec::IntoIter};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::Flatten,
+    slice,
+    sync::Arc,
+    vec::IntoIter,
+};
 
 use codemap::Spanned;
 
 use crate::{
     common::{Brackets, ListSeparator},
+    utils::{HashTrie, PersistentVec},
     value::Value,
 };
 
-#[derive(Debug, Clone, Default)]
-pub(crate) struct SassMap(Vec<(Spanned<Value>, Value)>);
+/// Maps with more entries than this find keys by their hash, rather than by
+/// comparing against every key
+const MAX_UNINDEXED_LEN: usize = 8;
+
+type Entry = (Spanned<Value>, Value);
+
+/// A Sass map, which is cheap to clone and which can have entries added or
+/// removed without copying the entries of the map it was cloned from
+#[derive(Clone, Default)]
+pub(crate) struct SassMap {
+    /// Entries in insertion order. Removed entries are left as `None`, so that
+    /// the positions in `index` stay valid, until they outnumber the rest
+    entries: PersistentVec<Option<Entry>>,
+    len: usize,
+    /// The positions in `entries` of each key, by the hash of that key. This is
+    /// only built once the map has more than `MAX_UNINDEXED_LEN` entries
+    index: Option<HashTrie<Positions>>,
+}
+
+/// The positions of the keys which share a hash
+#[derive(Clone)]
+enum Positions {
+    One(usize),
+    Many(Arc<Vec<usize>>),
+}
+
+impl Positions {
+    fn as_slice(&self) -> &[usize] {
+        match self {
+            Positions::One(position) => slice::from_ref(position),
+            Positions::Many(positions) => positions,
+        }
+    }
+
+    fn with(&self, position: usize) -> Self {
+        let mut positions = self.as_slice().to_vec();
+        positions.push(position);
+        Positions::Many(Arc::new(positions))
+    }
+
+    fn without(&self, position: usize) -> Option<Self> {
+        let positions: Vec<usize> = self
+            .as_slice()
+            .iter()
+            .copied()
+            .filter(|&p| p != position)
+            .collect();
+
+        match positions.as_slice() {
+            [] => None,
+            &[position] => Some(Positions::One(position)),
+            _ => Some(Positions::Many(Arc::new(positions))),
+        }
+    }
+}
+
+fn hash_key(key: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
 
 impl PartialEq for SassMap {
     fn eq(&self, other: &Self) -> bool {
-        if self.0.len() != other.0.len() {
+        if self.len != other.len {
             return false;
         }
-        for (key, value) in &self.0 {
-            if !other
-                .0
-                .iter()
-                .any(|(key2, value2)| key.node == key2.node && value == value2)
-            {
-                return false;
-            }
-        }
-        true
+
+        self.iter()
+            .all(|(key, value)| other.get_ref(&key.node) == Some(value))
     }
 }
 
 impl Eq for SassMap {}
 
+impl Hash for SassMap {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // maps are equal regardless of the order of their entries, so the
+        // hashes of the entries are combined in a way that ignores order
+        let mut entries = 0_u64;
+
+        for (key, value) in self.iter() {
+            let mut hasher = DefaultHasher::new();
+            key.node.hash(&mut hasher);
+            value.hash(&mut hasher);
+            entries = entries.wrapping_add(hasher.finish());
+        }
+
+        self.len.hash(state);
+        entries.hash(state);
+    }
+}
+
+impl fmt::Debug for SassMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|(key, value)| (&key.node, value)))
+            .finish()
+    }
+}
+
 impl SassMap {
-    pub const fn new() -> SassMap {
-        SassMap(Vec::new())
+    pub fn new() -> SassMap {
+        SassMap::default()
     }
 
-    pub const fn new_with(elements: Vec<(Spanned<Value>, Value)>) -> SassMap {
-        SassMap(elements)
+    /// Creates a map from entries whose keys are known to be unique
+    pub fn new_with(elements: Vec<(Spanned<Value>, Value)>) -> SassMap {
+        let len = elements.len();
+
+        let mut map = SassMap {
+            entries: elements.into_iter().map(Some).collect(),
+            len,
+            index: None,
+        };
+
+        if len > MAX_UNINDEXED_LEN {
+            map.rebuild_index();
+        }
+
+        map
     }
 
-    pub fn get(self, key: &Value) -> Option<Value> {
-        for (k, v) in self.0 {
-            if &k.node == key {
-                return Some(v);
+    /// The position in `entries` of `key`
+    fn position(&self, key: &Value) -> Option<usize> {
+        let is_key =
+            |entry: Option<&Option<Entry>>| matches!(entry, Some(Some((k, ..))) if &k.node == key);
+
+        match &self.index {
+            Some(index) => index
+                .get(hash_key(key))?
+                .as_slice()
+                .iter()
+                .copied()
+                .find(|&position| is_key(self.entries.get(position))),
+            None => self.entries.iter().position(|entry| is_key(Some(entry))),
+        }
+    }
+
+    fn rebuild_index(&mut self) {
+        let mut index = HashTrie::new();
+
+        for (position, entry) in self.entries.iter().enumerate() {
+            if let Some((key, ..)) = entry {
+                Self::index_position(&mut index, hash_key(&key.node), position);
             }
         }
 
-        None
+        self.index = Some(index);
+    }
+
+    fn index_position(index: &mut HashTrie<Positions>, hash: u64, position: usize) {
+        let positions = match index.get(hash) {
+            Some(positions) => positions.with(position),
+            None => Positions::One(position),
+        };
+
+        index.insert(hash, positions);
+    }
+
+    pub fn get(self, key: &Value) -> Option<Value> {
+        self.get_ref(key).cloned()
     }
 
     pub fn get_ref(&self, key: &Value) -> Option<&Value> {
-        for (k, v) in &self.0 {
-            if &k.node == key {
-                return Some(v);
+        match self.entries.get(self.position(key)?) {
+            Some(Some((_, value))) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn remove(&mut self, key: &Value) {
+        let position = match self.position(key) {
+            Some(position) => position,
+            None => return,
+        };
+
+        if let Some(index) = &mut self.index {
+            let hash = hash_key(key);
+
+            if let Some(positions) = index.get(hash).and_then(|p| p.without(position)) {
+                index.insert(hash, positions);
+            } else {
+                index.remove(hash);
             }
         }
 
-        None
+        *self.entries.get_mut(position).unwrap() = None;
+        self.len -= 1;
+
+        if self.entries.len() - self.len > self.len.max(MAX_UNINDEXED_LEN) {
+            self.compact();
+        }
     }
 
-    pub fn remove(&mut self, key: &Value) {
-        self.0.retain(|(ref k, ..)| k.not_equals(key));
+    /// Drops removed entries, which changes the positions of those remaining
+    fn compact(&mut self) {
+        self.entries = std::mem::take(&mut self.entries)
+            .into_iter()
+            .flatten()
+            .map(Some)
+            .collect();
+
+        if self.index.is_some() {
+            self.rebuild_index();
+        }
     }
 
     pub fn merge(&mut self, other: SassMap) {
@@ -69,47 +230,64 @@ impl SassMap {
         }
     }
 
-    pub fn iter(&self) -> Iter<(Spanned<Value>, Value)> {
-        self.0.iter()
+    pub fn iter(&self) -> impl Iterator<Item = &(Spanned<Value>, Value)> {
+        self.entries.iter().flatten()
     }
 
-    pub fn keys(self) -> Vec<Value> {
-        self.0.into_iter().map(|(k, ..)| k.node).collect()
+    pub fn keys(self) -> PersistentVec<Value> {
+        self.into_iter().map(|(k, ..)| k.node).collect()
     }
 
-    pub fn values(self) -> Vec<Value> {
-        self.0.into_iter().map(|(.., v)| v).collect()
+    pub fn values(self) -> PersistentVec<Value> {
+        self.into_iter().map(|(.., v)| v).collect()
     }
 
-    pub fn as_list(self) -> Vec<Value> {
-        self.0
-            .into_iter()
-            .map(|(k, v)| Value::List(vec![k.node, v], ListSeparator::Space, Brackets::None))
+    pub fn as_list(self) -> PersistentVec<Value> {
+        self.into_iter()
+            .map(|(k, v)| Value::List(vec![k.node, v].into(), ListSeparator::Space, Brackets::None))
             .collect()
     }
 
     /// Returns true if the key already exists
     pub fn insert(&mut self, key: Spanned<Value>, value: Value) -> bool {
-        for (ref k, ref mut v) in &mut self.0 {
-            if k.node == key.node {
+        if let Some(position) = self.position(&key.node) {
+            if let Some(Some((_, v))) = self.entries.get_mut(position) {
                 *v = value;
-                return true;
             }
+
+            return true;
         }
-        self.0.push((key, value));
+
+        let position = self.entries.len();
+
+        if let Some(index) = &mut self.index {
+            Self::index_position(index, hash_key(&key.node), position);
+        }
+
+        self.entries.push(Some((key, value)));
+        self.len += 1;
+
+        if self.index.is_none() && self.len > MAX_UNINDEXED_LEN {
+            self.rebuild_index();
+        }
+
         false
     }
 
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.len == 0
     }
 }
 
 impl IntoIterator for SassMap {
     type Item = (Spanned<Value>, Value);
-    type IntoIter = IntoIter<Self::Item>;
+    type IntoIter = Flatten<IntoIter<Option<Self::Item>>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.entries.into_iter().flatten()
     }
 }
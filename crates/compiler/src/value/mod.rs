@@ -1,4 +1,9 @@
-use std::{cmp::Ordering, sync::Arc};
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    mem,
+    sync::Arc,
+};
 
 use codemap::{Span, Spanned};
 
@@ -10,7 +15,7 @@ use crate::{
     selector::Selector,
     serializer::{inspect_value, serialize_value},
     unit::Unit,
-    utils::is_special_function,
+    utils::{is_special_function, PersistentVec},
     Options, OutputStyle,
 };
 
@@ -18,7 +23,9 @@ pub(crate) use arglist::ArgList;
 pub(crate) use calculation::*;
 pub(crate) use map::SassMap;
 pub(crate) use number::*;
-pub(crate) use sass_function::{SassFunction, UserDefinedFunction};
+pub(crate) use sass_function::{
+    FunctionHost, HostFunction, HostFunctionId, SassFunction, UserDefinedFunction,
+};
 pub(crate) use sass_number::{conversion_factor, SassNumber};
 
 mod arglist;
@@ -34,7 +41,7 @@ pub(crate) enum Value {
     False,
     Null,
     Dimension(SassNumber),
-    List(Vec<Value>, ListSeparator, Brackets),
+    List(PersistentVec<Value>, ListSeparator, Brackets),
     Color(Arc<Color>),
     String(String, QuoteKind),
     Map(SassMap),
@@ -121,6 +128,44 @@ impl PartialEq for Value {
 
 impl Eq for Value {}
 
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            // argument lists are equal to comma-separated lists with the same
+            // elements, so both are hashed by their elements alone
+            Value::List(elems, ..) => {
+                elems.len().hash(state);
+                elems.iter().for_each(|elem| elem.hash(state));
+            }
+            Value::ArgList(list) => {
+                list.len().hash(state);
+                list.elems.iter().for_each(|elem| elem.hash(state));
+            }
+            Value::String(s, ..) => {
+                mem::discriminant(self).hash(state);
+                s.hash(state);
+            }
+            Value::Dimension(n) => {
+                mem::discriminant(self).hash(state);
+                n.hash(state);
+            }
+            Value::Color(color) => {
+                mem::discriminant(self).hash(state);
+                color.hash(state);
+            }
+            Value::Map(map) => {
+                mem::discriminant(self).hash(state);
+                map.hash(state);
+            }
+            Value::True
+            | Value::False
+            | Value::Null
+            | Value::FunctionRef(..)
+            | Value::Calculation(..) => mem::discriminant(self).hash(state),
+        }
+    }
+}
+
 impl Value {
     pub fn with_slash(
         self,
@@ -357,59 +402,12 @@ impl Value {
         })
     }
 
-    pub fn not_equals(&self, other: &Self) -> bool {
-        match self {
-            Value::String(s1, ..) => match other {
-                Value::String(s2, ..) => s1 != s2,
-                _ => true,
-            },
-            Value::Dimension(SassNumber {
-                num: n,
-                unit,
-                as_slash: _,
-            }) if !n.is_nan() => match other {
-                Value::Dimension(SassNumber {
-                    num: n2,
-                    unit: unit2,
-                    as_slash: _,
-                }) if !n2.is_nan() => {
-                    if !unit.comparable(unit2) {
-                        true
-                    } else if unit == unit2 {
-                        n != n2
-                    } else if unit == &Unit::None || unit2 == &Unit::None {
-                        true
-                    } else {
-                        n != &n2.convert(unit2, unit)
-                    }
-                }
-                _ => true,
-            },
-            Value::List(list1, sep1, brackets1) => match other {
-                Value::List(list2, sep2, brackets2) => {
-                    if sep1 != sep2 || brackets1 != brackets2 || list1.len() != list2.len() {
-                        true
-                    } else {
-                        for (a, b) in list1.iter().zip(list2) {
-                            if a.not_equals(b) {
-                                return true;
-                            }
-                        }
-                        false
-                    }
-                }
-                _ => true,
-            },
-            s => s != other,
-        }
-    }
-
-    pub fn as_list(self) -> Vec<Value> {
+    pub fn as_list(self) -> PersistentVec<Value> {
         match self {
             Value::List(v, ..) => v,
             Value::Map(m) => m.as_list(),
-            Value::ArgList(v) => v.elems,
-            v => vec![v],
+            Value::ArgList(v) => v.elems.into(),
+            v => vec![v].into(),
         }
     }
 
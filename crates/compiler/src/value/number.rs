@@ -1,6 +1,9 @@
 use std::{
+    cell::Cell,
     convert::From,
-    fmt, mem,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
     ops::{
         Add, AddAssign, Deref, DerefMut, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub,
         SubAssign,
@@ -14,14 +17,42 @@ use crate::{
 
 use codemap::Span;
 
-const PRECISION: i32 = 10;
+pub(crate) const DEFAULT_PRECISION: u8 = 10;
+pub(crate) const MAX_PRECISION: u8 = 16;
+
+// Numbers are compared and serialized outside of the visitor (e.g. in
+// `PartialEq`), so the precision of the current compilation is stored per
+// thread rather than threaded through every call
+thread_local!(static PRECISION: Cell<u8> = const { Cell::new(DEFAULT_PRECISION) });
+
+/// The number of digits after the decimal point used by the current
+/// compilation
+pub(crate) fn precision() -> usize {
+    PRECISION.with(|precision| precision.get() as usize)
+}
+
+/// Run `callback` with the given precision, restoring the previous precision
+/// afterwards
+pub(crate) fn with_precision<T>(precision: u8, callback: impl FnOnce() -> T) -> T {
+    struct Reset(u8);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            PRECISION.with(|precision| precision.set(self.0));
+        }
+    }
+
+    let _reset = Reset(PRECISION.with(|old| old.replace(precision)));
+
+    callback()
+}
 
 fn epsilon() -> f64 {
-    10.0_f64.powi(-PRECISION - 1)
+    10.0_f64.powi(-(precision() as i32) - 1)
 }
 
 fn inverse_epsilon() -> f64 {
-    10.0_f64.powi(PRECISION + 1)
+    10.0_f64.powi(precision() as i32 + 1)
 }
 
 /// Thin wrapper around `f64` providing utility functions and more accurate
@@ -39,6 +70,15 @@ impl PartialEq for Number {
 
 impl Eq for Number {}
 
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // fuzzily equal numbers round to the same value at the current
+        // precision, and `-0.0` is normalized as it is equal to `0.0`
+        let rounded = (self.0 * inverse_epsilon()).round() + 0.0;
+        rounded.to_bits().hash(state);
+    }
+}
+
 pub(crate) fn fuzzy_equals(a: f64, b: f64) -> bool {
     if a == b {
         return true;
@@ -299,20 +339,22 @@ impl Number {
             buffer.push('-');
         }
 
-        let num = self.0.abs();
+        let formatted = format!("{:.*}", precision(), self.0.abs());
 
-        if is_compressed && num < 1.0 {
-            buffer.push_str(
-                format!("{:.10}", num)[1..]
-                    .trim_end_matches('0')
-                    .trim_end_matches('.'),
-            );
+        // with a precision of 0 there is no decimal point, and the zeros are
+        // significant
+        let formatted = if formatted.contains('.') {
+            formatted.trim_end_matches('0').trim_end_matches('.')
         } else {
-            buffer.push_str(
-                format!("{:.10}", num)
-                    .trim_end_matches('0')
-                    .trim_end_matches('.'),
-            );
+            &formatted
+        };
+
+        // rounding may have carried up to 1, so only a leading 0 is dropped
+        match formatted.strip_prefix('0') {
+            Some(fraction) if is_compressed && fraction.starts_with('.') => {
+                buffer.push_str(fraction);
+            }
+            _ => buffer.push_str(formatted),
         }
 
         if buffer.is_empty() || buffer == "-" || buffer == "-0" {
@@ -1,6 +1,10 @@
 use std::{fmt, sync::Arc};
 
-use crate::{ast::AstFunctionDecl, builtin::Builtin, common::Identifier, evaluate::Environment};
+use codemap::Span;
+
+use crate::{
+    ast::AstFunctionDecl, builtin::Builtin, common::Identifier, evaluate::Environment, value::Value,
+};
 
 /// A Sass function
 ///
@@ -20,6 +24,63 @@ pub(crate) enum SassFunction {
     Plain {
         name: Identifier,
     },
+    /// Host functions are those implemented by the program embedding the
+    /// compiler, such as a JavaScript host speaking the embedded protocol.
+    Host(HostFunction),
+}
+
+/// A function implemented by a [`FunctionHost`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HostFunction {
+    /// The host's identifier for the function, which is either its name or a
+    /// numeric ID
+    pub id: HostFunctionId,
+    pub name: Identifier,
+    /// The function's signature, such as `foo($a, $b: 1)`
+    ///
+    /// This is parsed lazily, the first time the function is called
+    pub signature: String,
+}
+
+impl HostFunction {
+    pub fn new(id: HostFunctionId, signature: String) -> Self {
+        let name = signature
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .into();
+
+        Self {
+            id,
+            name,
+            signature,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum HostFunctionId {
+    Name(String),
+    Id(u32),
+}
+
+/// Calls functions implemented by the program embedding the compiler
+pub(crate) trait FunctionHost: fmt::Debug {
+    /// The functions available in the global scope, which take precedence
+    /// over built-in functions
+    fn global_functions(&self) -> &[HostFunction];
+
+    /// Calls `function` with its arguments in the order they are declared in
+    /// its signature, followed by its argument list if it takes one
+    ///
+    /// `span` is the span of the call, for values which need one
+    fn call(
+        &self,
+        function: &HostFunction,
+        arguments: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, String>;
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +107,7 @@ impl SassFunction {
             Self::Builtin(_, name)
             | Self::UserDefined(UserDefinedFunction { name, .. })
             | Self::Plain { name } => *name,
+            Self::Host(HostFunction { name, .. }) => *name,
         }
     }
 
@@ -57,6 +119,7 @@ impl SassFunction {
             Self::Plain { .. } => "Plain",
             Self::Builtin(..) => "Builtin",
             Self::UserDefined { .. } => "UserDefined",
+            Self::Host(..) => "Host",
         }
     }
 }
@@ -1,4 +1,5 @@
 use std::{
+    hash::{Hash, Hasher},
     ops::{Add, Div, Mul, Sub},
     sync::Arc,
 };
@@ -225,6 +226,20 @@ impl PartialEq for SassNumber {
 
 impl Eq for SassNumber {}
 
+impl Hash for SassNumber {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // numbers with compatible units are compared after converting one to
+        // the other's unit, which may round differently than converting both to
+        // the canonical unit, so only the canonical unit is hashed for them
+        if self.unit.is_convertible() {
+            self.unit.canonical().hash(state);
+        } else {
+            self.num.hash(state);
+            self.unit.hash(state);
+        }
+    }
+}
+
 impl Add<SassNumber> for SassNumber {
     type Output = SassNumber;
     fn add(self, rhs: SassNumber) -> Self::Output {
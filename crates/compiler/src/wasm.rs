@@ -0,0 +1,432 @@
+//! JavaScript bindings, exported with the `"wasm-exports"` feature
+//!
+//! The API is modeled on the [Sass JavaScript API](https://sass-lang.com/documentation/js-api/):
+//!
+//! ```js
+//! import { compileString } from "grass";
+//!
+//! const result = compileString("@use 'theme';\na { color: theme.$primary; }", {
+//!   style: "compressed",
+//!   files: { "theme.scss": "$primary: red;" },
+//!   sourceMap: true,
+//! });
+//!
+//! console.log(result.css, result.sourceMap);
+//! ```
+//!
+//! All options are optional:
+//!
+//! - `style`: `"expanded"` (the default) or `"compressed"`
+//! - `syntax`: `"scss"`, `"indented"`, or `"css"`. By default, the syntax is
+//!   inferred from `url`
+//! - `url`: the file name of the stylesheet passed to `compileString`, used to
+//!   resolve relative loads and in error messages
+//! - `files`: an object or `Map` from paths to the contents of files which can
+//!   be loaded. There is no other file system in WebAssembly
+//! - `loadPaths`: paths in `files` to resolve loads relative to
+//! - `importers`: objects with `canonicalize(url, { containingUrl, fromImport })`
+//!   and `load(canonicalUrl)` methods, consulted in order after `files`.
+//!   `canonicalize` returns a canonical URL or `null`, and `load` returns
+//!   `{ contents, syntax }`
+//! - `logger`: an object with optional `warn(message, options)` and
+//!   `debug(message, options)` methods. `options` has `file`, `line`, and
+//!   `column` properties, and for warnings `deprecation` and `deprecationType`
+//! - `quiet`, `charset`, `precision`: see the corresponding [`Options`] methods
+//! - `sourceMap`: whether to return a source map as `result.sourceMap`
+//! - `sourceMapIncludeSources`: whether the source map embeds the contents of
+//!   each source
+//!
+//! Errors are thrown as `Error` objects whose message is the formatted error,
+//! with `sassMessage`, `file`, `line`, and `column` properties. Lines and
+//! columns are 1-based.
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use codemap::SpanLoc;
+use js_sys::{Array, Function, JsString, Object, Reflect, JSON};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{
+    compile_with_file_name, value, Deprecation, Error, ErrorKind, Fs, ImportContext, Importer,
+    ImporterResult, InputSyntax, Logger, Options, OutputStyle, StackFrame,
+};
+
+/// Compiles the stylesheet `source` to CSS, returning `{ css, sourceMap }`
+#[wasm_bindgen(js_name = compileString)]
+pub fn compile_string(source: String, options: JsValue) -> Result<JsValue, JsValue> {
+    let url = get_string(&options, "url").unwrap_or_else(|| "stdin".to_owned());
+    compile(source, &url, &options)
+}
+
+/// Compiles the stylesheet at `path` in `options.files` to CSS, returning
+/// `{ css, sourceMap }`
+#[wasm_bindgen(js_name = compile)]
+pub fn compile_path(path: String, options: JsValue) -> Result<JsValue, JsValue> {
+    let files = MapFs::new(&get(&options, "files"));
+
+    let source = files
+        .read(Path::new(&path))
+        .map_err(|err| error_to_js(&Box::<Error>::from(err)))?;
+    let source = String::from_utf8(source).map_err(|err| error_to_js(&Box::<Error>::from(err)))?;
+
+    compile(source, &path, &options)
+}
+
+fn compile(source: String, url: &str, js_options: &JsValue) -> Result<JsValue, JsValue> {
+    let files = MapFs::new(&get(js_options, "files"));
+    let importers = get_array(js_options, "importers")
+        .iter()
+        .map(JsImporter::new)
+        .collect::<Result<Vec<_>, _>>()?;
+    let logger = JsLogger::new(&get(js_options, "logger"));
+
+    let mut options = Options::default().fs(&files).logger(&logger).load_paths(
+        &get_array(js_options, "loadPaths")
+            .iter()
+            .filter_map(|path| path.as_string())
+            .collect::<Vec<_>>(),
+    );
+
+    match get_string(js_options, "style").as_deref() {
+        Some("compressed") => options = options.style(OutputStyle::Compressed),
+        Some("expanded") | None => {}
+        Some(style) => return Err(js_error(&format!("Unknown style \"{}\".", style))),
+    }
+
+    match get_string(js_options, "syntax").as_deref() {
+        Some("scss") => options = options.input_syntax(InputSyntax::Scss),
+        Some("indented") => options = options.input_syntax(InputSyntax::Sass),
+        Some("css") => options = options.input_syntax(InputSyntax::Css),
+        None => {}
+        Some(syntax) => return Err(js_error(&format!("Unknown syntax \"{}\".", syntax))),
+    }
+
+    if let Some(quiet) = get(js_options, "quiet").as_bool() {
+        options = options.quiet(quiet);
+    }
+
+    if let Some(charset) = get(js_options, "charset").as_bool() {
+        options = options.allows_charset(charset);
+    }
+
+    if let Some(precision) = get(js_options, "precision").as_f64() {
+        options = options.precision(precision as u8);
+    }
+
+    for importer in &importers {
+        options = options.importer(importer);
+    }
+
+    let source_map = get(js_options, "sourceMap").as_bool().unwrap_or(false);
+
+    let (css, source_map) = value::with_precision(options.precision, || {
+        compile_with_file_name(source, Path::new(url), &options, source_map)
+    })
+    .map_err(|err| error_to_js(&err))?;
+
+    let result = Object::new();
+    set(&result, "css", &css.into());
+
+    if let Some(source_map) = source_map {
+        let include_sources = get(js_options, "sourceMapIncludeSources")
+            .as_bool()
+            .unwrap_or(false);
+        set(
+            &result,
+            "sourceMap",
+            &JSON::parse(&source_map.to_json(None, include_sources))?,
+        );
+    }
+
+    Ok(result.into())
+}
+
+fn get(object: &JsValue, key: &str) -> JsValue {
+    if object.is_object() {
+        Reflect::get(object, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+    } else {
+        JsValue::UNDEFINED
+    }
+}
+
+fn get_string(object: &JsValue, key: &str) -> Option<String> {
+    get(object, key).as_string()
+}
+
+fn get_array(object: &JsValue, key: &str) -> Array {
+    get(object, key).dyn_into::<Array>().unwrap_or_default()
+}
+
+fn set(object: &Object, key: &str, value: &JsValue) {
+    Reflect::set(object, &JsValue::from_str(key), value).unwrap();
+}
+
+fn js_error(message: &str) -> JsValue {
+    js_sys::Error::new(message).into()
+}
+
+/// The message of an exception thrown by a JavaScript callback
+fn thrown_message(thrown: &JsValue) -> String {
+    match thrown.dyn_ref::<js_sys::Error>() {
+        Some(err) => String::from(err.message()),
+        None => thrown
+            .as_string()
+            .unwrap_or_else(|| String::from(JsString::from(thrown.clone()))),
+    }
+}
+
+fn error_to_js(err: &Error) -> JsValue {
+    let error = js_sys::Error::new(&err.to_string());
+
+    match err.clone().kind() {
+        ErrorKind::ParseError { message, loc, .. } => {
+            set(&error, "sassMessage", &message.into());
+            set_location(&error, Some(&loc));
+        }
+        _ => {
+            let message = err.to_string();
+            let message = message.trim_end();
+            set(
+                &error,
+                "sassMessage",
+                &message.strip_prefix("Error: ").unwrap_or(message).into(),
+            );
+            set_location(&error, None);
+        }
+    }
+
+    error.into()
+}
+
+/// Sets the 1-based `file`, `line`, and `column` properties of `object`
+fn set_location(object: &Object, loc: Option<&SpanLoc>) {
+    match loc {
+        Some(loc) => {
+            set(object, "file", &loc.file.name().into());
+            set(object, "line", &(loc.begin.line as f64 + 1.0).into());
+            set(object, "column", &(loc.begin.column as f64 + 1.0).into());
+        }
+        None => {
+            set(object, "file", &JsValue::NULL);
+            set(object, "line", &JsValue::NULL);
+            set(object, "column", &JsValue::NULL);
+        }
+    }
+}
+
+/// Normalizes `path` so that `./a/../b.scss` and `b.scss` refer to the same
+/// entry in a [`MapFs`]
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// A file system backed by the `files` option
+#[derive(Debug)]
+struct MapFs {
+    files: HashMap<PathBuf, String>,
+}
+
+impl MapFs {
+    fn new(files: &JsValue) -> Self {
+        let mut map = HashMap::new();
+
+        let entries = match files.dyn_ref::<js_sys::Map>() {
+            Some(files) => Array::from(&files.entries()),
+            None if files.is_object() => Object::entries(files.unchecked_ref()),
+            None => Array::new(),
+        };
+
+        for entry in entries.iter() {
+            let entry = Array::from(&entry);
+            if let (Some(path), Some(contents)) =
+                (entry.get(0).as_string(), entry.get(1).as_string())
+            {
+                map.insert(normalize(Path::new(&path)), contents);
+            }
+        }
+
+        Self { files: map }
+    }
+}
+
+impl Fs for MapFs {
+    fn is_dir(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        self.files
+            .keys()
+            .any(|file| file != &path && file.starts_with(&path))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&normalize(path))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.files.get(&normalize(path)) {
+            Some(contents) => Ok(contents.as_bytes().to_vec()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{}: No such file", path.display()),
+            )),
+        }
+    }
+}
+
+/// An importer from the `importers` option
+#[derive(Debug)]
+struct JsImporter {
+    this: JsValue,
+    canonicalize: Function,
+    load: Function,
+}
+
+impl JsImporter {
+    fn new(importer: JsValue) -> Result<Self, JsValue> {
+        let method = |name: &str| {
+            get(&importer, name).dyn_into::<Function>().map_err(|_| {
+                js_error(&format!(
+                    "Importers must have canonicalize and load methods, but {} is missing.",
+                    name
+                ))
+            })
+        };
+
+        Ok(Self {
+            canonicalize: method("canonicalize")?,
+            load: method("load")?,
+            this: importer,
+        })
+    }
+}
+
+impl Importer for JsImporter {
+    fn canonicalize(&self, url: &str, context: &ImportContext) -> Result<Option<String>, String> {
+        let js_context = Object::new();
+        set(
+            &js_context,
+            "containingUrl",
+            &context
+                .containing_url
+                .map_or(JsValue::NULL, JsValue::from_str),
+        );
+        set(&js_context, "fromImport", &context.from_import.into());
+
+        let canonical_url = self
+            .canonicalize
+            .call2(&self.this, &JsValue::from_str(url), &js_context)
+            .map_err(|thrown| thrown_message(&thrown))?;
+
+        if canonical_url.is_null() || canonical_url.is_undefined() {
+            return Ok(None);
+        }
+
+        canonical_url
+            .as_string()
+            .map(Some)
+            .ok_or_else(|| "canonicalize must return a string or null.".to_owned())
+    }
+
+    fn load(&self, canonical_url: &str) -> Result<ImporterResult, String> {
+        let result = self
+            .load
+            .call1(&self.this, &JsValue::from_str(canonical_url))
+            .map_err(|thrown| thrown_message(&thrown))?;
+
+        let contents = get_string(&result, "contents")
+            .ok_or_else(|| "load must return an object with string contents.".to_owned())?;
+
+        let syntax = match get_string(&result, "syntax").as_deref() {
+            Some("scss") | None => InputSyntax::Scss,
+            Some("indented") => InputSyntax::Sass,
+            Some("css") => InputSyntax::Css,
+            Some(syntax) => return Err(format!("Unknown syntax \"{}\".", syntax)),
+        };
+
+        Ok(ImporterResult::new(contents, syntax))
+    }
+}
+
+/// A logger from the `logger` option. Messages without a corresponding method
+/// are discarded
+#[derive(Debug)]
+struct JsLogger {
+    this: JsValue,
+    warn: Option<Function>,
+    debug: Option<Function>,
+}
+
+impl JsLogger {
+    fn new(logger: &JsValue) -> Self {
+        Self {
+            this: logger.clone(),
+            warn: get(logger, "warn").dyn_into::<Function>().ok(),
+            debug: get(logger, "debug").dyn_into::<Function>().ok(),
+        }
+    }
+
+    fn warn_with_options(
+        &self,
+        message: &str,
+        deprecation: Option<Deprecation>,
+        location: Option<&SpanLoc>,
+    ) {
+        if let Some(warn) = &self.warn {
+            let options = Object::new();
+            set(&options, "deprecation", &deprecation.is_some().into());
+            set(
+                &options,
+                "deprecationType",
+                &deprecation.map_or(JsValue::UNDEFINED, |deprecation| deprecation.id().into()),
+            );
+            set_location(&options, location);
+
+            // exceptions thrown by the logger are ignored, as in Dart Sass
+            let _ = warn.call2(&self.this, &JsValue::from_str(message), &options);
+        }
+    }
+}
+
+impl Logger for JsLogger {
+    fn debug(&self, location: SpanLoc, message: &str, _trace: &[StackFrame]) {
+        if let Some(debug) = &self.debug {
+            let options = Object::new();
+            set_location(&options, Some(&location));
+
+            let _ = debug.call2(&self.this, &JsValue::from_str(message), &options);
+        }
+    }
+
+    fn warn(&self, location: SpanLoc, message: &str, _trace: &[StackFrame]) {
+        self.warn_with_options(message, None, Some(&location));
+    }
+
+    fn deprecation(
+        &self,
+        deprecation: Deprecation,
+        location: SpanLoc,
+        message: &str,
+        _trace: &[StackFrame],
+    ) {
+        self.warn_with_options(message, Some(deprecation), Some(&location));
+    }
+
+    fn warn_without_span(&self, message: &str) {
+        self.warn_with_options(message, None, None);
+    }
+}
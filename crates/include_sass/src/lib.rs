@@ -1,17 +1,28 @@
 #![cfg_attr(feature = "nightly", feature(track_path))]
 
-use std::{cell::RefCell, collections::HashSet, path::PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
-use grass_compiler::StdFs;
+use grass_compiler::{ErrorKind, Options, OutputStyle, StdFs};
 use proc_macro::TokenStream;
+use proc_macro2::Span;
 use quote::format_ident;
-use syn::{parse_macro_input, LitStr};
+use syn::{
+    bracketed,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Ident, LitBool, LitStr, Token,
+};
 
 use quote::__private::TokenStream as TokenStream2;
 
 #[derive(Debug)]
 struct FileTracker<'a> {
-    files: RefCell<HashSet<PathBuf>>,
+    files: Mutex<HashSet<PathBuf>>,
     fs: &'a dyn grass_compiler::Fs,
 }
 
@@ -19,7 +30,7 @@ impl<'a> grass_compiler::Fs for FileTracker<'a> {
     fn is_dir(&self, path: &std::path::Path) -> bool {
         #[cfg(feature = "nightly")]
         if let Ok(p) = std::fs::canonicalize(path) {
-            self.files.borrow_mut().insert(p);
+            self.files.lock().unwrap().insert(p);
         }
 
         self.fs.is_dir(path)
@@ -28,7 +39,7 @@ impl<'a> grass_compiler::Fs for FileTracker<'a> {
     fn is_file(&self, path: &std::path::Path) -> bool {
         #[cfg(feature = "nightly")]
         if let Ok(p) = std::fs::canonicalize(path) {
-            self.files.borrow_mut().insert(p);
+            self.files.lock().unwrap().insert(p);
         }
 
         self.fs.is_file(path)
@@ -36,7 +47,7 @@ impl<'a> grass_compiler::Fs for FileTracker<'a> {
 
     fn read(&self, path: &std::path::Path) -> std::io::Result<Vec<u8>> {
         if let Ok(p) = std::fs::canonicalize(path) {
-            self.files.borrow_mut().insert(p);
+            self.files.lock().unwrap().insert(p);
         }
 
         self.fs.read(path)
@@ -84,33 +95,227 @@ fn finish(css: String, files: &HashSet<PathBuf>) -> TokenStream {
     quote::quote!(#css).into()
 }
 
-#[proc_macro]
-pub fn include_sass(item: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(item as LitStr);
+/// The arguments passed to `grass::include!` or `grass::scss!`: a string
+/// literal followed by options, e.g.
+/// `"styles/app.scss", style = "expanded", load_paths = ["vendor"], quiet = true`
+struct MacroInput {
+    literal: LitStr,
+    style: Option<OutputStyle>,
+    load_paths: Vec<LitStr>,
+    quiet: bool,
+}
+
+impl Parse for MacroInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let literal = input.parse()?;
+
+        let mut style = None;
+        let mut load_paths = None;
+        let mut quiet = None;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+
+            // allow trailing comma
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            let is_duplicate = match key.to_string().as_str() {
+                "style" => {
+                    let value: LitStr = input.parse()?;
+                    let parsed = match value.value().as_str() {
+                        "expanded" => OutputStyle::Expanded,
+                        "compressed" => OutputStyle::Compressed,
+                        _ => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "expected \"expanded\" or \"compressed\"",
+                            ))
+                        }
+                    };
+                    style.replace(parsed).is_some()
+                }
+                "load_paths" => {
+                    let content;
+                    bracketed!(content in input);
+                    let paths = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+                    load_paths.replace(paths.into_iter().collect()).is_some()
+                }
+                "quiet" => {
+                    let value: LitBool = input.parse()?;
+                    quiet.replace(value.value).is_some()
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown option `{}`, expected one of `style`, `load_paths`, `quiet`",
+                            key
+                        ),
+                    ))
+                }
+            };
+
+            if is_duplicate {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("option `{}` was passed more than once", key),
+                ));
+            }
+        }
+
+        Ok(Self {
+            literal,
+            style,
+            load_paths: load_paths.unwrap_or_default(),
+            quiet: quiet.unwrap_or(false),
+        })
+    }
+}
+
+/// Resolve `path` relative to the manifest of the crate invoking the macro,
+/// falling back to the current directory if not compiled by cargo
+fn resolve_path(path: &str) -> PathBuf {
+    match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) => Path::new(&manifest_dir).join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Format a compilation error, leading with the location in the Sass file so
+/// that it is easy to find from the compiler output
+fn error_message(err: &grass_compiler::Error) -> String {
+    match err.clone().kind() {
+        ErrorKind::ParseError { message, loc, .. } => format!(
+            "Failed to compile Sass: {}\n --> {}:{}:{}\n\n{}",
+            message,
+            loc.file.name(),
+            loc.begin.line + 1,
+            loc.begin.column + 1,
+            err
+        ),
+        _ => format!("Failed to compile Sass\n{}", err),
+    }
+}
+
+/// The span within `literal` corresponding to the location of `err`
+///
+/// Only raw string literals can be mapped precisely, and only on compilers
+/// which support `Literal::subspan`. Otherwise, the span of the entire literal
+/// is used.
+fn error_span(literal: &LitStr, path: &Path, err: &grass_compiler::Error) -> Span {
+    let loc = match err.clone().kind() {
+        ErrorKind::ParseError { loc, .. } if Path::new(loc.file.name()) == path => loc,
+        _ => return literal.span(),
+    };
+
+    let token = literal.token().to_string();
 
-    let options = grass_compiler::Options::default();
+    let prefix_len = match token.find('"') {
+        Some(idx) if token.starts_with('r') => idx + 1,
+        _ => return literal.span(),
+    };
+
+    let offset = |line: usize, column: usize| -> usize {
+        let line_start = (loc.file.line_span(line).low() - loc.file.span.low()) as usize;
+        let column_bytes: usize = loc
+            .file
+            .source_line(line)
+            .chars()
+            .take(column)
+            .map(char::len_utf8)
+            .sum();
+        prefix_len + line_start + column_bytes
+    };
+
+    let start = offset(loc.begin.line, loc.begin.column);
+    let end = offset(loc.end.line, loc.end.column).max(start + 1);
+
+    literal
+        .token()
+        .subspan(start..end)
+        .unwrap_or_else(|| literal.span())
+}
 
+fn compile(
+    input: &MacroInput,
+    compile: impl FnOnce(&Options) -> grass_compiler::Result<String>,
+    on_error: impl FnOnce(&grass_compiler::Error) -> syn::Error,
+) -> TokenStream {
     let fs = FileTracker {
-        files: RefCell::new(HashSet::new()),
+        files: Mutex::new(HashSet::new()),
         fs: &StdFs,
     };
 
-    let value = input.value();
+    let load_paths: Vec<PathBuf> = input
+        .load_paths
+        .iter()
+        .map(|path| resolve_path(&path.value()))
+        .collect();
 
-    let css = match grass_compiler::from_path(
-        value,
-        &options
-            .fs(&fs)
-            .style(grass_compiler::OutputStyle::Compressed),
-    ) {
+    let options = Options::default()
+        .fs(&fs)
+        .style(input.style.unwrap_or(OutputStyle::Compressed))
+        .load_paths(&load_paths)
+        .quiet(input.quiet);
+
+    let css = match compile(&options) {
         Ok(css) => css,
-        Err(e) => {
-            let err = syn::Error::new(input.span(), format!("Failed to compile Sass\n{}", e));
-            return syn::Error::into_compile_error(err).into();
-        }
+        Err(e) => return syn::Error::into_compile_error(on_error(&e)).into(),
     };
 
-    let files = &*fs.files.borrow();
+    let files = &*fs.files.lock().unwrap();
 
     finish(css, files)
 }
+
+#[proc_macro]
+pub fn include_sass(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as MacroInput);
+
+    let path = resolve_path(&input.literal.value());
+
+    compile(
+        &input,
+        |options| grass_compiler::from_path(&path, options),
+        |err| syn::Error::new(input.literal.span(), error_message(err)),
+    )
+}
+
+#[proc_macro]
+pub fn scss(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as MacroInput);
+
+    let source = input.literal.value();
+
+    // relative imports are resolved against the directory containing this
+    // (nonexistent) file, i.e. the crate manifest directory
+    let path = resolve_path("scss!");
+
+    compile(
+        &input,
+        |options| grass_compiler::from_string_with_file_name(source.clone(), &path, options),
+        |err| {
+            let message = match err.clone().kind() {
+                // the file name is meaningless here, so refer to the literal instead
+                ErrorKind::ParseError { message, loc, .. }
+                    if Path::new(loc.file.name()) == path =>
+                {
+                    format!(
+                        "Failed to compile Sass: {}\n --> line {}, column {} of the literal",
+                        message,
+                        loc.begin.line + 1,
+                        loc.begin.column + 1,
+                    )
+                }
+                _ => error_message(err),
+            };
+
+            syn::Error::new(error_span(&input.literal, &path, err), message)
+        },
+    )
+}
@@ -0,0 +1,102 @@
+//! Times stylesheets which build up a map or list one entry at a time
+//!
+//! Each size is double the one before it, so the time taken should roughly
+//! double from one size to the next. If it instead roughly quadruples, every
+//! step of the loop is copying the whole collection.
+//!
+//! Run with `cargo bench -p grass --bench collections`
+
+use std::time::{Duration, Instant};
+
+const SIZES: [usize; 5] = [1000, 2000, 4000, 8000, 16000];
+
+/// Compilations per size, of which the fastest is reported
+const RUNS: usize = 5;
+
+fn time(input: &str) -> Duration {
+    let start = Instant::now();
+    grass::from_string(input.to_owned(), &grass::Options::default()).unwrap();
+    start.elapsed()
+}
+
+fn bench(name: &str, input: impl Fn(usize) -> String) {
+    println!("{}", name);
+
+    let mut previous: Option<Duration> = None;
+
+    for size in SIZES {
+        let input = input(size);
+        let elapsed = (0..RUNS).map(|_| time(&input)).min().unwrap();
+
+        match previous {
+            Some(previous) => println!(
+                "  {:>6}: {:>10.2?} ({:.1}x)",
+                size,
+                elapsed,
+                elapsed.as_secs_f64() / previous.as_secs_f64()
+            ),
+            None => println!("  {:>6}: {:>10.2?}", size, elapsed),
+        }
+
+        previous = Some(elapsed);
+    }
+}
+
+fn main() {
+    bench("map.merge", |n| {
+        format!(
+            "@use 'sass:map';
+            $map: ();
+            @for $i from 1 through {n} {{
+                $map: map.merge($map, (key-#{{$i}}: $i));
+            }}
+            a {{ b: length($map); }}"
+        )
+    });
+
+    bench("map.set", |n| {
+        format!(
+            "@use 'sass:map';
+            $map: ();
+            @for $i from 1 through {n} {{
+                $map: map.set($map, key-#{{$i}}, $i);
+            }}
+            a {{ b: map.get($map, key-1); }}"
+        )
+    });
+
+    bench("append", |n| {
+        format!(
+            "$list: ();
+            @for $i from 1 through {n} {{
+                $list: append($list, $i, comma);
+            }}
+            a {{ b: length($list); }}"
+        )
+    });
+
+    bench("join", |n| {
+        format!(
+            "$list: ();
+            @for $i from 1 through {n} {{
+                $list: join($list, ($i, $i + 1));
+            }}
+            a {{ b: length($list); }}"
+        )
+    });
+
+    bench("utility classes", |n| {
+        format!(
+            "@use 'sass:map';
+            $spacers: ();
+            @for $i from 1 through {n} {{
+                $spacers: map.merge($spacers, ($i: $i * 0.25rem));
+            }}
+            $classes: ();
+            @each $key, $value in $spacers {{
+                $classes: append($classes, m-#{{$key}});
+                .m-#{{$key}} {{ margin: map.get($spacers, $key); }}
+            }}"
+        )
+    });
+}
@@ -0,0 +1,365 @@
+//! Compile Sass from a build script
+//!
+//! This requires the `"build"` feature, which is not enabled by default.
+//!
+//! ```no_run
+//! // in `main` of build.rs
+//! grass::build::Build::new()
+//!     .input_dir("styles")
+//!     .load_path("vendor/bootstrap/scss")
+//!     .style(grass::OutputStyle::Compressed)
+//!     .compile()
+//!     .unwrap();
+//! ```
+//!
+//! ```ignore
+//! // src/main.rs
+//! static APP_CSS: &str = include_str!(concat!(env!("OUT_DIR"), "/app.css"));
+//! ```
+//!
+//! By default, every file loaded during compilation, including transitive
+//! imports, is emitted as a `cargo:rerun-if-changed` line, and Sass warnings
+//! are emitted as `cargo:warning` lines.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use grass_compiler::{
+    Compiler, Deprecation, Error, Fs, Logger, Options, OutputStyle, SpanLoc, StackFrame, StdFs,
+};
+
+/// A builder for compiling a set of stylesheets into an output directory
+#[derive(Debug, Clone)]
+pub struct Build {
+    inputs: Vec<PathBuf>,
+    input_dirs: Vec<PathBuf>,
+    out_dir: Option<PathBuf>,
+    load_paths: Vec<PathBuf>,
+    style: OutputStyle,
+    quiet: bool,
+    cargo_metadata: bool,
+}
+
+/// The result of a successful [`Build::compile`]
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Output {
+    /// The CSS files that were written, in the order they were compiled
+    pub files: Vec<PathBuf>,
+
+    /// Every file and directory the build depends on, including transitive
+    /// imports
+    pub dependencies: BTreeSet<PathBuf>,
+
+    /// Every warning emitted during compilation, formatted as a single string
+    pub warnings: Vec<String>,
+}
+
+impl Default for Build {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Build {
+    /// Create a build with no inputs, writing to `OUT_DIR`
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            input_dirs: Vec::new(),
+            out_dir: None,
+            load_paths: Vec::new(),
+            style: OutputStyle::Expanded,
+            quiet: false,
+            cargo_metadata: true,
+        }
+    }
+
+    /// Compile a single stylesheet. The output file is named after the input,
+    /// e.g. `styles/app.scss` is written to `app.css`
+    #[must_use]
+    #[inline]
+    pub fn input<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.inputs.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Compile every `.scss` and `.sass` file in a directory, recursively.
+    ///
+    /// Partials, files whose names begin with `_`, are skipped. The directory
+    /// structure is preserved in the output directory, e.g.
+    /// `styles/pages/home.scss` is written to `pages/home.css`
+    #[must_use]
+    #[inline]
+    pub fn input_dir<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.input_dirs.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// The directory to write CSS files to
+    ///
+    /// By default, this is the `OUT_DIR` environment variable set by cargo
+    /// for build scripts
+    #[must_use]
+    #[inline]
+    pub fn out_dir<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.out_dir = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Add a path to use when resolving imports. See [`Options::load_path`]
+    #[must_use]
+    #[inline]
+    pub fn load_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.load_paths.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Add multiple paths to use when resolving imports. See
+    /// [`Options::load_paths`]
+    #[must_use]
+    #[inline]
+    pub fn load_paths<P: AsRef<Path>>(mut self, paths: &[P]) -> Self {
+        self.load_paths
+            .extend(paths.iter().map(|path| path.as_ref().to_owned()));
+        self
+    }
+
+    /// The output style. See [`Options::style`]
+    #[must_use]
+    #[inline]
+    pub const fn style(mut self, style: OutputStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Don't report warnings. See [`Options::quiet`]
+    #[must_use]
+    #[inline]
+    pub const fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Whether to print `cargo:rerun-if-changed` and `cargo:warning` lines to
+    /// stdout
+    ///
+    /// By default, this value is `true`
+    #[must_use]
+    #[inline]
+    pub const fn cargo_metadata(mut self, cargo_metadata: bool) -> Self {
+        self.cargo_metadata = cargo_metadata;
+        self
+    }
+
+    /// Compile every input, writing the resulting CSS to the output directory
+    ///
+    /// Inputs are compiled with a single [`Compiler`], so stylesheets they
+    /// share, such as a framework's partials, are only parsed once.
+    ///
+    /// Compilation stops at the first error. Dependencies discovered before
+    /// the error are still reported to cargo, so that fixing the error triggers
+    /// a rebuild.
+    pub fn compile(&self) -> Result<Output, Box<Error>> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        ErrorKind::NotFound,
+                        "OUT_DIR is not set, use `Build::out_dir`",
+                    )
+                })?,
+        };
+
+        let fs = TrackingFs {
+            files: Mutex::new(BTreeSet::new()),
+        };
+        let logger = CollectingLogger {
+            warnings: Mutex::new(Vec::new()),
+        };
+
+        let options = Options::default()
+            .fs(&fs)
+            .logger(&logger)
+            .load_paths(&self.load_paths)
+            .style(self.style)
+            .quiet(self.quiet);
+
+        let mut output = Output::default();
+
+        let result = self.compile_all(&out_dir, &Compiler::new(options), &mut output);
+
+        output.dependencies.extend(fs.files.into_inner().unwrap());
+        output.dependencies.extend(self.input_dirs.iter().cloned());
+        output.warnings = logger.warnings.into_inner().unwrap();
+
+        if self.cargo_metadata {
+            for dependency in &output.dependencies {
+                println!("cargo:rerun-if-changed={}", dependency.display());
+            }
+
+            for warning in &output.warnings {
+                for line in warning.lines() {
+                    println!("cargo:warning={}", line);
+                }
+            }
+        }
+
+        result.map(|()| output)
+    }
+
+    fn compile_all(
+        &self,
+        out_dir: &Path,
+        compiler: &Compiler,
+        output: &mut Output,
+    ) -> Result<(), Box<Error>> {
+        let mut jobs = Vec::new();
+
+        for input in &self.inputs {
+            let file_name = input.with_extension("css");
+            let file_name = file_name.file_name().ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} is not a file", input.display()),
+                )
+            })?;
+            jobs.push((input.clone(), out_dir.join(file_name)));
+        }
+
+        for dir in &self.input_dirs {
+            for input in sass_files_in(dir)? {
+                let relative = input.strip_prefix(dir).unwrap_or(&input);
+                jobs.push((input.clone(), out_dir.join(relative.with_extension("css"))));
+            }
+        }
+
+        for (input, out_file) in jobs {
+            let css = compiler.compile(&input)?;
+
+            if let Some(parent) = out_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&out_file, css)?;
+
+            output.files.push(out_file);
+        }
+
+        Ok(())
+    }
+}
+
+/// Every non-partial Sass file in `dir` and its subdirectories, sorted
+fn sass_files_in(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            files.append(&mut sass_files_in(&path)?);
+            continue;
+        }
+
+        let is_partial = matches!(
+            path.file_name().and_then(|name| name.to_str()),
+            Some(name) if name.starts_with('_')
+        );
+
+        let is_sass = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("scss" | "sass")
+        );
+
+        if is_sass && !is_partial {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Records every file read during compilation
+#[derive(Debug)]
+struct TrackingFs {
+    files: Mutex<BTreeSet<PathBuf>>,
+}
+
+impl Fs for TrackingFs {
+    fn is_dir(&self, path: &Path) -> bool {
+        StdFs.is_dir(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        StdFs.is_file(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let contents = StdFs.read(path)?;
+        self.files.lock().unwrap().insert(path.to_owned());
+        Ok(contents)
+    }
+}
+
+/// Collects warnings and `@debug` messages so they can be reported to cargo
+#[derive(Debug)]
+struct CollectingLogger {
+    warnings: Mutex<Vec<String>>,
+}
+
+impl CollectingLogger {
+    fn push(&self, header: &str, location: &SpanLoc, message: &str) {
+        self.warnings.lock().unwrap().push(format!(
+            "{}: {}\n    {}:{}:{}",
+            header,
+            message,
+            location.file.name(),
+            location.begin.line + 1,
+            location.begin.column + 1
+        ));
+    }
+}
+
+impl Logger for CollectingLogger {
+    fn debug(&self, location: SpanLoc, message: &str, _trace: &[StackFrame]) {
+        self.push("DEBUG", &location, message);
+    }
+
+    fn warn(&self, location: SpanLoc, message: &str, _trace: &[StackFrame]) {
+        self.push("Warning", &location, message);
+    }
+
+    fn deprecation(
+        &self,
+        deprecation: Deprecation,
+        location: SpanLoc,
+        message: &str,
+        _trace: &[StackFrame],
+    ) {
+        self.push(
+            &format!("Deprecation Warning [{}]", deprecation),
+            &location,
+            message,
+        );
+    }
+
+    fn warn_without_span(&self, message: &str) {
+        self.warnings
+            .lock()
+            .unwrap()
+            .push(format!("Warning: {}", message));
+    }
+}
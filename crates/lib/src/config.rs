@@ -0,0 +1,189 @@
+//! Project configuration read from a `grass.toml` file
+//!
+//! The file is looked up in the current directory and its ancestors, so that
+//! every developer and CI machine compiling a project uses the same settings,
+//! e.g.
+//!
+//! ```toml
+//! load-paths = ["vendor", "node_modules/bootstrap/scss"]
+//! style = "compressed"
+//!
+//! [deprecations]
+//! silence = ["import"]
+//! fatal = ["slash-div"]
+//! future = ["global-builtin"]
+//!
+//! [outputs]
+//! "styles/app.scss" = "public/app.css"
+//! "styles/admin.scss" = "public/admin.css"
+//! ```
+//!
+//! Relative paths are resolved against the directory containing the file.
+//! Flags passed on the command line take precedence over the file: `--style`
+//! and each kind of deprecation flag replace the corresponding setting, while
+//! `--load-path`s are searched before the load paths from the file.
+//!
+//! Any TOML syntax may be used, such as inline tables or dotted keys, but every
+//! setting must be a string or an array of strings.
+
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use grass::{Deprecation, OutputStyle};
+use toml_edit::{ImDocument, Item, TableLike};
+
+pub const FILE_NAME: &str = "grass.toml";
+
+#[derive(Debug, Default)]
+pub struct Config {
+    pub load_paths: Vec<PathBuf>,
+    pub style: Option<OutputStyle>,
+    pub silence_deprecations: Vec<Deprecation>,
+    pub fatal_deprecations: Vec<Deprecation>,
+    pub future_deprecations: Vec<Deprecation>,
+    /// Pairs of input stylesheet and output CSS file, compiled when no input
+    /// is given on the command line
+    pub outputs: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Config {
+    /// Find `grass.toml` in `dir` or the closest ancestor that contains one
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+
+        Self::parse(&text, root).map_err(|e| format!("{}:{}", path.display(), e))
+    }
+
+    fn parse(text: &str, root: &Path) -> Result<Self, String> {
+        let document = ImDocument::parse(text).map_err(|e| {
+            // syntax errors may list what was expected on following lines
+            let message = e.message().lines().collect::<Vec<_>>().join(": ");
+            format!("{}: {}", line(text, e.span()), message)
+        })?;
+
+        let mut config = Self::default();
+
+        let table = document.as_table();
+
+        for (key, value) in table.iter() {
+            let err = |message: String| format!("{}: {}", key_line(text, table, key), message);
+
+            match key {
+                "load-paths" => {
+                    for path in strings(value).map_err(err)? {
+                        config.load_paths.push(root.join(path));
+                    }
+                }
+                "style" => {
+                    config.style = Some(match string(value).map_err(err)? {
+                        "expanded" => OutputStyle::Expanded,
+                        "compressed" => OutputStyle::Compressed,
+                        style => {
+                            return Err(err(format!(
+                                "unknown style \"{}\", expected \"expanded\" or \"compressed\"",
+                                style
+                            )))
+                        }
+                    });
+                }
+                "deprecations" => {
+                    config.parse_deprecations(text, table_like(value).map_err(err)?)?
+                }
+                "outputs" => {
+                    let outputs = table_like(value).map_err(err)?;
+
+                    for (input, output) in outputs.iter() {
+                        let output = string(output).map_err(|message| {
+                            format!("{}: {}", key_line(text, outputs, input), message)
+                        })?;
+                        config.outputs.push((root.join(input), root.join(output)));
+                    }
+                }
+                key => return Err(err(format!("unknown key `{}`", key))),
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn parse_deprecations(&mut self, text: &str, table: &dyn TableLike) -> Result<(), String> {
+        for (kind, value) in table.iter() {
+            let err = |message: String| format!("{}: {}", key_line(text, table, kind), message);
+
+            let mut deprecations = Vec::new();
+
+            for id in strings(value).map_err(err)? {
+                deprecations.push(
+                    Deprecation::from_id(id)
+                        .ok_or_else(|| err(format!("unknown deprecation \"{}\"", id)))?,
+                );
+            }
+
+            match kind {
+                "silence" => self.silence_deprecations = deprecations,
+                "fatal" => self.fatal_deprecations = deprecations,
+                "future" => self.future_deprecations = deprecations,
+                kind => return Err(err(format!("unknown key `deprecations.{}`", kind))),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn string(item: &Item) -> Result<&str, String> {
+    item.as_str()
+        .ok_or_else(|| format!("expected a string, found {}", item.type_name()))
+}
+
+fn strings(item: &Item) -> Result<Vec<&str>, String> {
+    let array = item
+        .as_array()
+        .ok_or_else(|| format!("expected an array of strings, found {}", item.type_name()))?;
+
+    array
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .ok_or_else(|| format!("expected a string, found {}", value.type_name()))
+        })
+        .collect()
+}
+
+fn table_like(item: &Item) -> Result<&dyn TableLike, String> {
+    item.as_table_like()
+        .ok_or_else(|| format!("expected a table, found {}", item.type_name()))
+}
+
+/// The line on which `key` is declared in `table`, for error messages
+fn key_line(text: &str, table: &dyn TableLike, key: &str) -> usize {
+    line(
+        text,
+        table
+            .get_key_value(key)
+            .and_then(|(key, value)| key.span().or_else(|| value.span())),
+    )
+}
+
+/// The 1-indexed line containing the start of `span`
+fn line(text: &str, span: Option<Range<usize>>) -> usize {
+    let start = span.map_or(0, |span| span.start.min(text.len()));
+    text.as_bytes()[..start]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count()
+        + 1
+}
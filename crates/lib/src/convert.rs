@@ -0,0 +1,91 @@
+//! The `grass convert` subcommand
+//!
+//! Converts a stylesheet between SCSS and the indented syntax. Like the main
+//! command, the stylesheet is read from stdin if no input is given, and
+//! written to stdout if no output is given.
+
+use std::{
+    fs,
+    io::{stdin, stdout, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use grass::{format::convert, InputSyntax};
+
+use crate::fmt::{format_options, report_error, with_format_args};
+
+pub fn subcommand() -> App<'static, 'static> {
+    with_format_args(
+        SubCommand::with_name("convert")
+            .about("Convert a stylesheet between SCSS and the indented syntax")
+            .arg(
+                Arg::with_name("TO")
+                    .long("to")
+                    .help("The syntax to convert to.")
+                    .case_insensitive(true)
+                    .possible_values(&["scss", "sass"])
+                    .required(true)
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("SYNTAX")
+                    .long("from")
+                    .help("The syntax of the input stylesheet. By default, this is inferred from the file extension.")
+                    .case_insensitive(true)
+                    .possible_values(&["scss", "sass", "css"])
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("INPUT")
+                    .help("The stylesheet to convert. If omitted, the stylesheet is read from stdin."),
+            )
+            .arg(
+                Arg::with_name("OUTPUT")
+                    .help("Output file, or a directory to write it to. If omitted, the stylesheet is written to stdout."),
+            ),
+    )
+}
+
+pub fn run(matches: &ArgMatches) -> std::io::Result<()> {
+    let (to, extension) = match matches
+        .value_of("TO")
+        .unwrap()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "scss" => (InputSyntax::Scss, "scss"),
+        "sass" => (InputSyntax::Sass, "sass"),
+        _ => unreachable!(),
+    };
+
+    let options = format_options(matches);
+
+    let input = matches.value_of("INPUT");
+
+    let converted = match input {
+        Some(path) => convert(fs::read_to_string(path)?, Path::new(path), to, &options),
+        None => {
+            let mut buffer = String::new();
+            stdin().read_to_string(&mut buffer)?;
+            convert(buffer, Path::new("stdin"), to, &options)
+        }
+    }
+    .unwrap_or_else(|e| report_error(e));
+
+    match matches.value_of("OUTPUT") {
+        Some(path) => {
+            let mut path = PathBuf::from(path);
+
+            // when writing to a directory, name the output after the input
+            if path.is_dir() {
+                let input = Path::new(input.unwrap_or("stdin"));
+                path.push(input.with_extension(extension).file_name().unwrap());
+            }
+
+            fs::write(path, converted)
+        }
+        None => stdout().write_all(converted.as_bytes()),
+    }
+}
@@ -0,0 +1,174 @@
+//! Machine-readable diagnostics for the `--error-format=json` flag
+//!
+//! Each diagnostic is written to stderr as a single line containing one JSON
+//! object, e.g.
+//!
+//! ```json
+//! {"severity":"error","message":"Expected expression.","file":"input.scss","start":{"line":1,"column":11,"offset":10},"end":{"line":1,"column":11,"offset":10},"stack":[{"member":"root stylesheet","file":"input.scss","line":1,"column":11}]}
+//! ```
+//!
+//! Lines and columns are 1-indexed. Offsets are 0-indexed byte offsets into
+//! the file. The `stack` is the Sass stack trace, starting with the innermost
+//! frame. Deprecation warnings additionally carry the id of the deprecation,
+//! e.g. `"deprecation":"slash-div"`, and findings of `grass lint` the id of
+//! the lint, e.g. `"lint":"unused-member"`.
+
+use std::fmt::Write;
+
+use grass::{
+    lint::Lint, utils::write_json_string, Deprecation, Error, ErrorKind, Logger, SpanLoc,
+    StackFrame,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl ErrorFormat {
+    pub const VARIANTS: [&'static str; 2] = ["human", "json"];
+
+    pub fn from_str(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Self::Json,
+            _ => Self::Human,
+        }
+    }
+}
+
+/// Emits `@warn` and `@debug` messages as JSON objects
+#[derive(Debug)]
+pub struct JsonLogger;
+
+impl Logger for JsonLogger {
+    fn debug(&self, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+        eprintln!(
+            "{}",
+            diagnostic_json("debug", message, Some(&location), trace)
+        );
+    }
+
+    fn warn(&self, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+        eprintln!(
+            "{}",
+            diagnostic_json("warning", message, Some(&location), trace)
+        );
+    }
+
+    fn deprecation(
+        &self,
+        deprecation: Deprecation,
+        location: SpanLoc,
+        message: &str,
+        trace: &[StackFrame],
+    ) {
+        let mut json = diagnostic_json("warning", message, Some(&location), trace);
+        push_field(&mut json, "deprecation", deprecation.id());
+
+        eprintln!("{}", json);
+    }
+
+    fn warn_without_span(&self, message: &str) {
+        eprintln!("{}", diagnostic_json("warning", message, None, &[]));
+    }
+}
+
+/// Serialize a compilation error as a single-line JSON object
+pub fn error_to_json(err: &Error) -> String {
+    match err.clone().kind() {
+        ErrorKind::ParseError {
+            message,
+            loc,
+            stack,
+            ..
+        } => diagnostic_json("error", &message, Some(&loc), &stack),
+        ErrorKind::IoError(io) => diagnostic_json("error", &io.to_string(), None, &[]),
+        ErrorKind::FromUtf8Error(message) => diagnostic_json("error", &message, None, &[]),
+        _ => diagnostic_json("error", &err.to_string(), None, &[]),
+    }
+}
+
+/// Serialize a finding of `grass lint` as a single-line JSON object
+pub fn lint_to_json(lint: &Lint) -> String {
+    let mut json = diagnostic_json("warning", &lint.message, Some(&lint.location), &[]);
+    push_field(&mut json, "lint", lint.kind.id());
+    json
+}
+
+/// Insert a string field before the closing brace of a JSON object
+fn push_field(json: &mut String, name: &str, value: &str) {
+    json.pop();
+    json.push(',');
+    write_json_string(json, name);
+    json.push(':');
+    write_json_string(json, value);
+    json.push('}');
+}
+
+fn diagnostic_json(
+    severity: &str,
+    message: &str,
+    loc: Option<&SpanLoc>,
+    stack: &[StackFrame],
+) -> String {
+    let mut out = String::new();
+
+    out.push_str("{\"severity\":");
+    write_json_string(&mut out, severity);
+    out.push_str(",\"message\":");
+    write_json_string(&mut out, message);
+
+    match loc {
+        Some(loc) => {
+            out.push_str(",\"file\":");
+            write_json_string(&mut out, loc.file.name());
+            out.push_str(",\"start\":");
+            write_position(&mut out, loc, loc.begin.line, loc.begin.column);
+            out.push_str(",\"end\":");
+            write_position(&mut out, loc, loc.end.line, loc.end.column);
+        }
+        None => out.push_str(",\"file\":null,\"start\":null,\"end\":null"),
+    }
+
+    out.push_str(",\"stack\":[");
+    for (idx, frame) in stack.iter().enumerate() {
+        if idx != 0 {
+            out.push(',');
+        }
+        out.push_str("{\"member\":");
+        write_json_string(&mut out, &frame.member);
+        out.push_str(",\"file\":");
+        write_json_string(&mut out, frame.loc.file.name());
+        write!(
+            out,
+            ",\"line\":{},\"column\":{}}}",
+            frame.loc.begin.line + 1,
+            frame.loc.begin.column + 1
+        )
+        .unwrap();
+    }
+    out.push_str("]}");
+
+    out
+}
+
+fn write_position(out: &mut String, loc: &SpanLoc, line: usize, column: usize) {
+    let line_start = (loc.file.line_span(line).low() - loc.file.span.low()) as usize;
+    let column_bytes: usize = loc
+        .file
+        .source_line(line)
+        .chars()
+        .take(column)
+        .map(char::len_utf8)
+        .sum();
+
+    write!(
+        out,
+        "{{\"line\":{},\"column\":{},\"offset\":{}}}",
+        line + 1,
+        column + 1,
+        line_start + column_bytes
+    )
+    .unwrap();
+}
@@ -0,0 +1,150 @@
+//! The `grass fmt` subcommand
+//!
+//! Formats stylesheets in place, or with `--check`, lists the stylesheets
+//! which aren't formatted and exits with a non-zero status if there are any.
+//! Without any files, the stylesheet is read from stdin and written to stdout.
+
+use std::{
+    fs,
+    io::{stdin, stdout, Read, Write},
+    path::Path,
+};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use grass::{
+    format::{format, FormatOptions},
+    InputSyntax,
+};
+
+fn is_number(val: String) -> Result<(), String> {
+    val.parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| format!("expected a number, found '{}'", val))
+}
+
+pub fn subcommand() -> App<'static, 'static> {
+    with_format_args(
+        SubCommand::with_name("fmt")
+            .about("Format stylesheets in place")
+            .arg(
+                Arg::with_name("CHECK")
+                    .long("check")
+                    .help("Don't write any files. Instead, list the files which aren't formatted and exit with a non-zero status if there are any."),
+            )
+            .arg(
+                Arg::with_name("SYNTAX")
+                    .long("syntax")
+                    .help("The syntax of the input stylesheets. By default, this is inferred from the file extension.")
+                    .case_insensitive(true)
+                    .possible_values(&["scss", "sass", "css"])
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("FILES")
+                    .help("Stylesheets to format. If omitted, the stylesheet is read from stdin and written to stdout.")
+                    .multiple(true),
+            ),
+    )
+}
+
+/// Adds the flags controlling indentation and line width, which are shared
+/// with `grass convert`
+pub fn with_format_args(app: App<'static, 'static>) -> App<'static, 'static> {
+    app.arg(
+        Arg::with_name("INDENT_WIDTH")
+            .long("indent-width")
+            .help("The number of spaces to indent each level of nesting by.")
+            .default_value("2")
+            .takes_value(true)
+            .validator(is_number),
+    )
+    .arg(
+        Arg::with_name("USE_TABS")
+            .long("use-tabs")
+            .help("Indent with tabs rather than spaces."),
+    )
+    .arg(
+        Arg::with_name("LINE_WIDTH")
+            .long("line-width")
+            .help("The width lines should fit within.")
+            .default_value("80")
+            .takes_value(true)
+            .validator(is_number),
+    )
+}
+
+/// Reads the options set by [`with_format_args`], along with the input syntax
+pub fn format_options(matches: &ArgMatches) -> FormatOptions {
+    let mut options = FormatOptions::default()
+        .indent_width(matches.value_of("INDENT_WIDTH").unwrap().parse().unwrap())
+        .use_tabs(matches.is_present("USE_TABS"))
+        .line_width(matches.value_of("LINE_WIDTH").unwrap().parse().unwrap());
+
+    if let Some(syntax) = matches.value_of("SYNTAX") {
+        options = options.input_syntax(match syntax.to_ascii_lowercase().as_str() {
+            "scss" => InputSyntax::Scss,
+            "sass" => InputSyntax::Sass,
+            "css" => InputSyntax::Css,
+            _ => unreachable!(),
+        });
+    }
+
+    options
+}
+
+pub fn run(matches: &ArgMatches) -> std::io::Result<()> {
+    let options = format_options(matches);
+    let check = matches.is_present("CHECK");
+
+    let files = match matches.values_of("FILES") {
+        Some(files) => files,
+        None => {
+            let mut input = String::new();
+            stdin().read_to_string(&mut input)?;
+
+            let formatted = format(input.clone(), Path::new("stdin"), &options)
+                .unwrap_or_else(|e| report_error(e));
+
+            if check {
+                if formatted != input {
+                    println!("stdin");
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+
+            return stdout().write_all(formatted.as_bytes());
+        }
+    };
+
+    let mut unformatted = false;
+
+    for file in files {
+        let path = Path::new(file);
+        let input = fs::read_to_string(path)?;
+        let formatted = format(input.clone(), path, &options).unwrap_or_else(|e| report_error(e));
+
+        if formatted == input {
+            continue;
+        }
+
+        if check {
+            println!("{}", file);
+            unformatted = true;
+        } else {
+            fs::write(path, formatted)?;
+        }
+    }
+
+    if unformatted {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+pub fn report_error(e: Box<grass::Error>) -> ! {
+    eprintln!("{}", e);
+    std::process::exit(1)
+}
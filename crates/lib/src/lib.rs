@@ -24,6 +24,31 @@ fn main() -> Result<(), Box<grass::Error>> {
 }
 ```
 
+## Compiling in parallel
+[`Options`] is `Send` and `Sync`, and compilations don't share any state, so
+a single set of options can be used to compile many stylesheets at once, and a
+compilation can run on any thread. Custom [`Fs`], [`Logger`] and [`Importer`]
+implementations must be `Sync` for this reason.
+
+```
+let options = grass::Options::default().style(grass::OutputStyle::Compressed);
+let inputs = ["a { b: c; }", "d { e: f; }"];
+
+let outputs = std::thread::scope(|scope| {
+    let handles = inputs
+        .iter()
+        .map(|input| scope.spawn(|| grass::from_string(input.to_string(), &options)))
+        .collect::<Vec<_>>();
+
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect::<Result<Vec<_>, _>>()
+});
+
+assert_eq!(outputs.unwrap(), ["a{b:c}", "d{e:f}"]);
+```
+
 ## Use as binary
 ```bash
 cargo install grass
@@ -82,6 +107,10 @@ grass input.scss
 
 pub use grass_compiler::*;
 
+#[cfg(any(feature = "build", doc))]
+#[cfg_attr(doc, doc(cfg(feature = "build")))]
+pub mod build;
+
 /// Include CSS in your binary at compile time from a Sass source file
 ///
 /// ```
@@ -90,6 +119,31 @@ pub use grass_compiler::*;
 ///
 /// This requires the `"macro"` feature, which is not enabled by default.
 ///
+/// Paths are resolved relative to the directory containing the `Cargo.toml` of
+/// the crate invoking the macro, so this works the same way within workspaces.
+///
+/// ###### Options
+///
+/// A subset of [`Options`] may be passed after the path:
+///
+/// ```
+/// static CSS: &str = grass::include!(
+///     "../static/_index.scss",
+///     style = "expanded",
+///     load_paths = ["../static"],
+///     quiet = true,
+/// );
+/// ```
+///
+/// - `style`: either `"expanded"` or `"compressed"`. Defaults to `"compressed"`
+/// - `load_paths`: a list of paths to use when resolving imports, which are also
+///   resolved relative to the crate manifest. Defaults to `[]`
+/// - `quiet`: whether to silence warnings. Defaults to `false`
+///
+/// All other options use their default values.
+///
+/// ###### Incremental compilation
+///
 /// By default `grass` will track files using [`include_str!`]. This allows incremental
 /// compilation to be updated when any Sass files are modified.
 ///
@@ -97,17 +151,38 @@ pub use grass_compiler::*;
 /// [proc_macro::tracked_path](https://github.com/rust-lang/rust/issues/99515)
 /// in order to force incremental recompilation, which is more robust and potentially
 /// faster. This is enabled by the `"nightly"` feature.
+#[macro_export]
+#[cfg(any(feature = "macro", doc))]
+#[cfg_attr(doc, doc(cfg(feature = "macro")))]
+macro_rules! include {
+    ($path:literal $(, $($options:tt)*)?) => {
+        $crate::__internal::include_sass::include_sass!($path $(, $($options)*)?)
+    };
+}
+
+/// Compile an inline Sass string to CSS at compile time
 ///
-/// ###### Limitations
+/// ```
+/// static CSS: &str = grass::scss!(r#"a { b { color: red; } }"#);
+///
+/// assert_eq!(CSS, "a b{color:red}");
+/// ```
 ///
-/// Compilation options are not configurable with this macro. The default values
-/// for all options are used, except for output style, which is compressed.
+/// This requires the `"macro"` feature, which is not enabled by default.
+///
+/// Relative imports are resolved against the directory containing the
+/// `Cargo.toml` of the crate invoking the macro. The same options as
+/// [`include!`](crate::include!) are accepted after the literal, and
+/// imported files are tracked in the same way.
+///
+/// Errors point into the literal when it is a raw string and the compiler
+/// supports it, and at the entire literal otherwise.
 #[macro_export]
 #[cfg(any(feature = "macro", doc))]
 #[cfg_attr(doc, doc(cfg(feature = "macro")))]
-macro_rules! include {
-    ($path:literal) => {
-        $crate::__internal::include_sass::include_sass!($path);
+macro_rules! scss {
+    ($source:literal $(, $($options:tt)*)?) => {
+        $crate::__internal::include_sass::scss!($source $(, $($options)*)?)
     };
 }
 
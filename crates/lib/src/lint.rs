@@ -0,0 +1,106 @@
+//! The `grass lint` subcommand
+//!
+//! Reports unused members, unused `@use` rules, shadowed globals, duplicate
+//! properties and unmatched `@extend` rules in each stylesheet, and exits with
+//! a non-zero status if there are any. Options given before the subcommand,
+//! such as `--load-path`, apply while evaluating the stylesheets.
+//!
+//! With `--error-format=json`, each finding is written as a JSON object as
+//! described in [`crate::diagnostic`].
+
+use std::{fs, path::Path};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use grass::{
+    lint::{lint, LintKind},
+    Options,
+};
+
+use crate::diagnostic::{error_to_json, lint_to_json, ErrorFormat};
+
+const LINT_IDS: [&str; LintKind::ALL.len()] = [
+    LintKind::ALL[0].id(),
+    LintKind::ALL[1].id(),
+    LintKind::ALL[2].id(),
+    LintKind::ALL[3].id(),
+    LintKind::ALL[4].id(),
+    LintKind::ALL[5].id(),
+];
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("lint")
+        .about("Report likely mistakes in stylesheets")
+        .arg(
+            Arg::with_name("ALLOW")
+                .long("allow")
+                .help("Don't report this kind of lint.")
+                .possible_values(&LINT_IDS)
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("ERROR_FORMAT")
+                .long("error-format")
+                .help("How to format lints and errors.")
+                .case_insensitive(true)
+                .possible_values(&ErrorFormat::VARIANTS)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("FILES")
+                .help("Stylesheets to lint")
+                .required(true)
+                .multiple(true),
+        )
+}
+
+pub fn run(
+    matches: &ArgMatches,
+    options: &Options,
+    error_format: ErrorFormat,
+) -> std::io::Result<()> {
+    let allowed: Vec<LintKind> = matches
+        .values_of("ALLOW")
+        .into_iter()
+        .flatten()
+        .filter_map(LintKind::from_id)
+        .collect();
+
+    let mut found = false;
+
+    for file in matches.values_of("FILES").unwrap() {
+        let path = Path::new(file);
+        let input = fs::read_to_string(path)?;
+
+        let lints = match lint(input, path, options) {
+            Ok(lints) => lints,
+            Err(e) => {
+                match error_format {
+                    ErrorFormat::Human => eprintln!("{}", e),
+                    ErrorFormat::Json => eprintln!("{}", error_to_json(&e)),
+                }
+                std::process::exit(1)
+            }
+        };
+
+        for lint in lints {
+            if allowed.contains(&lint.kind) {
+                continue;
+            }
+
+            match error_format {
+                ErrorFormat::Human => eprintln!("{}", lint),
+                ErrorFormat::Json => eprintln!("{}", lint_to_json(&lint)),
+            }
+            found = true;
+        }
+    }
+
+    if found {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
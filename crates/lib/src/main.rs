@@ -1,12 +1,26 @@
 use std::{
-    fs::OpenOptions,
+    fs::{self, OpenOptions},
     io::{stdin, stdout, Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use clap::{arg_enum, App, AppSettings, Arg};
 
-use grass::{from_path, from_string, Options, OutputStyle};
+use grass::{
+    from_path, from_string, from_string_with_file_name, Deprecation, InputSyntax, Options,
+    OutputStyle, StdLogger,
+};
+
+use config::Config;
+use diagnostic::{error_to_json, ErrorFormat, JsonLogger};
+
+mod config;
+mod convert;
+mod diagnostic;
+mod fmt;
+mod lint;
+mod migrate;
+mod watch;
 
 // TODO remove this
 arg_enum! {
@@ -26,6 +40,8 @@ arg_enum! {
 }
 
 fn main() -> std::io::Result<()> {
+    let deprecation_ids: Vec<&str> = Deprecation::ALL.iter().map(|d| d.id()).collect();
+
     let matches = App::new("grass")
         .setting(AppSettings::ColoredHelp)
         .version(env!("CARGO_PKG_VERSION"))
@@ -39,18 +55,45 @@ fn main() -> std::io::Result<()> {
         .arg(
             Arg::with_name("INDENTED")
                 .long("indented")
-                .hidden(true)
                 .help("Use the indented syntax for input from stdin"),
         )
+        .arg(
+            Arg::with_name("SYNTAX")
+                .long("syntax")
+                .help("The syntax of the input stylesheet. By default, this is inferred from the file extension.")
+                .case_insensitive(true)
+                .possible_values(&["scss", "sass", "css"])
+                .conflicts_with("INDENTED")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("STDIN_FILENAME")
+                .long("stdin-filename")
+                .help("The path of the stylesheet read from stdin, used to resolve relative imports and in error messages")
+                .requires("STDIN")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("LOAD_PATH")
                 .short("I")
                 .long("load-path")
-                .help("A path to use when resolving imports. May be passed multiple times.")
+                .help("A path to use when resolving imports. May be passed multiple times. Paths in the SASS_PATH environment variable are used after these.")
                 .multiple(true)
                 .takes_value(true)
                 .number_of_values(1)
         )
+        .arg(
+            Arg::with_name("CONFIG")
+                .long("config")
+                .help("The project config file to use. By default, grass.toml is looked up in the current directory and its ancestors.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("NO_CONFIG")
+                .long("no-config")
+                .help("Don't read a project config file.")
+                .conflicts_with("CONFIG")
+        )
         .arg(
             Arg::with_name("STYLE")
                 // this is required for compatibility with ruby sass
@@ -113,7 +156,6 @@ fn main() -> std::io::Result<()> {
         .arg(
             Arg::with_name("WATCH")
                 .long("watch")
-                .hidden(true)
                 .help("Watch stylesheets and recompile when they change."),
         )
         .arg(
@@ -146,14 +188,49 @@ fn main() -> std::io::Result<()> {
         .arg(
             Arg::with_name("VERBOSE")
                 .long("verbose")
-                .hidden(true)
                 .help("Print all deprecation warnings even when they're repetitive.")
         )
+        .arg(
+            Arg::with_name("SILENCE_DEPRECATION")
+                .long("silence-deprecation")
+                .help("Deprecations to ignore. May be passed multiple times.")
+                .multiple(true)
+                .takes_value(true)
+                .number_of_values(1)
+                .possible_values(&deprecation_ids)
+        )
+        .arg(
+            Arg::with_name("FATAL_DEPRECATION")
+                .long("fatal-deprecation")
+                .help("Deprecations to treat as errors. May be passed multiple times.")
+                .multiple(true)
+                .takes_value(true)
+                .number_of_values(1)
+                .possible_values(&deprecation_ids)
+        )
+        .arg(
+            Arg::with_name("FUTURE_DEPRECATION")
+                .long("future-deprecation")
+                .help("Opt in to a deprecation early. May be passed multiple times.")
+                .multiple(true)
+                .takes_value(true)
+                .number_of_values(1)
+                .possible_values(&deprecation_ids)
+        )
         .arg(
             Arg::with_name("NO_UNICODE")
                 .long("no-unicode")
                 .help("Whether to use Unicode characters for messages.")
         )
+        .arg(
+            Arg::with_name("ERROR_FORMAT")
+                .long("error-format")
+                .help("How to format errors and warnings.")
+                .default_value("human")
+                .case_insensitive(true)
+                .possible_values(&ErrorFormat::VARIANTS)
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("QUIET")
                 .short("q")
@@ -161,43 +238,212 @@ fn main() -> std::io::Result<()> {
                 .help("Don't print warnings."),
         )
         .arg(
-            Arg::with_name("INPUT")
-                .required_unless("STDIN")
-                .help("SCSS files"),
+            Arg::with_name("PRECISION")
+                .long("precision")
+                .help("How many digits of precision to use when outputting decimal numbers.")
+                .default_value("10")
+                .takes_value(true)
+                .validator(|val| {
+                    val.parse::<u8>()
+                        .map(|_| ())
+                        .map_err(|_| format!("expected a number from 0 to 255, found '{}'", val))
+                })
         )
         .arg(
-            Arg::with_name("OUTPUT")
-                .help("Output SCSS file")
+            Arg::with_name("PKG_IMPORTER")
+                .long("pkg-importer")
+                .help("Resolve `pkg:` URLs using the given package importer.")
+                .takes_value(true)
+                .possible_values(&["node"])
         )
-
-        // Hidden, legacy arguments
         .arg(
-            Arg::with_name("PRECISION")
-                .long("precision")
-                .hidden(true)
-                .takes_value(true)
+            Arg::with_name("EMBEDDED")
+                .long("embedded")
+                .help("Run as a compiler for the Embedded Sass protocol, over stdin and stdout")
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("SCSS files. If omitted, the outputs listed in the project config file are compiled"),
+        )
+        .arg(
+            Arg::with_name("OUTPUT")
+                .help("Output CSS file, or a directory to write it to")
         )
+        .subcommand(fmt::subcommand())
+        .subcommand(convert::subcommand())
+        .subcommand(lint::subcommand())
+        .subcommand(migrate::subcommand())
         .get_matches();
 
-    let load_paths = matches
+    if let Some(matches) = matches.subcommand_matches("fmt") {
+        return fmt::run(matches);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("convert") {
+        return convert::run(matches);
+    }
+
+    if matches.is_present("EMBEDDED") {
+        return grass::embedded::serve(stdin(), stdout());
+    }
+
+    // `grass lint --error-format` overrides the flag given before the subcommand
+    let error_format = ErrorFormat::from_str(
+        matches
+            .subcommand_matches("lint")
+            .and_then(|lint| lint.value_of("ERROR_FORMAT"))
+            .or_else(|| matches.value_of("ERROR_FORMAT"))
+            .unwrap(),
+    );
+
+    let config_path = if matches.is_present("NO_CONFIG") {
+        None
+    } else if let Some(path) = matches.value_of("CONFIG") {
+        Some(PathBuf::from(path))
+    } else {
+        Config::find(&std::env::current_dir()?)
+    };
+
+    let config = match config_path {
+        Some(path) => Config::load(&path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1)
+        }),
+        None => Config::default(),
+    };
+
+    // load paths from the command line take precedence over those from the
+    // config file, which take precedence over `SASS_PATH`
+    let mut load_paths: Vec<PathBuf> = matches
         .values_of("LOAD_PATH")
-        .map_or_else(Vec::new, |vals| vals.map(Path::new).collect());
+        .map_or_else(Vec::new, |vals| vals.map(PathBuf::from).collect());
+    load_paths.extend(config.load_paths.iter().cloned());
+    if let Some(sass_path) = std::env::var_os("SASS_PATH") {
+        load_paths
+            .extend(std::env::split_paths(&sass_path).filter(|path| !path.as_os_str().is_empty()));
+    }
 
-    let style = match &matches.value_of("STYLE").unwrap().to_lowercase() as &str {
-        "expanded" => OutputStyle::Expanded,
-        "compressed" => OutputStyle::Compressed,
-        _ => unreachable!(),
+    let style = match config.style {
+        Some(style) if matches.occurrences_of("STYLE") == 0 => style,
+        _ => match &matches.value_of("STYLE").unwrap().to_lowercase() as &str {
+            "expanded" => OutputStyle::Expanded,
+            "compressed" => OutputStyle::Compressed,
+            _ => unreachable!(),
+        },
     };
 
-    let options = &Options::default()
+    let mut options = Options::default()
+        .logger(match error_format {
+            ErrorFormat::Human => &StdLogger,
+            ErrorFormat::Json => &JsonLogger,
+        })
         .load_paths(&load_paths)
         .style(style)
         .quiet(matches.is_present("QUIET"))
         .unicode_error_messages(!matches.is_present("NO_UNICODE"))
-        .allows_charset(!matches.is_present("NO_CHARSET"));
+        .allows_charset(!matches.is_present("NO_CHARSET"))
+        .precision(matches.value_of("PRECISION").unwrap().parse().unwrap())
+        .verbose(matches.is_present("VERBOSE"));
+
+    if matches.is_present("INDENTED") {
+        options = options.input_syntax(InputSyntax::Sass);
+    } else if let Some(syntax) = matches.value_of("SYNTAX") {
+        options = options.input_syntax(match syntax.to_ascii_lowercase().as_str() {
+            "scss" => InputSyntax::Scss,
+            "sass" => InputSyntax::Sass,
+            "css" => InputSyntax::Css,
+            _ => unreachable!(),
+        });
+    }
+
+    if matches.value_of("PKG_IMPORTER") == Some("node") {
+        options = options.node_package_importer(std::env::current_dir()?);
+    }
+
+    // deprecations passed on the command line replace those of the same kind
+    // from the config file
+    let deprecations = |name: &str, configured: &[Deprecation]| -> Vec<Deprecation> {
+        match matches.values_of(name) {
+            Some(ids) => ids.map(|id| Deprecation::from_id(id).unwrap()).collect(),
+            None => configured.to_vec(),
+        }
+    };
+
+    for deprecation in deprecations("SILENCE_DEPRECATION", &config.silence_deprecations) {
+        options = options.silence_deprecation(deprecation);
+    }
+
+    for deprecation in deprecations("FATAL_DEPRECATION", &config.fatal_deprecations) {
+        options = options.fatal_deprecation(deprecation);
+    }
+
+    for deprecation in deprecations("FUTURE_DEPRECATION", &config.future_deprecations) {
+        options = options.future_deprecation(deprecation);
+    }
+
+    let options = &options;
+
+    let report_error = |e: Box<grass::Error>| -> ! {
+        match error_format {
+            ErrorFormat::Human => eprintln!("{}", e),
+            ErrorFormat::Json => eprintln!("{}", error_to_json(&e)),
+        }
+        std::process::exit(1)
+    };
+
+    if let Some(matches) = matches.subcommand_matches("lint") {
+        return lint::run(matches, options, error_format);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("migrate") {
+        return migrate::run(matches, options);
+    }
+
+    if matches.is_present("WATCH") {
+        let jobs = watch_jobs(&matches, &config);
+        return watch::run(&jobs, options, error_format);
+    }
+
+    if !matches.is_present("STDIN") && !matches.is_present("INPUT") {
+        if config.outputs.is_empty() {
+            clap::Error::with_description(
+                "The following required arguments were not provided:\n    <INPUT>\n\nPass an input file, --stdin, or list outputs in grass.toml",
+                clap::ErrorKind::MissingRequiredArgument,
+            )
+            .exit();
+        }
+
+        for (input, output) in &config.outputs {
+            let css = from_path(input, options).unwrap_or_else(|e| report_error(e));
+
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(output, css)?;
+        }
+
+        return Ok(());
+    }
+
+    // with `--stdin`, the only positional argument is the output path
+    let (input, output) = if matches.is_present("STDIN") {
+        (
+            None,
+            matches
+                .value_of("OUTPUT")
+                .or_else(|| matches.value_of("INPUT")),
+        )
+    } else {
+        (matches.value_of("INPUT"), matches.value_of("OUTPUT"))
+    };
 
     let (mut stdout_write, mut file_write);
-    let buf_out: &mut dyn Write = if let Some(path) = matches.value_of("OUTPUT") {
+    let buf_out: &mut dyn Write = if let Some(path) = output {
+        let input = input
+            .or_else(|| matches.value_of("STDIN_FILENAME"))
+            .unwrap_or("stdin");
+        let path = output_path(input, path);
+
         file_write = OpenOptions::new()
             .create(true)
             .write(true)
@@ -210,25 +456,52 @@ fn main() -> std::io::Result<()> {
     };
 
     buf_out.write_all(
-        if let Some(name) = matches.value_of("INPUT") {
+        if let Some(name) = input {
             from_path(name, options)
-        } else if matches.is_present("STDIN") {
-            from_string(
-                {
-                    let mut buffer = String::new();
-                    stdin().read_to_string(&mut buffer)?;
-                    buffer
-                },
-                options,
-            )
         } else {
-            unreachable!()
+            let mut buffer = String::new();
+            stdin().read_to_string(&mut buffer)?;
+
+            match matches.value_of("STDIN_FILENAME") {
+                Some(path) => from_string_with_file_name(buffer, path, options),
+                None => from_string(buffer, options),
+            }
         }
-        .unwrap_or_else(|e| {
-            eprintln!("{}", e);
-            std::process::exit(1)
-        })
+        .unwrap_or_else(|e| report_error(e))
         .as_bytes(),
     )?;
     Ok(())
 }
+
+/// The file to write the CSS compiled from `input` to
+fn output_path(input: &str, output: &str) -> PathBuf {
+    let mut path = PathBuf::from(output);
+
+    // when writing to a directory, name the output after the input
+    if path.is_dir() {
+        path.push(Path::new(input).with_extension("css").file_name().unwrap());
+    }
+
+    path
+}
+
+/// The inputs and outputs to compile with `--watch`, which can only write to
+/// files
+fn watch_jobs(matches: &clap::ArgMatches, config: &Config) -> Vec<(PathBuf, PathBuf)> {
+    let error = |description: &str| -> ! {
+        clap::Error::with_description(description, clap::ErrorKind::ArgumentConflict).exit()
+    };
+
+    if matches.is_present("STDIN") {
+        error("--watch is not allowed when reading from stdin");
+    }
+
+    match (matches.value_of("INPUT"), matches.value_of("OUTPUT")) {
+        (Some(input), Some(output)) => vec![(PathBuf::from(input), output_path(input, output))],
+        (Some(_), None) => error("--watch requires an output file"),
+        (None, _) if config.outputs.is_empty() => {
+            error("--watch requires an input and output file, or outputs listed in grass.toml")
+        }
+        (None, _) => config.outputs.clone(),
+    }
+}
@@ -0,0 +1,160 @@
+//! The `grass migrate` subcommand
+//!
+//! Migrates stylesheets from `@import` to `@use` and `@forward`, rewriting
+//! them in place, or with `--dry-run`, printing a unified diff of the changes
+//! instead. Options given before the subcommand, such as `--load-path`, apply
+//! while resolving imports.
+
+use std::{fs, path::Path};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use grass::{
+    migrate::{migrate, MigrateOptions},
+    Options,
+};
+
+use crate::fmt::report_error;
+
+/// The number of unchanged lines shown around each change in a diff
+const CONTEXT: usize = 3;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("migrate")
+        .about("Migrate stylesheets from @import to @use and @forward")
+        .arg(
+            Arg::with_name("MIGRATE_DEPS")
+                .long("migrate-deps")
+                .help("Migrate the files imported by the stylesheets as well."),
+        )
+        .arg(
+            Arg::with_name("DRY_RUN")
+                .long("dry-run")
+                .help("Don't write any files. Instead, print a diff of the changes."),
+        )
+        .arg(
+            Arg::with_name("FILES")
+                .help("Stylesheets to migrate")
+                .required(true)
+                .multiple(true),
+        )
+}
+
+pub fn run(matches: &ArgMatches, options: &Options) -> std::io::Result<()> {
+    let migrate_options =
+        MigrateOptions::default().migrate_deps(matches.is_present("MIGRATE_DEPS"));
+    let dry_run = matches.is_present("DRY_RUN");
+
+    for file in matches.values_of("FILES").unwrap() {
+        let migrated =
+            migrate(Path::new(file), options, &migrate_options).unwrap_or_else(|e| report_error(e));
+
+        for file in migrated {
+            if dry_run {
+                print!(
+                    "{}",
+                    unified_diff(&file.path.to_string_lossy(), &file.original, &file.migrated)
+                );
+            } else {
+                fs::write(&file.path, file.migrated)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// A line-based diff of `old` and `new` in the unified format
+fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+
+    // the length of the longest common subsequence of `old[i..]` and
+    // `new[j..]`
+    let mut lengths = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    // each operation, along with the line it applies to
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((Op::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            ops.push((Op::Delete, old[i]));
+            i += 1;
+        } else {
+            ops.push((Op::Insert, new[j]));
+            j += 1;
+        }
+    }
+
+    let changes: Vec<usize> = (0..ops.len())
+        .filter(|&idx| ops[idx].0 != Op::Equal)
+        .collect();
+
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let mut change = 0;
+
+    while change < changes.len() {
+        let start = changes[change].saturating_sub(CONTEXT);
+
+        // merge changes whose context overlaps into a single hunk
+        let mut last = changes[change];
+        while change + 1 < changes.len() && changes[change + 1] <= last + 2 * CONTEXT {
+            change += 1;
+            last = changes[change];
+        }
+        change += 1;
+
+        let end = (last + 1 + CONTEXT).min(ops.len());
+
+        let line_before = |op: Op| ops[..start].iter().filter(|(kind, _)| *kind != op).count();
+        let count = |op: Op| {
+            ops[start..end]
+                .iter()
+                .filter(|(kind, _)| *kind != op)
+                .count()
+        };
+
+        let (old_count, new_count) = (count(Op::Insert), count(Op::Delete));
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            line_before(Op::Insert) + usize::from(old_count != 0),
+            old_count,
+            line_before(Op::Delete) + usize::from(new_count != 0),
+            new_count,
+        ));
+
+        for &(op, line) in &ops[start..end] {
+            diff.push(match op {
+                Op::Equal => ' ',
+                Op::Delete => '-',
+                Op::Insert => '+',
+            });
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    diff
+}
@@ -0,0 +1,114 @@
+//! The `--watch` flag
+//!
+//! Compiles each input to its output, then polls the inputs and every file
+//! they loaded for changes, recompiling an input whenever one of its files
+//! changes. A single [`Compiler`] is kept for the whole session, so after a
+//! change only the modules which changed and the modules which depend on them
+//! are executed again. Errors are reported without stopping the watcher.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use grass::{Compiler, Options};
+
+use crate::diagnostic::{error_to_json, ErrorFormat};
+
+/// How long to wait between checking files for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The last modification time of each file a job depends on, or `None` if it
+/// couldn't be read, e.g. because it doesn't exist
+type ModifiedTimes = HashMap<PathBuf, Option<SystemTime>>;
+
+pub fn run(
+    jobs: &[(PathBuf, PathBuf)],
+    options: &Options,
+    error_format: ErrorFormat,
+) -> std::io::Result<()> {
+    let compiler = Compiler::new(options.clone());
+
+    let mut watched = Vec::with_capacity(jobs.len());
+
+    for (input, output) in jobs {
+        compile(&compiler, input, output, error_format)?;
+        watched.push(watched_files(&compiler, input, &ModifiedTimes::new()));
+    }
+
+    println!("Sass is watching for changes. Press Ctrl-C to stop.\n");
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        for ((input, output), times) in jobs.iter().zip(&mut watched) {
+            // read before compiling, so that changes made while compiling are
+            // picked up by the next poll
+            let current: ModifiedTimes = times
+                .keys()
+                .map(|path| (path.clone(), modified(path)))
+                .collect();
+
+            if current == *times {
+                continue;
+            }
+
+            compile(&compiler, input, output, error_format)?;
+            *times = watched_files(&compiler, input, &current);
+        }
+    }
+}
+
+fn compile(
+    compiler: &Compiler,
+    input: &Path,
+    output: &Path,
+    error_format: ErrorFormat,
+) -> std::io::Result<()> {
+    match compiler.compile(input) {
+        Ok(css) => {
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(output, css)?;
+
+            println!("Compiled {} to {}.", input.display(), output.display());
+        }
+        Err(e) => match error_format {
+            ErrorFormat::Human => eprintln!("{}", e),
+            ErrorFormat::Json => eprintln!("{}", error_to_json(&e)),
+        },
+    }
+
+    Ok(())
+}
+
+/// The files the last compilation of `input` depends on, including the
+/// directories containing them so that new files which would be loaded instead
+/// are noticed
+fn watched_files(compiler: &Compiler, input: &Path, known: &ModifiedTimes) -> ModifiedTimes {
+    let mut files = compiler.loaded_files(input);
+    files.push(input.to_path_buf());
+
+    let directories: Vec<PathBuf> = files
+        .iter()
+        .filter_map(|file| file.parent())
+        .map(Path::to_path_buf)
+        .collect();
+
+    files
+        .into_iter()
+        .chain(directories)
+        .map(|path| {
+            let time = known.get(&path).copied().unwrap_or_else(|| modified(&path));
+            (path, time)
+        })
+        .collect()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
@@ -0,0 +1,284 @@
+use std::path::Path;
+
+use grass::analysis::{analyze, Analysis, Reference, SymbolKind};
+
+use macros::TestFs;
+
+#[macro_use]
+mod macros;
+
+fn analyze_with_fs(input: &str, fs: &TestFs) -> Analysis {
+    analyze(
+        input.to_owned(),
+        Path::new("input.scss"),
+        &grass::Options::default().fs(fs),
+    )
+}
+
+fn reference<'a>(analysis: &'a Analysis, kind: SymbolKind, name: &str) -> &'a Reference {
+    analysis
+        .references
+        .iter()
+        .find(|reference| reference.kind == kind && reference.name == name)
+        .unwrap()
+}
+
+#[test]
+fn variable_declaration_and_value() {
+    let analysis = analyze_with_fs("$a: 1px + 2px;\n$b: $a;", &TestFs::new());
+
+    assert!(analysis.error.is_none());
+    assert_eq!(2, analysis.symbols.len());
+
+    let a = &analysis.symbols[0];
+    assert_eq!(SymbolKind::Variable, a.kind);
+    assert_eq!("a", a.name);
+    assert_eq!(
+        (0, 0, 2),
+        (
+            a.location.begin.line,
+            a.location.begin.column,
+            a.location.end.column
+        )
+    );
+    assert_eq!(Some("3px"), a.value.as_deref());
+
+    let reference = reference(&analysis, SymbolKind::Variable, "a");
+    assert_eq!(
+        (1, 4, 6),
+        (
+            reference.location.begin.line,
+            reference.location.begin.column,
+            reference.location.end.column
+        )
+    );
+    assert_eq!(
+        Some("3px"),
+        reference.definition.as_ref().unwrap().value.as_deref()
+    );
+}
+
+#[test]
+fn reassigned_variable_value_is_last_value() {
+    let analysis = analyze_with_fs(
+        "$a: 1;\n@for $i from 1 through 3 {\n  $a: $i !global;\n}",
+        &TestFs::new(),
+    );
+
+    assert_eq!(Some("1"), analysis.symbols[0].value.as_deref());
+    assert_eq!(Some("3"), analysis.symbols[1].value.as_deref());
+}
+
+#[test]
+fn mixin_and_function_declared_later() {
+    let analysis = analyze_with_fs(
+        "@mixin foo {\n  a { b: bar(); }\n}\n@function bar() {\n  @return 1;\n}\n@include foo;",
+        &TestFs::new(),
+    );
+
+    assert!(analysis.error.is_none());
+
+    let function = reference(&analysis, SymbolKind::Function, "bar");
+    let definition = function.definition.as_ref().unwrap();
+    assert_eq!(SymbolKind::Function, definition.kind);
+    assert_eq!(
+        (3, 10),
+        (
+            definition.location.begin.line,
+            definition.location.begin.column
+        )
+    );
+
+    let mixin = reference(&analysis, SymbolKind::Mixin, "foo");
+    assert_eq!(
+        (6, 9),
+        (mixin.location.begin.line, mixin.location.begin.column)
+    );
+    let definition = mixin.definition.as_ref().unwrap();
+    assert_eq!(
+        (0, 7),
+        (
+            definition.location.begin.line,
+            definition.location.begin.column
+        )
+    );
+}
+
+#[test]
+fn parameters_shadow_global_variables() {
+    let analysis = analyze_with_fs(
+        "$a: 1;\n@function foo($a) {\n  @return $a;\n}\nb { c: $a; }",
+        &TestFs::new(),
+    );
+
+    let references = analysis
+        .references
+        .iter()
+        .filter(|reference| reference.kind == SymbolKind::Variable)
+        .collect::<Vec<_>>();
+
+    assert_eq!(2, references.len());
+    assert!(references[0].definition.is_none());
+    assert_eq!(
+        0,
+        references[1]
+            .definition
+            .as_ref()
+            .unwrap()
+            .location
+            .begin
+            .line
+    );
+}
+
+#[test]
+fn builtin_functions_have_no_definition() {
+    let analysis = analyze_with_fs(
+        "a { color: red(#123); width: calc(1px + 2%); }",
+        &TestFs::new(),
+    );
+
+    assert!(reference(&analysis, SymbolKind::Function, "red")
+        .definition
+        .is_none());
+}
+
+#[test]
+fn use_namespace_members() {
+    let mut fs = TestFs::new();
+    fs.add_file(
+        "_lib.scss",
+        "$size: 2px * 3;\n@mixin box {\n  width: $size;\n}\n$-private: 1;",
+    );
+
+    let analysis = analyze_with_fs(
+        "@use 'lib';\na { @include lib.box; width: lib.$size; }",
+        &fs,
+    );
+
+    assert!(analysis.error.is_none());
+
+    let namespace = &analysis.symbols[0];
+    assert_eq!(SymbolKind::Namespace, namespace.kind);
+    assert_eq!("lib", namespace.name);
+
+    let mixin = reference(&analysis, SymbolKind::Mixin, "box");
+    assert_eq!(Some("lib"), mixin.namespace.as_deref());
+    assert_eq!(
+        (1, 17, 20),
+        (
+            mixin.location.begin.line,
+            mixin.location.begin.column,
+            mixin.location.end.column
+        )
+    );
+    let definition = mixin.definition.as_ref().unwrap();
+    assert_eq!("_lib.scss", definition.location.file.name());
+    assert_eq!(
+        (1, 7),
+        (
+            definition.location.begin.line,
+            definition.location.begin.column
+        )
+    );
+
+    let variable = reference(&analysis, SymbolKind::Variable, "size");
+    assert_eq!(
+        Some("6px"),
+        variable.definition.as_ref().unwrap().value.as_deref()
+    );
+
+    let namespace = reference(&analysis, SymbolKind::Namespace, "lib");
+    assert_eq!("lib", namespace.definition.as_ref().unwrap().name);
+
+    let module = &analysis.modules[0];
+    assert_eq!(Some("lib"), module.namespace.as_deref());
+    assert_eq!("lib", module.url);
+    assert_eq!(Some(Path::new("_lib.scss")), module.path.as_deref());
+    assert_eq!(
+        vec![("size".to_owned(), "6px".to_owned())],
+        module.variables
+    );
+    assert_eq!(vec!["box".to_owned()], module.mixins);
+}
+
+#[test]
+fn forwarded_members_with_prefix() {
+    let mut fs = TestFs::new();
+    fs.add_file("_lib.scss", "@forward 'colors' as color-*;");
+    fs.add_file("_colors.scss", "@function mix-red() { @return red; }");
+
+    let analysis = analyze_with_fs("@use 'lib';\na { color: lib.color-mix-red(); }", &fs);
+
+    let function = reference(&analysis, SymbolKind::Function, "color-mix-red");
+    assert_eq!(
+        "_colors.scss",
+        function.definition.as_ref().unwrap().location.file.name()
+    );
+    assert_eq!(
+        vec!["color-mix-red".to_owned()],
+        analysis.modules[0].functions
+    );
+}
+
+#[test]
+fn global_use_and_import() {
+    let mut fs = TestFs::new();
+    fs.add_file("_a.scss", "$a: 1;");
+    fs.add_file("_b.scss", "$b: 2;");
+
+    let analysis = analyze_with_fs("@use 'a' as *;\n@import 'b';\nc { d: $a $b; }", &fs);
+
+    let a = reference(&analysis, SymbolKind::Variable, "a");
+    assert_eq!(
+        "_a.scss",
+        a.definition.as_ref().unwrap().location.file.name()
+    );
+
+    let b = reference(&analysis, SymbolKind::Variable, "b");
+    assert_eq!(
+        "_b.scss",
+        b.definition.as_ref().unwrap().location.file.name()
+    );
+
+    assert_eq!(None, analysis.modules[0].namespace);
+    assert_eq!(
+        vec![("a".to_owned(), "1".to_owned())],
+        analysis.modules[0].variables
+    );
+}
+
+#[test]
+fn builtin_module() {
+    let analysis = analyze_with_fs(
+        "@use 'sass:math';\na { b: math.div(1, 2); }",
+        &TestFs::new(),
+    );
+
+    assert!(reference(&analysis, SymbolKind::Function, "div")
+        .definition
+        .is_none());
+
+    let module = &analysis.modules[0];
+    assert_eq!(Some("math"), module.namespace.as_deref());
+    assert_eq!(None, module.path);
+    assert!(module.functions.contains(&"div".to_owned()));
+    assert!(module.variables.iter().any(|(name, _)| name == "pi"));
+}
+
+#[test]
+fn parse_error() {
+    let analysis = analyze_with_fs("a {", &TestFs::new());
+
+    assert!(analysis.error.is_some());
+    assert!(analysis.symbols.is_empty());
+}
+
+#[test]
+fn evaluation_error_keeps_symbols() {
+    let analysis = analyze_with_fs("$a: 1;\n@error 'oops';", &TestFs::new());
+
+    assert!(analysis.error.is_some());
+    assert_eq!(1, analysis.symbols.len());
+    assert_eq!(Some("1"), analysis.symbols[0].value.as_deref());
+}
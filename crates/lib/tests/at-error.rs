@@ -15,3 +15,14 @@ error!(
     error_is_inspected,
     "a {\n  @error null;\n}\n", "Error: null"
 );
+
+#[test]
+fn error_inside_function_has_stack_trace() {
+    let input = "@function foo() {\n  @error bar;\n}\na {\n  b: foo();\n}";
+
+    let err = grass::from_string(input.to_string(), &grass::Options::default()).expect_err(input);
+
+    assert!(err
+        .to_string()
+        .ends_with("  stdin 2:10  foo()\n  stdin 5:6   root stylesheet\n"));
+}
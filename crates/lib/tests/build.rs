@@ -0,0 +1,162 @@
+#![cfg(feature = "build")]
+
+use std::{fs, path::Path};
+
+use grass::build::Build;
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn compiles_single_input() {
+    let src = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    write(&src.path().join("app.scss"), "a { b { color: red; } }");
+
+    let output = Build::new()
+        .input(src.path().join("app.scss"))
+        .out_dir(out.path())
+        .cargo_metadata(false)
+        .compile()
+        .unwrap();
+
+    assert_eq!(vec![out.path().join("app.css")], output.files);
+    assert_eq!(
+        "a b {\n  color: red;\n}\n",
+        fs::read_to_string(out.path().join("app.css")).unwrap()
+    );
+}
+
+#[test]
+fn compiles_input_dir_skipping_partials() {
+    let src = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    write(
+        &src.path().join("app.scss"),
+        "@use 'vars'; a { color: vars.$a; }",
+    );
+    write(&src.path().join("_vars.scss"), "$a: red;");
+    write(&src.path().join("pages/home.sass"), "a\n  color: blue\n");
+    write(&src.path().join("readme.md"), "not sass");
+
+    let output = Build::new()
+        .input_dir(src.path())
+        .out_dir(out.path())
+        .style(grass::OutputStyle::Compressed)
+        .cargo_metadata(false)
+        .compile()
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            out.path().join("app.css"),
+            out.path().join("pages/home.css")
+        ],
+        output.files
+    );
+    assert_eq!(
+        "a{color:red}",
+        fs::read_to_string(out.path().join("app.css")).unwrap()
+    );
+    assert_eq!(
+        "a{color:blue}",
+        fs::read_to_string(out.path().join("pages/home.css")).unwrap()
+    );
+    assert!(!out.path().join("_vars.css").exists());
+}
+
+#[test]
+fn dependencies_include_transitive_imports() {
+    let src = tempfile::tempdir().unwrap();
+    let vendor = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    write(&src.path().join("app.scss"), "@use 'a';");
+    write(
+        &src.path().join("_a.scss"),
+        "@use 'lib'; a { color: lib.$b; }",
+    );
+    write(&vendor.path().join("_lib.scss"), "$b: red;");
+
+    let output = Build::new()
+        .input(src.path().join("app.scss"))
+        .load_path(vendor.path())
+        .out_dir(out.path())
+        .cargo_metadata(false)
+        .compile()
+        .unwrap();
+
+    let mut dependencies: Vec<_> = output
+        .dependencies
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap())
+        .collect();
+
+    dependencies.sort_unstable();
+
+    assert_eq!(vec!["_a.scss", "_lib.scss", "app.scss"], dependencies);
+}
+
+#[test]
+fn warnings_are_collected() {
+    let src = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    write(
+        &src.path().join("app.scss"),
+        "@warn foo;\na { color: red; }",
+    );
+
+    let output = Build::new()
+        .input(src.path().join("app.scss"))
+        .out_dir(out.path())
+        .cargo_metadata(false)
+        .compile()
+        .unwrap();
+
+    assert_eq!(1, output.warnings.len());
+    assert!(output.warnings[0].starts_with("Warning: foo\n"));
+    assert!(output.warnings[0].ends_with("app.scss:1:7"));
+}
+
+#[test]
+fn quiet_silences_warnings() {
+    let src = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    write(
+        &src.path().join("app.scss"),
+        "@warn foo;\na { color: red; }",
+    );
+
+    let output = Build::new()
+        .input(src.path().join("app.scss"))
+        .out_dir(out.path())
+        .quiet(true)
+        .cargo_metadata(false)
+        .compile()
+        .unwrap();
+
+    assert!(output.warnings.is_empty());
+}
+
+#[test]
+fn error_is_returned() {
+    let src = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    write(&src.path().join("app.scss"), "a {");
+
+    let err = Build::new()
+        .input(src.path().join("app.scss"))
+        .out_dir(out.path())
+        .cargo_metadata(false)
+        .compile()
+        .unwrap_err();
+
+    assert!(err.to_string().starts_with("Error: expected \"}\"."));
+}
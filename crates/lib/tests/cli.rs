@@ -0,0 +1,715 @@
+#![cfg(feature = "commandline")]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn run_with_stdin(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn json_error_format_for_parse_error() {
+    let output = run_with_stdin(&["--stdin", "--error-format=json"], "a {\n  color: ;\n}");
+
+    assert!(!output.status.success());
+    assert_eq!(
+        "{\"severity\":\"error\",\"message\":\"Expected expression.\",\"file\":\"stdin\",\"start\":{\"line\":2,\"column\":9,\"offset\":12},\"end\":{\"line\":2,\"column\":11,\"offset\":14},\"stack\":[{\"member\":\"root stylesheet\",\"file\":\"stdin\",\"line\":2,\"column\":9}]}\n",
+        String::from_utf8(output.stderr).unwrap()
+    );
+}
+
+#[test]
+fn json_error_format_for_warning() {
+    let output = run_with_stdin(&["--stdin", "--error-format", "json"], "@warn \"a\\\"b\";");
+
+    assert!(output.status.success());
+    assert_eq!(
+        "{\"severity\":\"warning\",\"message\":\"'a\\\"b'\",\"file\":\"stdin\",\"start\":{\"line\":1,\"column\":7,\"offset\":6},\"end\":{\"line\":1,\"column\":13,\"offset\":12},\"stack\":[{\"member\":\"root stylesheet\",\"file\":\"stdin\",\"line\":1,\"column\":7}]}\n",
+        String::from_utf8(output.stderr).unwrap()
+    );
+}
+
+#[test]
+fn human_error_format_is_default() {
+    let output = run_with_stdin(&["--stdin"], "a {\n  color: ;\n}");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Error: Expected expression.\n"));
+}
+
+#[test]
+fn json_error_format_includes_stack_trace() {
+    let output = run_with_stdin(
+        &["--stdin", "--error-format=json"],
+        "@function foo() {\n  @error bar;\n}\na {\n  b: foo();\n}",
+    );
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().ends_with(
+        "\"stack\":[{\"member\":\"foo()\",\"file\":\"stdin\",\"line\":2,\"column\":10},{\"member\":\"root stylesheet\",\"file\":\"stdin\",\"line\":5,\"column\":6}]}\n"
+    ));
+}
+
+#[test]
+fn precision_flag() {
+    let output = run_with_stdin(&["--stdin", "--precision", "3"], "a {\n  color: (1/3);\n}");
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: 0.333;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+/// Creates an empty directory unique to the calling test
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("grass-cli-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn indented_stdin() {
+    let output = run_with_stdin(&["--stdin", "--indented"], "a\n  color: red\n");
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn syntax_flag() {
+    let output = run_with_stdin(&["--stdin", "--syntax=sass"], "a\n  color: red\n");
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn syntax_flag_css_disallows_sass_features() {
+    let output = run_with_stdin(&["--stdin", "--syntax=css"], "$a: red;");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Error: Sass variables aren't allowed in plain CSS."));
+}
+
+#[test]
+fn stdin_filename_resolves_relative_imports() {
+    let dir = temp_dir("stdin-filename");
+    std::fs::write(dir.join("_a.scss"), "a { color: red; }").unwrap();
+
+    let stdin_filename = dir.join("input.scss");
+    let output = run_with_stdin(
+        &[
+            "--stdin",
+            "--stdin-filename",
+            stdin_filename.to_str().unwrap(),
+        ],
+        "@use 'a';",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn stdin_filename_infers_syntax() {
+    let output = run_with_stdin(
+        &["--stdin", "--stdin-filename", "input.sass"],
+        "a\n  color: red\n",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn stdin_filename_is_used_in_errors() {
+    let output = run_with_stdin(&["--stdin", "--stdin-filename", "input.scss"], "a {");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("./input.scss:1:3"));
+}
+
+#[test]
+fn stdin_output_to_directory() {
+    let dir = temp_dir("output-dir");
+
+    let output = run_with_stdin(
+        &[
+            "--stdin",
+            "--stdin-filename",
+            "input.scss",
+            dir.to_str().unwrap(),
+        ],
+        "a { color: red; }",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        std::fs::read_to_string(dir.join("input.css")).unwrap()
+    );
+}
+
+#[test]
+fn pkg_importer_node() {
+    let dir = temp_dir("pkg-importer");
+    let package = dir.join("node_modules").join("lib");
+    std::fs::create_dir_all(&package).unwrap();
+    std::fs::write(package.join("_index.scss"), "a { color: red; }").unwrap();
+    std::fs::write(dir.join("input.scss"), "@use 'pkg:lib';").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["--pkg-importer", "node", "input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn sass_path_env_var() {
+    let dir = temp_dir("sass-path");
+    let (a, b) = (dir.join("a"), dir.join("b"));
+    std::fs::create_dir_all(&a).unwrap();
+    std::fs::create_dir_all(&b).unwrap();
+    std::fs::write(b.join("_lib.scss"), "$color: red;").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["--stdin"])
+        .env("SASS_PATH", std::env::join_paths([&a, &b]).unwrap())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(b"@use 'lib'; a { color: lib.$color; }")?;
+            child.wait_with_output()
+        })
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn config_file_settings() {
+    let dir = temp_dir("config-settings");
+    std::fs::create_dir_all(dir.join("vendor")).unwrap();
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("vendor").join("_lib.scss"), "$a: 4;").unwrap();
+    std::fs::write(
+        dir.join("src").join("input.scss"),
+        "@use 'lib'; a { b { width: (lib.$a/2); } }",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("grass.toml"),
+        "# project settings\nload-paths = [\n  \"vendor\", # vendored libraries\n]\nstyle = 'compressed'\n\n[deprecations]\nfatal = [\"slash-div\"]\n",
+    )
+    .unwrap();
+
+    // the config file is found in an ancestor of the current directory
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["input.scss"])
+        .current_dir(dir.join("src"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains(
+        "This is only an error because you've set the slash-div deprecation to be fatal."
+    ));
+
+    std::fs::write(
+        dir.join("src").join("input.scss"),
+        "@use 'lib'; a { b { width: lib.$a; } }",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["input.scss"])
+        .current_dir(dir.join("src"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!("a b{width:4}", String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn command_line_overrides_config_file() {
+    let dir = temp_dir("config-override");
+    std::fs::write(dir.join("input.scss"), "a { color: red; }").unwrap();
+    std::fs::write(dir.join("grass.toml"), "style = \"compressed\"\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["--style", "expanded", "input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["--no-config", "input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn command_line_deprecations_replace_config_file() {
+    let dir = temp_dir("config-deprecations");
+    std::fs::write(dir.join("input.scss"), "a { b: (4/2); }").unwrap();
+    std::fs::write(
+        dir.join("grass.toml"),
+        "[deprecations]\nfatal = [\"slash-div\"]\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["--fatal-deprecation", "import", "input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  b: 2;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn config_file_outputs() {
+    let dir = temp_dir("config-outputs");
+    std::fs::create_dir_all(dir.join("styles")).unwrap();
+    std::fs::write(dir.join("styles").join("app.scss"), "a { color: red; }").unwrap();
+    std::fs::write(dir.join("styles").join("admin.sass"), "b\n  color: blue\n").unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        "[outputs]\n\"styles/app.scss\" = \"public/app.css\"\n\"styles/admin.sass\" = \"public/admin/admin.css\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["--config", dir.join("config.toml").to_str().unwrap()])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        std::fs::read_to_string(dir.join("public").join("app.css")).unwrap()
+    );
+    assert_eq!(
+        "b {\n  color: blue;\n}\n",
+        std::fs::read_to_string(dir.join("public").join("admin").join("admin.css")).unwrap()
+    );
+}
+
+#[test]
+fn invalid_config_file() {
+    let dir = temp_dir("config-invalid");
+    std::fs::write(dir.join("input.scss"), "a { color: red; }").unwrap();
+    std::fs::write(
+        dir.join("grass.toml"),
+        "style = \"compressed\"\nstyel = \"expanded\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .ends_with("grass.toml:2: unknown key `styel`\n"));
+}
+
+#[test]
+fn config_file_toml_syntax() {
+    let dir = temp_dir("config-toml-syntax");
+    std::fs::create_dir_all(dir.join("vendor")).unwrap();
+    std::fs::write(dir.join("vendor").join("_lib.scss"), "$a: 4;").unwrap();
+    std::fs::write(
+        dir.join("input.scss"),
+        "@use 'lib'; a { b { width: (lib.$a/2); } }",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("grass.toml"),
+        "load-paths = [\"\"\"\nvendor\"\"\"]\nstyle = \"\\u0063ompressed\"\ndeprecations = { fatal = ['slash-div'] }\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains(
+        "This is only an error because you've set the slash-div deprecation to be fatal."
+    ));
+
+    std::fs::write(
+        dir.join("grass.toml"),
+        "load-paths = [\"vendor\"]\nstyle = \"compressed\"\ndeprecations.fatal = []\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!("a b{width:2}", String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn config_file_errors() {
+    let dir = temp_dir("config-errors");
+    std::fs::write(dir.join("input.scss"), "a { color: red; }").unwrap();
+
+    for (config, error) in [
+        (
+            "style = \"compressed\"\nstyle = \"expanded\"\n",
+            "grass.toml:2: ",
+        ),
+        ("style = \"compressed\n", "grass.toml:1: "),
+        (
+            "\nstyle = compressed\n",
+            "grass.toml:2: invalid string: expected `\"`, `'`\n",
+        ),
+        (
+            "\nload-paths = \"vendor\"\n",
+            "grass.toml:2: expected an array of strings, found string\n",
+        ),
+        (
+            "[deprecations]\nsilence = [1]\n",
+            "grass.toml:2: expected a string, found integer\n",
+        ),
+        (
+            "[deprecations]\nignore = []\n",
+            "grass.toml:2: unknown key `deprecations.ignore`\n",
+        ),
+        (
+            "outputs = \"public\"\n",
+            "grass.toml:1: expected a table, found string\n",
+        ),
+    ] {
+        std::fs::write(dir.join("grass.toml"), config).unwrap();
+
+        let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+            .args(["input.scss"])
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains(error) && stderr.ends_with('\n'),
+            "{:?} for {:?}",
+            stderr,
+            config
+        );
+    }
+}
+
+#[test]
+fn missing_input_without_config_outputs() {
+    let dir = temp_dir("no-input");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("<INPUT>"));
+}
+
+#[test]
+fn fmt_stdin() {
+    let output = run_with_stdin(&["fmt"], "a{color:red}");
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn fmt_files_in_place() {
+    let dir = temp_dir("fmt-in-place");
+    std::fs::write(dir.join("a.scss"), "a{color:red}").unwrap();
+    std::fs::write(dir.join("b.sass"), "a\n    color : red\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["fmt", "--indent-width", "4", "a.scss", "b.sass"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n    color: red;\n}\n",
+        std::fs::read_to_string(dir.join("a.scss")).unwrap()
+    );
+    assert_eq!(
+        "a\n    color: red\n",
+        std::fs::read_to_string(dir.join("b.sass")).unwrap()
+    );
+}
+
+#[test]
+fn fmt_check() {
+    let dir = temp_dir("fmt-check");
+    std::fs::write(dir.join("formatted.scss"), "a {\n  color: red;\n}\n").unwrap();
+    std::fs::write(dir.join("unformatted.scss"), "a{color:red}").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["fmt", "--check", "formatted.scss", "unformatted.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(
+        "unformatted.scss\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+    assert_eq!(
+        "a{color:red}",
+        std::fs::read_to_string(dir.join("unformatted.scss")).unwrap()
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["fmt", "--check", "formatted.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+}
+
+#[test]
+fn convert_stdin() {
+    let output = run_with_stdin(&["convert", "--to", "sass"], "a{color:red}");
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a\n  color: red\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn convert_to_directory() {
+    let dir = temp_dir("convert-to-directory");
+    std::fs::write(dir.join("input.sass"), "a\n  color: red\n").unwrap();
+    std::fs::create_dir_all(dir.join("out")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["convert", "--to", "scss", "input.sass", "out"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        std::fs::read_to_string(dir.join("out").join("input.scss")).unwrap()
+    );
+}
+
+#[test]
+fn lint_files() {
+    let dir = temp_dir("lint");
+    std::fs::write(dir.join("clean.scss"), "a {\n  color: red;\n}\n").unwrap();
+    std::fs::write(
+        dir.join("dead.scss"),
+        "$-unused: 1;\na {\n  color: red;\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["lint", "clean.scss", "dead.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Warning [unused-member]: Private variable $-unused is never used.\n"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args([
+            "lint",
+            "--allow",
+            "unused-member",
+            "clean.scss",
+            "dead.scss",
+        ])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn lint_json_error_format() {
+    let dir = temp_dir("lint-json");
+    std::fs::write(dir.join("dead.scss"), "$-unused: 1;\n").unwrap();
+
+    for args in [
+        ["lint", "--error-format=json", "dead.scss"],
+        ["--error-format=json", "lint", "dead.scss"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        assert_eq!(
+            "{\"severity\":\"warning\",\"message\":\"Private variable $-unused is never used.\",\"file\":\"dead.scss\",\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":9,\"offset\":8},\"stack\":[],\"lint\":\"unused-member\"}\n",
+            String::from_utf8(output.stderr).unwrap()
+        );
+    }
+}
+
+#[test]
+fn migrate_dry_run_and_in_place() {
+    let dir = temp_dir("migrate");
+    std::fs::write(
+        dir.join("input.scss"),
+        "@import \"lib\";\na {\n  b: $c;\n}\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("_lib.scss"), "$c: d;\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["migrate", "--dry-run", "input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "--- a/input.scss\n+++ b/input.scss\n@@ -1,4 +1,4 @@\n-@import \"lib\";\n+@use \"lib\";\n a {\n-  b: $c;\n+  b: lib.$c;\n }\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .args(["migrate", "input.scss"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "@use \"lib\";\na {\n  b: lib.$c;\n}\n",
+        std::fs::read_to_string(dir.join("input.scss")).unwrap()
+    );
+}
+
+#[test]
+fn embedded_version_request() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_grass"))
+        .arg("--embedded")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // a `VersionRequest` with an ID of 1, for compilation 0
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(&[5, 0, 0x32, 2, 8, 1])
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = output.stdout;
+    assert_eq!(usize::from(stdout[0]), stdout.len() - 1);
+    assert_eq!([0, 0x42], stdout[1..3]);
+    assert!(stdout.windows(5).any(|bytes| bytes == b"grass"));
+}
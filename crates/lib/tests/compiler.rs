@@ -0,0 +1,463 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use grass::{
+    Compiler, Fs, ImportContext, Importer, ImporterResult, InputSyntax, Logger, Options, SpanLoc,
+    StackFrame,
+};
+
+/// An in-memory file system whose files can be changed between compilations
+#[derive(Debug, Default)]
+struct ChangingFs {
+    files: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl ChangingFs {
+    fn write(&self, path: &str, contents: &str) {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(PathBuf::from(path), contents.to_owned());
+    }
+}
+
+impl Fs for ChangingFs {
+    fn is_dir(&self, _: &Path) -> bool {
+        false
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|contents| contents.as_bytes().to_vec())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+}
+
+#[test]
+fn compiler_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Compiler<'static>>();
+}
+
+#[test]
+fn compile_matches_from_path() {
+    let fs = ChangingFs::default();
+    fs.write("_shared.scss", "$color: red;\n@mixin m { c: $color; }");
+    fs.write("a.scss", "@use 'shared';\na { @include shared.m; }");
+
+    let options = Options::default().fs(&fs);
+    let compiler = Compiler::new(options.clone());
+
+    assert_eq!(
+        grass::from_path("a.scss", &options).unwrap(),
+        compiler.compile("a.scss").unwrap()
+    );
+    assert_eq!("a {\n  c: red;\n}\n", compiler.compile("a.scss").unwrap());
+}
+
+#[test]
+fn shared_stylesheets_are_cached_once() {
+    let fs = ChangingFs::default();
+    fs.write("_shared.scss", "$color: red;");
+    fs.write("a.scss", "@use 'shared';\na { b: shared.$color; }");
+    fs.write("b.scss", "@use 'shared';\nb { c: shared.$color; }");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+
+    assert_eq!("a {\n  b: red;\n}\n", compiler.compile("a.scss").unwrap());
+    assert_eq!("b {\n  c: red;\n}\n", compiler.compile("b.scss").unwrap());
+    assert_eq!(3, compiler.cached_style_sheets());
+}
+
+#[test]
+fn changed_files_are_reparsed() {
+    let fs = ChangingFs::default();
+    fs.write("_shared.scss", "$color: red;");
+    fs.write("a.scss", "@use 'shared';\na { b: shared.$color; }");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+    assert_eq!("a {\n  b: red;\n}\n", compiler.compile("a.scss").unwrap());
+
+    fs.write("_shared.scss", "$color: blue;");
+    assert_eq!("a {\n  b: blue;\n}\n", compiler.compile("a.scss").unwrap());
+
+    fs.write("a.scss", "@use 'shared';\nc { d: shared.$color; }");
+    assert_eq!("c {\n  d: blue;\n}\n", compiler.compile("a.scss").unwrap());
+
+    assert_eq!(2, compiler.cached_style_sheets());
+}
+
+#[test]
+fn errors_in_cached_stylesheets_have_spans() {
+    let fs = ChangingFs::default();
+    fs.write("_shared.scss", "@function f() {\n  @return 1px + 1s;\n}");
+    fs.write("a.scss", "@use 'shared';\na { b: shared.f(); }");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+
+    let first = compiler.compile("a.scss").unwrap_err().to_string();
+    let second = compiler.compile("a.scss").unwrap_err().to_string();
+
+    assert_eq!(first, second);
+    assert!(second.contains("2 │   @return 1px + 1s;"), "{}", second);
+}
+
+#[test]
+fn cached_modules_can_be_configured_differently() {
+    let fs = ChangingFs::default();
+    fs.write(
+        "_shared.scss",
+        "$color: red !default;\nshared { b: $color; }",
+    );
+    fs.write("a.scss", "@use 'shared' with ($color: blue);");
+    fs.write("b.scss", "@use 'shared';");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+
+    assert_eq!(
+        "shared {\n  b: blue;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+    assert_eq!(
+        "shared {\n  b: red;\n}\n",
+        compiler.compile("b.scss").unwrap()
+    );
+}
+
+#[test]
+fn compile_string_replaces_previous_input() {
+    let compiler = Compiler::new(Options::default());
+
+    assert_eq!(
+        "a {\n  b: c;\n}\n",
+        compiler.compile_string("a { b: c; }".to_owned()).unwrap()
+    );
+    assert_eq!(
+        "d {\n  e: f;\n}\n",
+        compiler.compile_string("d { e: f; }".to_owned()).unwrap()
+    );
+    assert_eq!(1, compiler.cached_style_sheets());
+}
+
+#[test]
+fn compile_string_with_file_name_uses_syntax_of_path() {
+    let compiler = Compiler::new(Options::default());
+
+    assert_eq!(
+        "a {\n  b: c;\n}\n",
+        compiler
+            .compile_string_with_file_name("a\n  b: c".to_owned(), "input.sass")
+            .unwrap()
+    );
+}
+
+#[test]
+fn clear_removes_cached_stylesheets() {
+    let compiler = Compiler::new(Options::default());
+    compiler.compile_string("a { b: c; }".to_owned()).unwrap();
+    assert_eq!(1, compiler.cached_style_sheets());
+
+    compiler.clear();
+    assert_eq!(0, compiler.cached_style_sheets());
+
+    assert_eq!(
+        "a {\n  b: c;\n}\n",
+        compiler.compile_string("a { b: c; }".to_owned()).unwrap()
+    );
+}
+
+#[derive(Debug, Default)]
+struct ChangingImporter {
+    contents: Mutex<String>,
+}
+
+impl Importer for ChangingImporter {
+    fn canonicalize(&self, url: &str, _: &ImportContext) -> Result<Option<String>, String> {
+        Ok(url.strip_prefix("virtual:").map(|_| url.to_owned()))
+    }
+
+    fn load(&self, _: &str) -> Result<ImporterResult, String> {
+        Ok(ImporterResult::new(
+            self.contents.lock().unwrap().clone(),
+            InputSyntax::Scss,
+        ))
+    }
+}
+
+#[test]
+fn stylesheets_from_importers_are_cached() {
+    let importer = ChangingImporter::default();
+    *importer.contents.lock().unwrap() = "a { b: c; }".to_owned();
+
+    let compiler = Compiler::new(Options::default().importer(&importer));
+    let input = "@use 'virtual:a' as a;";
+
+    assert_eq!(
+        "a {\n  b: c;\n}\n",
+        compiler.compile_string(input.to_owned()).unwrap()
+    );
+    assert_eq!(2, compiler.cached_style_sheets());
+
+    *importer.contents.lock().unwrap() = "d { e: f; }".to_owned();
+    assert_eq!(
+        "d {\n  e: f;\n}\n",
+        compiler.compile_string(input.to_owned()).unwrap()
+    );
+    assert_eq!(2, compiler.cached_style_sheets());
+}
+
+/// Records the messages of `@debug` rules
+#[derive(Debug, Default)]
+struct DebugLogger {
+    messages: Mutex<Vec<String>>,
+}
+
+impl Logger for DebugLogger {
+    fn debug(&self, _: SpanLoc, message: &str, _: &[StackFrame]) {
+        self.messages.lock().unwrap().push(message.to_owned());
+    }
+
+    fn warn(&self, _: SpanLoc, _: &str, _: &[StackFrame]) {}
+}
+
+#[test]
+fn unchanged_modules_are_not_executed_again() {
+    let fs = ChangingFs::default();
+    let logger = DebugLogger::default();
+    fs.write("_colors.scss", "@debug colors;\n$primary: red;");
+    fs.write(
+        "_theme.scss",
+        "@use 'colors';\n@debug theme;\ntheme { a: colors.$primary; }",
+    );
+    fs.write("a.scss", "@use 'theme';\na { b: c; }");
+
+    let compiler = Compiler::new(Options::default().fs(&fs).logger(&logger));
+
+    assert_eq!(
+        "theme {\n  a: red;\n}\n\na {\n  b: c;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+    assert_eq!(2, compiler.cached_modules());
+
+    fs.write("a.scss", "@use 'theme';\nd { e: f; }");
+    assert_eq!(
+        "theme {\n  a: red;\n}\n\nd {\n  e: f;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+    assert_eq!(2, compiler.cached_modules());
+
+    assert_eq!(vec!["colors", "theme"], *logger.messages.lock().unwrap());
+}
+
+#[test]
+fn dependents_of_changed_modules_are_executed_again() {
+    let fs = ChangingFs::default();
+    let logger = DebugLogger::default();
+    fs.write("_colors.scss", "@debug colors;\n$primary: red;");
+    fs.write("_sizes.scss", "@debug sizes;\n$small: 1px;");
+    fs.write(
+        "_theme.scss",
+        "@use 'colors';\n@use 'sizes';\n@debug theme;\ntheme { a: colors.$primary sizes.$small; }",
+    );
+    fs.write("a.scss", "@use 'theme';");
+
+    let compiler = Compiler::new(Options::default().fs(&fs).logger(&logger));
+    compiler.compile("a.scss").unwrap();
+    logger.messages.lock().unwrap().clear();
+
+    fs.write("_colors.scss", "@debug colors;\n$primary: blue;");
+    assert_eq!(
+        "theme {\n  a: blue 1px;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+    assert_eq!(vec!["colors", "theme"], *logger.messages.lock().unwrap());
+    assert_eq!(3, compiler.cached_modules());
+}
+
+#[test]
+fn changed_configuration_executes_module_again() {
+    let fs = ChangingFs::default();
+    fs.write(
+        "_shared.scss",
+        "$color: red !default;\nshared { b: $color; }",
+    );
+    fs.write("a.scss", "@use 'shared' with ($color: blue);");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+    assert_eq!(
+        "shared {\n  b: blue;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+
+    fs.write("a.scss", "@use 'shared' with ($color: green);");
+    assert_eq!(
+        "shared {\n  b: green;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+
+    fs.write("a.scss", "@use 'shared';");
+    assert_eq!(
+        "shared {\n  b: red;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+
+    fs.write("a.scss", "@use 'shared' with ($color: blue);");
+    assert_eq!(
+        "shared {\n  b: blue;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+}
+
+#[test]
+fn reused_modules_are_restored_to_their_state_after_execution() {
+    let fs = ChangingFs::default();
+    fs.write(
+        "_counter.scss",
+        "$count: 0;\n@mixin increment { $count: $count + 1 !global; }",
+    );
+    fs.write(
+        "a.scss",
+        "@use 'counter';\n@include counter.increment;\na { b: counter.$count; }",
+    );
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+    let css = "a {\n  b: 1;\n}\n";
+
+    assert_eq!(css, compiler.compile("a.scss").unwrap());
+    assert_eq!(css, compiler.compile("a.scss").unwrap());
+}
+
+#[test]
+fn forwarded_modules_are_reused() {
+    let fs = ChangingFs::default();
+    fs.write("_colors.scss", "$primary: red;\ncolors { a: b; }");
+    fs.write("_theme.scss", "@forward 'colors' as color-*;");
+    fs.write("a.scss", "@use 'theme';\na { b: theme.$color-primary; }");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+    compiler.compile("a.scss").unwrap();
+
+    fs.write("_theme.scss", "@forward 'colors' as c-*;");
+    fs.write("a.scss", "@use 'theme';\na { b: theme.$c-primary; }");
+    assert_eq!(
+        "colors {\n  a: b;\n}\n\na {\n  b: red;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+}
+
+#[test]
+fn new_files_which_change_a_resolution_are_loaded() {
+    let fs = ChangingFs::default();
+    fs.write(
+        "_theme.scss",
+        "@use 'colors';\ntheme { a: colors.$primary; }",
+    );
+    fs.write("lib/_colors.scss", "$primary: red;");
+    fs.write("a.scss", "@use 'theme';");
+
+    let compiler = Compiler::new(Options::default().fs(&fs).load_path("lib"));
+    assert_eq!(
+        "theme {\n  a: red;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+
+    // files relative to the loading stylesheet take precedence over load paths
+    fs.write("_colors.scss", "$primary: blue;");
+    assert_eq!(
+        "theme {\n  a: blue;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+}
+
+#[test]
+fn modules_calling_unique_id_are_not_reused() {
+    let fs = ChangingFs::default();
+    fs.write("_id.scss", "$id: unique-id();");
+    fs.write("_plain.scss", "$a: b;");
+    fs.write("a.scss", "@use 'id';\n@use 'plain';\na { b: id.$id; }");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+
+    assert_ne!(
+        compiler.compile("a.scss").unwrap(),
+        compiler.compile("a.scss").unwrap()
+    );
+    assert_eq!(1, compiler.cached_modules());
+}
+
+#[test]
+fn modules_are_reused_after_an_error() {
+    let fs = ChangingFs::default();
+    fs.write("_shared.scss", "$color: red;\nshared { a: b; }");
+    fs.write("a.scss", "@use 'shared';\na { b: shared.$color; }");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+    compiler.compile("a.scss").unwrap();
+
+    fs.write("a.scss", "@use 'shared';\na { b: shared.$colour; }");
+    assert!(compiler.compile("a.scss").is_err());
+    assert_eq!(1, compiler.cached_modules());
+
+    fs.write("a.scss", "@use 'shared';\na { b: shared.$color; }");
+    assert_eq!(
+        "shared {\n  a: b;\n}\n\na {\n  b: red;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+}
+
+#[test]
+fn loaded_files_lists_transitive_dependencies() {
+    let fs = ChangingFs::default();
+    fs.write("_colors.scss", "$primary: red;");
+    fs.write("_theme.scss", "@use 'colors';");
+    fs.write("a.scss", "@use 'theme';");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+    assert!(compiler.loaded_files("a.scss").is_empty());
+
+    compiler.compile("a.scss").unwrap();
+    let expected = vec![PathBuf::from("_colors.scss"), PathBuf::from("_theme.scss")];
+    assert_eq!(expected, compiler.loaded_files("a.scss"));
+
+    // files loaded by reused modules are still listed
+    compiler.compile("a.scss").unwrap();
+    assert_eq!(expected, compiler.loaded_files("a.scss"));
+}
+
+#[test]
+fn modules_are_cached_per_thread() {
+    let fs = ChangingFs::default();
+    fs.write("_shared.scss", "$color: red;\nshared { a: b; }");
+    fs.write("a.scss", "@use 'shared';\na { b: shared.$color; }");
+
+    let compiler = Compiler::new(Options::default().fs(&fs));
+    let css = "shared {\n  a: b;\n}\n\na {\n  b: red;\n}\n";
+
+    assert_eq!(css, compiler.compile("a.scss").unwrap());
+    assert_eq!(1, compiler.cached_modules());
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            assert_eq!(0, compiler.cached_modules());
+            assert_eq!(css, compiler.compile("a.scss").unwrap());
+            assert_eq!(1, compiler.cached_modules());
+        });
+    });
+
+    fs.write("_shared.scss", "$color: blue;\nshared { a: b; }");
+    assert_eq!(
+        "shared {\n  a: b;\n}\n\na {\n  b: blue;\n}\n",
+        compiler.compile("a.scss").unwrap()
+    );
+}
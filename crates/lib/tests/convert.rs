@@ -0,0 +1,195 @@
+use std::path::Path;
+
+use macros::TestFs;
+
+use grass::{
+    format::{convert, FormatOptions},
+    InputSyntax,
+};
+
+#[macro_use]
+mod macros;
+
+/// Converts `input` to `to`, asserting that both compile to the same CSS
+#[track_caller]
+fn assert_converts(input: &str, from: InputSyntax, to: InputSyntax) -> String {
+    assert_converts_with_fs(input, from, to, &TestFs::new())
+}
+
+#[track_caller]
+fn assert_converts_with_fs(input: &str, from: InputSyntax, to: InputSyntax, fs: &TestFs) -> String {
+    let path = match from {
+        InputSyntax::Sass => "input.sass",
+        _ => "input.scss",
+    };
+
+    let converted = convert(
+        input.to_owned(),
+        Path::new(path),
+        to,
+        &FormatOptions::default(),
+    )
+    .unwrap();
+
+    let compile = |input: &str, syntax: InputSyntax| {
+        grass::from_string(
+            input.to_owned(),
+            &grass::Options::default().input_syntax(syntax).fs(fs),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        compile(input, from),
+        compile(&converted, to),
+        "converted stylesheet compiles differently:\n{}",
+        converted
+    );
+
+    converted
+}
+
+fn to_sass(input: &str) -> String {
+    assert_converts(input, InputSyntax::Scss, InputSyntax::Sass)
+}
+
+fn to_scss(input: &str) -> String {
+    assert_converts(input, InputSyntax::Sass, InputSyntax::Scss)
+}
+
+#[test]
+fn style_rules_to_sass() {
+    assert_eq!(
+        "a\n  color: red\n  &:hover\n    color: blue\n",
+        to_sass("a {\n  color: red;\n  &:hover { color: blue; }\n}\n")
+    );
+}
+
+#[test]
+fn style_rules_to_scss() {
+    assert_eq!(
+        "a {\n  color: red;\n  &:hover {\n    color: blue;\n  }\n}\n",
+        to_scss("a\n  color: red\n  &:hover\n    color: blue\n")
+    );
+}
+
+#[test]
+fn multiline_selectors_to_sass() {
+    assert_eq!(
+        "a,\nb > c\n  color: red\n",
+        to_sass("a,\nb > c {\n  color: red;\n}\n")
+    );
+}
+
+#[test]
+fn multiline_selectors_to_scss() {
+    assert_eq!(
+        "a,\nb > c {\n  color: red;\n}\n",
+        to_scss("a,\nb > c\n  color: red\n")
+    );
+}
+
+#[test]
+fn silent_comments_to_sass() {
+    assert_eq!(
+        "// one\n// two\na\n  color: red // trailing\n",
+        to_sass("// one\n// two\na {\n  color: red; // trailing\n}\n")
+    );
+}
+
+#[test]
+fn silent_comments_to_scss() {
+    assert_eq!(
+        "// one\n//   indented\na {\n  color: red; // trailing\n}\n",
+        to_scss("// one\n     indented\na\n  color: red // trailing\n")
+    );
+}
+
+#[test]
+fn silent_comment_in_expression_to_sass() {
+    assert_eq!(
+        "a\n  margin: 1px /* top */ 2px\n",
+        to_sass("a {\n  margin: 1px // top\n    2px;\n}\n")
+    );
+}
+
+#[test]
+fn loud_comments_to_sass() {
+    assert_eq!(
+        "/* top */\na\n  color: red\n  /* after */\n",
+        to_sass("/* top */\na {\n  color: red; /* after */\n}\n")
+    );
+}
+
+#[test]
+fn multiline_loud_comment_to_sass() {
+    assert_eq!(
+        "/* one\n  two\n   three */\na\n  color: red\n",
+        convert(
+            "/* one\ntwo\n   three */\na {\n  color: red;\n}\n".to_owned(),
+            Path::new("input.scss"),
+            InputSyntax::Sass,
+            &FormatOptions::default()
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn multiline_loud_comment_to_scss() {
+    assert_eq!(
+        "/* one\n * two\n *   three */\na {\n  color: red;\n}\n",
+        to_scss("/* one\n   two\n     three\na\n  color: red\n")
+    );
+}
+
+#[test]
+fn interpolation() {
+    let input = "$n: foo;\n.#{$n}-x {\n  #{$n}-prop: #{$n}-value;\n  content: \"#{$n} bar\";\n}\n";
+    assert_eq!(input, to_scss(&to_sass(input)));
+}
+
+#[test]
+fn use_configuration() {
+    let mut fs = TestFs::new();
+    fs.add_file(
+        "_lib.scss",
+        "$a: red !default;\n$b: blue !default;\nlib { a: $a; b: $b; }",
+    );
+
+    let input = "@use \"lib\" with (\n  $a: green,\n  $b: black\n);\n";
+
+    let sass = assert_converts_with_fs(input, InputSyntax::Scss, InputSyntax::Sass, &fs);
+    assert_eq!("@use \"lib\" with ($a: green, $b: black)\n", sass);
+
+    assert_eq!(
+        "@use \"lib\" with ($a: green, $b: black);\n",
+        assert_converts_with_fs(&sass, InputSyntax::Sass, InputSyntax::Scss, &fs)
+    );
+}
+
+#[test]
+fn mixins_and_control_flow() {
+    let input = "@mixin foo($a, $b: 2) {\n  width: $a;\n  @content;\n}\n@function double($x) {\n  @return $x * 2;\n}\na {\n  @include foo(1px) {\n    x: y;\n  }\n  @if 1 == 1 {\n    b: c;\n  } @else if 1 == 2 {\n    d: e;\n  } @else {\n    f: g;\n  }\n  @each $k, $v in (a: 1, b: 2) {\n    #{$k}: $v;\n  }\n  @for $i from 1 through 3 {\n    .x-#{$i} {\n      w: double($i);\n    }\n  }\n  @while false {\n    a: b;\n  }\n}\n";
+
+    let sass = to_sass(input);
+    assert_eq!(
+        "@mixin foo($a, $b: 2)\n  width: $a\n  @content\n@function double($x)\n  @return $x * 2\na\n  @include foo(1px)\n    x: y\n  @if 1 == 1\n    b: c\n  @else if 1 == 2\n    d: e\n  @else\n    f: g\n  @each $k, $v in (a: 1, b: 2)\n    #{$k}: $v\n  @for $i from 1 through 3\n    .x-#{$i}\n      w: double($i)\n  @while false\n    a: b\n",
+        sass
+    );
+    assert_eq!(input, to_scss(&sass));
+}
+
+#[test]
+fn indented_shorthands_to_scss() {
+    assert_eq!(
+        "@mixin foo {\n  a: b;\n}\nc {\n  @include foo;\n}\n",
+        to_scss("=foo\n  a: b\nc\n  +foo\n")
+    );
+}
+
+#[test]
+fn nested_properties_and_at_rules() {
+    let input = "a {\n  font: {\n    family: x;\n    size: 1px;\n  }\n  @media screen and (min-width: 100px) {\n    b: c;\n  }\n}\n@supports (display: grid) {\n  a {\n    b: c;\n  }\n}\n@at-root .foo {\n  a: b;\n}\n%p {\n  a: b;\n}\n.q {\n  @extend %p;\n}\n";
+    assert_eq!(input, to_scss(&to_sass(input)));
+}
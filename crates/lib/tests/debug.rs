@@ -1,5 +1,26 @@
+use macros::TestLogger;
+
 #[macro_use]
 mod macros;
 
 test!(simple_debug, "@debug 2", "");
 test!(simple_debug_with_semicolon, "@debug 2;", "");
+
+#[test]
+fn debug_is_passed_to_logger() {
+    let logger = TestLogger::new();
+
+    let input = "@debug 1 + 1;";
+
+    grass::from_string(
+        input.to_string(),
+        &grass::Options::default().logger(&logger),
+    )
+    .expect(input);
+
+    assert_eq!(
+        &[("2".to_owned(), 1, 8)],
+        logger.debugs.lock().unwrap().as_slice()
+    );
+    assert!(logger.warnings.lock().unwrap().is_empty());
+}
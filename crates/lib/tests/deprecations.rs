@@ -0,0 +1,168 @@
+use grass::Deprecation;
+use macros::TestLogger;
+
+#[macro_use]
+mod macros;
+
+#[test]
+fn slash_div_is_passed_to_logger() {
+    let logger = TestLogger::new();
+
+    let input = "a {\n  color: (4/2);\n}";
+
+    grass::from_string(input.to_owned(), &grass::Options::default().logger(&logger)).expect(input);
+
+    assert_eq!(
+        &[(Deprecation::SlashDiv, 2, 11)],
+        logger.deprecations.lock().unwrap().as_slice()
+    );
+}
+
+#[test]
+fn slash_div_in_plain_css_value_is_not_deprecated() {
+    let logger = TestLogger::new();
+
+    let input = "a {\n  font: 12px/30px;\n}";
+
+    grass::from_string(input.to_owned(), &grass::Options::default().logger(&logger)).expect(input);
+
+    assert!(logger.deprecations.lock().unwrap().is_empty());
+}
+
+#[test]
+fn silenced_deprecation_is_not_passed_to_logger() {
+    let logger = TestLogger::new();
+
+    let input = "a {\n  color: (4/2);\n}";
+
+    grass::from_string(
+        input.to_owned(),
+        &grass::Options::default()
+            .logger(&logger)
+            .silence_deprecation(Deprecation::SlashDiv),
+    )
+    .expect(input);
+
+    assert!(logger.deprecations.lock().unwrap().is_empty());
+}
+
+#[test]
+fn fatal_deprecation_is_an_error() {
+    let input = "a {\n  color: (4/2);\n}";
+
+    let err = grass::from_string(
+        input.to_owned(),
+        &grass::Options::default()
+            .logger(&grass::NullLogger)
+            .fatal_deprecation(Deprecation::SlashDiv),
+    )
+    .expect_err(input);
+
+    assert!(err.to_string().contains(
+        "This is only an error because you've set the slash-div deprecation to be fatal."
+    ));
+}
+
+#[test]
+fn future_deprecation_is_not_emitted_by_default() {
+    let logger = TestLogger::new();
+
+    let input = "a {\n  color: map-get((a: b), a);\n}";
+
+    grass::from_string(input.to_owned(), &grass::Options::default().logger(&logger)).expect(input);
+
+    assert!(logger.deprecations.lock().unwrap().is_empty());
+}
+
+#[test]
+fn future_deprecation_is_emitted_when_opted_in() {
+    let logger = TestLogger::new();
+
+    let input = "a {\n  color: map-get((a: b), a);\n}";
+
+    grass::from_string(
+        input.to_owned(),
+        &grass::Options::default()
+            .logger(&logger)
+            .future_deprecation(Deprecation::GlobalBuiltin),
+    )
+    .expect(input);
+
+    assert_eq!(
+        &[(Deprecation::GlobalBuiltin, 2, 10)],
+        logger.deprecations.lock().unwrap().as_slice()
+    );
+}
+
+#[test]
+fn new_global_variable_is_deprecated() {
+    let logger = TestLogger::new();
+
+    let input = "a {\n  $foo: red !global;\n  color: $foo;\n}";
+
+    grass::from_string(input.to_owned(), &grass::Options::default().logger(&logger)).expect(input);
+
+    assert_eq!(
+        &[(Deprecation::NewGlobal, 2, 3)],
+        logger.deprecations.lock().unwrap().as_slice()
+    );
+}
+
+#[test]
+fn existing_global_variable_is_not_deprecated() {
+    let logger = TestLogger::new();
+
+    let input = "$foo: null;\na {\n  $foo: red !global;\n  color: $foo;\n}";
+
+    grass::from_string(input.to_owned(), &grass::Options::default().logger(&logger)).expect(input);
+
+    assert!(logger.deprecations.lock().unwrap().is_empty());
+}
+
+#[test]
+fn call_string_is_deprecated() {
+    let logger = TestLogger::new();
+
+    let input = "a {\n  color: call(\"rgb\", 1, 2, 3);\n}";
+
+    grass::from_string(input.to_owned(), &grass::Options::default().logger(&logger)).expect(input);
+
+    assert_eq!(
+        &[(Deprecation::CallString, 2, 10)],
+        logger.deprecations.lock().unwrap().as_slice()
+    );
+}
+
+#[test]
+fn repetitive_deprecations_are_omitted() {
+    let logger = TestLogger::new();
+
+    let input = "@for $i from 1 through 8 {\n  a {\n    color: (4/2);\n  }\n}";
+
+    grass::from_string(input.to_owned(), &grass::Options::default().logger(&logger)).expect(input);
+
+    assert_eq!(5, logger.deprecations.lock().unwrap().len());
+    assert_eq!(
+        &[
+            "3 repetitive deprecation warnings omitted.\nRun in verbose mode to see all warnings."
+                .to_owned()
+        ],
+        logger.warnings_without_span.lock().unwrap().as_slice()
+    );
+}
+
+#[test]
+fn repetitive_deprecations_are_emitted_when_verbose() {
+    let logger = TestLogger::new();
+
+    let input = "@for $i from 1 through 8 {\n  a {\n    color: (4/2);\n  }\n}";
+
+    grass::from_string(
+        input.to_owned(),
+        &grass::Options::default().logger(&logger).verbose(true),
+    )
+    .expect(input);
+
+    assert_eq!(8, logger.deprecations.lock().unwrap().len());
+    assert!(logger.warnings_without_span.lock().unwrap().is_empty());
+}
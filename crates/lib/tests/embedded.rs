@@ -0,0 +1,700 @@
+//! Tests for the embedded protocol, driven by a fake host
+//!
+//! Since the compiler numbers its requests to the host sequentially from zero,
+//! the host's responses can be written before the compilation starts.
+
+use std::{fs, path::Path};
+
+/// Builds a protocol buffer message, as the host would
+#[derive(Default, Clone)]
+struct Message(Vec<u8>);
+
+fn msg() -> Message {
+    Message::default()
+}
+
+fn varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+impl Message {
+    fn uint(mut self, number: u32, value: u64) -> Self {
+        varint(&mut self.0, u64::from(number) << 3);
+        varint(&mut self.0, value);
+        self
+    }
+
+    fn double(mut self, number: u32, value: f64) -> Self {
+        varint(&mut self.0, u64::from(number) << 3 | 1);
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn bytes(mut self, number: u32, bytes: &[u8]) -> Self {
+        varint(&mut self.0, u64::from(number) << 3 | 2);
+        varint(&mut self.0, bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    fn string(self, number: u32, value: &str) -> Self {
+        self.bytes(number, value.as_bytes())
+    }
+
+    fn message(self, number: u32, message: Message) -> Self {
+        self.bytes(number, &message.0)
+    }
+}
+
+/// A decoded protocol buffer message
+#[derive(Debug, Clone)]
+struct Fields(Vec<(u32, Field)>);
+
+#[derive(Debug, Clone)]
+enum Field {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(Vec<u8>),
+}
+
+fn read_varint(buf: &mut &[u8]) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = buf[0];
+        *buf = &buf[1..];
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+impl Fields {
+    fn decode(mut buf: &[u8]) -> Self {
+        let mut fields = Vec::new();
+
+        while !buf.is_empty() {
+            let key = read_varint(&mut buf);
+            let field = match key & 7 {
+                0 => Field::Varint(read_varint(&mut buf)),
+                1 => {
+                    let (bytes, rest) = buf.split_at(8);
+                    buf = rest;
+                    Field::Fixed64(u64::from_le_bytes(bytes.try_into().unwrap()))
+                }
+                2 => {
+                    let len = read_varint(&mut buf) as usize;
+                    let (bytes, rest) = buf.split_at(len);
+                    buf = rest;
+                    Field::Bytes(bytes.to_vec())
+                }
+                wire_type => panic!("unexpected wire type {}", wire_type),
+            };
+            fields.push(((key >> 3) as u32, field));
+        }
+
+        Self(fields)
+    }
+
+    fn get(&self, number: u32) -> Option<&Field> {
+        self.0
+            .iter()
+            .find(|(n, _)| *n == number)
+            .map(|(_, field)| field)
+    }
+
+    fn has(&self, number: u32) -> bool {
+        self.get(number).is_some()
+    }
+
+    fn uint(&self, number: u32) -> u64 {
+        match self.get(number) {
+            Some(Field::Varint(value)) => *value,
+            None => 0,
+            field => panic!("expected varint, found {:?}", field),
+        }
+    }
+
+    fn double(&self, number: u32) -> f64 {
+        match self.get(number) {
+            Some(Field::Fixed64(value)) => f64::from_bits(*value),
+            None => 0.0,
+            field => panic!("expected double, found {:?}", field),
+        }
+    }
+
+    fn string(&self, number: u32) -> String {
+        match self.get(number) {
+            Some(Field::Bytes(bytes)) => String::from_utf8(bytes.clone()).unwrap(),
+            None => String::new(),
+            field => panic!("expected string, found {:?}", field),
+        }
+    }
+
+    fn strings(&self, number: u32) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|(n, _)| *n == number)
+            .map(|(_, field)| match field {
+                Field::Bytes(bytes) => String::from_utf8(bytes.clone()).unwrap(),
+                field => panic!("expected string, found {:?}", field),
+            })
+            .collect()
+    }
+
+    fn message(&self, number: u32) -> Fields {
+        match self.get(number) {
+            Some(Field::Bytes(bytes)) => Fields::decode(bytes),
+            field => panic!("expected message {}, found {:?}", number, field),
+        }
+    }
+
+    fn messages(&self, number: u32) -> Vec<Fields> {
+        self.0
+            .iter()
+            .filter(|(n, _)| *n == number)
+            .map(|(_, field)| match field {
+                Field::Bytes(bytes) => Fields::decode(bytes),
+                field => panic!("expected message, found {:?}", field),
+            })
+            .collect()
+    }
+}
+
+/// Sends `inbound` to the compiler, as pairs of compilation IDs and
+/// `InboundMessage`s, and returns the `OutboundMessage`s it sends back, as
+/// the compilation ID, the number of the field which is set, and its contents
+fn run(inbound: &[(u32, Message)]) -> Vec<(u32, u32, Fields)> {
+    let mut input = Vec::new();
+    for (id, message) in inbound {
+        let mut packet = Vec::new();
+        varint(&mut packet, u64::from(*id));
+        packet.extend_from_slice(&message.0);
+
+        varint(&mut input, packet.len() as u64);
+        input.extend_from_slice(&packet);
+    }
+
+    let mut output = Vec::new();
+    grass::embedded::serve(input.as_slice(), &mut output).unwrap();
+
+    let mut outbound = Vec::new();
+    let mut buf = output.as_slice();
+    while !buf.is_empty() {
+        let len = read_varint(&mut buf) as usize;
+        let (mut packet, rest) = buf.split_at(len);
+        buf = rest;
+
+        let id = read_varint(&mut packet) as u32;
+        let message = Fields::decode(packet);
+        assert_eq!(1, message.0.len());
+        let (field, _) = message.0[0];
+        outbound.push((id, field, message.message(field)));
+    }
+
+    outbound
+}
+
+fn compile_string(source: &str) -> Message {
+    msg().message(2, msg().string(1, source))
+}
+
+fn compile_request(request: Message) -> Message {
+    msg().message(1, request)
+}
+
+/// Runs a single compilation and returns its `CompileResponse`
+fn compile(request: Message) -> Fields {
+    let outbound = run(&[(1, compile_request(request))]);
+    let (id, field, response) = outbound.last().unwrap().clone();
+    assert_eq!((1, 2), (id, field));
+    response
+}
+
+fn css(response: &Fields) -> String {
+    assert!(
+        response.has(2),
+        "expected success, found {:?}",
+        response.message(3).string(1)
+    );
+    response.message(2).string(1)
+}
+
+fn file_url(path: &Path) -> String {
+    format!("file://{}", path.to_str().unwrap())
+}
+
+#[test]
+fn version_response() {
+    let outbound = run(&[(0, msg().message(6, msg().uint(1, 7)))]);
+
+    assert_eq!(1, outbound.len());
+    let (id, field, response) = &outbound[0];
+    assert_eq!((0, 8), (*id, *field));
+    assert_eq!(7, response.uint(5));
+    assert!(response.string(1).starts_with("2."));
+    assert_eq!("grass", response.string(4));
+    assert_eq!(env!("CARGO_PKG_VERSION"), response.string(2));
+}
+
+#[test]
+fn compile_string_input() {
+    let response = compile(compile_string("a { b: 1 + 1 }"));
+
+    assert_eq!("a {\n  b: 2;\n}", css(&response));
+}
+
+#[test]
+fn compile_compressed() {
+    let response = compile(compile_string("a { b: c }").uint(4, 1));
+
+    assert_eq!("a{b:c}", css(&response));
+}
+
+#[test]
+fn compile_indented_syntax() {
+    let response = compile(msg().message(2, msg().string(1, "a\n  b: c").uint(3, 1)));
+
+    assert_eq!("a {\n  b: c;\n}", css(&response));
+}
+
+#[test]
+fn compile_path_input() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("_lib.scss"), "$c: red;").unwrap();
+    fs::write(
+        dir.path().join("input.scss"),
+        "@use 'lib';\na { b: lib.$c }",
+    )
+    .unwrap();
+
+    let input = dir.path().join("input.scss");
+    let response = compile(msg().string(3, input.to_str().unwrap()));
+
+    assert_eq!("a {\n  b: red;\n}", css(&response));
+    assert_eq!(
+        vec![file_url(&input), file_url(&dir.path().join("_lib.scss"))],
+        response.strings(4)
+    );
+}
+
+#[test]
+fn source_map() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("_lib.scss"), "c {\n  d: e;\n}").unwrap();
+    fs::write(
+        dir.path().join("input.scss"),
+        "@use 'lib';\na {\n  b: c;\n}",
+    )
+    .unwrap();
+
+    let input = dir.path().join("input.scss");
+    let response = compile(msg().string(3, input.to_str().unwrap()).uint(5, 1));
+
+    assert_eq!("c {\n  d: e;\n}\n\na {\n  b: c;\n}", css(&response));
+    assert_eq!(
+        format!(
+            "{{\"version\":3,\"sources\":[\"{}\",\"{}\"],\"names\":[],\"mappings\":\"AAAA;EACE;;;ACAF;EACE\"}}",
+            file_url(&dir.path().join("_lib.scss")),
+            file_url(&input),
+        ),
+        response.message(2).string(2)
+    );
+}
+
+#[test]
+fn source_map_include_sources() {
+    let response = compile(compile_string("a {\n  b: c;\n}").uint(5, 1).uint(12, 1));
+
+    let source_map = response.message(2).string(2);
+    assert!(
+        source_map.contains(r#""sourcesContent":["a {\n  b: c;\n}"]"#),
+        "{}",
+        source_map
+    );
+}
+
+#[test]
+fn no_source_map_unless_requested() {
+    let response = compile(compile_string("a {\n  b: c;\n}"));
+
+    assert!(!response.message(2).has(2));
+}
+
+#[test]
+fn compile_failure() {
+    let response = compile(compile_string("a {\n  b: 1px + 1s;\n}"));
+
+    let failure = response.message(3);
+    assert_eq!("Incompatible units s and px.", failure.string(1));
+    assert!(failure.string(4).starts_with("Error: "));
+
+    let span = failure.message(2);
+    assert_eq!("1px + 1s", span.string(1));
+    assert_eq!(1, span.message(2).uint(2));
+    assert_eq!(5, span.message(2).uint(3));
+    assert_eq!(9, span.message(2).uint(1));
+    assert_eq!(17, span.message(3).uint(1));
+    assert_eq!("  b: 1px + 1s;\n", span.string(5));
+}
+
+#[test]
+fn load_path_importer() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("_lib.scss"), "a { b: c }").unwrap();
+
+    let response = compile(
+        compile_string("@use 'lib';").message(6, msg().string(1, dir.path().to_str().unwrap())),
+    );
+
+    assert_eq!("a {\n  b: c;\n}", css(&response));
+}
+
+#[test]
+fn host_importer() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("@use 'virtual/a';").message(6, msg().uint(2, 5))),
+        ),
+        (1, msg().message(2, msg().string(2, "virtual:a"))),
+        (
+            1,
+            msg().message(
+                3,
+                msg()
+                    .uint(1, 1)
+                    .message(2, msg().string(1, "b\n  c: d").uint(2, 1)),
+            ),
+        ),
+    ]);
+
+    assert_eq!(3, outbound.len());
+
+    let (_, field, canonicalize) = &outbound[0];
+    assert_eq!(4, *field);
+    assert_eq!(0, canonicalize.uint(1));
+    assert_eq!(5, canonicalize.uint(3));
+    assert_eq!("virtual/a", canonicalize.string(4));
+    assert_eq!(0, canonicalize.uint(5));
+    assert!(canonicalize.string(6).starts_with("file:///"));
+
+    let (_, field, import) = &outbound[1];
+    assert_eq!(5, *field);
+    assert_eq!(1, import.uint(1));
+    assert_eq!(5, import.uint(3));
+    assert_eq!("virtual:a", import.string(4));
+
+    let (_, field, response) = &outbound[2];
+    assert_eq!(2, *field);
+    assert_eq!("b {\n  c: d;\n}", css(response));
+    assert_eq!(vec!["virtual:a".to_owned()], response.strings(4));
+}
+
+#[test]
+fn host_importer_error() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("@use 'virtual/a';").message(6, msg().uint(2, 0))),
+        ),
+        (1, msg().message(2, msg().string(3, "not allowed"))),
+    ]);
+
+    let (_, _, response) = outbound.last().unwrap();
+    assert_eq!("not allowed", response.message(3).string(1));
+}
+
+#[test]
+fn host_importer_not_found() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("@use 'virtual/a';").message(6, msg().uint(2, 0))),
+        ),
+        (1, msg().message(2, msg())),
+    ]);
+
+    let (_, _, response) = outbound.last().unwrap();
+    assert_eq!(
+        "Can't find stylesheet to import.",
+        response.message(3).string(1)
+    );
+}
+
+#[test]
+fn host_file_importer() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("_theme.scss"), "a { b: c }").unwrap();
+
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("@import 'theme/dark';").message(6, msg().uint(3, 2))),
+        ),
+        (
+            1,
+            msg().message(4, msg().string(2, &file_url(&dir.path().join("theme")))),
+        ),
+    ]);
+
+    let (_, field, request) = &outbound[0];
+    assert_eq!(6, *field);
+    assert_eq!(2, request.uint(3));
+    assert_eq!("theme/dark", request.string(4));
+    assert_eq!(1, request.uint(5));
+
+    let (_, _, response) = outbound.last().unwrap();
+    assert_eq!("a {\n  b: c;\n}", css(response));
+}
+
+#[test]
+fn host_function() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("a { b: double(2px) }").string(7, "double($n)")),
+        ),
+        (
+            1,
+            msg().message(
+                5,
+                msg().message(2, msg().message(2, msg().double(1, 4.0).string(2, "px"))),
+            ),
+        ),
+    ]);
+
+    let (_, field, request) = &outbound[0];
+    assert_eq!(7, *field);
+    assert_eq!("double", request.string(2));
+
+    let arguments = request.messages(4);
+    assert_eq!(1, arguments.len());
+    let number = arguments[0].message(2);
+    assert_eq!(2.0, number.double(1));
+    assert_eq!(vec!["px".to_owned()], number.strings(2));
+
+    let (_, _, response) = outbound.last().unwrap();
+    assert_eq!("a {\n  b: 4px;\n}", css(response));
+}
+
+#[test]
+fn host_function_default_and_rest_arguments() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(
+                compile_string("a { b: f(x, y, z) }").string(7, "f($a, $b: 1, $rest...)"),
+            ),
+        ),
+        (1, msg().message(5, msg().message(2, msg().uint(7, 2)))),
+    ]);
+
+    let (_, _, request) = &outbound[0];
+    let arguments = request.messages(4);
+    assert_eq!(3, arguments.len());
+    assert_eq!("x", arguments[0].message(1).string(1));
+    assert_eq!("y", arguments[1].message(1).string(1));
+
+    let rest = arguments[2].message(10);
+    assert_eq!(1, rest.uint(1));
+    assert_eq!(1, rest.messages(3).len());
+    assert_eq!("z", rest.messages(3)[0].message(1).string(1));
+
+    let (_, _, response) = outbound.last().unwrap();
+    assert_eq!("", css(response));
+}
+
+#[test]
+fn host_function_error() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("a { b: f() }").string(7, "f()")),
+        ),
+        (1, msg().message(5, msg().string(3, "host failure"))),
+    ]);
+
+    let (_, _, response) = outbound.last().unwrap();
+    assert_eq!("host failure", response.message(3).string(1));
+}
+
+#[test]
+fn host_function_returns_list_and_map() {
+    let list = msg().message(
+        5,
+        msg()
+            .uint(1, 0)
+            .message(3, msg().message(1, msg().string(1, "a")))
+            .message(3, msg().uint(7, 0)),
+    );
+    let map = msg().message(
+        6,
+        msg().message(
+            1,
+            msg()
+                .message(1, msg().message(1, msg().string(1, "k")))
+                .message(2, list),
+        ),
+    );
+
+    let outbound = run(&[
+        (
+            1,
+            compile_request(
+                compile_string("@use 'sass:map';\na { b: map.get(f(), k) }").string(7, "f()"),
+            ),
+        ),
+        (1, msg().message(5, msg().message(2, map))),
+    ]);
+
+    let (_, _, response) = outbound.last().unwrap();
+    assert_eq!("a {\n  b: a, true;\n}", css(response));
+}
+
+#[test]
+fn host_function_returns_color() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("a { b: f() }").string(7, "f()")),
+        ),
+        (
+            1,
+            msg().message(
+                5,
+                msg().message(
+                    2,
+                    msg().message(3, msg().uint(1, 255).uint(2, 0).uint(3, 0).double(4, 1.0)),
+                ),
+            ),
+        ),
+    ]);
+
+    let (_, _, response) = outbound.last().unwrap();
+    assert_eq!("a {\n  b: red;\n}", css(response));
+}
+
+#[test]
+fn log_events() {
+    let outbound = run(&[(
+        1,
+        compile_request(compile_string("@warn careful;\n@debug 1 + 1;")),
+    )]);
+
+    assert_eq!(3, outbound.len());
+
+    let (_, field, warning) = &outbound[0];
+    assert_eq!(3, *field);
+    assert_eq!(0, warning.uint(2));
+    assert_eq!("careful", warning.string(3));
+    assert!(warning.string(6).starts_with("Warning: careful"));
+
+    let (_, field, debug) = &outbound[1];
+    assert_eq!(3, *field);
+    assert_eq!(2, debug.uint(2));
+    assert_eq!("2", debug.string(3));
+    assert_eq!(1, debug.message(4).message(2).uint(2));
+}
+
+#[test]
+fn silent_suppresses_warnings() {
+    let outbound = run(&[(
+        1,
+        compile_request(compile_string("@warn 'careful';").uint(14, 1)),
+    )]);
+
+    assert_eq!(1, outbound.len());
+}
+
+#[test]
+fn version_request_during_callback() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("a { b: f() }").string(7, "f()")),
+        ),
+        (0, msg().message(6, msg().uint(1, 3))),
+        (
+            1,
+            msg().message(5, msg().message(2, msg().message(1, msg().string(1, "c")))),
+        ),
+    ]);
+
+    let fields = outbound
+        .iter()
+        .map(|(_, field, _)| *field)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![7, 8, 2], fields);
+    assert_eq!("a {\n  b: c;\n}", css(&outbound[2].2));
+}
+
+#[test]
+fn compile_request_during_callback_is_queued() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("a { b: f() }").string(7, "f()")),
+        ),
+        (2, compile_request(compile_string("c { d: e }"))),
+        (
+            1,
+            msg().message(5, msg().message(2, msg().message(1, msg().string(1, "c")))),
+        ),
+    ]);
+
+    let responses = outbound
+        .iter()
+        .filter(|(_, field, _)| *field == 2)
+        .map(|(id, _, response)| (*id, css(response)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (1, "a {\n  b: c;\n}".to_owned()),
+            (2, "c {\n  d: e;\n}".to_owned())
+        ],
+        responses
+    );
+}
+
+#[test]
+fn unexpected_response_is_protocol_error() {
+    let outbound = run(&[
+        (1, msg().message(3, msg().uint(1, 0))),
+        (0, msg().message(6, msg().uint(1, 1))),
+    ]);
+
+    assert_eq!(1, outbound.len());
+    let (_, field, error) = &outbound[0];
+    assert_eq!(1, *field);
+    assert_eq!(1, error.uint(1));
+}
+
+#[test]
+fn mismatched_response_id_is_protocol_error() {
+    let outbound = run(&[
+        (
+            1,
+            compile_request(compile_string("a { b: f() }").string(7, "f()")),
+        ),
+        (
+            1,
+            msg().message(
+                5,
+                msg()
+                    .uint(1, 4)
+                    .message(2, msg().message(1, msg().string(1, "c"))),
+            ),
+        ),
+    ]);
+
+    let (_, field, error) = outbound.last().unwrap();
+    assert_eq!(1, *field);
+    assert_eq!(4, error.uint(2));
+}
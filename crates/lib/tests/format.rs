@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use grass::format::{format, FormatOptions};
+
+fn fmt(input: &str, path: &str, options: &FormatOptions) -> String {
+    let formatted = format(input.to_owned(), Path::new(path), options).unwrap();
+
+    assert_eq!(
+        formatted,
+        format(formatted.clone(), Path::new(path), options).unwrap(),
+        "formatting is not idempotent"
+    );
+
+    formatted
+}
+
+fn scss(input: &str) -> String {
+    fmt(input, "input.scss", &FormatOptions::default())
+}
+
+#[test]
+fn nested_style_rules() {
+    assert_eq!(
+        "a {\n  color: red;\n  &:hover {\n    color: blue;\n  }\n}\n",
+        scss("a{color:red;&:hover{color : blue}}")
+    );
+}
+
+#[test]
+fn empty_style_rule() {
+    assert_eq!("a {}\n", scss("a {\n\n}"));
+}
+
+#[test]
+fn selector_combinators_and_lists() {
+    assert_eq!("a, b > c ~ d {\n  x: y;\n}\n", scss("a,b>c~d{x:y}"));
+}
+
+#[test]
+fn preserves_multiline_selector_lists() {
+    assert_eq!("a,\nb > c {\n  x: y;\n}\n", scss("a,\n  b>c{x:y}"));
+}
+
+#[test]
+fn statements_on_one_line_after_blank_line() {
+    assert_eq!(
+        "$a: 1;\n\n@mixin foo {\n  b: c;\n  @content;\n}\n",
+        scss("$a: 1;\n\n@mixin foo { b: c; @content; }")
+    );
+}
+
+#[test]
+fn preserves_silent_and_loud_comments() {
+    assert_eq!(
+        "// one\n// two\na {\n  /* loud\n     comment */\n  b: c; // trailing\n}\n",
+        scss("// one\n// two\na {\n    /* loud\n       comment */\n  b: c;   // trailing\n}")
+    );
+}
+
+#[test]
+fn collapses_blank_lines() {
+    assert_eq!("$a: 1;\n\n$b: 2;\n", scss("$a : 1;\n\n\n\n$b:2;"));
+}
+
+#[test]
+fn control_flow() {
+    assert_eq!(
+        "a {\n  @if $a == 1 {\n    b: c;\n  } @else if $a == 2 {\n    d: e;\n  } @else {\n    f: g;\n  }\n  @for $i from 1 through 3 {\n    w: $i;\n  }\n  @each $k, $v in $map {\n    #{$k}: $v;\n  }\n}\n",
+        scss("a{@if $a == 1 {b:c}\n@else if $a == 2 {d:e} @else{f:g}\n@for $i from 1   through 3 {w:$i}\n@each $k,$v in $map{#{$k}:$v}}")
+    );
+}
+
+#[test]
+fn mixins_and_functions() {
+    assert_eq!(
+        "@mixin foo($a, $b: 2) {\n  width: $a;\n  @content;\n}\n@function f($x) {\n  @return $x * 2;\n}\na {\n  @include foo(1px) {\n    x: y;\n  }\n}\n",
+        scss("@mixin foo($a,$b: 2){width:$a;@content}\n@function f($x){@return $x * 2}\na{@include foo(1px){x:y}}")
+    );
+}
+
+#[test]
+fn nested_properties() {
+    assert_eq!(
+        "a {\n  font: {\n    family: x;\n    size: 1px;\n  }\n}\n",
+        scss("a { font: { family: x; size: 1px } }")
+    );
+}
+
+#[test]
+fn breaks_long_lists() {
+    assert_eq!(
+        "$list: aaaaaaaaaaaa,\n  bbbbbbbbbbbbbbbbb,\n  cccccccccccccccccc;\n",
+        fmt(
+            "$list: aaaaaaaaaaaa, bbbbbbbbbbbbbbbbb, cccccccccccccccccc;",
+            "input.scss",
+            &FormatOptions::default().line_width(40)
+        )
+    );
+}
+
+#[test]
+fn keeps_short_lists_on_one_line() {
+    assert_eq!("$map: (a: 1, b: 2);\n", scss("$map:(a: 1,b: 2);"));
+}
+
+#[test]
+fn indent_with_tabs() {
+    assert_eq!(
+        "a {\n\tb {\n\t\tc: d;\n\t}\n}\n",
+        fmt(
+            "a { b { c: d } }",
+            "input.scss",
+            &FormatOptions::default().use_tabs(true)
+        )
+    );
+}
+
+#[test]
+fn indent_width() {
+    assert_eq!(
+        "a {\n    b: c;\n}\n",
+        fmt(
+            "a { b: c }",
+            "input.scss",
+            &FormatOptions::default().indent_width(4)
+        )
+    );
+}
+
+#[test]
+fn indented_syntax() {
+    assert_eq!(
+        "// c\n$a: 1\n@mixin foo($x)\n  width: $x\na\n  color: red\n  @include foo(1px)\n  @if $a == 1\n    b: c\n  @else\n    d: e\n",
+        fmt(
+            "// c\n$a : 1\n=foo($x)\n    width: $x\na\n    color : red\n    +foo(1px)\n    @if $a == 1\n        b: c\n    @else\n        d: e\n",
+            "input.sass",
+            &FormatOptions::default()
+        )
+    );
+}
+
+#[test]
+fn error_on_invalid_input() {
+    assert!(format(
+        "a {".to_owned(),
+        Path::new("input.scss"),
+        &FormatOptions::default()
+    )
+    .is_err());
+}
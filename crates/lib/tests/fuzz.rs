@@ -0,0 +1,181 @@
+//! Inputs found by fuzzing that previously panicked or overflowed the stack
+//!
+//! Every stylesheet in `tests/fuzz-corpus` is compiled with each of the output
+//! styles used by the fuzz targets. Directories hold stylesheets which load
+//! other files, starting from `input.scss`. Compilation may fail, but must
+//! never panic.
+
+use std::path::{Path, PathBuf};
+
+use macros::TestFs;
+
+#[macro_use]
+mod macros;
+
+fn corpus() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fuzz-corpus");
+
+    let mut inputs = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter_map(|path| {
+            if path.is_dir() {
+                Some(path.join("input.scss"))
+            } else if path
+                .file_name()
+                .is_some_and(|name| !name.to_string_lossy().starts_with('_'))
+            {
+                Some(path)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    inputs.sort();
+    inputs
+}
+
+#[test]
+fn corpus_does_not_panic() {
+    let inputs = corpus();
+    assert!(inputs.len() > 10);
+
+    for style in [grass::OutputStyle::Expanded, grass::OutputStyle::Compressed] {
+        let options = grass::Options::default()
+            .quiet(true)
+            .style(style)
+            .max_call_depth(32)
+            .max_loop_iterations(1_000);
+
+        for input in &inputs {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                grass::from_path(input, &options)
+            }));
+            assert!(result.is_ok(), "{} panicked", input.display());
+        }
+    }
+}
+
+error!(
+    simple_selectors_leading_combinator,
+    "a { b: simple-selectors(\"> a\"); }", "Error: $selector: expected selector."
+);
+error!(
+    simple_selectors_complex,
+    "a { b: simple-selectors(\"a b\"); }", "Error: $selector: expected selector."
+);
+error!(
+    selector_extend_combinator_target,
+    "a { b: selector-extend(\"a\", \">\", \"b\"); }", "Error: Can't extend complex selector >."
+);
+error!(
+    extend_leading_combinator,
+    "a { @extend > b; }", "Error: complex selectors may not be extended."
+);
+test!(
+    is_superselector_combinator_in_pseudo,
+    "a { b: is-superselector(\"c:is(a)\", \"c:is(>)\"); }",
+    "a {\n  b: false;\n}\n"
+);
+error!(
+    darken_nan,
+    "@use 'sass:math';\na { b: darken(red, math.div(0, 0)); }",
+    "Error: $amount: Expected NaN to be within 0 and 100."
+);
+error!(
+    escape_in_selector_reports_location,
+    "\\\u{6dd}| {}", "Error: Expected identifier."
+);
+error!(
+    indented_unterminated_loud_comment,
+    "a\n  b: c /*",
+    "Error: expected more input.",
+    grass::Options::default().input_syntax(grass::InputSyntax::Sass)
+);
+error!(
+    indented_after_leading_blank_line,
+    "\n and ",
+    "Error: Expected no indentation.",
+    grass::Options::default().input_syntax(grass::InputSyntax::Sass)
+);
+
+#[test]
+fn use_loop() {
+    let mut fs = TestFs::new();
+    fs.add_file("_a.scss", "@use 'b';");
+    fs.add_file("_b.scss", "@use 'a';");
+
+    assert_eq!(
+        "Error: Module loop: this module is already being loaded.",
+        grass::from_string("@use 'a';".to_owned(), &grass::Options::default().fs(&fs))
+            .unwrap_err()
+            .to_string()
+            .lines()
+            .next()
+            .unwrap()
+    );
+}
+
+#[test]
+fn import_loop() {
+    let mut fs = TestFs::new();
+    fs.add_file("_a.scss", "@import 'a';");
+
+    assert_eq!(
+        "Error: This file is already being loaded.",
+        grass::from_string(
+            "@import 'a';".to_owned(),
+            &grass::Options::default().quiet(true).fs(&fs)
+        )
+        .unwrap_err()
+        .to_string()
+        .lines()
+        .next()
+        .unwrap()
+    );
+}
+
+#[test]
+fn same_module_can_be_loaded_twice() {
+    let mut fs = TestFs::new();
+    fs.add_file("_a.scss", "$b: c;");
+    fs.add_file("_d.scss", "@use 'a';\ne { f: a.$b; }");
+
+    assert_eq!(
+        "e {\n  f: c;\n}\n",
+        grass::from_string(
+            "@use 'a';\n@use 'd';".to_owned(),
+            &grass::Options::default().fs(&fs)
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn io_error_is_returned() {
+    #[derive(Debug)]
+    struct UnreadableFs;
+
+    impl grass::Fs for UnreadableFs {
+        fn is_dir(&self, _: &Path) -> bool {
+            false
+        }
+
+        fn is_file(&self, _: &Path) -> bool {
+            true
+        }
+
+        fn read(&self, _: &Path) -> std::io::Result<Vec<u8>> {
+            Err(std::io::Error::other("unreadable"))
+        }
+    }
+
+    let err = grass::from_string(
+        "@use 'a';".to_owned(),
+        &grass::Options::default().fs(&UnreadableFs),
+    )
+    .unwrap_err();
+
+    assert_eq!("Error: unreadable\n", err.to_string());
+}
@@ -0,0 +1,230 @@
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
+use grass::{FileImporter, ImportContext, Importer, ImporterResult, InputSyntax};
+
+use macros::TestFs;
+
+#[macro_use]
+mod macros;
+
+/// Loads stylesheets with URLs like `virtual/name` and canonicalizes them to
+/// `virtual:name`, recording every call to `canonicalize`
+#[derive(Debug, Default)]
+struct VirtualImporter {
+    files: HashMap<&'static str, (&'static str, InputSyntax)>,
+    canonicalized: Mutex<Vec<(String, Option<String>, bool)>>,
+}
+
+impl VirtualImporter {
+    fn new(files: &[(&'static str, &'static str)]) -> Self {
+        Self {
+            files: files
+                .iter()
+                .map(|&(name, contents)| (name, (contents, InputSyntax::Scss)))
+                .collect(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Importer for VirtualImporter {
+    fn canonicalize(&self, url: &str, context: &ImportContext) -> Result<Option<String>, String> {
+        self.canonicalized.lock().unwrap().push((
+            url.to_owned(),
+            context.containing_url.map(str::to_owned),
+            context.from_import,
+        ));
+
+        if url == "virtual/error" {
+            return Err("virtual error".to_owned());
+        }
+
+        let name = url.strip_prefix("virtual/").unwrap_or(url);
+        Ok(self
+            .files
+            .contains_key(name)
+            .then(|| format!("virtual:{}", name)))
+    }
+
+    fn load(&self, canonical_url: &str) -> Result<ImporterResult, String> {
+        let (contents, syntax) = self.files[canonical_url.strip_prefix("virtual:").unwrap()];
+        Ok(ImporterResult::new(contents.to_owned(), syntax))
+    }
+}
+
+/// Redirects `theme/name` to `themes/name`
+#[derive(Debug)]
+struct ThemeImporter;
+
+impl FileImporter for ThemeImporter {
+    fn find_file(&self, url: &str, _context: &ImportContext) -> Result<Option<PathBuf>, String> {
+        Ok(url
+            .strip_prefix("theme/")
+            .map(|name| PathBuf::from("themes").join(name)))
+    }
+}
+
+fn compile_with_importer(
+    input: &str,
+    importer: &VirtualImporter,
+) -> Result<String, Box<grass::Error>> {
+    grass::from_string(
+        input.to_owned(),
+        &grass::Options::default().importer(importer),
+    )
+}
+
+#[test]
+fn use_from_importer() {
+    let importer = VirtualImporter::new(&[("colors", "$primary: red;")]);
+
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        compile_with_importer(
+            "@use 'virtual/colors';\na { color: colors.$primary; }",
+            &importer
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn import_from_importer_passes_from_import() {
+    let importer = VirtualImporter::new(&[("colors", "a { color: red; }")]);
+
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        grass::from_string(
+            "@import 'virtual/colors';".to_owned(),
+            &grass::Options::default().quiet(true).importer(&importer),
+        )
+        .unwrap()
+    );
+
+    let canonicalized = importer.canonicalized.lock().unwrap();
+    assert_eq!(1, canonicalized.len());
+    assert!(canonicalized[0].2);
+}
+
+#[test]
+fn relative_load_passes_canonical_containing_url() {
+    let importer = VirtualImporter::new(&[("a", "@use 'b';\nc { d: b.$e; }"), ("b", "$e: f;")]);
+
+    assert_eq!(
+        "c {\n  d: f;\n}\n",
+        compile_with_importer("@use 'virtual/a';", &importer).unwrap()
+    );
+
+    let canonicalized = importer.canonicalized.lock().unwrap();
+    assert_eq!("b", canonicalized[1].0);
+    assert_eq!(Some("virtual:a"), canonicalized[1].1.as_deref());
+    assert!(!canonicalized[1].2);
+}
+
+#[test]
+fn containing_url_of_file_is_file_url() {
+    let importer = VirtualImporter::new(&[("colors", "")]);
+
+    compile_with_importer("@use 'virtual/colors';", &importer).unwrap();
+
+    let canonicalized = importer.canonicalized.lock().unwrap();
+    assert!(canonicalized[0]
+        .1
+        .as_deref()
+        .unwrap()
+        .starts_with("file:///"));
+}
+
+#[test]
+fn file_system_takes_precedence_over_importer() {
+    let mut fs = TestFs::new();
+    fs.add_file("_colors.scss", "a { color: red; }");
+
+    let importer = VirtualImporter::new(&[("colors", "a { color: blue; }")]);
+
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        grass::from_string(
+            "@use 'colors';".to_owned(),
+            &grass::Options::default().fs(&fs).importer(&importer),
+        )
+        .unwrap()
+    );
+    assert!(importer.canonicalized.lock().unwrap().is_empty());
+}
+
+#[test]
+fn importer_loads_indented_syntax() {
+    let mut importer = VirtualImporter::new(&[]);
+    importer
+        .files
+        .insert("indented", ("a\n  color: red", InputSyntax::Sass));
+
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        compile_with_importer("@use 'virtual/indented';", &importer).unwrap()
+    );
+}
+
+#[test]
+fn importer_error_is_reported_at_rule() {
+    let importer = VirtualImporter::new(&[]);
+
+    let err = compile_with_importer("@use 'virtual/error';", &importer).unwrap_err();
+
+    match err.kind() {
+        grass::ErrorKind::ParseError { message, loc, .. } => {
+            assert_eq!("virtual error", message);
+            assert_eq!(0, loc.begin.line);
+        }
+        kind => panic!("unexpected error {:?}", kind),
+    }
+}
+
+#[test]
+fn importer_not_found() {
+    let importer = VirtualImporter::new(&[]);
+
+    let err = compile_with_importer("@use 'virtual/missing';", &importer).unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: Can't find stylesheet to import."));
+}
+
+#[test]
+fn file_importer_redirects_to_file_system() {
+    let mut fs = TestFs::new();
+    fs.add_file("themes/_dark.scss", "a { color: black; }");
+
+    assert_eq!(
+        "a {\n  color: black;\n}\n",
+        grass::from_string(
+            "@use 'theme/dark';".to_owned(),
+            &grass::Options::default()
+                .fs(&fs)
+                .file_importer(&ThemeImporter),
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn importers_are_consulted_in_order() {
+    let mut fs = TestFs::new();
+    fs.add_file("themes/_dark.scss", "a { color: black; }");
+
+    let importer = VirtualImporter::new(&[("theme/dark", "a { color: white; }")]);
+
+    assert_eq!(
+        "a {\n  color: white;\n}\n",
+        grass::from_string(
+            "@use 'theme/dark';".to_owned(),
+            &grass::Options::default()
+                .fs(&fs)
+                .importer(&importer)
+                .file_importer(&ThemeImporter),
+        )
+        .unwrap()
+    );
+}
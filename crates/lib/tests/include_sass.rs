@@ -1,7 +1,90 @@
 #[cfg(feature = "macro")]
 #[test]
 fn basic() {
-    let css: &str = grass::include!("./input.scss");
+    let css: &str = grass::include!("tests/include_sass/input.scss");
 
     assert_eq!(css, "a{color:red}");
 }
+
+#[cfg(feature = "macro")]
+#[test]
+fn expanded_style() {
+    let css: &str = grass::include!("tests/include_sass/input.scss", style = "expanded");
+
+    assert_eq!(css, "a {\n  color: red;\n}\n");
+}
+
+#[cfg(feature = "macro")]
+#[test]
+fn compressed_style() {
+    let css: &str = grass::include!("tests/include_sass/input.scss", style = "compressed");
+
+    assert_eq!(css, "a{color:red}");
+}
+
+#[cfg(feature = "macro")]
+#[test]
+fn load_paths() {
+    let css: &str = grass::include!(
+        "tests/include_sass/with_load_path.scss",
+        load_paths = ["tests/include_sass/vendor"],
+    );
+
+    assert_eq!(css, "a{color:blue}");
+}
+
+#[cfg(feature = "macro")]
+#[test]
+fn multiple_options() {
+    let css: &str = grass::include!(
+        "tests/include_sass/warn.scss",
+        quiet = true,
+        style = "expanded",
+        load_paths = []
+    );
+
+    assert_eq!(css, "a {\n  color: red;\n}\n");
+}
+
+#[cfg(feature = "macro")]
+#[test]
+fn scss_literal() {
+    let css: &str = grass::scss!(r#"a { b { color: red; } }"#);
+
+    assert_eq!(css, "a b{color:red}");
+}
+
+#[cfg(feature = "macro")]
+#[test]
+fn scss_literal_with_options() {
+    let css: &str = grass::scss!("a { b { color: red; } }", style = "expanded");
+
+    assert_eq!(css, "a b {\n  color: red;\n}\n");
+}
+
+#[cfg(feature = "macro")]
+#[test]
+fn scss_literal_relative_import() {
+    let css: &str = grass::scss!(
+        r#"
+        @use "tests/include_sass/vendor/colors";
+
+        a {
+          color: colors.$primary;
+        }
+        "#
+    );
+
+    assert_eq!(css, "a{color:blue}");
+}
+
+#[cfg(feature = "macro")]
+#[test]
+fn scss_literal_load_paths() {
+    let css: &str = grass::scss!(
+        r#"@use "colors"; a { color: colors.$primary; }"#,
+        load_paths = ["tests/include_sass/vendor"]
+    );
+
+    assert_eq!(css, "a{color:blue}");
+}
@@ -0,0 +1,132 @@
+use std::time::{Duration, Instant};
+
+#[macro_use]
+mod macros;
+
+error!(
+    recursive_function_exceeds_call_depth,
+    "@function foo($a) { @return foo($a); }\na { b: foo(1); }",
+    "Error: Maximum call depth (50) exceeded.",
+    grass::Options::default().max_call_depth(50)
+);
+error!(
+    recursive_mixin_exceeds_call_depth,
+    "@mixin foo { @include foo; }\na { @include foo; }",
+    "Error: Maximum call depth (50) exceeded.",
+    grass::Options::default().max_call_depth(50)
+);
+error!(
+    nested_content_exceeds_call_depth,
+    "@mixin foo { @content; }\na { @include foo { @include foo { b: c; } } }",
+    "Error: Maximum call depth (3) exceeded.",
+    grass::Options::default().max_call_depth(3)
+);
+test!(
+    calls_within_call_depth,
+    "@function foo($a) { @if $a == 0 { @return 0; } @return foo($a - 1); }\na { b: foo(9); }",
+    "a {\n  b: 0;\n}\n",
+    grass::Options::default().max_call_depth(10)
+);
+test!(
+    sequential_calls_do_not_accumulate_call_depth,
+    "@function foo() { @return 1; }\na { b: foo() foo() foo() foo(); }",
+    "a {\n  b: 1 1 1 1;\n}\n",
+    grass::Options::default().max_call_depth(1)
+);
+error!(
+    infinite_while_exceeds_loop_iterations,
+    "$a: 0;\n@while true { $a: $a + 1; }",
+    "Error: Maximum number of loop iterations (1000) exceeded.",
+    grass::Options::default().max_loop_iterations(1000)
+);
+error!(
+    for_exceeds_loop_iterations,
+    "@for $i from 1 through 100 { a { b: $i; } }",
+    "Error: Maximum number of loop iterations (99) exceeded.",
+    grass::Options::default().max_loop_iterations(99)
+);
+test!(
+    for_within_loop_iterations,
+    "@for $i from 1 through 3 { a { b: $i; } }",
+    "a {\n  b: 1;\n}\n\na {\n  b: 2;\n}\n\na {\n  b: 3;\n}\n",
+    grass::Options::default().max_loop_iterations(3)
+);
+error!(
+    loop_iterations_are_counted_across_loops,
+    "@each $a in 1 2 3 { @each $b in 1 2 3 { c { d: $a $b; } } }",
+    "Error: Maximum number of loop iterations (10) exceeded.",
+    grass::Options::default().max_loop_iterations(10)
+);
+error!(
+    output_exceeds_max_size,
+    "@for $i from 1 through 100 { a { b: $i; } }",
+    "Error: Output exceeded the maximum size of 100 bytes.",
+    grass::Options::default().max_output_size(100)
+);
+test!(
+    output_within_max_size,
+    "a {\n  b: c;\n}\n",
+    "a {\n  b: c;\n}\n",
+    grass::Options::default().max_output_size(13)
+);
+error!(
+    cancelled_before_compilation,
+    "a { b: c; }",
+    "Error: Compilation was cancelled.",
+    grass::Options::default().cancellation_token({
+        let token = grass::CancellationToken::new();
+        token.cancel();
+        token
+    })
+);
+error!(
+    deadline_exceeded,
+    "@while true { a { b: c; } }",
+    "Error: Compilation exceeded its deadline.",
+    grass::Options::default().deadline(Instant::now())
+);
+
+#[test]
+fn cancel_from_another_thread() {
+    let token = grass::CancellationToken::new();
+    let options = grass::Options::default().cancellation_token(token.clone());
+
+    let handle = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        token.cancel();
+    });
+
+    let err =
+        grass::from_string("$a: 0;\n@while true { $a: $a + 1; }".to_owned(), &options).unwrap_err();
+
+    handle.join().unwrap();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: Compilation was cancelled."));
+}
+
+#[test]
+fn limit_errors_have_spans() {
+    let err = grass::from_string(
+        "@mixin foo {\n  @include foo;\n}\na {\n  @include foo;\n}".to_owned(),
+        &grass::Options::default().max_call_depth(5),
+    )
+    .unwrap_err();
+
+    let err = err.to_string();
+    assert!(err.contains("2 │   @include foo;"));
+    assert!(err.contains("stdin 5:12  root stylesheet"));
+}
+
+#[test]
+fn no_limits_by_default() {
+    assert_eq!(
+        "",
+        grass::from_string(
+            "@for $i from 1 through 100000 {}".to_owned(),
+            &grass::Options::default()
+        )
+        .unwrap()
+    );
+}
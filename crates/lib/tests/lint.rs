@@ -0,0 +1,234 @@
+use std::path::Path;
+
+use grass::lint::{lint, Lint, LintKind};
+
+use macros::TestFs;
+
+#[macro_use]
+mod macros;
+
+fn lint_with_fs(input: &str, fs: &TestFs) -> Vec<Lint> {
+    lint(
+        input.to_owned(),
+        Path::new("input.scss"),
+        &grass::Options::default().fs(fs),
+    )
+    .unwrap()
+}
+
+fn lints(input: &str) -> Vec<(LintKind, String)> {
+    lint_with_fs(input, &TestFs::new())
+        .into_iter()
+        .map(|lint| (lint.kind, lint.message))
+        .collect()
+}
+
+#[test]
+fn no_lints() {
+    assert!(lints(
+        "$a: 1;\n@mixin foo($b) { width: $a + $b; }\na { @include foo(2); color: red; }"
+    )
+    .is_empty());
+}
+
+#[test]
+fn unused_private_members() {
+    assert_eq!(
+        vec![
+            (
+                LintKind::UnusedMember,
+                "Private variable $-a is never used.".to_owned()
+            ),
+            (
+                LintKind::UnusedMember,
+                "Private mixin -b is never used.".to_owned()
+            ),
+            (
+                LintKind::UnusedMember,
+                "Private function -c is never used.".to_owned()
+            ),
+        ],
+        lints("$-a: 1;\n@mixin _b {}\n@function -c() { @return 1; }\n$public: 2;")
+    );
+}
+
+#[test]
+fn private_member_used_before_declaration() {
+    assert!(
+        lints("@mixin foo { a: -b(); }\n@function -b() { @return 1; }\nc { @include foo; }")
+            .is_empty()
+    );
+}
+
+#[test]
+fn private_member_used_with_other_spelling() {
+    assert!(lints("$-a: 1;\nb { c: $_a; }").is_empty());
+}
+
+#[test]
+fn unused_local_variable() {
+    assert_eq!(
+        vec![(
+            LintKind::UnusedVariable,
+            "Local variable $b is never used.".to_owned()
+        )],
+        lints("@function foo($a) { $b: 1; @return $a; }\nc { d: foo(1); }")
+    );
+}
+
+#[test]
+fn reassigned_local_variable_is_used() {
+    assert!(lints("a { $b: 1; @if true { $b: 2; } c: $b; }").is_empty());
+}
+
+#[test]
+fn unused_use() {
+    let lint = &lint_with_fs("@use \"sass:math\";", &TestFs::new())[0];
+
+    assert_eq!(LintKind::UnusedUse, lint.kind);
+    assert_eq!(
+        "The module \"sass:math\" is never used through its namespace math.",
+        lint.message
+    );
+    assert_eq!(
+        (0, 0),
+        (lint.location.begin.line, lint.location.begin.column)
+    );
+}
+
+#[test]
+fn used_use() {
+    assert!(lints("@use \"sass:math\";\na { b: math.div(1, 2); }").is_empty());
+    assert!(lints("@use \"sass:math\" as m;\na { b: m.$pi; }").is_empty());
+}
+
+#[test]
+fn use_without_namespace() {
+    assert!(lints("@use \"sass:math\" as *;").is_empty());
+}
+
+#[test]
+fn unused_use_of_module_with_css() {
+    let mut fs = TestFs::new();
+    fs.add_file("_styles.scss", "a { b: c; }");
+    fs.add_file("_members.scss", "$a: 1;");
+
+    let lints = lint_with_fs("@use \"styles\";\n@use \"members\";", &fs);
+
+    assert_eq!(1, lints.len());
+    assert_eq!(LintKind::UnusedUse, lints[0].kind);
+    assert_eq!(1, lints[0].location.begin.line);
+}
+
+#[test]
+fn local_shadows_global() {
+    assert_eq!(
+        vec![
+            (
+                LintKind::ShadowedGlobal,
+                "This declares a local variable which shadows the global $a. Use !global to assign to the global variable instead.".to_owned()
+            ),
+        ],
+        lints("$a: 1;\n@mixin foo { $a: 2; b: $a; }\nc { @include foo; }")
+    );
+}
+
+#[test]
+fn global_flag_in_local_scope() {
+    assert!(lints("$a: 1;\n@mixin foo { $a: 2 !global; }\nc { @include foo; b: $a; }").is_empty());
+}
+
+#[test]
+fn control_flow_at_root_assigns_global() {
+    assert!(lints("$a: 1;\n@if true { $a: 2; }\nb { c: $a; }").is_empty());
+}
+
+#[test]
+fn global_flag_at_root() {
+    assert_eq!(
+        vec![(
+            LintKind::ShadowedGlobal,
+            "!global has no effect at the root of a stylesheet, where $a is already global."
+                .to_owned()
+        )],
+        lints("$a: 1 !global;")
+    );
+}
+
+#[test]
+fn duplicate_property() {
+    let lints = lint_with_fs(
+        "a {\n  color: red;\n  width: 1px;\n  color: blue;\n}",
+        &TestFs::new(),
+    );
+
+    assert_eq!(1, lints.len());
+    assert_eq!(LintKind::DuplicateProperty, lints[0].kind);
+    assert_eq!(
+        "The property color is already declared in this style rule.",
+        lints[0].message
+    );
+    assert_eq!(
+        (3, 2, 3, 7),
+        (
+            lints[0].location.begin.line,
+            lints[0].location.begin.column,
+            lints[0].location.end.line,
+            lints[0].location.end.column
+        )
+    );
+}
+
+#[test]
+fn consecutive_duplicate_property_with_same_value() {
+    assert_eq!(
+        vec![(
+            LintKind::DuplicateProperty,
+            "The property color is already declared in this style rule.".to_owned()
+        )],
+        lints("a { color: red; color: red; }")
+    );
+}
+
+#[test]
+fn fallback_property() {
+    assert!(lints("a { display: flex; display: grid; }").is_empty());
+}
+
+#[test]
+fn unmatched_extend() {
+    assert_eq!(
+        vec![(
+            LintKind::UnmatchedExtend,
+            "The target selector .b was not found, so this @extend has no effect.".to_owned()
+        )],
+        lints("a { @extend .b !optional; }\n.c { @extend .d; }\n.d { e: f; }")
+    );
+}
+
+#[test]
+fn lint_display() {
+    let lint = &lint_with_fs("$-a: 1;", &TestFs::new())[0];
+
+    assert_eq!(
+        "Warning [unused-member]: Private variable $-a is never used.\n  ╷\n1 │ $-a: 1;\n  │ ^^^\n  ╵\n./input.scss:1:1\n",
+        lint.to_string()
+    );
+}
+
+#[test]
+fn lint_kind_ids() {
+    for kind in LintKind::ALL {
+        assert_eq!(Some(kind), LintKind::from_id(kind.id()));
+    }
+}
+
+#[test]
+fn error_in_stylesheet() {
+    assert!(lint(
+        "a { b: c(; }".to_owned(),
+        Path::new("input.scss"),
+        &grass::Options::default()
+    )
+    .is_err());
+}
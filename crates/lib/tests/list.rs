@@ -474,6 +474,105 @@ error!(
     "a {\n  color: set-nth([], 1px, a);\n}\n",
     "Error: $n: Invalid index 1px for a list with 0 elements."
 );
+test!(
+    long_list_nth_and_set_nth,
+    "$list: ();
+    @for $i from 1 through 100 {
+        $list: append($list, $i, comma);
+    }
+    $updated: set-nth($list, 40, x);
+
+    a {
+        color: length($list);
+        color: nth($list, 1);
+        color: nth($list, 33);
+        color: nth($list, -1);
+        color: nth($updated, 40);
+        color: nth($list, 40);
+        color: index($list, 77);
+    }",
+    "a {\n  color: 100;\n  color: 1;\n  color: 33;\n  color: 100;\n  color: x;\n  color: 40;\n  color: 77;\n}\n"
+);
+test!(
+    list_longer_than_two_levels,
+    "$list: ();
+    @for $i from 1 through 2000 {
+        $list: append($list, $i);
+    }
+
+    a {
+        color: length($list);
+        color: nth($list, 1024);
+        color: nth($list, 1025);
+        color: nth($list, 1057);
+        color: nth($list, 2000);
+        color: index($list, 1500);
+    }",
+    "a {\n  color: 2000;\n  color: 1024;\n  color: 1025;\n  color: 1057;\n  color: 2000;\n  color: 1500;\n}\n"
+);
+test!(
+    long_list_serializes_every_element,
+    "$list: ();
+    @for $i from 1 through 40 {
+        $list: append($list, $i, comma);
+    }
+
+    a {
+        color: $list;
+    }",
+    "a {\n  color: 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40;\n}\n"
+);
+test!(
+    join_long_lists,
+    "$a: ();
+    $b: ();
+    @for $i from 1 through 40 {
+        $a: append($a, $i);
+        $b: append($b, $i + 40);
+    }
+    $joined: join($a, $b);
+
+    a {
+        color: length($joined);
+        color: nth($joined, 41);
+        color: nth($joined, -1);
+        color: length($a);
+    }",
+    "a {\n  color: 80;\n  color: 41;\n  color: 80;\n  color: 40;\n}\n"
+);
+test!(
+    each_over_long_list,
+    "$list: ();
+    @for $i from 1 through 100 {
+        $list: append($list, $i);
+    }
+    $sum: 0;
+    @each $i in $list {
+        $sum: $sum + $i;
+    }
+
+    a {
+        color: $sum;
+    }",
+    "a {\n  color: 5050;\n}\n"
+);
+test!(
+    append_does_not_modify_original_long_list,
+    "$a: ();
+    @for $i from 1 through 100 {
+        $a: append($a, $i);
+    }
+    $b: append($a, x);
+    $c: append($a, y);
+
+    a {
+        color: length($a);
+        color: nth($b, -1);
+        color: nth($c, -1);
+        color: nth($c, 100);
+    }",
+    "a {\n  color: 100;\n  color: x;\n  color: y;\n  color: 100;\n}\n"
+);
 error!(
     #[ignore = ""]
     empty_list_is_invalid,
@@ -2,9 +2,10 @@ use std::{
     borrow::Cow,
     collections::BTreeMap,
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
-use grass::Fs;
+use grass::{Deprecation, Fs, Logger, SpanLoc, StackFrame};
 
 #[macro_export]
 macro_rules! test {
@@ -149,3 +150,78 @@ impl Fs for TestFs {
         Ok(self.files.get(path).unwrap().as_bytes().to_vec())
     }
 }
+
+/// Records every message passed to it so that tests can inspect them
+#[derive(Debug, Default)]
+pub struct TestLogger {
+    pub debugs: Mutex<Vec<(String, usize, usize)>>,
+    pub warnings: Mutex<Vec<(String, usize, usize)>>,
+    pub deprecations: Mutex<Vec<(Deprecation, usize, usize)>>,
+    pub warnings_without_span: Mutex<Vec<String>>,
+    /// The Sass stack trace of every event, in the order they were logged
+    pub traces: Mutex<Vec<Vec<(String, usize, usize)>>>,
+}
+
+#[allow(unused)]
+impl TestLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record_trace(&self, trace: &[StackFrame]) {
+        self.traces.lock().unwrap().push(
+            trace
+                .iter()
+                .map(|frame| {
+                    (
+                        frame.member.clone(),
+                        frame.loc.begin.line + 1,
+                        frame.loc.begin.column + 1,
+                    )
+                })
+                .collect(),
+        );
+    }
+}
+
+impl Logger for TestLogger {
+    fn debug(&self, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+        self.record_trace(trace);
+        self.debugs.lock().unwrap().push((
+            message.to_owned(),
+            location.begin.line + 1,
+            location.begin.column + 1,
+        ));
+    }
+
+    fn warn(&self, location: SpanLoc, message: &str, trace: &[StackFrame]) {
+        self.record_trace(trace);
+        self.warnings.lock().unwrap().push((
+            message.to_owned(),
+            location.begin.line + 1,
+            location.begin.column + 1,
+        ));
+    }
+
+    fn deprecation(
+        &self,
+        deprecation: Deprecation,
+        location: SpanLoc,
+        _message: &str,
+        trace: &[StackFrame],
+    ) {
+        self.record_trace(trace);
+        self.deprecations.lock().unwrap().push((
+            deprecation,
+            location.begin.line + 1,
+            location.begin.column + 1,
+        ));
+    }
+
+    fn warn_without_span(&self, message: &str) {
+        self.warnings_without_span
+            .lock()
+            .unwrap()
+            .push(message.to_owned());
+    }
+}
@@ -319,6 +319,105 @@ test!(
     );"#,
     ""
 );
+test!(
+    large_map_get_and_has_key,
+    "$map: ();
+    @for $i from 1 through 20 {
+        $map: map-merge($map, (key-#{$i}: $i));
+    }
+
+    a {
+        color: map-get($map, key-15);
+        color: map-has-key($map, key-21);
+        color: length($map);
+    }",
+    "a {\n  color: 15;\n  color: false;\n  color: 20;\n}\n"
+);
+test!(
+    large_map_merge_keeps_position_of_existing_key,
+    "$map: (a: 1, b: 2, c: 3, d: 4, e: 5, f: 6, g: 7, h: 8, i: 9, j: 10);
+    $map: map-merge($map, (c: x));
+
+    a {
+        color: map-keys($map);
+        color: map-get($map, c);
+    }",
+    "a {\n  color: a, b, c, d, e, f, g, h, i, j;\n  color: x;\n}\n"
+);
+test!(
+    large_map_remove_then_set,
+    "@use 'sass:map';
+    $map: ();
+    @for $i from 1 through 20 {
+        $map: map.set($map, key-#{$i}, $i);
+    }
+    @for $i from 1 through 15 {
+        $map: map.remove($map, key-#{$i});
+    }
+    $map: map.set($map, key-1, x);
+
+    a {
+        color: map.keys($map);
+        color: map.get($map, key-18);
+        color: map.get($map, key-1);
+        color: map.has-key($map, key-2);
+    }",
+    "a {\n  color: key-16, key-17, key-18, key-19, key-20, key-1;\n  color: 18;\n  color: x;\n  color: false;\n}\n"
+);
+test!(
+    large_map_keys_of_every_type,
+    "$map: (1in: a, red: b, \"c\": c, (1 2): d, null: e, true: f, (x: y): g, 3: h, 4: i, 5: j);
+
+    a {
+        color: map-get($map, 96px);
+        color: map-get($map, #ff0000);
+        color: map-get($map, c);
+        color: map-get($map, 1 2);
+        color: map-get($map, null);
+        color: map-get($map, true);
+        color: map-get($map, (x: y));
+        color: map-has-key($map, 1);
+        color: map-has-key($map, 3px);
+        color: map-has-key($map, (1, 2));
+    }",
+    "a {\n  color: a;\n  color: b;\n  color: c;\n  color: d;\n  color: e;\n  color: f;\n  color: g;\n  color: false;\n  color: false;\n  color: false;\n}\n"
+);
+test!(
+    large_map_lookup_by_converted_unit,
+    "@use 'sass:map';
+    $m: (1cm: hit, a: 1, b: 2, c: 3, d: 4, e: 5, f: 6, g: 7, h: 8);
+
+    a {
+        color: map.get($m, 37.7952755905572px);
+        color: map.has-key($m, 37.7952755905572px);
+        color: map.keys(map.remove($m, 37.7952755905572px));
+    }",
+    "a {\n  color: hit;\n  color: true;\n  color: a, b, c, d, e, f, g, h;\n}\n"
+);
+test!(
+    large_maps_are_equal_regardless_of_order,
+    "$a: (a: 1, b: 2, c: 3, d: 4, e: 5, f: 6, g: 7, h: 8, i: 9, j: 10);
+    $b: (j: 10, i: 9, h: 8, g: 7, f: 6, e: 5, d: 4, c: 3, b: 2, a: 1);
+
+    a {
+        color: $a == $b;
+        color: $a == map-merge($b, (e: x));
+    }",
+    "a {\n  color: true;\n  color: false;\n}\n"
+);
+test!(
+    merge_does_not_modify_original_large_map,
+    "$a: (a: 1, b: 2, c: 3, d: 4, e: 5, f: 6, g: 7, h: 8, i: 9, j: 10);
+    $b: map-merge($a, (a: x, k: 11));
+
+    a {
+        color: map-get($a, a);
+        color: map-has-key($a, k);
+        color: map-get($b, a);
+        color: length($b);
+    }",
+    "a {\n  color: 1;\n  color: false;\n  color: x;\n  color: 11;\n}\n"
+);
 error!(
     second_map_value_missing_colon,
     "a {\n  color: (a: b, c", "Error: expected \":\"."
@@ -0,0 +1,274 @@
+use std::path::Path;
+
+use grass::migrate::{migrate, MigrateOptions, MigratedFile};
+
+use macros::TestFs;
+
+#[macro_use]
+mod macros;
+
+fn migrate_with_fs(fs: &TestFs, migrate_deps: bool) -> Vec<MigratedFile> {
+    migrate(
+        Path::new("input.scss"),
+        &grass::Options::default().fs(fs),
+        &MigrateOptions::default().migrate_deps(migrate_deps),
+    )
+    .unwrap()
+}
+
+/// Asserts that migrating `files`, the first of which is the entrypoint,
+/// produces `expected`, and that the migrated stylesheets compile to the same
+/// CSS as the originals
+fn assert_migrates(
+    files: &[(&'static str, &'static str)],
+    migrate_deps: bool,
+    expected: &[(&str, &str)],
+) {
+    let mut fs = TestFs::new();
+    for &(name, contents) in files {
+        fs.add_file(name, contents);
+    }
+
+    let migrated = migrate_with_fs(&fs, migrate_deps);
+
+    assert_eq!(
+        expected,
+        migrated
+            .iter()
+            .map(|file| (file.path.to_str().unwrap(), file.migrated.as_str()))
+            .collect::<Vec<_>>()
+    );
+
+    let mut migrated_fs = TestFs::new();
+    for &(name, contents) in files {
+        let contents = migrated
+            .iter()
+            .find(|file| file.path == Path::new(name))
+            .map_or(contents, |file| {
+                Box::leak(file.migrated.clone().into_boxed_str())
+            });
+        migrated_fs.add_file(name, contents);
+    }
+
+    let options = grass::Options::default().quiet(true);
+    assert_eq!(
+        grass::from_path("input.scss", &options.clone().fs(&fs)).unwrap(),
+        grass::from_path("input.scss", &options.fs(&migrated_fs)).unwrap()
+    );
+}
+
+#[test]
+fn variable_function_and_mixin() {
+    assert_migrates(
+        &[
+            ("input.scss", "@import \"lib\";\na {\n  color: $color;\n  width: double(1px);\n  @include m;\n}\n"),
+            ("_lib.scss", "$color: red;\n@function double($n) { @return $n * 2; }\n@mixin m { b: c; }\n"),
+        ],
+        false,
+        &[("input.scss", "@use \"lib\";\na {\n  color: lib.$color;\n  width: lib.double(1px);\n  @include lib.m;\n}\n")],
+    );
+}
+
+#[test]
+fn configuration() {
+    assert_migrates(
+        &[
+            (
+                "input.scss",
+                "$color: blue;\n@import \"lib\";\na { color: $color; }\n",
+            ),
+            ("_lib.scss", "$color: red !default;\nb { color: $color; }\n"),
+        ],
+        false,
+        &[(
+            "input.scss",
+            "$color: blue;\n@use \"lib\" with ($color: $color);\na { color: $color; }\n",
+        )],
+    );
+}
+
+#[test]
+fn reassigned_variable() {
+    assert_migrates(
+        &[
+            (
+                "input.scss",
+                "@import \"lib\";\n$color: blue;\na { @include m; color: $color; }\n",
+            ),
+            ("_lib.scss", "$color: red;\n@mixin m { b: $color; }\n"),
+        ],
+        false,
+        &[(
+            "input.scss",
+            "@use \"lib\";\nlib.$color: blue;\na { @include lib.m; color: lib.$color; }\n",
+        )],
+    );
+}
+
+#[test]
+fn namespace_collision() {
+    assert_migrates(
+        &[
+            (
+                "input.scss",
+                "@import \"a/vars\", \"b/vars\";\nc { d: $a $b; }\n",
+            ),
+            ("a/_vars.scss", "$a: 1;\n"),
+            ("b/_vars.scss", "$b: 2;\n"),
+        ],
+        false,
+        &[(
+            "input.scss",
+            "@use \"a/vars\";\n@use \"b/vars\" as vars2;\nc { d: vars.$a vars2.$b; }\n",
+        )],
+    );
+}
+
+#[test]
+fn plain_css_import_is_kept() {
+    assert_migrates(
+        &[
+            (
+                "input.scss",
+                "@import \"lib\";\n@import \"foo.css\";\na { b: $c; }\n",
+            ),
+            ("_lib.scss", "$c: d;\n"),
+        ],
+        false,
+        &[(
+            "input.scss",
+            "@use \"lib\";\n@import \"foo.css\";\na { b: lib.$c; }\n",
+        )],
+    );
+}
+
+#[test]
+fn indented_syntax() {
+    assert_migrates(
+        &[
+            ("input.scss", "@import \"lib\";\na { b: $c; }\n"),
+            ("_lib.sass", "$c: d\n"),
+        ],
+        false,
+        &[("input.scss", "@use \"lib\";\na { b: lib.$c; }\n")],
+    );
+
+    let mut fs = TestFs::new();
+    fs.add_file("input.sass", "@import \"lib\"\na\n  b: $c\n");
+    fs.add_file("_lib.scss", "$c: d;");
+
+    let migrated = migrate(
+        Path::new("input.sass"),
+        &grass::Options::default().fs(&fs),
+        &MigrateOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!("@use \"lib\"\na\n  b: lib.$c\n", migrated[0].migrated);
+}
+
+#[test]
+fn transitive_member_without_migrating_deps() {
+    assert_migrates(
+        &[
+            ("input.scss", "@import \"theme\";\na { b: $c; }\n"),
+            ("_theme.scss", "@import \"colors\";\n"),
+            ("_colors.scss", "$c: d;\n"),
+        ],
+        false,
+        &[("input.scss", "@use \"theme\";\na { b: theme.$c; }\n")],
+    );
+}
+
+#[test]
+fn migrate_deps_forwards_transitive_members() {
+    assert_migrates(
+        &[
+            (
+                "input.scss",
+                "@import \"theme\";\na { b: $c; width: $width; }\n",
+            ),
+            (
+                "_theme.scss",
+                "@import \"colors\";\n$width: 1px;\nx { y: $c; }\n",
+            ),
+            ("_colors.scss", "$c: d;\n"),
+        ],
+        true,
+        &[
+            (
+                "input.scss",
+                "@use \"theme\";\na { b: theme.$c; width: theme.$width; }\n",
+            ),
+            (
+                "_theme.scss",
+                "@forward \"colors\";\n@use \"colors\";\n$width: 1px;\nx { y: colors.$c; }\n",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn unchanged_files_are_omitted() {
+    let mut fs = TestFs::new();
+    fs.add_file(
+        "input.scss",
+        "@use \"sass:math\";\na { b: math.div(1, 2); }\n",
+    );
+
+    assert!(migrate_with_fs(&fs, true).is_empty());
+}
+
+#[test]
+fn private_member_is_an_error() {
+    let mut fs = TestFs::new();
+    fs.add_file("input.scss", "@import \"lib\";\na { b: $-c; }\n");
+    fs.add_file("_lib.scss", "$-c: d;\n");
+
+    let err = migrate(
+        Path::new("input.scss"),
+        &grass::Options::default().fs(&fs),
+        &MigrateOptions::default(),
+    )
+    .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: This refers to the private variable -c from _lib.scss, which can't be used from another module."));
+}
+
+#[test]
+fn nested_import_is_an_error() {
+    let mut fs = TestFs::new();
+    fs.add_file("input.scss", "a { @import \"lib\"; }\n");
+    fs.add_file("_lib.scss", "b { c: d; }\n");
+
+    let err = migrate(
+        Path::new("input.scss"),
+        &grass::Options::default().fs(&fs),
+        &MigrateOptions::default(),
+    )
+    .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: Nested @import rules can't be migrated to @use."));
+}
+
+#[test]
+fn import_after_style_rule_is_an_error() {
+    let mut fs = TestFs::new();
+    fs.add_file("input.scss", "a { b: c; }\n@import \"lib\";\n");
+    fs.add_file("_lib.scss", "b { c: d; }\n");
+
+    let err = migrate(
+        Path::new("input.scss"),
+        &grass::Options::default().fs(&fs),
+        &MigrateOptions::default(),
+    )
+    .unwrap_err();
+
+    assert!(err.to_string().starts_with(
+        "Error: This @import can't be migrated to @use, because it follows rules which aren't allowed before @use."
+    ));
+}
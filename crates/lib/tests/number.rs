@@ -218,3 +218,74 @@ error!(
     scientific_notation_no_number_after_decimal,
     "a {\n  color: 1.e3;\n}\n", "Error: Expected digit."
 );
+test!(
+    precision_truncates_output,
+    "a {\n  color: (1/3);\n}\n",
+    "a {\n  color: 0.33333;\n}\n",
+    grass::Options::default().precision(5)
+);
+test!(
+    precision_rounds_output,
+    "a {\n  color: (2/3);\n}\n",
+    "a {\n  color: 0.667;\n}\n",
+    grass::Options::default().precision(3)
+);
+test!(
+    precision_zero,
+    "a {\n  color: 1.5px;\n}\n",
+    "a {\n  color: 2px;\n}\n",
+    grass::Options::default().precision(0)
+);
+test!(
+    precision_affects_equality,
+    "a {\n  color: 0.1234567 == 0.1234568;\n}\n",
+    "a {\n  color: true;\n}\n",
+    grass::Options::default().precision(5)
+);
+test!(
+    precision_affects_inspect,
+    "a {\n  color: inspect(1/3);\n}\n",
+    "a {\n  color: 0.333;\n}\n",
+    grass::Options::default().precision(3)
+);
+test!(
+    precision_affects_compressed_output,
+    "a {\n  color: (1/3);\n}\n",
+    "a{color:.33}",
+    grass::Options::default()
+        .precision(2)
+        .style(grass::OutputStyle::Compressed)
+);
+test!(
+    compressed_rounding_carries_to_one,
+    "a {\n  b: 0.999;\n  c: -0.996;\n}\n",
+    "a{b:1;c:-1}",
+    grass::Options::default()
+        .precision(2)
+        .style(grass::OutputStyle::Compressed)
+);
+test!(
+    compressed_many_nines_becomes_one,
+    "a {\n  b: 0.9999999999999999;\n  c: -0.9999999999999999;\n}\n",
+    "a{b:1;c:-1}",
+    grass::Options::default().style(grass::OutputStyle::Compressed)
+);
+test!(
+    compressed_precision_zero_rounds_up,
+    "a {\n  b: 0.7;\n}\n",
+    "a{b:1}",
+    grass::Options::default()
+        .precision(0)
+        .style(grass::OutputStyle::Compressed)
+);
+test!(
+    precision_zero_keeps_trailing_zeros_of_integers,
+    "a {\n  b: 10;\n  c: 100px;\n}\n",
+    "a {\n  b: 10;\n  c: 100px;\n}\n",
+    grass::Options::default().precision(0)
+);
+test!(
+    default_precision_is_ten,
+    "a {\n  color: (1/3);\n}\n",
+    "a {\n  color: 0.3333333333;\n}\n"
+);
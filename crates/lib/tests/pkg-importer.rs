@@ -0,0 +1,211 @@
+use std::{fs, path::Path};
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn compile(root: &Path, input: &str) -> Result<String, Box<grass::Error>> {
+    grass::from_string(
+        input.to_owned(),
+        &grass::Options::default()
+            .node_package_importer(root)
+            .style(grass::OutputStyle::Compressed),
+    )
+}
+
+#[test]
+fn exports_string() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/lib");
+
+    write(
+        &pkg.join("package.json"),
+        r#"{"exports": "./dist/lib.scss"}"#,
+    );
+    write(&pkg.join("dist/lib.scss"), "a { color: red; }");
+
+    assert_eq!(
+        "a{color:red}",
+        compile(root.path(), "@use 'pkg:lib';").unwrap()
+    );
+}
+
+#[test]
+fn exports_conditions_prefer_sass() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/lib");
+
+    write(
+        &pkg.join("package.json"),
+        r#"{"exports": {".": {"import": "./index.js", "sass": "./lib.scss", "default": "./other.scss"}}}"#,
+    );
+    write(&pkg.join("lib.scss"), "a { color: red; }");
+    write(&pkg.join("other.scss"), "a { color: blue; }");
+
+    assert_eq!(
+        "a{color:red}",
+        compile(root.path(), "@use 'pkg:lib';").unwrap()
+    );
+}
+
+#[test]
+fn exports_subpath_pattern() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/lib");
+
+    write(
+        &pkg.join("package.json"),
+        r#"{"exports": {"./themes/*.scss": {"sass": "./src/themes/*.scss"}}}"#,
+    );
+    write(&pkg.join("src/themes/dark.scss"), "a { color: red; }");
+
+    assert_eq!(
+        "a{color:red}",
+        compile(root.path(), "@use 'pkg:lib/themes/dark';").unwrap()
+    );
+}
+
+#[test]
+fn sass_field() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/lib");
+
+    write(&pkg.join("package.json"), r#"{"sass": "scss/main.scss"}"#);
+    write(&pkg.join("scss/main.scss"), "a { color: red; }");
+
+    assert_eq!(
+        "a{color:red}",
+        compile(root.path(), "@use 'pkg:lib';").unwrap()
+    );
+}
+
+#[test]
+fn index_fallback_without_manifest() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/lib");
+
+    write(&pkg.join("_index.scss"), "a { color: red; }");
+
+    assert_eq!(
+        "a{color:red}",
+        compile(root.path(), "@use 'pkg:lib';").unwrap()
+    );
+}
+
+#[test]
+fn subpath_without_exports_resolves_partial() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/lib");
+
+    write(&pkg.join("package.json"), "{}");
+    write(&pkg.join("src/_colors.scss"), "$red: red;");
+
+    assert_eq!(
+        "a{color:red}",
+        compile(
+            root.path(),
+            "@use 'pkg:lib/src/colors';\na { color: colors.$red; }"
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn scoped_package() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/@scope/lib");
+
+    write(&pkg.join("package.json"), r#"{"style": "lib.css"}"#);
+    write(&pkg.join("lib.css"), "a { color: red; }");
+
+    assert_eq!(
+        "a{color:red}",
+        compile(root.path(), "@use 'pkg:@scope/lib';").unwrap()
+    );
+}
+
+#[test]
+fn import_rule() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/lib");
+
+    write(&pkg.join("_index.scss"), "a { color: red; }");
+
+    assert_eq!(
+        "a{color:red}",
+        compile(root.path(), "@import 'pkg:lib';").unwrap()
+    );
+}
+
+#[test]
+fn missing_package_is_error() {
+    let root = tempfile::tempdir().unwrap();
+
+    let err = compile(root.path(), "@use 'pkg:missing';").unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: Can't find stylesheet to import."));
+}
+
+#[test]
+fn absolute_url_is_error() {
+    let root = tempfile::tempdir().unwrap();
+
+    let err = compile(root.path(), "@use 'pkg:/lib';").unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: pkg: URL \"pkg:/lib\" must not be an absolute path."));
+}
+
+#[test]
+fn invalid_scoped_name_is_error() {
+    let root = tempfile::tempdir().unwrap();
+
+    let err = compile(root.path(), "@use 'pkg:@scope' as s;").unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: pkg: URL \"pkg:@scope\" has an invalid scoped package name."));
+}
+
+#[test]
+fn export_to_non_sass_file_is_error() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/lib");
+
+    write(&pkg.join("package.json"), r#"{"exports": "./index.js"}"#);
+    write(&pkg.join("index.js"), "");
+
+    let err = compile(root.path(), "@use 'pkg:lib';").unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("which is not a '.scss', '.sass', or '.css' file."));
+}
+
+#[test]
+fn pkg_url_is_plain_import_when_disabled() {
+    let err =
+        grass::from_string("@use 'pkg:lib';".to_owned(), &grass::Options::default()).unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: Can't find stylesheet to import."));
+}
+
+#[test]
+fn deeply_nested_manifest_is_an_error() {
+    let root = tempfile::tempdir().unwrap();
+    let pkg = root.path().join("node_modules/deep");
+
+    write(
+        &pkg.join("package.json"),
+        &format!("{{\"x\":{}{}}}", "[".repeat(200_000), "]".repeat(200_000)),
+    );
+
+    let err = compile(root.path(), "@use 'pkg:deep';").unwrap_err();
+    assert!(err.to_string().contains("nested too deeply"), "{}", err);
+}
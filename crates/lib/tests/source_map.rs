@@ -0,0 +1,197 @@
+use macros::TestFs;
+
+#[macro_use]
+mod macros;
+
+/// A decoded mapping: the generated line and column, and the source index,
+/// line, and column
+type Mapping = (usize, i64, i64, i64, i64);
+
+fn decode_mappings(mappings: &str) -> Vec<Mapping> {
+    const BASE64: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut result = Vec::new();
+    let mut fields = [0_i64; 4];
+
+    for (line, segments) in mappings.split(';').enumerate() {
+        fields[0] = 0;
+
+        for segment in segments.split(',').filter(|segment| !segment.is_empty()) {
+            let mut values = Vec::new();
+            let mut value = 0;
+            let mut shift = 0;
+
+            for c in segment.chars() {
+                let digit = BASE64.find(c).unwrap() as i64;
+                value |= (digit & 0b11111) << shift;
+                shift += 5;
+
+                if digit & 0b100000 == 0 {
+                    values.push(if value & 1 == 1 {
+                        -(value >> 1)
+                    } else {
+                        value >> 1
+                    });
+                    value = 0;
+                    shift = 0;
+                }
+            }
+
+            for (field, delta) in fields.iter_mut().zip(values) {
+                *field += delta;
+            }
+
+            result.push((line, fields[0], fields[1], fields[2], fields[3]));
+        }
+    }
+
+    result
+}
+
+fn compile(input: &str, options: &grass::Options) -> (String, grass::SourceMap) {
+    grass::from_string_with_source_map(input.to_owned(), options).unwrap()
+}
+
+#[test]
+fn maps_selectors_and_declarations() {
+    let (css, source_map) = compile("a {\n  color: red;\n}\n", &grass::Options::default());
+
+    assert_eq!("a {\n  color: red;\n}\n", css);
+    assert_eq!(["stdin"], source_map.sources());
+    assert_eq!("AAAA;EACE", source_map.mappings());
+}
+
+#[test]
+fn maps_nested_rules_to_their_selector() {
+    let (css, source_map) = compile(
+        "a {\n  b {\n    color: red;\n  }\n  c: d;\n}\n",
+        &grass::Options::default(),
+    );
+
+    assert_eq!("a {\n  c: d;\n}\na b {\n  color: red;\n}\n", css);
+    assert_eq!(
+        vec![
+            (0, 0, 0, 0, 0),
+            (1, 2, 0, 4, 2),
+            (3, 0, 0, 1, 2),
+            (4, 2, 0, 2, 4),
+        ],
+        decode_mappings(source_map.mappings())
+    );
+}
+
+#[test]
+fn maps_declarations_from_mixins_to_the_mixin() {
+    let (_, source_map) = compile(
+        "@mixin foo {\n  color: red;\n}\n\na {\n  @include foo;\n}\n",
+        &grass::Options::default(),
+    );
+
+    assert_eq!(
+        vec![(0, 0, 0, 4, 0), (1, 2, 0, 1, 2)],
+        decode_mappings(source_map.mappings())
+    );
+}
+
+#[test]
+fn maps_compressed_output_on_one_line() {
+    let (css, source_map) = compile(
+        "a {\n  color: red;\n  b: c;\n}\nd {\n  e: f;\n}\n",
+        &grass::Options::default().style(grass::OutputStyle::Compressed),
+    );
+
+    assert_eq!("a{color:red;b:c}d{e:f}", css);
+    assert_eq!(
+        vec![
+            (0, 0, 0, 0, 0),
+            (0, 2, 0, 1, 2),
+            (0, 12, 0, 2, 2),
+            (0, 16, 0, 4, 0),
+            (0, 18, 0, 5, 2),
+        ],
+        decode_mappings(source_map.mappings())
+    );
+}
+
+#[test]
+fn charset_shifts_mappings() {
+    let (css, source_map) = compile("a {\n  content: \"é\";\n}\n", &grass::Options::default());
+
+    assert!(css.starts_with("@charset \"UTF-8\";\n"));
+    assert_eq!(
+        vec![(1, 0, 0, 0, 0), (2, 2, 0, 1, 2)],
+        decode_mappings(source_map.mappings())
+    );
+}
+
+#[test]
+fn columns_are_utf16_code_units() {
+    let (_, source_map) = compile(
+        "a {\n  b: \"😀\"; c: d;\n}\n",
+        &grass::Options::default().style(grass::OutputStyle::Compressed),
+    );
+
+    // the BOM and emoji are 1 and 2 code units, in both the CSS and the Sass
+    assert_eq!(
+        vec![(0, 1, 0, 0, 0), (0, 3, 0, 1, 2), (0, 10, 0, 1, 11)],
+        decode_mappings(source_map.mappings())
+    );
+}
+
+#[test]
+fn includes_loaded_files_as_sources() {
+    let mut fs = TestFs::new();
+    fs.add_file("_library.scss", "a {\n  color: red;\n}\n");
+
+    let (_, source_map) = compile(
+        "@use 'library';\nb {\n  c: d;\n}\n",
+        &grass::Options::default().fs(&fs),
+    );
+
+    assert_eq!(2, source_map.sources().len());
+    assert!(source_map.sources()[0].ends_with("_library.scss"));
+    assert_eq!("stdin", source_map.sources()[1]);
+    assert_eq!("a {\n  color: red;\n}\n", source_map.sources_content()[0]);
+    assert_eq!(
+        vec![
+            (0, 0, 0, 0, 0),
+            (1, 2, 0, 1, 2),
+            (4, 0, 1, 1, 0),
+            (5, 2, 1, 2, 2),
+        ],
+        decode_mappings(source_map.mappings())
+    );
+}
+
+#[test]
+fn to_json() {
+    let (_, source_map) = compile("a {\n  b: c;\n}\n", &grass::Options::default());
+
+    assert_eq!(
+        r#"{"version":3,"sources":["stdin"],"names":[],"mappings":"AAAA;EACE"}"#,
+        source_map.to_json(None, false)
+    );
+    assert_eq!(
+        r#"{"version":3,"file":"out.css","sources":["stdin"],"sourcesContent":["a {\n  b: c;\n}\n"],"names":[],"mappings":"AAAA;EACE"}"#,
+        source_map.to_json(Some("out.css"), true)
+    );
+}
+
+#[test]
+fn empty_output_has_no_mappings() {
+    let (css, source_map) = compile("$a: b;", &grass::Options::default());
+
+    assert_eq!("", css);
+    assert!(source_map.sources().is_empty());
+    assert_eq!("", source_map.mappings());
+}
+
+#[test]
+fn output_is_unchanged_by_source_maps() {
+    let input = "@media screen {\n  a {\n    b: c;\n  }\n}\n/* d */\ne {\n  f: g;\n}\n";
+
+    assert_eq!(
+        grass::from_string(input.to_owned(), &grass::Options::default()).unwrap(),
+        compile(input, &grass::Options::default()).0
+    );
+}
@@ -0,0 +1,127 @@
+use std::{sync::Arc, thread};
+
+use macros::{TestFs, TestLogger};
+
+#[macro_use]
+mod macros;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn public_types_are_send_and_sync() {
+    assert_send_sync::<grass::Options<'static>>();
+    assert_send_sync::<grass::Error>();
+    assert_send_sync::<Box<grass::Error>>();
+    assert_send_sync::<&dyn grass::Fs>();
+    assert_send_sync::<&dyn grass::Logger>();
+    assert_send_sync::<&dyn grass::Importer>();
+}
+
+#[test]
+fn compile_many_files_in_parallel() {
+    let mut fs = TestFs::new();
+    fs.add_file(
+        "_colors.scss",
+        "$primary: red;\n@function shade($c) { @return darken($c, 10%); }",
+    );
+
+    let logger = TestLogger::new();
+    let options = grass::Options::default().fs(&fs).logger(&logger);
+
+    let outputs = thread::scope(|scope| {
+        let handles = (0..32)
+            .map(|i| {
+                let options = &options;
+                scope.spawn(move || {
+                    grass::from_string(
+                        format!(
+                            "@use 'colors';\n.a-{i} {{ b: colors.shade(colors.$primary); c: {i}px * 2; d: {i}foo; }}"
+                        ),
+                        options,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    for (i, output) in outputs.iter().enumerate() {
+        assert_eq!(
+            &format!(
+                ".a-{i} {{\n  b: #cc0000;\n  c: {}px;\n  d: {i}foo;\n}}\n",
+                i * 2
+            ),
+            output
+        );
+    }
+}
+
+#[test]
+fn errors_can_be_sent_between_threads() {
+    let err = thread::spawn(|| {
+        grass::from_string("a { b: 1px + 1s; }".to_owned(), &grass::Options::default()).unwrap_err()
+    })
+    .join()
+    .unwrap();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: Incompatible units s and px."));
+}
+
+#[test]
+fn options_can_be_moved_to_another_thread() {
+    let fs: &'static TestFs = Box::leak(Box::new({
+        let mut fs = TestFs::new();
+        fs.add_file("_a.scss", "a { b: c; }");
+        fs
+    }));
+
+    let options = grass::Options::default().fs(fs);
+
+    let css = thread::spawn(move || grass::from_string("@use 'a';".to_owned(), &options))
+        .join()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!("a {\n  b: c;\n}\n", css);
+}
+
+#[test]
+fn unknown_units_are_shared_between_threads() {
+    let options = Arc::new(grass::Options::default());
+
+    let handles = (0..8)
+        .map(|i| {
+            let options = Arc::clone(&options);
+            thread::spawn(move || {
+                grass::from_string(format!("a {{ b: 1unit{i} * 2; }}"), &options).unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for (i, handle) in handles.into_iter().enumerate() {
+        assert_eq!(
+            format!("a {{\n  b: 2unit{i};\n}}\n"),
+            handle.join().unwrap()
+        );
+    }
+}
+
+#[test]
+fn warnings_from_parallel_compilations_are_all_logged() {
+    let logger = TestLogger::new();
+    let options = grass::Options::default().logger(&logger);
+
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| grass::from_string("@warn 'a';".to_owned(), &options).unwrap());
+        }
+    });
+
+    assert_eq!(8, logger.warnings.lock().unwrap().len());
+}
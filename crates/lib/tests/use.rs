@@ -640,4 +640,34 @@ fn module_functions_through_forward() {
     );
 }
 
+#[test]
+fn error_in_used_module_has_use_stack_frame() {
+    let mut fs = TestFs::new();
+
+    fs.add_file("_a.scss", "a {\n  color: 1 + red;\n}");
+
+    let input = "@use \"a\";";
+
+    let err =
+        grass::from_string(input.to_string(), &grass::Options::default().fs(&fs)).expect_err(input);
+
+    match err.kind() {
+        grass::ErrorKind::ParseError { stack, .. } => assert_eq!(
+            vec![
+                ("@use", "_a.scss".to_owned(), 1),
+                ("root stylesheet", "stdin".to_owned(), 0)
+            ],
+            stack
+                .iter()
+                .map(|frame| (
+                    frame.member.as_str(),
+                    frame.loc.file.name().to_owned(),
+                    frame.loc.begin.line
+                ))
+                .collect::<Vec<_>>()
+        ),
+        e => panic!("unexpected error kind: {:?}", e),
+    }
+}
+
 // todo: refactor these tests to use testfs where possible
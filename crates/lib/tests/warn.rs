@@ -1,4 +1,62 @@
+use macros::TestLogger;
+
 #[macro_use]
 mod macros;
 
 test!(simple_warn, "@warn 2", "");
+
+#[test]
+fn warn_is_passed_to_logger() {
+    let logger = TestLogger::new();
+
+    let input = "a {\n  @warn foo;\n}";
+
+    grass::from_string(
+        input.to_string(),
+        &grass::Options::default().logger(&logger),
+    )
+    .expect(input);
+
+    assert_eq!(
+        &[("foo".to_owned(), 2, 9)],
+        logger.warnings.lock().unwrap().as_slice()
+    );
+    assert!(logger.debugs.lock().unwrap().is_empty());
+}
+
+#[test]
+fn warn_is_not_passed_to_logger_when_quiet() {
+    let logger = TestLogger::new();
+
+    let input = "@warn \"foo\";";
+
+    grass::from_string(
+        input.to_string(),
+        &grass::Options::default().logger(&logger).quiet(true),
+    )
+    .expect(input);
+
+    assert!(logger.warnings.lock().unwrap().is_empty());
+}
+
+#[test]
+fn warn_inside_mixin_and_function_has_stack_trace() {
+    let logger = TestLogger::new();
+
+    let input = "@function foo() {\n  @warn foo;\n  @return 1;\n}\n@mixin bar {\n  a: foo();\n}\na {\n  @include bar;\n}";
+
+    grass::from_string(
+        input.to_string(),
+        &grass::Options::default().logger(&logger),
+    )
+    .expect(input);
+
+    assert_eq!(
+        &[vec![
+            ("foo()".to_owned(), 2, 9),
+            ("bar()".to_owned(), 6, 6),
+            ("root stylesheet".to_owned(), 9, 12),
+        ]],
+        logger.traces.lock().unwrap().as_slice()
+    );
+}
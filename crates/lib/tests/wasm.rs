@@ -0,0 +1,257 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+
+use grass::wasm::{compile_path, compile_string};
+use js_sys::{Array, Function, Object, Reflect, JSON};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn options(json: &str) -> JsValue {
+    JSON::parse(json).unwrap()
+}
+
+fn get(object: &JsValue, key: &str) -> JsValue {
+    Reflect::get(object, &JsValue::from_str(key)).unwrap()
+}
+
+fn set(object: &JsValue, key: &str, value: &JsValue) {
+    Reflect::set(object, &JsValue::from_str(key), value).unwrap();
+}
+
+fn css(result: Result<JsValue, JsValue>) -> String {
+    get(&result.unwrap(), "css").as_string().unwrap()
+}
+
+#[wasm_bindgen_test]
+fn compile_string_without_options() {
+    assert_eq!(
+        "a b {\n  color: red;\n}\n",
+        css(compile_string(
+            "a { b { color: red; } }".to_owned(),
+            JsValue::UNDEFINED
+        ))
+    );
+}
+
+#[wasm_bindgen_test]
+fn compile_string_compressed() {
+    assert_eq!(
+        "a b{color:red}",
+        css(compile_string(
+            "a { b { color: red; } }".to_owned(),
+            options(r#"{"style":"compressed"}"#)
+        ))
+    );
+}
+
+#[wasm_bindgen_test]
+fn compile_string_indented_syntax() {
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        css(compile_string(
+            "a\n  color: red".to_owned(),
+            options(r#"{"syntax":"indented"}"#)
+        ))
+    );
+}
+
+#[wasm_bindgen_test]
+fn unknown_style_is_error() {
+    assert!(compile_string("".to_owned(), options(r#"{"style":"nested"}"#)).is_err());
+}
+
+#[wasm_bindgen_test]
+fn loads_from_files_and_load_paths() {
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        css(compile_string(
+            "@use 'theme';\na { color: theme.$primary; }".to_owned(),
+            options(r#"{"files":{"vendor/_theme.scss":"$primary: red;"},"loadPaths":["vendor"]}"#)
+        ))
+    );
+}
+
+#[wasm_bindgen_test]
+fn loads_relative_to_url() {
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        css(compile_string(
+            "@use 'theme';\na { color: theme.$primary; }".to_owned(),
+            options(r#"{"url":"src/input.scss","files":{"src/_theme.scss":"$primary: red;"}}"#)
+        ))
+    );
+}
+
+#[wasm_bindgen_test]
+fn files_can_be_a_map() {
+    let files = js_sys::Map::new();
+    files.set(&"_theme.scss".into(), &"$primary: red;".into());
+
+    let options = Object::new();
+    set(&options, "files", &files);
+
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        css(compile_string(
+            "@use 'theme';\na { color: theme.$primary; }".to_owned(),
+            options.into()
+        ))
+    );
+}
+
+#[wasm_bindgen_test]
+fn compile_path_from_files() {
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        css(compile_path(
+            "src/input.sass".to_owned(),
+            options(r#"{"files":{"src/input.sass":"a\n  color: red"}}"#)
+        ))
+    );
+}
+
+#[wasm_bindgen_test]
+fn compile_missing_path_is_error() {
+    assert!(compile_path("input.scss".to_owned(), JsValue::UNDEFINED).is_err());
+}
+
+#[wasm_bindgen_test]
+fn importer() {
+    let importer = Object::new();
+    set(
+        &importer,
+        "canonicalize",
+        &Function::new_with_args(
+            "url, context",
+            "return url.startsWith('virtual/') ? 'virtual:' + url.slice(8) : null;",
+        ),
+    );
+    set(
+        &importer,
+        "load",
+        &Function::new_with_args(
+            "url",
+            "if (url === 'virtual:error') throw new Error('virtual error');
+             return { contents: 'a\\n  color: red', syntax: 'indented' };",
+        ),
+    );
+
+    let options = Object::new();
+    set(&options, "importers", &Array::of1(&importer));
+
+    assert_eq!(
+        "a {\n  color: red;\n}\n",
+        css(compile_string(
+            "@use 'virtual/colors';".to_owned(),
+            options.clone().into()
+        ))
+    );
+
+    let err = compile_string("@use 'virtual/error';".to_owned(), options.into()).unwrap_err();
+    assert_eq!(
+        Some("virtual error".to_owned()),
+        get(&err, "sassMessage").as_string()
+    );
+}
+
+#[wasm_bindgen_test]
+fn logger() {
+    let messages = Array::new();
+
+    let logger = Object::new();
+    set(
+        &logger,
+        "warn",
+        &Function::new_with_args(
+            "message, options",
+            "this.messages.push(['warn', message, options]);",
+        ),
+    );
+    set(
+        &logger,
+        "debug",
+        &Function::new_with_args(
+            "message, options",
+            "this.messages.push(['debug', message, options]);",
+        ),
+    );
+    set(&logger, "messages", &messages);
+
+    let options = Object::new();
+    set(&options, "logger", &logger);
+
+    css(compile_string(
+        "@warn 'a';\n@debug 'b';\nc { $d: e !global; }".to_owned(),
+        options.into(),
+    ));
+
+    assert_eq!(3, messages.length());
+
+    let warn = Array::from(&messages.get(0));
+    assert_eq!(Some("warn".to_owned()), warn.get(0).as_string());
+    assert_eq!(Some(1.0), get(&warn.get(2), "line").as_f64());
+    assert_eq!(Some(false), get(&warn.get(2), "deprecation").as_bool());
+
+    let debug = Array::from(&messages.get(1));
+    assert_eq!(Some("debug".to_owned()), debug.get(0).as_string());
+    assert_eq!(Some(2.0), get(&debug.get(2), "line").as_f64());
+
+    let deprecation = Array::from(&messages.get(2));
+    assert_eq!(
+        Some(true),
+        get(&deprecation.get(2), "deprecation").as_bool()
+    );
+    assert_eq!(
+        Some("new-global".to_owned()),
+        get(&deprecation.get(2), "deprecationType").as_string()
+    );
+}
+
+#[wasm_bindgen_test]
+fn error_is_structured() {
+    let err = compile_string(
+        "a {\n  b: $c;\n}".to_owned(),
+        options(r#"{"url":"input.scss"}"#),
+    )
+    .unwrap_err();
+
+    assert!(err.is_instance_of::<js_sys::Error>());
+    assert_eq!(
+        Some("Undefined variable.".to_owned()),
+        get(&err, "sassMessage").as_string()
+    );
+    assert_eq!(Some("input.scss".to_owned()), get(&err, "file").as_string());
+    assert_eq!(Some(2.0), get(&err, "line").as_f64());
+    assert_eq!(Some(6.0), get(&err, "column").as_f64());
+
+    let message = String::from(err.unchecked_into::<js_sys::Error>().message());
+    assert!(message.starts_with("Error: Undefined variable."));
+}
+
+#[wasm_bindgen_test]
+fn source_map() {
+    let result = compile_string(
+        "a {\n  b: c;\n}\n".to_owned(),
+        options(r#"{"sourceMap":true,"sourceMapIncludeSources":true}"#),
+    )
+    .unwrap();
+
+    let source_map = get(&result, "sourceMap");
+    assert_eq!(Some(3.0), get(&source_map, "version").as_f64());
+    assert_eq!(
+        Some("AAAA;EACE".to_owned()),
+        get(&source_map, "mappings").as_string()
+    );
+    assert_eq!(
+        Some("a {\n  b: c;\n}\n".to_owned()),
+        Array::from(&get(&source_map, "sourcesContent"))
+            .get(0)
+            .as_string()
+    );
+}
+
+#[wasm_bindgen_test]
+fn no_source_map_by_default() {
+    let result = compile_string("a { b: c; }".to_owned(), JsValue::UNDEFINED).unwrap();
+
+    assert!(get(&result, "sourceMap").is_undefined());
+}
@@ -0,0 +1,230 @@
+use std::io::{self, Write};
+
+/// Compiles `input` with both [`grass::compile_to_writer`] and
+/// [`grass::from_string`], asserting that they produce the same CSS
+fn assert_same_output(input: &str, options: &grass::Options) -> String {
+    let expected = grass::from_string(input.to_owned(), options).unwrap();
+
+    let mut output = Vec::new();
+    grass::compile_to_writer(input.to_owned(), options, &mut output).unwrap();
+
+    assert_eq!(expected, String::from_utf8(output).unwrap());
+    expected
+}
+
+/// Records each call to `write`, to check that output is streamed
+#[derive(Default)]
+struct RecordingWriter {
+    writes: Vec<Vec<u8>>,
+}
+
+impl Write for RecordingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes.push(buf.to_vec());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn same_output_as_from_string() {
+    let inputs = [
+        "",
+        "a { b: c; }",
+        "a { b: c; }\nd { e: f; }",
+        "@import 'a.css';\n@import url(b);\na { b: c; }",
+        "/* comment */\na { b: c; }\n/*! loud */",
+        "@media screen { a { b: c; } }\nd { e: f; }",
+        "@supports (a: b) { c { d: e; } }",
+        "@keyframes k { from { a: b; } to { a: c; } }",
+        "@font-face { a: b; }\n@unknown;\n@unknown foo {}",
+        "%placeholder { a: b; }\nc { @extend %placeholder; }",
+        "a { b { c: d; } e: f; }",
+    ];
+
+    for style in [grass::OutputStyle::Expanded, grass::OutputStyle::Compressed] {
+        let options = grass::Options::default().style(style);
+
+        for input in inputs {
+            assert_same_output(input, &options);
+        }
+    }
+}
+
+#[test]
+fn charset_when_later_rule_is_not_ascii() {
+    assert_eq!(
+        "@charset \"UTF-8\";\na {\n  b: c;\n}\n\nd {\n  e: \"👭\";\n}\n",
+        assert_same_output("a { b: c; }\nd { e: \"👭\"; }", &grass::Options::default())
+    );
+}
+
+#[test]
+fn byte_order_mark_when_compressed() {
+    assert_eq!(
+        "\u{FEFF}a{b:c}d{e:\"👭\"}",
+        assert_same_output(
+            "a { b: c; }\nd { e: \"👭\"; }",
+            &grass::Options::default().style(grass::OutputStyle::Compressed)
+        )
+    );
+}
+
+#[test]
+fn no_charset_for_escaped_ascii() {
+    assert_eq!(
+        "a {\n  b: \"c\";\n}\n",
+        assert_same_output("a { b: \"\\63\"; }", &grass::Options::default())
+    );
+}
+
+#[test]
+fn charset_for_non_ascii_from_escape() {
+    assert_eq!(
+        "@charset \"UTF-8\";\na {\n  b: \"☃\";\n}\n",
+        assert_same_output("a { b: \"\\2603\"; }", &grass::Options::default())
+    );
+}
+
+#[test]
+fn output_is_written_per_rule() {
+    let mut writer = RecordingWriter::default();
+
+    grass::compile_to_writer(
+        "a { b: c; }\nd { e: f; }\ng { h: i; }".to_owned(),
+        &grass::Options::default(),
+        &mut writer,
+    )
+    .unwrap();
+
+    let writes = writer
+        .writes
+        .iter()
+        .filter(|write| !write.is_empty())
+        .count();
+
+    assert!(writes >= 3, "{:?}", writer.writes);
+    assert_eq!(
+        b"a {\n  b: c;\n}\n\nd {\n  e: f;\n}\n\ng {\n  h: i;\n}\n".to_vec(),
+        writer.writes.concat()
+    );
+}
+
+#[test]
+fn nothing_written_on_evaluation_error() {
+    let mut output = Vec::new();
+
+    let err = grass::compile_to_writer(
+        "a { b: c; }\nd { e: 1px + 1s; }".to_owned(),
+        &grass::Options::default(),
+        &mut output,
+    )
+    .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: Incompatible units s and px."));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn earlier_rules_written_on_serialization_error() {
+    let mut output = Vec::new();
+
+    let err = grass::compile_to_writer(
+        "@function f($a...) { @return $a; }\na { b: c; }\nd { e: f(); }".to_owned(),
+        &grass::Options::default(),
+        &mut output,
+    )
+    .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: () isn't a valid CSS value."));
+    assert_eq!("a {\n  b: c;\n}", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn nothing_written_on_serialization_error_before_charset_is_known() {
+    let mut output = Vec::new();
+
+    let err = grass::compile_to_writer(
+        "@function f($a...) { @return $a; }\na { b: \"\\63\"; }\nd { e: f(); }".to_owned(),
+        &grass::Options::default(),
+        &mut output,
+    )
+    .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: () isn't a valid CSS value."));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn output_is_written_per_rule_after_non_ascii() {
+    let mut writer = RecordingWriter::default();
+
+    grass::compile_to_writer(
+        "a { b: \"👭\"; }\nd { e: f; }\ng { h: i; }".to_owned(),
+        &grass::Options::default(),
+        &mut writer,
+    )
+    .unwrap();
+
+    let writes = writer
+        .writes
+        .iter()
+        .filter(|write| !write.is_empty())
+        .count();
+
+    assert!(writes >= 3, "{:?}", writer.writes);
+    assert_eq!(
+        "@charset \"UTF-8\";\na {\n  b: \"👭\";\n}\n\nd {\n  e: f;\n}\n\ng {\n  h: i;\n}\n",
+        String::from_utf8(writer.writes.concat()).unwrap()
+    );
+}
+
+#[test]
+fn max_output_size_is_enforced() {
+    let mut output = Vec::new();
+
+    let err = grass::compile_to_writer(
+        "@for $i from 1 through 100 { a { b: $i; } }".to_owned(),
+        &grass::Options::default().max_output_size(100),
+        &mut output,
+    )
+    .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error: Output exceeded the maximum size of 100 bytes."));
+    assert!(output.len() <= 100);
+}
+
+#[test]
+fn io_errors_are_returned() {
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let err = grass::compile_to_writer(
+        "a { b: c; }".to_owned(),
+        &grass::Options::default(),
+        &mut FailingWriter,
+    )
+    .unwrap_err();
+
+    assert_eq!("Error: disk full\n", err.to_string());
+}
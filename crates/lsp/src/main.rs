@@ -0,0 +1,113 @@
+//! A language server for Sass and SCSS, speaking the language server protocol
+//! over stdin and stdout
+//!
+//! Stylesheets are analyzed with [`grass_compiler::analysis`] whenever they are
+//! opened or changed, which provides:
+//!
+//! - diagnostics for parse and evaluation errors, as well as warnings
+//! - go to definition for variables, mixins, functions and `@use` namespaces
+//! - hover showing the value assigned to a variable
+//! - completion of module members after `namespace.`
+//!
+//! Load paths may be passed as `initializationOptions.loadPaths`, and are also
+//! read from the `SASS_PATH` environment variable.
+
+#![forbid(unsafe_code)]
+
+use std::{
+    io::{self, BufRead, Write},
+    process,
+};
+
+use serde_json::Value;
+use server::Server;
+
+mod server;
+
+fn main() {
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    let mut server = Server::new();
+
+    loop {
+        let body = match read_message(&mut stdin) {
+            Ok(Some(body)) => body,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("grass-lsp: failed to read message: {}", e);
+                process::exit(1);
+            }
+        };
+
+        let outgoing = match serde_json::from_str(&body) {
+            Ok(message) => server.handle(&message),
+            Err(e) => vec![server::error_response(Value::Null, -32700, e.to_string())],
+        };
+
+        for message in outgoing {
+            if let Err(e) = write_message(&mut stdout, &message) {
+                eprintln!("grass-lsp: failed to write message: {}", e);
+                process::exit(1);
+            }
+        }
+
+        if server.has_exited() {
+            break;
+        }
+    }
+
+    // the protocol asks for a non-zero exit code if the client exits without
+    // first requesting a shutdown
+    process::exit(if server.is_shut_down() { 0 } else { 1 });
+}
+
+/// Read the body of a single message, framed by a `Content-Length` header
+///
+/// Returns `None` once the client closes stdin
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            match content_length {
+                Some(..) => break,
+                None => continue,
+            }
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+                );
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
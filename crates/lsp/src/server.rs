@@ -0,0 +1,732 @@
+//! Handlers for the requests and notifications sent by the client
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use grass_compiler::{
+    analysis::{analyze, Analysis, ModuleInfo, Reference, Symbol, SymbolKind},
+    ErrorKind, Logger, Options, SpanLoc, StackFrame,
+};
+
+use serde_json::{json, Value};
+
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+
+/// `TextDocumentSyncKind.Full`: the client sends the whole document on every
+/// change
+const SYNC_FULL: u32 = 1;
+
+const SEVERITY_ERROR: u32 = 1;
+const SEVERITY_WARNING: u32 = 2;
+
+// `CompletionItemKind`s
+const COMPLETION_METHOD: u32 = 2;
+const COMPLETION_FUNCTION: u32 = 3;
+const COMPLETION_VARIABLE: u32 = 6;
+const COMPLETION_MODULE: u32 = 9;
+
+#[derive(Debug, Default)]
+pub struct Server {
+    /// Open documents, keyed by URI
+    documents: HashMap<String, Document>,
+    load_paths: Vec<PathBuf>,
+    is_shut_down: bool,
+    has_exited: bool,
+}
+
+#[derive(Debug)]
+struct Document {
+    uri: String,
+    path: PathBuf,
+    text: String,
+    analysis: Analysis,
+    warnings: Vec<(SpanLoc, String)>,
+    /// The symbols and modules offered as completions, which come from the
+    /// last version of the document that parsed
+    completion_symbols: Vec<Symbol>,
+    completion_modules: Vec<ModuleInfo>,
+}
+
+/// Collects the warnings emitted while analyzing a document, so that they can
+/// be reported as diagnostics
+#[derive(Debug, Default)]
+struct WarningCollector(Mutex<Vec<(SpanLoc, String)>>);
+
+impl Logger for WarningCollector {
+    fn debug(&self, _location: SpanLoc, _message: &str, _trace: &[StackFrame]) {}
+
+    fn warn(&self, location: SpanLoc, message: &str, _trace: &[StackFrame]) {
+        self.0.lock().unwrap().push((location, message.to_owned()));
+    }
+
+    fn warn_without_span(&self, _message: &str) {}
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.is_shut_down
+    }
+
+    pub fn has_exited(&self) -> bool {
+        self.has_exited
+    }
+
+    /// Handle a single message from the client, returning the messages to send
+    /// in reply
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = match message.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            // responses to requests we never send
+            None => return Vec::new(),
+        };
+
+        let id = message.get("id").cloned();
+        let params = message.get("params").unwrap_or(&Value::Null);
+
+        let mut outgoing = Vec::new();
+
+        let result = match method {
+            "initialize" => Ok(self.initialize(params)),
+            "initialized" => Ok(Value::Null),
+            "shutdown" => {
+                self.is_shut_down = true;
+                Ok(Value::Null)
+            }
+            "exit" => {
+                self.has_exited = true;
+                Ok(Value::Null)
+            }
+            "textDocument/didOpen" => {
+                let uri = params.pointer("/textDocument/uri").and_then(Value::as_str);
+                let text = params.pointer("/textDocument/text").and_then(Value::as_str);
+
+                match (uri, text) {
+                    (Some(uri), Some(text)) => {
+                        self.open(uri.to_owned(), text.to_owned());
+                        outgoing.push(self.publish_diagnostics(uri));
+                        Ok(Value::Null)
+                    }
+                    _ => Err(invalid_params()),
+                }
+            }
+            "textDocument/didChange" => {
+                let uri = params.pointer("/textDocument/uri").and_then(Value::as_str);
+                // with full sync, the last change holds the whole document
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+
+                match (uri, text) {
+                    (Some(uri), Some(text)) => {
+                        self.open(uri.to_owned(), text.to_owned());
+                        outgoing.push(self.publish_diagnostics(uri));
+                        Ok(Value::Null)
+                    }
+                    _ => Err(invalid_params()),
+                }
+            }
+            "textDocument/didClose" => {
+                match params.pointer("/textDocument/uri").and_then(Value::as_str) {
+                    Some(uri) => {
+                        self.documents.remove(uri);
+                        outgoing.push(self.publish_diagnostics(uri));
+                        Ok(Value::Null)
+                    }
+                    None => Err(invalid_params()),
+                }
+            }
+            "textDocument/definition" => Ok(self.definition(params)),
+            "textDocument/hover" => Ok(self.hover(params)),
+            "textDocument/completion" => Ok(self.completion(params)),
+            _ => Err((METHOD_NOT_FOUND, format!("unhandled method {}", method))),
+        };
+
+        // notifications never receive a response, even if they fail
+        if let Some(id) = id {
+            outgoing.push(match result {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err((code, message)) => error_response(id, code, message),
+            });
+        }
+
+        outgoing
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        self.load_paths = params
+            .pointer("/initializationOptions/loadPaths")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(PathBuf::from)
+            .collect();
+
+        if let Some(sass_path) = env::var_os("SASS_PATH") {
+            self.load_paths.extend(env::split_paths(&sass_path));
+        }
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": SYNC_FULL,
+                "definitionProvider": true,
+                "hoverProvider": true,
+                "completionProvider": { "triggerCharacters": [".", "$"] },
+            },
+            "serverInfo": {
+                "name": "grass-lsp",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    fn open(&mut self, uri: String, text: String) {
+        let path = uri_to_path(&uri);
+        let logger = WarningCollector::default();
+        let options = Options::default()
+            .load_paths(&self.load_paths)
+            .logger(&logger);
+
+        let analysis = analyze(text.clone(), &path, &options);
+
+        // documents usually don't parse while they are being typed, which is
+        // exactly when completions are wanted
+        let failed_to_parse = analysis.error.is_some()
+            && analysis.symbols.is_empty()
+            && analysis.references.is_empty()
+            && analysis.modules.is_empty();
+
+        let (completion_symbols, completion_modules) = match self.documents.remove(&uri) {
+            Some(previous) if failed_to_parse => {
+                (previous.completion_symbols, previous.completion_modules)
+            }
+            _ => (analysis.symbols.clone(), analysis.modules.clone()),
+        };
+
+        let document = Document {
+            uri: uri.clone(),
+            path,
+            text,
+            analysis,
+            warnings: logger.0.into_inner().unwrap(),
+            completion_symbols,
+            completion_modules,
+        };
+
+        self.documents.insert(uri, document);
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Value {
+        let diagnostics = self
+            .documents
+            .get(uri)
+            .map(Document::diagnostics)
+            .unwrap_or_default();
+
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        })
+    }
+
+    /// The document and the zero-indexed line and column, in characters, of a
+    /// `TextDocumentPositionParams`
+    fn position<'a>(&'a self, params: &Value) -> Option<(&'a Document, usize, usize)> {
+        let uri = params.pointer("/textDocument/uri")?.as_str()?;
+        let document = self.documents.get(uri)?;
+        let line = params.pointer("/position/line")?.as_u64()? as usize;
+        let character = u32::try_from(params.pointer("/position/character")?.as_u64()?).ok()?;
+
+        let column = utf16_to_chars(document.line(line), character);
+
+        Some((document, line, column))
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let (document, line, column) = match self.position(params) {
+            Some(position) => position,
+            None => return Value::Null,
+        };
+
+        let reference = match document.reference_at(line, column) {
+            Some(reference) => reference,
+            None => return Value::Null,
+        };
+
+        // jump to the module itself rather than the `@use` rule, if it is a file
+        if reference.kind == SymbolKind::Namespace {
+            if let Some(path) = document
+                .module(&reference.name)
+                .and_then(|module| module.path.as_deref())
+            {
+                return json!({ "uri": path_to_uri(path), "range": range(0, 0, 0, 0) });
+            }
+        }
+
+        match &reference.definition {
+            Some(definition) => document.location(&definition.location),
+            None => Value::Null,
+        }
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let (document, line, column) = match self.position(params) {
+            Some(position) => position,
+            None => return Value::Null,
+        };
+
+        let (location, contents) = if let Some(reference) = document.reference_at(line, column) {
+            (&reference.location, document.describe_reference(reference))
+        } else if let Some(symbol) = document.symbol_at(line, column) {
+            (&symbol.location, describe_symbol(symbol))
+        } else {
+            return Value::Null;
+        };
+
+        match contents {
+            Some(contents) => json!({
+                "contents": {
+                    "kind": "markdown",
+                    "value": format!("```scss\n{}\n```", contents),
+                },
+                "range": span_range(location),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let (document, line, column) = match self.position(params) {
+            Some(position) => position,
+            None => return Value::Null,
+        };
+
+        let text = document.line(line);
+        let before = text.chars().take(column).collect::<Vec<char>>();
+
+        // the start of the partially typed name, including any `$`
+        let mut start = before.len();
+        while start > 0 && is_name_char(before[start - 1]) {
+            start -= 1;
+        }
+
+        let is_variable = start > 0 && before[start - 1] == '$';
+        if is_variable {
+            start -= 1;
+        }
+
+        let namespace = match start.checked_sub(1) {
+            Some(dot) if before[dot] == '.' => {
+                let mut namespace_start = dot;
+                while namespace_start > 0 && is_name_char(before[namespace_start - 1]) {
+                    namespace_start -= 1;
+                }
+                Some(before[namespace_start..dot].iter().collect::<String>())
+                    .filter(|namespace| !namespace.is_empty())
+            }
+            _ => None,
+        };
+
+        let mut items = Vec::new();
+
+        match namespace {
+            Some(namespace) => {
+                if let Some(module) = document
+                    .completion_modules
+                    .iter()
+                    .find(|module| module.namespace.as_deref() == Some(namespace.as_str()))
+                {
+                    module_items(module, is_variable, &mut items);
+                }
+            }
+            None => {
+                for symbol in &document.completion_symbols {
+                    if !is_variable || symbol.kind == SymbolKind::Variable {
+                        items.push(symbol_item(symbol));
+                    }
+                }
+
+                for module in &document.completion_modules {
+                    if module.namespace.is_none() {
+                        module_items(module, is_variable, &mut items);
+                    }
+                }
+            }
+        }
+
+        // replace the partially typed name, since clients disagree on whether
+        // `$` is part of a word
+        let edit_range = range(
+            line,
+            chars_to_utf16(text, start),
+            line,
+            chars_to_utf16(text, column),
+        );
+
+        let mut seen = HashSet::new();
+
+        let items = items
+            .into_iter()
+            .filter(|item| seen.insert(item.label.clone()))
+            .map(|item| {
+                json!({
+                    "label": item.label.clone(),
+                    "kind": item.kind,
+                    "detail": item.detail,
+                    "textEdit": { "range": edit_range.clone(), "newText": item.label },
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        Value::Array(items)
+    }
+}
+
+impl Document {
+    fn line(&self, line: usize) -> &str {
+        self.text.lines().nth(line).unwrap_or("")
+    }
+
+    fn is_in_document(&self, location: &SpanLoc) -> bool {
+        Path::new(location.file.name()) == self.path
+    }
+
+    fn reference_at(&self, line: usize, column: usize) -> Option<&Reference> {
+        self.analysis.references.iter().find(|reference| {
+            self.is_in_document(&reference.location) && contains(&reference.location, line, column)
+        })
+    }
+
+    fn symbol_at(&self, line: usize, column: usize) -> Option<&Symbol> {
+        self.analysis
+            .symbols
+            .iter()
+            .find(|symbol| contains(&symbol.location, line, column))
+    }
+
+    fn module(&self, namespace: &str) -> Option<&ModuleInfo> {
+        self.analysis
+            .modules
+            .iter()
+            .find(|module| module.namespace.as_deref() == Some(namespace))
+    }
+
+    fn location(&self, location: &SpanLoc) -> Value {
+        let uri = if self.is_in_document(location) {
+            self.uri.clone()
+        } else {
+            path_to_uri(Path::new(location.file.name()))
+        };
+
+        json!({ "uri": uri, "range": span_range(location) })
+    }
+
+    fn describe_reference(&self, reference: &Reference) -> Option<String> {
+        if let Some(definition) = &reference.definition {
+            if reference.kind != SymbolKind::Namespace {
+                return describe_symbol(definition);
+            }
+        }
+
+        let module = match (&reference.namespace, reference.kind) {
+            (None, SymbolKind::Namespace) => {
+                let module = self.module(&reference.name)?;
+                return Some(format!("@use \"{}\"", module.url));
+            }
+            (Some(namespace), _) => self.module(namespace)?,
+            (None, _) => return None,
+        };
+
+        // members of built-in modules have no declaration to describe
+        match reference.kind {
+            SymbolKind::Variable => module
+                .variables
+                .iter()
+                .find(|(name, _)| *name == reference.name)
+                .map(|(name, value)| format!("${}: {};", name, value)),
+            SymbolKind::Function => Some(format!("@function {}", reference.name)),
+            SymbolKind::Mixin => Some(format!("@mixin {}", reference.name)),
+            SymbolKind::Namespace => None,
+        }
+    }
+
+    fn diagnostics(&self) -> Vec<Value> {
+        let mut diagnostics = Vec::new();
+
+        if let Some(error) = &self.analysis.error {
+            let (range, message) = match (**error).clone().kind() {
+                ErrorKind::ParseError {
+                    message,
+                    loc,
+                    stack,
+                    ..
+                } => {
+                    if self.is_in_document(&loc) {
+                        (span_range(&loc), message)
+                    } else {
+                        // report errors in other files where this document
+                        // loads them, if possible
+                        let range = stack
+                            .iter()
+                            .map(|frame| &frame.loc)
+                            .find(|loc| self.is_in_document(loc))
+                            .map_or_else(|| range(0, 0, 0, 0), span_range);
+
+                        let message = format!(
+                            "{} ({} {}:{})",
+                            message,
+                            loc.file.name(),
+                            loc.begin.line + 1,
+                            loc.begin.column + 1
+                        );
+
+                        (range, message)
+                    }
+                }
+                ErrorKind::IoError(error) => (range(0, 0, 0, 0), error.to_string()),
+                ErrorKind::FromUtf8Error(message) => (range(0, 0, 0, 0), message),
+                _ => (range(0, 0, 0, 0), error.to_string()),
+            };
+
+            diagnostics.push(diagnostic(range, SEVERITY_ERROR, message));
+        }
+
+        for (location, message) in &self.warnings {
+            if self.is_in_document(location) {
+                diagnostics.push(diagnostic(
+                    span_range(location),
+                    SEVERITY_WARNING,
+                    message.clone(),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+fn describe_symbol(symbol: &Symbol) -> Option<String> {
+    match symbol.kind {
+        SymbolKind::Variable => symbol
+            .value
+            .as_ref()
+            .map(|value| format!("${}: {};", symbol.name, value)),
+        SymbolKind::Function => Some(format!("@function {}", symbol.name)),
+        SymbolKind::Mixin => Some(format!("@mixin {}", symbol.name)),
+        SymbolKind::Namespace => None,
+    }
+}
+
+struct CompletionItem {
+    label: String,
+    kind: u32,
+    detail: Option<String>,
+}
+
+fn symbol_item(symbol: &Symbol) -> CompletionItem {
+    match symbol.kind {
+        SymbolKind::Variable => CompletionItem {
+            label: format!("${}", symbol.name),
+            kind: COMPLETION_VARIABLE,
+            detail: symbol.value.clone(),
+        },
+        SymbolKind::Function => CompletionItem {
+            label: symbol.name.clone(),
+            kind: COMPLETION_FUNCTION,
+            detail: Some("function".to_owned()),
+        },
+        SymbolKind::Mixin => CompletionItem {
+            label: symbol.name.clone(),
+            kind: COMPLETION_METHOD,
+            detail: Some("mixin".to_owned()),
+        },
+        SymbolKind::Namespace => CompletionItem {
+            label: symbol.name.clone(),
+            kind: COMPLETION_MODULE,
+            detail: Some("module".to_owned()),
+        },
+    }
+}
+
+fn module_items(module: &ModuleInfo, variables_only: bool, items: &mut Vec<CompletionItem>) {
+    for (name, value) in &module.variables {
+        items.push(CompletionItem {
+            label: format!("${}", name),
+            kind: COMPLETION_VARIABLE,
+            detail: Some(value.clone()),
+        });
+    }
+
+    if variables_only {
+        return;
+    }
+
+    for name in &module.functions {
+        items.push(CompletionItem {
+            label: name.clone(),
+            kind: COMPLETION_FUNCTION,
+            detail: Some("function".to_owned()),
+        });
+    }
+
+    for name in &module.mixins {
+        items.push(CompletionItem {
+            label: name.clone(),
+            kind: COMPLETION_METHOD,
+            detail: Some("mixin".to_owned()),
+        });
+    }
+}
+
+pub fn error_response(id: Value, code: i32, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn invalid_params() -> (i32, String) {
+    (INVALID_PARAMS, "missing or invalid params".to_owned())
+}
+
+fn diagnostic(range: Value, severity: u32, message: String) -> Value {
+    json!({
+        "range": range,
+        "severity": severity,
+        "source": "grass",
+        "message": message,
+    })
+}
+
+fn range(start_line: usize, start_character: u32, end_line: usize, end_character: u32) -> Value {
+    json!({
+        "start": { "line": start_line, "character": start_character },
+        "end": { "line": end_line, "character": end_character },
+    })
+}
+
+/// Convert a location, whose columns count characters, to a range, whose
+/// columns count UTF-16 code units
+fn span_range(location: &SpanLoc) -> Value {
+    let start = chars_to_utf16(
+        location.file.source_line(location.begin.line),
+        location.begin.column,
+    );
+    let end = chars_to_utf16(
+        location.file.source_line(location.end.line),
+        location.end.column,
+    );
+
+    range(location.begin.line, start, location.end.line, end)
+}
+
+fn contains(location: &SpanLoc, line: usize, column: usize) -> bool {
+    (location.begin.line, location.begin.column) <= (line, column)
+        && (line, column) <= (location.end.line, location.end.column)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+fn utf16_to_chars(line: &str, character: u32) -> usize {
+    let mut units = 0;
+
+    for (idx, c) in line.chars().enumerate() {
+        if units >= character as usize {
+            return idx;
+        }
+        units += c.len_utf16();
+    }
+
+    line.chars().count()
+}
+
+fn chars_to_utf16(line: &str, column: usize) -> u32 {
+    line.chars()
+        .take(column)
+        .map(char::len_utf16)
+        .sum::<usize>() as u32
+}
+
+fn uri_to_path(uri: &str) -> PathBuf {
+    let path = match uri.strip_prefix("file://") {
+        Some(path) => path.strip_prefix("localhost").unwrap_or(path),
+        // documents which aren't files, such as unsaved buffers, are analyzed
+        // as if the URI were a path
+        None => return PathBuf::from(uri),
+    };
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut chars = path.bytes();
+
+    while let Some(byte) = chars.next() {
+        if byte == b'%' {
+            let hex = [chars.next(), chars.next()];
+            if let [Some(high), Some(low)] = hex {
+                if let Ok(byte) =
+                    u8::from_str_radix(std::str::from_utf8(&[high, low]).unwrap_or(""), 16)
+                {
+                    bytes.push(byte);
+                    continue;
+                }
+            }
+            bytes.push(b'%');
+            bytes.extend(hex.iter().flatten());
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    let path = String::from_utf8_lossy(&bytes).into_owned();
+
+    // `file:///C:/styles` on Windows
+    if cfg!(windows) && path.as_bytes().get(2) == Some(&b':') {
+        return PathBuf::from(&path[1..]);
+    }
+
+    PathBuf::from(path)
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let path = if path.is_relative() {
+        env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_owned())
+    } else {
+        path.to_owned()
+    };
+
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    let mut uri = String::from("file://");
+
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char);
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
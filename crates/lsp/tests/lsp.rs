@@ -0,0 +1,473 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde_json::{json, Value};
+
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u32,
+    /// Notifications received while waiting for a response
+    notifications: Vec<Value>,
+}
+
+impl Client {
+    fn start() -> Self {
+        Self::start_with_options("{}")
+    }
+
+    fn start_with_options(initialization_options: &str) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_grass-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .env_remove("SASS_PATH")
+            .spawn()
+            .unwrap();
+
+        let mut client = Self {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            next_id: 0,
+            notifications: Vec::new(),
+        };
+
+        client.request(
+            "initialize",
+            &format!(
+                r#"{{"capabilities":{{}},"initializationOptions":{}}}"#,
+                initialization_options
+            ),
+        );
+        client.notify("initialized", "{}");
+
+        client
+    }
+
+    fn send(&mut self, body: &str) {
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut content_length = 0;
+
+        loop {
+            let mut line = String::new();
+            self.stdout.read_line(&mut line).unwrap();
+
+            match line.trim_end().split_once(": ") {
+                Some(("Content-Length", value)) => content_length = value.parse().unwrap(),
+                _ => break,
+            }
+        }
+
+        let mut body = vec![0; content_length];
+        self.stdout.read_exact(&mut body).unwrap();
+
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Send a request and wait for its result
+    fn request(&mut self, method: &str, params: &str) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+
+        self.send(&format!(
+            r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":{}}}"#,
+            id, method, params
+        ));
+
+        loop {
+            let message = self.receive();
+
+            if message.get("id") == Some(&json!(id)) {
+                return message.get("result").cloned().unwrap();
+            }
+
+            self.notifications.push(message);
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: &str) {
+        self.send(&format!(
+            r#"{{"jsonrpc":"2.0","method":"{}","params":{}}}"#,
+            method, params
+        ));
+    }
+
+    fn open(&mut self, uri: &str, text: &str) -> Vec<Value> {
+        self.notify(
+            "textDocument/didOpen",
+            &format!(
+                r#"{{"textDocument":{{"uri":"{}","languageId":"scss","version":1,"text":{}}}}}"#,
+                uri,
+                Value::from(text)
+            ),
+        );
+
+        self.diagnostics()
+    }
+
+    fn change(&mut self, uri: &str, text: &str) -> Vec<Value> {
+        self.notify(
+            "textDocument/didChange",
+            &format!(
+                r#"{{"textDocument":{{"uri":"{}","version":2}},"contentChanges":[{{"text":{}}}]}}"#,
+                uri,
+                Value::from(text)
+            ),
+        );
+
+        self.diagnostics()
+    }
+
+    /// Wait for the next `textDocument/publishDiagnostics` notification
+    fn diagnostics(&mut self) -> Vec<Value> {
+        loop {
+            let message = self.receive();
+
+            if message.get("method").and_then(Value::as_str)
+                == Some("textDocument/publishDiagnostics")
+            {
+                return message
+                    .pointer("/params/diagnostics")
+                    .and_then(Value::as_array)
+                    .unwrap()
+                    .to_vec();
+            }
+        }
+    }
+
+    fn position_request(&mut self, method: &str, uri: &str, line: u32, character: u32) -> Value {
+        self.request(
+            method,
+            &format!(
+                r#"{{"textDocument":{{"uri":"{}"}},"position":{{"line":{},"character":{}}}}}"#,
+                uri, line, character
+            ),
+        )
+    }
+
+    fn shutdown(mut self) -> i32 {
+        self.request("shutdown", "null");
+        self.notify("exit", "null");
+        self.child.wait().unwrap().code().unwrap()
+    }
+}
+
+fn file_uri(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+fn position(range: &Value) -> (u32, u32, u32, u32) {
+    let get = |pointer: &str| range.pointer(pointer).and_then(Value::as_u64).unwrap() as u32;
+
+    (
+        get("/start/line"),
+        get("/start/character"),
+        get("/end/line"),
+        get("/end/character"),
+    )
+}
+
+fn labels(completions: &Value) -> Vec<&str> {
+    completions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item.get("label").and_then(Value::as_str).unwrap())
+        .collect()
+}
+
+#[test]
+fn initialize_capabilities() {
+    let mut client = Client::start();
+
+    client.next_id += 1;
+    client.send(r#"{"jsonrpc":"2.0","id":1000,"method":"initialize","params":{}}"#);
+    let response = client.receive();
+
+    let capabilities = response.pointer("/result/capabilities").unwrap();
+    assert_eq!(
+        Some(&Value::Bool(true)),
+        capabilities.get("definitionProvider")
+    );
+    assert_eq!(Some(&Value::Bool(true)), capabilities.get("hoverProvider"));
+    assert_eq!(
+        Some("grass-lsp"),
+        response
+            .pointer("/result/serverInfo/name")
+            .and_then(Value::as_str)
+    );
+
+    assert_eq!(0, client.shutdown());
+}
+
+#[test]
+fn unknown_request_is_an_error() {
+    let mut client = Client::start();
+
+    client.send(r#"{"jsonrpc":"2.0","id":"a","method":"textDocument/rename","params":{}}"#);
+    let response = client.receive();
+
+    assert_eq!(
+        Some(-32601),
+        response.pointer("/error/code").and_then(Value::as_i64)
+    );
+}
+
+#[test]
+fn parse_error_diagnostic() {
+    let mut client = Client::start();
+
+    let diagnostics = client.open("untitled:a.scss", "a {\n  color: red;\n  b: (\n}");
+
+    assert_eq!(1, diagnostics.len());
+    assert_eq!(
+        Some(1),
+        diagnostics[0].get("severity").and_then(Value::as_u64)
+    );
+    assert_eq!((3, 0, 3, 1), position(diagnostics[0].get("range").unwrap()));
+
+    let diagnostics = client.change("untitled:a.scss", "a {\n  color: red;\n}");
+    assert!(diagnostics.is_empty());
+
+    assert_eq!(0, client.shutdown());
+}
+
+#[test]
+fn evaluation_error_and_warning_diagnostics() {
+    let mut client = Client::start();
+
+    let diagnostics = client.open(
+        "untitled:a.scss",
+        "@warn careful;\na {\n  width: 1px + 1em;\n}",
+    );
+
+    assert_eq!(2, diagnostics.len());
+
+    assert_eq!(
+        Some(1),
+        diagnostics[0].get("severity").and_then(Value::as_u64)
+    );
+    assert_eq!(
+        Some("Incompatible units em and px."),
+        diagnostics[0].get("message").and_then(Value::as_str)
+    );
+    assert_eq!(
+        (2, 9, 2, 18),
+        position(diagnostics[0].get("range").unwrap())
+    );
+
+    assert_eq!(
+        Some(2),
+        diagnostics[1].get("severity").and_then(Value::as_u64)
+    );
+    assert_eq!(
+        Some("careful"),
+        diagnostics[1].get("message").and_then(Value::as_str)
+    );
+}
+
+#[test]
+fn close_clears_diagnostics() {
+    let mut client = Client::start();
+
+    assert_eq!(1, client.open("untitled:a.scss", "a {").len());
+
+    client.notify(
+        "textDocument/didClose",
+        r#"{"textDocument":{"uri":"untitled:a.scss"}}"#,
+    );
+    assert!(client.diagnostics().is_empty());
+}
+
+#[test]
+fn definition_in_same_file() {
+    let mut client = Client::start();
+
+    client.open(
+        "untitled:a.scss",
+        "$width: 10px;\n@mixin box {\n  width: $width;\n}\na {\n  @include box;\n}",
+    );
+
+    let location = client.position_request("textDocument/definition", "untitled:a.scss", 2, 10);
+    assert_eq!(
+        Some("untitled:a.scss"),
+        location.get("uri").and_then(Value::as_str)
+    );
+    assert_eq!((0, 0, 0, 6), position(location.get("range").unwrap()));
+
+    let location = client.position_request("textDocument/definition", "untitled:a.scss", 5, 12);
+    assert_eq!((1, 7, 1, 10), position(location.get("range").unwrap()));
+
+    let location = client.position_request("textDocument/definition", "untitled:a.scss", 4, 0);
+    assert_eq!(Value::Null, location);
+}
+
+#[test]
+fn definition_in_other_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let lib = dir.path().join("_lib.scss");
+    fs::write(&lib, "// colors\n@function shade($c) {\n  @return $c;\n}").unwrap();
+
+    let uri = file_uri(&dir.path().join("input.scss"));
+
+    let mut client = Client::start();
+    client.open(&uri, "@use 'lib';\na {\n  color: lib.shade(red);\n}");
+
+    let location = client.position_request("textDocument/definition", &uri, 2, 14);
+    assert_eq!(
+        Some(file_uri(&lib).as_str()),
+        location.get("uri").and_then(Value::as_str)
+    );
+    assert_eq!((1, 10, 1, 15), position(location.get("range").unwrap()));
+
+    // the namespace jumps to the start of the module
+    let location = client.position_request("textDocument/definition", &uri, 2, 10);
+    assert_eq!(
+        Some(file_uri(&lib).as_str()),
+        location.get("uri").and_then(Value::as_str)
+    );
+    assert_eq!((0, 0, 0, 0), position(location.get("range").unwrap()));
+}
+
+#[test]
+fn load_paths_from_initialization_options() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("_theme.scss"), "$primary: blue;").unwrap();
+
+    let mut client = Client::start_with_options(&format!(
+        r#"{{"loadPaths":[{}]}}"#,
+        Value::from(dir.path().to_string_lossy().into_owned())
+    ));
+
+    let diagnostics = client.open(
+        "untitled:a.scss",
+        "@use 'theme';\na {\n  color: theme.$primary;\n}",
+    );
+    assert!(diagnostics.is_empty());
+
+    let hover = client.position_request("textDocument/hover", "untitled:a.scss", 2, 17);
+    assert_eq!(
+        Some("```scss\n$primary: blue;\n```"),
+        hover.pointer("/contents/value").and_then(Value::as_str)
+    );
+}
+
+#[test]
+fn hover_shows_variable_value() {
+    let mut client = Client::start();
+
+    client.open(
+        "untitled:a.scss",
+        "$gutter: 4px * 2;\n.a {\n  margin: $gutter;\n}",
+    );
+
+    let hover = client.position_request("textDocument/hover", "untitled:a.scss", 2, 12);
+    assert_eq!(
+        Some("```scss\n$gutter: 8px;\n```"),
+        hover.pointer("/contents/value").and_then(Value::as_str)
+    );
+    assert_eq!((2, 10, 2, 17), position(hover.get("range").unwrap()));
+
+    // hovering the declaration itself
+    let hover = client.position_request("textDocument/hover", "untitled:a.scss", 0, 2);
+    assert_eq!(
+        Some("```scss\n$gutter: 8px;\n```"),
+        hover.pointer("/contents/value").and_then(Value::as_str)
+    );
+}
+
+#[test]
+fn utf16_positions() {
+    let mut client = Client::start();
+
+    client.open("untitled:a.scss", "$x: 1;\na {\n  content: '😀' $x;\n}");
+
+    let hover = client.position_request("textDocument/hover", "untitled:a.scss", 2, 16);
+    assert_eq!((2, 16, 2, 18), position(hover.get("range").unwrap()));
+}
+
+#[test]
+fn completion_of_module_members() {
+    let mut client = Client::start();
+
+    client.open(
+        "untitled:a.scss",
+        "@use 'sass:math';\na {\n  b: math.div(1, 2);\n}",
+    );
+
+    let completions = client.position_request("textDocument/completion", "untitled:a.scss", 2, 10);
+    let labels = labels(&completions);
+    assert!(labels.contains(&"div"));
+    assert!(labels.contains(&"$pi"));
+
+    let completions = client.position_request("textDocument/completion", "untitled:a.scss", 2, 11);
+    let item = completions
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item.get("label").and_then(Value::as_str) == Some("div"))
+        .unwrap();
+    assert_eq!(
+        (2, 10, 2, 11),
+        position(item.pointer("/textEdit/range").unwrap())
+    );
+}
+
+#[test]
+fn completion_while_document_does_not_parse() {
+    let mut client = Client::start();
+
+    client.open(
+        "untitled:a.scss",
+        "@use 'sass:math';\n$size: 1px;\na {\n  b: 1;\n}",
+    );
+    client.change(
+        "untitled:a.scss",
+        "@use 'sass:math';\n$size: 1px;\na {\n  b: math.\n}",
+    );
+
+    let completions = client.position_request("textDocument/completion", "untitled:a.scss", 3, 10);
+    assert!(labels(&completions).contains(&"floor"));
+
+    let completions = client.position_request("textDocument/completion", "untitled:a.scss", 3, 5);
+    assert!(labels(&completions).contains(&"$size"));
+}
+
+#[test]
+fn completion_of_variables() {
+    let mut client = Client::start();
+
+    client.open(
+        "untitled:a.scss",
+        "$primary: red;\n@mixin m {}\na {\n  color: $p;\n}",
+    );
+
+    let completions = client.position_request("textDocument/completion", "untitled:a.scss", 3, 11);
+    assert_eq!(vec!["$primary"], labels(&completions));
+    assert_eq!(
+        (3, 9, 3, 11),
+        position(
+            completions.as_array().unwrap()[0]
+                .pointer("/textEdit/range")
+                .unwrap()
+        )
+    );
+}
+
+#[test]
+fn exit_without_shutdown() {
+    let mut client = Client::start();
+
+    client.notify("exit", "null");
+
+    assert_eq!(Some(1), client.child.wait().unwrap().code());
+}
@@ -0,0 +1,313 @@
+//! Structure-aware inputs shared by the fuzz targets
+//!
+//! Rather than raw bytes, stylesheets are generated as a sequence of Sass
+//! tokens and arbitrary text, which reaches deep into the evaluator far more
+//! often than random input does.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use arbitrary::Arbitrary;
+
+/// Resource limits applied to every fuzzed compilation, so that
+/// `@while true {}` and unbounded recursion are reported as errors rather than
+/// timeouts or stack overflows
+pub fn options<'a>() -> grass::Options<'a> {
+    grass::Options::default()
+        .quiet(true)
+        .max_call_depth(32)
+        .max_loop_iterations(1_000)
+        .max_output_size(1 << 16)
+}
+
+#[derive(Arbitrary, Debug)]
+pub struct Stylesheet(Vec<Fragment>);
+
+impl Stylesheet {
+    pub fn to_source(&self) -> String {
+        let mut source = String::new();
+
+        for fragment in &self.0 {
+            match fragment {
+                Fragment::Token(token) => source.push_str(token.as_str()),
+                Fragment::Text(text) => source.push_str(text),
+            }
+        }
+
+        source
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+enum Fragment {
+    Token(Token),
+    Text(String),
+}
+
+macro_rules! tokens {
+    ($($name:ident => $text:literal,)*) => {
+        #[derive(Arbitrary, Debug, Clone, Copy)]
+        enum Token {
+            $($name,)*
+        }
+
+        impl Token {
+            fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$name => $text,)*
+                }
+            }
+        }
+    };
+}
+
+tokens! {
+    Space => " ",
+    Newline => "\n",
+    Indent => "  ",
+    OpenCurly => "{",
+    CloseCurly => "}",
+    OpenParen => "(",
+    CloseParen => ")",
+    OpenSquare => "[",
+    CloseSquare => "]",
+    Comma => ",",
+    Semicolon => ";",
+    Colon => ":",
+    Ellipsis => "...",
+    Interpolation => "#{",
+    LineComment => "//",
+    BlockCommentStart => "/*",
+    BlockCommentEnd => "*/",
+
+    TypeSelector => "a",
+    OtherTypeSelector => "b",
+    NamespacedSelector => "ns|a",
+    Universal => "*",
+    Parent => "&",
+    ParentSuffix => "&-suffix",
+    Class => ".c",
+    Id => "#d",
+    Placeholder => "%p",
+    Attribute => "[e=f]",
+    Pseudo => ":hover",
+    PseudoElement => "::before",
+    Not => ":not(",
+    Is => ":is(",
+    NthChild => ":nth-child(2n+1 of ",
+    Child => ">",
+    Sibling => "~",
+    Next => "+",
+
+    VariableX => "$x",
+    VariableY => "$y",
+    Default => "!default",
+    Global => "!global",
+    Important => "!important",
+    Optional => "!optional",
+
+    Mixin => "@mixin m",
+    MixinWithArgs => "@mixin m($x, $y...)",
+    Include => "@include m",
+    Content => "@content",
+    ContentWithArgs => "@content($x)",
+    Using => "using ($x)",
+    Function => "@function f($x: 1)",
+    Call => "f(",
+    Return => "@return",
+    If => "@if",
+    Else => "@else",
+    ElseIf => "@else if",
+    Each => "@each $x in",
+    EachPair => "@each $x, $y in",
+    For => "@for $x from 1 through",
+    ForTo => "@for $x from 3 to",
+    While => "@while",
+    Extend => "@extend",
+    AtRoot => "@at-root",
+    AtRootWithout => "@at-root (without: all)",
+    Media => "@media screen and (min-width: 1px)",
+    Supports => "@supports (a: b)",
+    Keyframes => "@keyframes k",
+    FontFace => "@font-face",
+    Unknown => "@unknown",
+    Charset => "@charset \"UTF-8\"",
+    Debug => "@debug",
+    Warn => "@warn",
+    Error => "@error",
+
+    UseMath => "@use 'sass:math';",
+    UseMeta => "@use 'sass:meta';",
+    UseMap => "@use 'sass:map';",
+    UseList => "@use 'sass:list';",
+    UseString => "@use 'sass:string';",
+    UseSelector => "@use 'sass:selector';",
+    UseColor => "@use 'sass:color';",
+    UseA => "@use 'a'",
+    UseAWith => "@use 'a' with ($x: 1)",
+    UseAAs => "@use 'a' as *",
+    ForwardA => "@forward 'a'",
+    ForwardShow => "@forward 'a' show m, $x",
+    ImportA => "@import 'a'",
+    ImportB => "@import 'b', 'c'",
+    ImportCss => "@import 'd.css'",
+    ImportUrl => "@import url(e)",
+
+    Zero => "0",
+    One => "1",
+    Negative => "-1",
+    Decimal => "0.5",
+    Large => "1e300",
+    Px => "1px",
+    Em => "2em",
+    Percent => "50%",
+    Deg => "90deg",
+    Plus => " + ",
+    Minus => " - ",
+    Times => " * ",
+    Divide => "/",
+    Modulo => " % ",
+    Equals => " == ",
+    NotEquals => " != ",
+    LessThan => " < ",
+    And => " and ",
+    Or => " or ",
+    NotOp => "not ",
+    Null => "null",
+    True => "true",
+    False => "false",
+    Red => "red",
+    Hex => "#abc",
+    SingleQuoted => "'s'",
+    DoubleQuoted => "\"s\"",
+    Unquoted => "u",
+    Map => "(k: v, l: w)",
+    EmptyList => "()",
+    BracketedList => "[1 2]",
+    CustomProperty => "--v",
+    Var => "var(--v)",
+    Url => "url(",
+    Calc => "calc(",
+    Min => "min(",
+    Max => "max(",
+    Clamp => "clamp(",
+
+    MathDiv => "math.div(",
+    MathNan => "math.div(0, 0)",
+    MathSqrt => "math.sqrt(",
+    MathPow => "math.pow(",
+    MathSin => "math.sin(",
+    MathClamp => "math.clamp(",
+    Percentage => "percentage(",
+    Round => "round(",
+    Random => "random(",
+    Unit => "unit(",
+    Rgb => "rgb(",
+    Rgba => "rgba(",
+    Hsl => "hsl(",
+    Hwb => "color.hwb(",
+    Mix => "mix(",
+    Darken => "darken(",
+    Saturate => "saturate(",
+    Invert => "invert(",
+    Opacity => "opacity(",
+    Transparentize => "transparentize(",
+    AdjustColor => "adjust-color(",
+    ChangeColor => "change-color(",
+    ScaleColor => "scale-color(",
+    Hue => "$hue: ",
+    Alpha => "$alpha: ",
+    Nth => "nth(",
+    SetNth => "set-nth(",
+    Join => "join(",
+    Append => "append(",
+    Zip => "zip(",
+    Index => "index(",
+    ListSlash => "list.slash(",
+    MapGet => "map-get(",
+    MapMerge => "map.merge(",
+    MapDeepMerge => "map.deep-merge(",
+    MapRemove => "map-remove(",
+    MapSet => "map.set(",
+    StrSlice => "str-slice(",
+    StrInsert => "str-insert(",
+    StrIndex => "str-index(",
+    ToUpperCase => "to-upper-case(",
+    UniqueId => "unique-id()",
+    Inspect => "inspect(",
+    TypeOf => "type-of(",
+    GetFunction => "get-function(",
+    CallFn => "call(",
+    LoadCss => "meta.load-css(",
+    ModuleVariables => "meta.module-variables(",
+    IfFn => "if(",
+    SelectorNest => "selector-nest(",
+    SelectorAppend => "selector-append(",
+    SelectorExtend => "selector-extend(",
+    SelectorReplace => "selector-replace(",
+    SelectorUnify => "selector-unify(",
+    SelectorParse => "selector-parse(",
+    SimpleSelectors => "simple-selectors(",
+    IsSuperselector => "is-superselector(",
+}
+
+/// The names of the files available to `@use`, `@forward`, and `@import`
+#[derive(Arbitrary, Debug, Clone, Copy)]
+pub enum FileName {
+    Partial,
+    Indented,
+    Css,
+    Index,
+    Nested,
+}
+
+impl FileName {
+    fn path(self) -> &'static str {
+        match self {
+            Self::Partial => "_a.scss",
+            Self::Indented => "b.sass",
+            Self::Css => "c.css",
+            Self::Index => "d/_index.scss",
+            Self::Nested => "e/f.scss",
+        }
+    }
+}
+
+/// An in-memory file system, so that loading stylesheets never touches the disk
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl MemoryFs {
+    pub fn new(files: &[(FileName, Stylesheet)]) -> Self {
+        Self {
+            files: files
+                .iter()
+                .map(|(name, stylesheet)| (PathBuf::from(name.path()), stylesheet.to_source()))
+                .collect(),
+        }
+    }
+}
+
+impl grass::Fs for MemoryFs {
+    fn is_dir(&self, path: &Path) -> bool {
+        self.files.keys().any(|file| file.starts_with(path))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .map(|contents| contents.as_bytes().to_vec())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+}
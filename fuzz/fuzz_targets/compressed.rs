@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+mod common;
+
+fuzz_target!(|stylesheet: common::Stylesheet| {
+    let options = common::options().style(grass::OutputStyle::Compressed);
+
+    let _ = grass::from_string(stylesheet.to_source(), &options);
+});
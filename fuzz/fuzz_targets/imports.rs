@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+mod common;
+
+use common::{FileName, MemoryFs, Stylesheet};
+
+fuzz_target!(|input: (Stylesheet, Vec<(FileName, Stylesheet)>)| {
+    let (stylesheet, files) = input;
+    let fs = MemoryFs::new(&files);
+    let options = common::options().fs(&fs);
+
+    let _ = grass::from_string(stylesheet.to_source(), &options);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+mod common;
+
+fuzz_target!(|stylesheet: common::Stylesheet| {
+    let options = common::options().input_syntax(grass::InputSyntax::Sass);
+
+    let _ = grass::from_string(stylesheet.to_source(), &options);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+mod common;
+
+fuzz_target!(|stylesheet: common::Stylesheet| {
+    let _ = grass::from_string(stylesheet.to_source(), &common::options());
+});